@@ -0,0 +1,309 @@
+#![warn(missing_docs)]
+
+//! Derive macros for converting Rust types to and from Naumachia's `PlutusData`.
+//!
+//! The encoding matches the one Aiken and PlutusTx use for records and sum types: every
+//! constructor becomes a `Constr` whose index is the position of the variant in the enum (a
+//! struct is constructor `0`) and whose fields are the fields of the variant, in declaration
+//! order. The index can be overridden with `#[plutus(index = N)]`, e.g. to match an on-chain
+//! type whose constructors are declared in a different order.
+//!
+//! ```ignore
+//! use naumachia::scripts::plutus_validator::plutus_data::{
+//!     FromPlutusData,
+//!     ToPlutusData,
+//! };
+//!
+//! #[derive(ToPlutusData, FromPlutusData)]
+//! pub enum Action {
+//!     Deposit { amount: i64 },
+//!     Withdraw { amount: i64 },
+//!     #[plutus(index = 5)]
+//!     Close,
+//! }
+//! ```
+//!
+//! The generated code refers to the `naumachia` crate, so it has to be a dependency of the crate
+//! using the derives. With [`ToPlutusData`] every field must convert into `PlutusData`. With
+//! [`FromPlutusData`] every field must be `TryFrom<PlutusData>`, with an error that converts
+//! into `ScriptError`.
+
+use proc_macro::TokenStream;
+use proc_macro2::{
+    Ident,
+    TokenStream as TokenStream2,
+};
+use quote::{
+    format_ident,
+    quote,
+};
+use std::collections::HashMap;
+use syn::{
+    parse_macro_input,
+    parse_quote,
+    Attribute,
+    Data,
+    DeriveInput,
+    Fields,
+    Generics,
+    LitInt,
+};
+
+/// Derive `From<T> for PlutusData`
+#[proc_macro_derive(ToPlutusData, attributes(plutus))]
+pub fn derive_to_plutus_data(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    to_plutus_data(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derive `TryFrom<PlutusData> for T`, failing with `ScriptError::DatumDeserialization`
+#[proc_macro_derive(FromPlutusData, attributes(plutus))]
+pub fn derive_from_plutus_data(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    from_plutus_data(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// One constructor of the derived type: the struct itself or one of the enum variants
+struct Constructor<'a> {
+    index: u64,
+    /// `None` for structs
+    variant: Option<&'a Ident>,
+    fields: &'a Fields,
+}
+
+impl Constructor<'_> {
+    fn display_name(&self, type_name: &Ident) -> String {
+        match self.variant {
+            Some(variant) => format!("{type_name}::{variant}"),
+            None => type_name.to_string(),
+        }
+    }
+
+    fn bindings(&self) -> Vec<Ident> {
+        self.fields
+            .iter()
+            .enumerate()
+            .map(|(position, field)| match &field.ident {
+                Some(ident) => format_ident!("field_{}", ident),
+                None => format_ident!("field_{}", position),
+            })
+            .collect()
+    }
+
+    /// Pattern or expression building the constructor from `bindings`, rooted at `root`
+    fn construct(&self, root: TokenStream2, bindings: &[TokenStream2]) -> TokenStream2 {
+        let path = match self.variant {
+            Some(variant) => quote!(#root::#variant),
+            None => root,
+        };
+        match self.fields {
+            Fields::Named(named) => {
+                let names = named.named.iter().map(|field| &field.ident);
+                quote!(#path { #(#names: #bindings),* })
+            }
+            Fields::Unnamed(_) => quote!(#path(#(#bindings),*)),
+            Fields::Unit => quote!(#path),
+        }
+    }
+}
+
+fn plutus_data_module() -> TokenStream2 {
+    quote!(::naumachia::scripts::plutus_validator::plutus_data)
+}
+
+fn script_error() -> TokenStream2 {
+    quote!(::naumachia::scripts::ScriptError)
+}
+
+fn constructors(input: &DeriveInput) -> syn::Result<Vec<Constructor<'_>>> {
+    let constructors = match &input.data {
+        Data::Struct(data) => {
+            let index = plutus_index(&input.attrs)?.unwrap_or(0);
+            vec![Constructor {
+                index,
+                variant: None,
+                fields: &data.fields,
+            }]
+        }
+        Data::Enum(data) => {
+            if data.variants.is_empty() {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "PlutusData can't be derived for an enum without variants",
+                ))
+            }
+            let mut constructors = Vec::new();
+            let mut seen = HashMap::new();
+            for (position, variant) in data.variants.iter().enumerate() {
+                let index = plutus_index(&variant.attrs)?.unwrap_or(position as u64);
+                if let Some(other) = seen.insert(index, &variant.ident) {
+                    let message = format!(
+                        "Constructor index {index} is already used by variant `{other}`"
+                    );
+                    return Err(syn::Error::new_spanned(&variant.ident, message))
+                }
+                constructors.push(Constructor {
+                    index,
+                    variant: Some(&variant.ident),
+                    fields: &variant.fields,
+                });
+            }
+            constructors
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "PlutusData can't be derived for unions",
+            ))
+        }
+    };
+    Ok(constructors)
+}
+
+/// Read the index of a `#[plutus(index = N)]` attribute, if there is one
+fn plutus_index(attrs: &[Attribute]) -> syn::Result<Option<u64>> {
+    let mut index = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("plutus")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("index") {
+                let value: LitInt = meta.value()?.parse()?;
+                index = Some(value.base10_parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("Unsupported plutus attribute, expected `index = N`"))
+            }
+        })?;
+    }
+    Ok(index)
+}
+
+fn with_bounds(
+    generics: &Generics,
+    bounds: impl Fn(&Ident) -> Vec<syn::WherePredicate>,
+) -> Generics {
+    let mut generics = generics.clone();
+    let params: Vec<_> = generics
+        .type_params()
+        .map(|param| param.ident.clone())
+        .collect();
+    let where_clause = generics.make_where_clause();
+    for param in params {
+        where_clause.predicates.extend(bounds(&param));
+    }
+    generics
+}
+
+fn to_plutus_data(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let plutus_data = plutus_data_module();
+    let name = &input.ident;
+    let generics = with_bounds(&input.generics, |param| {
+        vec![parse_quote!(#param: ::core::convert::Into<#plutus_data::PlutusData>)]
+    });
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let arms = constructors(input)?.into_iter().map(|constructor| {
+        let index = constructor.index;
+        let bindings = constructor.bindings();
+        let pattern_bindings: Vec<_> = bindings.iter().map(|b| quote!(#b)).collect();
+        let pattern = constructor.construct(quote!(#name), &pattern_bindings);
+        quote! {
+            #pattern => #plutus_data::PlutusData::Constr(#plutus_data::Constr {
+                constr: #index,
+                fields: ::std::vec![#(::core::convert::Into::into(#bindings)),*],
+            })
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::core::convert::From<#name #ty_generics>
+            for #plutus_data::PlutusData #where_clause
+        {
+            fn from(value: #name #ty_generics) -> Self {
+                match value {
+                    #(#arms,)*
+                }
+            }
+        }
+    })
+}
+
+fn from_plutus_data(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let plutus_data = plutus_data_module();
+    let script_error = script_error();
+    let name = &input.ident;
+    let type_name = name.to_string();
+    let generics = with_bounds(&input.generics, |param| {
+        vec![
+            parse_quote!(#param: ::core::convert::TryFrom<#plutus_data::PlutusData>),
+            parse_quote!(#script_error: ::core::convert::From<
+                <#param as ::core::convert::TryFrom<#plutus_data::PlutusData>>::Error
+            >),
+        ]
+    });
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let arms = constructors(input)?.into_iter().map(|constructor| {
+        let index = constructor.index;
+        let display_name = constructor.display_name(name);
+        let bindings = constructor.bindings();
+        let field_count = bindings.len();
+        let conversions: Vec<_> = constructor
+            .fields
+            .iter()
+            .zip(&bindings)
+            .map(|(field, binding)| {
+                let ty = &field.ty;
+                quote! {
+                    <#ty as ::core::convert::TryFrom<#plutus_data::PlutusData>>::try_from(#binding)?
+                }
+            })
+            .collect();
+        let construct = constructor.construct(quote!(Self), &conversions);
+        quote! {
+            #index => {
+                let [#(#bindings),*]: [#plutus_data::PlutusData; #field_count] =
+                    ::core::convert::TryInto::try_into(fields).map_err(|fields: ::std::vec::Vec<#plutus_data::PlutusData>| {
+                        #script_error::DatumDeserialization(::std::format!(
+                            "{} expects {} fields, found {}",
+                            #display_name,
+                            #field_count,
+                            fields.len(),
+                        ))
+                    })?;
+                ::core::result::Result::Ok(#construct)
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::core::convert::TryFrom<#plutus_data::PlutusData>
+            for #name #ty_generics #where_clause
+        {
+            type Error = #script_error;
+
+            fn try_from(data: #plutus_data::PlutusData) -> ::core::result::Result<Self, Self::Error> {
+                match data {
+                    #plutus_data::PlutusData::Constr(#plutus_data::Constr { constr, fields }) => {
+                        match constr {
+                            #(#arms)*
+                            other => ::core::result::Result::Err(
+                                #script_error::DatumDeserialization(::std::format!(
+                                    "Unknown constructor {} for {}",
+                                    other,
+                                    #type_name,
+                                )),
+                            ),
+                        }
+                    }
+                    other => ::core::result::Result::Err(#script_error::DatumDeserialization(
+                        ::std::format!("Expected a constructor for {}, found {:?}", #type_name, other),
+                    )),
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,246 @@
+#![allow(non_snake_case)]
+
+use naumachia::scripts::{
+    context::PubKeyHash,
+    plutus_validator::plutus_data::{
+        Constr,
+        FromPlutusData,
+        PlutusData,
+        ToPlutusData,
+    },
+    ScriptError,
+};
+use std::fmt::Debug;
+
+#[derive(ToPlutusData, FromPlutusData, Debug, Clone, PartialEq, Eq)]
+struct OutputReference {
+    transaction_id: TransactionId,
+    output_index: u64,
+}
+
+#[derive(ToPlutusData, FromPlutusData, Debug, Clone, PartialEq, Eq)]
+struct TransactionId(Vec<u8>);
+
+#[derive(ToPlutusData, FromPlutusData, Debug, Clone, PartialEq, Eq)]
+enum CheckingAccountDatums {
+    CheckingAccount {
+        owner: PubKeyHash,
+        spend_token_policy: Vec<u8>,
+    },
+    AllowedPuller {
+        owner: PubKeyHash,
+        puller: PubKeyHash,
+        amount_lovelace: u64,
+        next_pull: i64,
+        period: i64,
+    },
+}
+
+#[derive(ToPlutusData, FromPlutusData, Debug, Clone, PartialEq, Eq)]
+enum Action {
+    Deposit(i64),
+    Withdraw {
+        amount: i64,
+        close: bool,
+    },
+    #[plutus(index = 5)]
+    Close,
+    Skip(Vec<u8>),
+}
+
+#[derive(ToPlutusData, FromPlutusData, Debug, Clone, PartialEq, Eq)]
+#[plutus(index = 3)]
+struct Marker;
+
+#[derive(ToPlutusData, FromPlutusData, Debug, Clone, PartialEq, Eq)]
+struct Wrapper<T> {
+    inner: T,
+    unit: (),
+}
+
+fn round_trip<T>(value: T) -> T
+where
+    T: Into<PlutusData> + TryFrom<PlutusData, Error = ScriptError> + Clone,
+{
+    let data: PlutusData = value.into();
+    T::try_from(data).unwrap()
+}
+
+fn constr(constr: u64, fields: Vec<PlutusData>) -> PlutusData {
+    PlutusData::Constr(Constr { constr, fields })
+}
+
+fn assert_round_trips<T>(value: T)
+where
+    T: Into<PlutusData>
+        + TryFrom<PlutusData, Error = ScriptError>
+        + Clone
+        + Debug
+        + PartialEq,
+{
+    assert_eq!(round_trip(value.clone()), value);
+}
+
+#[test]
+fn struct__encodes_fields_in_order_under_constr_0() {
+    // Given
+    let out_ref = OutputReference {
+        transaction_id: TransactionId(vec![1, 2, 3]),
+        output_index: 7,
+    };
+
+    // When
+    let data: PlutusData = out_ref.clone().into();
+
+    // Then
+    let expected = constr(
+        0,
+        vec![
+            constr(0, vec![PlutusData::BoundedBytes(vec![1, 2, 3])]),
+            7i64.into(),
+        ],
+    );
+    assert_eq!(data, expected);
+    assert_round_trips(out_ref);
+}
+
+#[test]
+fn enum__encodes_variant_position_as_constr() {
+    // Given
+    let owner = PubKeyHash::new(&[1; 28]);
+    let puller = PubKeyHash::new(&[2; 28]);
+    let account = CheckingAccountDatums::CheckingAccount {
+        owner: owner.clone(),
+        spend_token_policy: vec![3; 28],
+    };
+    let allowed_puller = CheckingAccountDatums::AllowedPuller {
+        owner: owner.clone(),
+        puller: puller.clone(),
+        amount_lovelace: 10_000_000,
+        next_pull: 1_000,
+        period: -60,
+    };
+
+    // When
+    let account_data: PlutusData = account.clone().into();
+    let puller_data: PlutusData = allowed_puller.clone().into();
+
+    // Then
+    assert_eq!(
+        account_data,
+        constr(0, vec![owner.clone().into(), vec![3; 28].into()])
+    );
+    assert_eq!(
+        puller_data,
+        constr(
+            1,
+            vec![
+                owner.into(),
+                puller.into(),
+                10_000_000u64.into(),
+                1_000i64.into(),
+                (-60i64).into(),
+            ]
+        )
+    );
+    assert_round_trips(account);
+    assert_round_trips(allowed_puller);
+}
+
+#[test]
+fn enum__index_attribute_overrides_position() {
+    // Given
+    let close = Action::Close;
+
+    // When
+    let data: PlutusData = close.into();
+
+    // Then
+    assert_eq!(data, constr(5, vec![]));
+    assert_round_trips(Action::Deposit(-5));
+    assert_round_trips(Action::Withdraw {
+        amount: 10,
+        close: true,
+    });
+    assert_round_trips(Action::Close);
+    assert_round_trips(Action::Skip(vec![4, 2]));
+}
+
+#[test]
+fn enum__variants_after_index_attribute_keep_their_position() {
+    // When
+    let data: PlutusData = Action::Skip(vec![4, 2]).into();
+
+    // Then
+    assert_eq!(data, constr(3, vec![PlutusData::BoundedBytes(vec![4, 2])]));
+}
+
+#[test]
+fn struct__index_attribute_overrides_constr_0() {
+    // When
+    let data: PlutusData = Marker.into();
+
+    // Then
+    assert_eq!(data, constr(3, vec![]));
+    assert_round_trips(Marker);
+}
+
+#[test]
+fn generic_struct__round_trips() {
+    assert_round_trips(Wrapper {
+        inner: Action::Deposit(1),
+        unit: (),
+    });
+    assert_round_trips(Wrapper {
+        inner: true,
+        unit: (),
+    });
+}
+
+#[test]
+fn try_from__rejects_unknown_constructor() {
+    // Given
+    let data = constr(2, vec![]);
+
+    // When
+    let res = CheckingAccountDatums::try_from(data);
+
+    // Then
+    assert!(matches!(res, Err(ScriptError::DatumDeserialization(_))));
+}
+
+#[test]
+fn try_from__rejects_wrong_field_count() {
+    // Given
+    let data = constr(0, vec![PlutusData::BoundedBytes(vec![1])]);
+
+    // When
+    let res = OutputReference::try_from(data);
+
+    // Then
+    assert!(matches!(res, Err(ScriptError::DatumDeserialization(_))));
+}
+
+#[test]
+fn try_from__rejects_wrong_field_type() {
+    // Given
+    let data = constr(0, vec![PlutusData::BoundedBytes(vec![1]), 7i64.into()]);
+
+    // When
+    let res = OutputReference::try_from(data);
+
+    // Then
+    assert!(matches!(res, Err(ScriptError::DatumDeserialization(_))));
+}
+
+#[test]
+fn try_from__rejects_non_constr_data() {
+    // Given
+    let data = PlutusData::BoundedBytes(vec![1]);
+
+    // When
+    let res = Action::try_from(data);
+
+    // Then
+    assert!(matches!(res, Err(ScriptError::DatumDeserialization(_))));
+}
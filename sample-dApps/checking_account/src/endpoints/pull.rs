@@ -116,7 +116,11 @@ pub async fn pull_from_account<LC: LedgerClient<CheckingAccountDatums, ()>>(
 
     let actions = TxActions::v2()
         .with_script_redeem(allow_pull_output, allow_pull_redeemer, allow_pull_script)
-        .with_script_init(new_allow_pull_datum, allow_pull_value, allow_pull_address)
+        .with_script_init_inline_datum(
+            new_allow_pull_datum,
+            allow_pull_value,
+            allow_pull_address,
+        )
         .with_script_redeem(
             checking_account_output,
             checking_account_redeemer,
@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+/// Caching ledger client module
+pub mod caching_ledger_client;
 /// Test ledger client module
 pub mod test_ledger_client;
 
@@ -110,6 +112,10 @@ pub enum LedgerClientError {
     ValidityRange(String),
     #[error("While getting last block time: {0:?}")]
     FailedToGetBlockTime(Box<dyn error::Error + Send + Sync>),
+    #[error("Error in test ledger storage: {0:?}")]
+    Storage(Box<dyn error::Error + Send + Sync>),
+    #[error("Error in ledger client cache: {0:?}")]
+    Cache(Box<dyn error::Error + Send + Sync>),
 }
 
 #[allow(missing_docs)]
@@ -0,0 +1,312 @@
+use crate::{
+    ledger_client::{
+        LedgerClient,
+        LedgerClientError,
+        LedgerClientResult,
+    },
+    output::Output,
+    transaction::{
+        TxId,
+        UnbuiltTransaction,
+    },
+};
+use async_trait::async_trait;
+use pallas_addresses::{
+    Address,
+    Network,
+};
+use std::{
+    collections::{
+        HashMap,
+        HashSet,
+    },
+    sync::{
+        Mutex,
+        MutexGuard,
+    },
+    time::{
+        Duration,
+        Instant,
+    },
+};
+use thiserror::Error;
+
+#[cfg(test)]
+mod tests;
+
+/// [`LedgerClient`] decorator that caches the outputs at each address for `ttl`, so repeated
+/// lookups of the same addresses don't all go to the backend. Everything else is passed
+/// through to the inner client.
+///
+/// Issuing a transaction through the [`CachingLedgerClient`] drops the cached outputs of every
+/// address the transaction spends from or pays to, as well as those of the signer. Transactions
+/// issued any other way are only seen once the cached outputs expire. Outputs fetched while
+/// their address is invalidated aren't cached, as they might predate the transaction.
+///
+/// ```ignore
+///     let backend = get_trireme_ledger_client_from_file().await?;
+///     let backend = CachingLedgerClient::new(backend, Duration::from_secs(20));
+///
+///     let contract = SmartContract::new(CheckingAccountLogic, backend);
+/// ```
+pub struct CachingLedgerClient<LC, Datum> {
+    inner: LC,
+    ttl: Duration,
+    cache: Mutex<Cache<Datum>>,
+    stats: Mutex<CacheStats>,
+}
+
+/// Outputs at an address, either all of them or limited by a count
+type CacheKey = (Vec<u8>, Option<usize>);
+
+/// Times [`CachingLedgerClient::clear`] was called, and times the address was invalidated
+type Epoch = (u64, u64);
+
+struct Cache<Datum> {
+    entries: HashMap<CacheKey, CacheEntry<Datum>>,
+    clears: u64,
+    invalidations: HashMap<Vec<u8>, u64>,
+}
+
+impl<Datum> Cache<Datum> {
+    fn epoch(&self, address: &[u8]) -> Epoch {
+        let invalidations = self.invalidations.get(address).copied().unwrap_or(0);
+        (self.clears, invalidations)
+    }
+}
+
+struct CacheEntry<Datum> {
+    outputs: Vec<Output<Datum>>,
+    fetched_at: Instant,
+}
+
+/// Counts of how the cache of a [`CachingLedgerClient`] has been used
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Lookups answered from the cache
+    pub hits: u64,
+    /// Lookups that went to the inner client, including those for expired entries
+    pub misses: u64,
+    /// Entries dropped because they expired
+    pub expirations: u64,
+    /// Entries dropped because a transaction was issued that affects them
+    pub invalidations: u64,
+}
+
+impl CacheStats {
+    /// Share of the lookups answered from the cache, `0.0` before any lookup
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+#[allow(missing_docs)]
+#[derive(Debug, Error)]
+pub enum CachingLedgerClientError {
+    #[error("Cache lock poisoned")]
+    Poisoned,
+}
+
+impl From<CachingLedgerClientError> for LedgerClientError {
+    fn from(error: CachingLedgerClientError) -> Self {
+        LedgerClientError::Cache(Box::new(error))
+    }
+}
+
+impl<LC, Datum> CachingLedgerClient<LC, Datum> {
+    /// Wrap `inner`, caching the outputs at each address for `ttl`
+    pub fn new(inner: LC, ttl: Duration) -> Self {
+        CachingLedgerClient {
+            inner,
+            ttl,
+            cache: Mutex::new(Cache {
+                entries: HashMap::new(),
+                clears: 0,
+                invalidations: HashMap::new(),
+            }),
+            stats: Mutex::new(CacheStats::default()),
+        }
+    }
+
+    /// Getter for the wrapped client
+    pub fn inner(&self) -> &LC {
+        &self.inner
+    }
+
+    /// Getter for how long outputs stay cached
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Current usage counts of the cache
+    pub fn stats(&self) -> LedgerClientResult<CacheStats> {
+        Ok(*lock(&self.stats)?)
+    }
+
+    /// Drop all cached outputs. The stats are kept
+    pub fn clear(&self) -> LedgerClientResult<()> {
+        let mut cache = lock(&self.cache)?;
+        cache.entries.clear();
+        cache.clears += 1;
+        Ok(())
+    }
+
+    /// Drop the cached outputs of `address`, e.g. after spending from it some other way
+    pub fn invalidate(&self, address: &Address) -> LedgerClientResult<()> {
+        let addresses = HashSet::from([address.to_vec()]);
+        self.invalidate_all(&addresses)
+    }
+
+    fn invalidate_all(&self, addresses: &HashSet<Vec<u8>>) -> LedgerClientResult<()> {
+        let mut cache = lock(&self.cache)?;
+        for address in addresses {
+            *cache.invalidations.entry(address.clone()).or_insert(0) += 1;
+        }
+        let before = cache.entries.len();
+        cache
+            .entries
+            .retain(|(address, _), _| !addresses.contains(address));
+        let dropped = before - cache.entries.len();
+        lock(&self.stats)?.invalidations += dropped as u64;
+        Ok(())
+    }
+
+    fn epoch(&self, address: &[u8]) -> LedgerClientResult<Epoch> {
+        Ok(lock(&self.cache)?.epoch(address))
+    }
+}
+
+impl<LC, Datum: Clone> CachingLedgerClient<LC, Datum> {
+    fn cached(&self, key: &CacheKey) -> LedgerClientResult<Option<Vec<Output<Datum>>>> {
+        let mut cache = lock(&self.cache)?;
+        let mut stats = lock(&self.stats)?;
+        let outputs = match cache.entries.get(key) {
+            Some(entry) if entry.fetched_at.elapsed() < self.ttl => {
+                Some(entry.outputs.clone())
+            }
+            Some(_) => {
+                cache.entries.remove(key);
+                stats.expirations += 1;
+                None
+            }
+            None => None,
+        };
+        match outputs {
+            Some(_) => stats.hits += 1,
+            None => stats.misses += 1,
+        }
+        Ok(outputs)
+    }
+
+    /// Only stores the `outputs` if their address wasn't invalidated since `fetched_in`, the
+    /// epoch of the address when the fetch started
+    fn store(
+        &self,
+        key: CacheKey,
+        fetched_in: Epoch,
+        outputs: &[Output<Datum>],
+    ) -> LedgerClientResult<()> {
+        let mut cache = lock(&self.cache)?;
+        if cache.epoch(&key.0) != fetched_in {
+            return Ok(())
+        }
+        let entry = CacheEntry {
+            outputs: outputs.to_vec(),
+            fetched_at: Instant::now(),
+        };
+        cache.entries.insert(key, entry);
+        Ok(())
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> LedgerClientResult<MutexGuard<T>> {
+    mutex
+        .lock()
+        .map_err(|_| CachingLedgerClientError::Poisoned.into())
+}
+
+/// Addresses whose outputs change when `tx` is issued, besides the signer's
+fn affected_addresses<Datum, Redeemer>(
+    tx: &UnbuiltTransaction<Datum, Redeemer>,
+) -> HashSet<Vec<u8>> {
+    let spent_from = tx
+        .script_inputs()
+        .iter()
+        .map(|(input, _, _)| input.owner())
+        .chain(tx.specific_wallet_inputs.iter().map(Output::owner));
+    let paid_to = tx.unbuilt_outputs().iter().map(|output| output.owner());
+    spent_from
+        .chain(paid_to)
+        .map(|address| address.to_vec())
+        .collect()
+}
+
+#[async_trait]
+impl<LC, Datum, Redeemer> LedgerClient<Datum, Redeemer> for CachingLedgerClient<LC, Datum>
+where
+    LC: LedgerClient<Datum, Redeemer>,
+    Datum: Clone + Send + Sync,
+    Redeemer: Send + Sync,
+{
+    async fn signer_base_address(&self) -> LedgerClientResult<Address> {
+        self.inner.signer_base_address().await
+    }
+
+    async fn outputs_at_address(
+        &self,
+        address: &Address,
+        count: usize,
+    ) -> LedgerClientResult<Vec<Output<Datum>>> {
+        let key = (address.to_vec(), Some(count));
+        if let Some(outputs) = self.cached(&key)? {
+            return Ok(outputs);
+        }
+        let epoch = self.epoch(&key.0)?;
+        let outputs = self.inner.outputs_at_address(address, count).await?;
+        self.store(key, epoch, &outputs)?;
+        Ok(outputs)
+    }
+
+    async fn all_outputs_at_address(
+        &self,
+        address: &Address,
+    ) -> LedgerClientResult<Vec<Output<Datum>>> {
+        let key = (address.to_vec(), None);
+        if let Some(outputs) = self.cached(&key)? {
+            return Ok(outputs);
+        }
+        let epoch = self.epoch(&key.0)?;
+        let outputs = self.inner.all_outputs_at_address(address).await?;
+        self.store(key, epoch, &outputs)?;
+        Ok(outputs)
+    }
+
+    async fn issue(
+        &self,
+        tx: UnbuiltTransaction<Datum, Redeemer>,
+    ) -> LedgerClientResult<TxId> {
+        let mut affected = affected_addresses(&tx);
+        affected.insert(self.inner.signer_base_address().await?.to_vec());
+        let res = self.inner.issue(tx).await;
+        // Even a failed issue might have reached the chain, e.g. if it timed out
+        self.invalidate_all(&affected)?;
+        res
+    }
+
+    async fn network(&self) -> LedgerClientResult<Network> {
+        self.inner.network().await
+    }
+
+    async fn last_block_time_secs(&self) -> LedgerClientResult<i64> {
+        self.inner.last_block_time_secs().await
+    }
+
+    async fn current_time_secs(&self) -> LedgerClientResult<i64> {
+        self.inner.current_time_secs().await
+    }
+}
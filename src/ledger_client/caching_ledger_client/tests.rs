@@ -0,0 +1,248 @@
+#![allow(non_snake_case)]
+
+use super::*;
+use crate::{
+    ledger_client::test_ledger_client::{
+        in_memory_storage::InMemoryStorage,
+        TestLedgerClient,
+        TestLedgerClientBuilder,
+    },
+    output::UnbuiltOutput,
+    transaction::TransactionVersion,
+    values::Values,
+    PolicyId,
+};
+use tokio::sync::Notify;
+
+const ALICE: &str = "addr_test1qrmezjhpelwzvz83wjl0e6mx766de7j3nksu2338s00yzx870xyxfa97xyz2zn5rknyntu5g0c66s7ktjnx0p6f0an6s3dyxwr";
+const BOB: &str = "addr_test1qzvrhz9v6lwcr26a52y8mmk2nzq37lky68359keq3dgth4lkzpnnjv8vf98m20lhqdzl60mcftq7r2lc4xtcsv0w6xjstag0ua";
+const CHARLIE: &str = "addr_test1qpmtp5t0t5y6cqkaz7rfsyrx7mld77kpvksgkwm0p7en7qum7a589n30e80tclzrrnj8qr4qvzj6al0vpgtnmrkkksnqd8upj0";
+
+const TTL: Duration = Duration::from_secs(60);
+
+fn address(bech32: &str) -> Address {
+    Address::from_bech32(bech32).unwrap()
+}
+
+fn ledger() -> TestLedgerClient<(), (), InMemoryStorage<()>> {
+    TestLedgerClientBuilder::new(&address(ALICE))
+        .start_output(&address(ALICE))
+        .with_value(PolicyId::Lovelace, 10_000_000)
+        .finish_output()
+        .start_output(&address(CHARLIE))
+        .with_value(PolicyId::Lovelace, 5_000_000)
+        .finish_output()
+        .build_in_memory()
+}
+
+fn caching_ledger(
+    ttl: Duration,
+) -> CachingLedgerClient<TestLedgerClient<(), (), InMemoryStorage<()>>, ()> {
+    CachingLedgerClient::new(ledger(), ttl)
+}
+
+fn transfer_tx(recipient: &Address, amount: u64) -> UnbuiltTransaction<(), ()> {
+    let mut values = Values::default();
+    values.add_one_value(&PolicyId::Lovelace, amount);
+    let new_output = UnbuiltOutput::new_wallet(recipient.clone(), values);
+    UnbuiltTransaction {
+        script_version: TransactionVersion::V2,
+        script_inputs: vec![],
+        unbuilt_outputs: vec![new_output],
+        minting: Default::default(),
+        specific_wallet_inputs: vec![],
+        valid_range: (None, None),
+    }
+}
+
+/// Ledger whose lookups of all the outputs at an address wait to be released, so the cache
+/// can be invalidated while a lookup is in flight
+struct PausedLedger {
+    inner: TestLedgerClient<(), (), InMemoryStorage<()>>,
+    fetched: Notify,
+    release: Notify,
+}
+
+#[async_trait]
+impl LedgerClient<(), ()> for PausedLedger {
+    async fn signer_base_address(&self) -> LedgerClientResult<Address> {
+        self.inner.signer_base_address().await
+    }
+
+    async fn outputs_at_address(
+        &self,
+        address: &Address,
+        count: usize,
+    ) -> LedgerClientResult<Vec<Output<()>>> {
+        self.inner.outputs_at_address(address, count).await
+    }
+
+    async fn all_outputs_at_address(
+        &self,
+        address: &Address,
+    ) -> LedgerClientResult<Vec<Output<()>>> {
+        let outputs = self.inner.all_outputs_at_address(address).await;
+        self.fetched.notify_one();
+        self.release.notified().await;
+        outputs
+    }
+
+    async fn issue(&self, tx: UnbuiltTransaction<(), ()>) -> LedgerClientResult<TxId> {
+        self.inner.issue(tx).await
+    }
+
+    async fn network(&self) -> LedgerClientResult<Network> {
+        self.inner.network().await
+    }
+
+    async fn last_block_time_secs(&self) -> LedgerClientResult<i64> {
+        self.inner.last_block_time_secs().await
+    }
+
+    async fn current_time_secs(&self) -> LedgerClientResult<i64> {
+        self.inner.current_time_secs().await
+    }
+}
+
+#[tokio::test]
+async fn all_outputs_at_address__repeated_lookup_is_a_hit() {
+    // Given
+    let client = caching_ledger(TTL);
+    let alice = address(ALICE);
+
+    // When
+    let first = client.all_outputs_at_address(&alice).await.unwrap();
+    let second = client.all_outputs_at_address(&alice).await.unwrap();
+
+    // Then
+    assert_eq!(first, second);
+    let stats = client.stats().unwrap();
+    assert_eq!(stats.misses, 1);
+    assert_eq!(stats.hits, 1);
+    assert_eq!(stats.hit_rate(), 0.5);
+}
+
+#[tokio::test]
+async fn outputs_at_address__caches_each_count_separately() {
+    // Given
+    let client = caching_ledger(TTL);
+    let alice = address(ALICE);
+    client.all_outputs_at_address(&alice).await.unwrap();
+
+    // When
+    let outputs = client.outputs_at_address(&alice, 1).await.unwrap();
+
+    // Then
+    assert_eq!(outputs.len(), 1);
+    let stats = client.stats().unwrap();
+    assert_eq!(stats.misses, 2);
+    assert_eq!(stats.hits, 0);
+}
+
+#[tokio::test]
+async fn all_outputs_at_address__refetches_expired_outputs() {
+    // Given
+    let client = caching_ledger(Duration::ZERO);
+    let alice = address(ALICE);
+    client.all_outputs_at_address(&alice).await.unwrap();
+
+    // When
+    client.all_outputs_at_address(&alice).await.unwrap();
+
+    // Then
+    let stats = client.stats().unwrap();
+    assert_eq!(stats.misses, 2);
+    assert_eq!(stats.expirations, 1);
+}
+
+#[tokio::test]
+async fn issue__invalidates_signer_and_recipient() {
+    // Given
+    let client = caching_ledger(TTL);
+    let alice = address(ALICE);
+    let bob = address(BOB);
+    client.all_outputs_at_address(&alice).await.unwrap();
+    client.all_outputs_at_address(&bob).await.unwrap();
+
+    // When
+    client.issue(transfer_tx(&bob, 1_000_000)).await.unwrap();
+
+    // Then
+    let bob_outputs = client.all_outputs_at_address(&bob).await.unwrap();
+    assert_eq!(bob_outputs.len(), 1);
+    let alice_balance = client
+        .balance_at_address(&alice, &PolicyId::Lovelace)
+        .await
+        .unwrap();
+    assert_eq!(alice_balance, 9_000_000);
+    let stats = client.stats().unwrap();
+    assert_eq!(stats.invalidations, 2);
+    assert_eq!(stats.hits, 0);
+}
+
+#[tokio::test]
+async fn issue__keeps_unaffected_addresses() {
+    // Given
+    let client = caching_ledger(TTL);
+    let charlie = address(CHARLIE);
+    client.all_outputs_at_address(&charlie).await.unwrap();
+
+    // When
+    client
+        .issue(transfer_tx(&address(BOB), 1_000_000))
+        .await
+        .unwrap();
+
+    // Then
+    client.all_outputs_at_address(&charlie).await.unwrap();
+    let stats = client.stats().unwrap();
+    assert_eq!(stats.hits, 1);
+    assert_eq!(stats.invalidations, 0);
+}
+
+#[tokio::test]
+async fn invalidate__drops_all_counts_for_address() {
+    // Given
+    let client = caching_ledger(TTL);
+    let alice = address(ALICE);
+    client.all_outputs_at_address(&alice).await.unwrap();
+    client.outputs_at_address(&alice, 1).await.unwrap();
+
+    // When
+    client.invalidate(&alice).unwrap();
+
+    // Then
+    client.all_outputs_at_address(&alice).await.unwrap();
+    let stats = client.stats().unwrap();
+    assert_eq!(stats.invalidations, 2);
+    assert_eq!(stats.misses, 3);
+}
+
+#[tokio::test]
+async fn all_outputs_at_address__does_not_cache_outputs_invalidated_during_lookup() {
+    // Given
+    let paused = PausedLedger {
+        inner: ledger(),
+        fetched: Notify::new(),
+        release: Notify::new(),
+    };
+    let client = CachingLedgerClient::new(paused, TTL);
+    let alice = address(ALICE);
+
+    // When
+    let lookup = client.all_outputs_at_address(&alice);
+    let invalidate = async {
+        client.inner().fetched.notified().await;
+        client.invalidate(&alice).unwrap();
+        client.inner().release.notify_one();
+    };
+    let (outputs, _) = tokio::join!(lookup, invalidate);
+    outputs.unwrap();
+
+    // Then
+    client.inner().release.notify_one();
+    client.all_outputs_at_address(&alice).await.unwrap();
+    let stats = client.stats().unwrap();
+    assert_eq!(stats.misses, 2);
+    assert_eq!(stats.hits, 0);
+}
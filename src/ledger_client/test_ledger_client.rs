@@ -1,30 +1,43 @@
 use std::{
+    collections::BTreeMap,
+    fmt,
     fmt::Debug,
     hash::Hash,
     marker::PhantomData,
     path::Path,
     sync::{
-        Arc,
         Mutex,
+        PoisonError,
     },
 };
 
 use crate::{
     ledger_client::{
-        test_ledger_client::in_memory_storage::InMemoryStorage,
+        test_ledger_client::{
+            in_memory_storage::InMemoryStorage,
+            snapshot::TestLedgerSnapshot,
+            tx_log::{
+                RecordedMint,
+                RecordedScriptInput,
+                RecordedTx,
+            },
+        },
         LedgerClient,
         LedgerClientError,
         LedgerClientResult,
     },
     output::{
+        DatumAttachment,
         DatumKind,
         Output,
+        OutputId,
         UnbuiltOutput,
     },
     scripts::{
         context::{
             pub_key_hash_from_address_if_available,
             CtxDatum,
+            CtxMint,
             CtxOutput,
             CtxOutputReference,
             CtxScriptPurpose,
@@ -36,6 +49,7 @@ use crate::{
         plutus_validator::plutus_data::PlutusData,
     },
     transaction::TxId,
+    trireme_ledger_client::cml_client::network_settings::NetworkSettings,
     values::Values,
     PolicyId,
     UnbuiltTransaction,
@@ -46,13 +60,19 @@ use pallas_addresses::{
     Address,
     Network,
 };
-use rand::Rng;
+use sqlite_storage::SqliteStorage;
 use thiserror::Error;
 
 /// In-memory storage module
 pub mod in_memory_storage;
 /// Local persisted storage module
 pub mod local_persisted_storage;
+/// Ledger snapshot module
+pub mod snapshot;
+/// SQLite storage module
+pub mod sqlite_storage;
+/// Recorded transaction log module
+pub mod tx_log;
 
 #[cfg(test)]
 mod tests;
@@ -78,6 +98,7 @@ pub struct TestLedgerClientBuilder<Datum, Redeemer> {
     outputs: Vec<(Address, Output<Datum>)>,
     starting_time: i64,
     block_length: i64,
+    network_settings: Option<NetworkSettings>,
     _redeemer: PhantomData<Redeemer>,
 }
 
@@ -93,6 +114,7 @@ where
             outputs: Vec::new(),
             starting_time: 0,
             block_length: 20,
+            network_settings: None,
             _redeemer: PhantomData,
         }
     }
@@ -126,16 +148,77 @@ where
         self
     }
 
+    /// Run the ledger client on slots, using the same slot conversions as the CML client does
+    /// on-chain. See [`TestLedgerClient::set_network_settings`].
+    ///
+    /// If the starting time is before the first slot of the network, it is moved up to the
+    /// first slot. [`TestLedgerClientBuilder::with_starting_time`] can still be used after this.
+    pub fn with_network_settings(mut self, network_settings: NetworkSettings) -> Self {
+        self.starting_time = self
+            .starting_time
+            .max(network_settings.starting_slot_time());
+        self.network_settings = Some(network_settings);
+        self
+    }
+
     /// Build the [`TestLedgerClient`] with an _ephemeral_ [`InMemoryStorage`] for [`TestLedgerStorage`]
     pub fn build_in_memory(
         &self,
     ) -> TestLedgerClient<Datum, Redeemer, InMemoryStorage<Datum>> {
-        TestLedgerClient::new_in_memory(
+        let storage = InMemoryStorage::new(
             self.signer.clone(),
             self.outputs.clone(),
-            self.block_length,
             self.starting_time,
-        )
+            self.block_length,
+        );
+        let storage = match self.network_settings {
+            Some(network_settings) => storage.with_network_settings(network_settings),
+            None => storage,
+        };
+        TestLedgerClient {
+            storage,
+            tx_log: Default::default(),
+            _datum: Default::default(),
+            _redeemer: Default::default(),
+        }
+    }
+}
+
+impl<Datum, Redeemer> TestLedgerClientBuilder<Datum, Redeemer>
+where
+    Datum:
+        Clone + PartialEq + Debug + Send + Sync + Into<PlutusData> + TryFrom<PlutusData>,
+    Redeemer: Clone + Eq + PartialEq + Debug + Hash + Send + Sync,
+{
+    /// Constructor for a [`TestLedgerClientBuilder`] that starts with the state captured in a
+    /// [`TestLedgerSnapshot`]. More outputs can be added on top with the builder methods.
+    pub fn from_snapshot(
+        snapshot: &TestLedgerSnapshot,
+    ) -> LedgerClientResult<TestLedgerClientBuilder<Datum, Redeemer>> {
+        let signer = snapshot.signer()?;
+        let outputs = snapshot
+            .outputs()?
+            .into_iter()
+            .map(|output: Output<Datum>| (output.owner(), output))
+            .collect();
+        let builder = TestLedgerClientBuilder {
+            signer,
+            outputs,
+            starting_time: snapshot.current_time(),
+            block_length: snapshot.block_length(),
+            network_settings: snapshot.network_settings(),
+            _redeemer: PhantomData,
+        };
+        Ok(builder)
+    }
+
+    /// Constructor for a [`TestLedgerClientBuilder`] that starts with the state of a snapshot
+    /// saved with [`TestLedgerSnapshot::save`]
+    pub fn from_snapshot_file<P: AsRef<Path>>(
+        path: P,
+    ) -> LedgerClientResult<TestLedgerClientBuilder<Datum, Redeemer>> {
+        let snapshot = TestLedgerSnapshot::load(path)?;
+        Self::from_snapshot(&snapshot)
     }
 }
 
@@ -185,7 +268,8 @@ where
             datum,
         } = self;
         let address = owner.clone();
-        let tx_hash = arbitrary_tx_id().to_vec();
+        let position = inner.outputs.len() as u64;
+        let tx_hash = starting_tx_hash(&owner, &values, datum.clone(), position);
         let index = 0;
         let output = if let Some(datum) = datum {
             Output::new_validator(tx_hash, index, address, values, datum)
@@ -206,14 +290,26 @@ enum TestLCError {
     NotEnoughInputs,
     #[error("The same input is listed twice")]
     DuplicateInput,
+    #[error("Input is not on the ledger")]
+    UnknownInput,
     #[error("Can't read Datum")]
     WrongDatum,
+    #[error("Datum doesn't match the datum hash of the output being spent")]
+    MissingDatumWitness,
+    #[error("Datum doesn't match the datum of the output being spent:\n{0}")]
+    DatumMismatch(DatumMismatch),
     #[error("Tx too early")]
     TxTooEarly,
     #[error("Tx too late")]
     TxTooLate,
     #[error("Not a valid signer address")]
     InvalidAddress,
+    #[error("Address can't be encoded as bech32: {0}")]
+    Bech32(String),
+    #[error("Ledger isn't running on slots. Specify `NetworkSettings` to use slots")]
+    NotSlotBased,
+    #[error("Time {0:?} is before the first slot of the network")]
+    BeforeFirstSlot(i64),
 }
 
 /// Interface for the storage of the [`TestLedgerClient`]
@@ -232,10 +328,26 @@ pub trait TestLedgerStorage<Datum> {
         &self,
         address: &Address,
     ) -> LedgerClientResult<Vec<Output<Datum>>>;
+    /// Get the output with the given id, if it is still on the ledger
+    async fn output_by_id(
+        &self,
+        id: &OutputId,
+    ) -> LedgerClientResult<Option<Output<Datum>>>;
     /// Remove the given output from the storage
     async fn remove_output(&self, output: &Output<Datum>) -> LedgerClientResult<()>;
     /// Add the given output to the storage
     async fn add_output(&self, output: &Output<Datum>) -> LedgerClientResult<()>;
+    /// Remove the `consumed` outputs, add the `produced` outputs and set the current time to
+    /// `new_time`, as the result of issuing a transaction.
+    ///
+    /// This must be atomic: if any part fails, e.g. a consumed output is missing, the storage is
+    /// left exactly as it was, and no other caller may observe the change half-applied.
+    async fn apply_transaction(
+        &self,
+        consumed: &[Output<Datum>],
+        produced: &[Output<Datum>],
+        new_time: i64,
+    ) -> LedgerClientResult<()>;
     /// Get the current time in seconds
     async fn current_time(&self) -> LedgerClientResult<i64>;
     /// Set the current time in seconds
@@ -244,6 +356,21 @@ pub trait TestLedgerStorage<Datum> {
     async fn get_block_length(&self) -> LedgerClientResult<i64>;
     /// Get the network identifier for the ledger
     async fn network(&self) -> LedgerClientResult<Network>;
+    /// Get the [`NetworkSettings`] if the ledger is running on slots
+    async fn network_settings(&self) -> LedgerClientResult<Option<NetworkSettings>>;
+    /// Run the ledger on slots with the given [`NetworkSettings`], or on raw POSIX seconds
+    async fn set_network_settings(
+        &self,
+        network_settings: Option<NetworkSettings>,
+    ) -> LedgerClientResult<()>;
+    /// Capture the entire state of the ledger
+    async fn snapshot(&self) -> LedgerClientResult<TestLedgerSnapshot>
+    where
+        Datum: Into<PlutusData>;
+    /// Replace the entire state of the ledger with the given snapshot
+    async fn restore(&self, snapshot: TestLedgerSnapshot) -> LedgerClientResult<()>
+    where
+        Datum: TryFrom<PlutusData>;
 }
 
 /// Implementation of the [`LedgerClient`] trait that mocks the ledger. Typically, the best way to
@@ -251,6 +378,7 @@ pub trait TestLedgerStorage<Datum> {
 #[derive(Debug)]
 pub struct TestLedgerClient<Datum, Redeemer, Storage: TestLedgerStorage<Datum>> {
     storage: Storage,
+    tx_log: Mutex<Vec<RecordedTx<Datum, Redeemer>>>,
     _datum: PhantomData<Datum>,
     _redeemer: PhantomData<Redeemer>,
 }
@@ -266,14 +394,10 @@ where
         block_length: i64,
         starting_time: i64,
     ) -> Self {
-        let storage = InMemoryStorage {
-            signer,
-            outputs: Arc::new(Mutex::new(outputs)),
-            current_posix_time: Arc::new(Mutex::new(starting_time)),
-            block_length,
-        };
+        let storage = InMemoryStorage::new(signer, outputs, starting_time, block_length);
         TestLedgerClient {
             storage,
+            tx_log: Default::default(),
             _datum: Default::default(),
             _redeemer: Default::default(),
         }
@@ -286,7 +410,11 @@ where
     T: AsRef<Path> + Send + Sync,
 {
     /// Constructor for the [`TestLedgerClient`] with a [`LocalPersistedStorage`]
-    pub fn new_local_persisted(dir: T, signer: &Address, starting_amount: u64) -> Self {
+    pub fn new_local_persisted(
+        dir: T,
+        signer: &Address,
+        starting_amount: u64,
+    ) -> LedgerClientResult<Self> {
         let signer_name = "Alice";
         let block_length = 20;
         let starting_time = 0;
@@ -297,24 +425,70 @@ where
             starting_amount,
             starting_time,
             block_length,
-        );
-        let _ = storage.get_data();
-        TestLedgerClient {
+        )?;
+        let ledger_client = TestLedgerClient {
             storage,
+            tx_log: Default::default(),
             _datum: Default::default(),
             _redeemer: Default::default(),
-        }
+        };
+        Ok(ledger_client)
     }
 
     /// Constructor for the [`TestLedgerClient`] with a [`LocalPersistedStorage`] that loads the data from the given directory
-    pub fn load_local_persisted(dir: T) -> Self {
-        let storage = LocalPersistedStorage::load(dir);
-        let _ = storage.get_data();
-        TestLedgerClient {
+    pub fn load_local_persisted(dir: T) -> LedgerClientResult<Self> {
+        let storage = LocalPersistedStorage::load(dir)?;
+        let ledger_client = TestLedgerClient {
             storage,
+            tx_log: Default::default(),
             _datum: Default::default(),
             _redeemer: Default::default(),
-        }
+        };
+        Ok(ledger_client)
+    }
+}
+
+impl<T, Datum, Redeemer> TestLedgerClient<Datum, Redeemer, SqliteStorage<T, Datum>>
+where
+    Datum: Clone + Send + Sync + PartialEq + Into<PlutusData> + TryFrom<PlutusData>,
+    T: AsRef<Path> + Send + Sync,
+{
+    /// Constructor for the [`TestLedgerClient`] with a [`SqliteStorage`]
+    pub fn new_sqlite(
+        dir: T,
+        signer: &Address,
+        starting_amount: u64,
+    ) -> LedgerClientResult<Self> {
+        let signer_name = "Alice";
+        let block_length = 20;
+        let starting_time = 0;
+        let storage = SqliteStorage::init(
+            dir,
+            signer_name,
+            signer,
+            starting_amount,
+            starting_time,
+            block_length,
+        )?;
+        let ledger_client = TestLedgerClient {
+            storage,
+            tx_log: Default::default(),
+            _datum: Default::default(),
+            _redeemer: Default::default(),
+        };
+        Ok(ledger_client)
+    }
+
+    /// Constructor for the [`TestLedgerClient`] with a [`SqliteStorage`] that loads the database from the given directory
+    pub fn load_sqlite(dir: T) -> LedgerClientResult<Self> {
+        let storage = SqliteStorage::load(dir)?;
+        let ledger_client = TestLedgerClient {
+            storage,
+            tx_log: Default::default(),
+            _datum: Default::default(),
+            _redeemer: Default::default(),
+        };
+        Ok(ledger_client)
     }
 }
 
@@ -323,6 +497,42 @@ where
     Datum: Clone + Send + Sync + PartialEq,
     Storage: TestLedgerStorage<Datum> + Send + Sync,
 {
+    /// Run the ledger on slots instead of raw POSIX seconds, using the given
+    /// [`NetworkSettings`], e.g. `NetworkSettings::from(Network::Preprod)`, or
+    /// [`NetworkSettings::new`] for a custom network. The settings are kept in the storage, so
+    /// a persisted ledger stays on slots when it's loaded again.
+    ///
+    /// Valid ranges are converted to slots exactly like the CML client does before submitting
+    /// to the chain, so scripts see the same rounding in tests as on-chain. Lower bounds are
+    /// inclusive, upper bounds are exclusive, and bounds before the first slot are rejected.
+    pub async fn set_network_settings(
+        &self,
+        network_settings: NetworkSettings,
+    ) -> LedgerClientResult<()> {
+        self.storage
+            .set_network_settings(Some(network_settings))
+            .await
+    }
+
+    /// Getter for the [`NetworkSettings`] if the ledger is running on slots
+    pub async fn network_settings(&self) -> LedgerClientResult<Option<NetworkSettings>> {
+        self.storage.network_settings().await
+    }
+
+    /// Get the current slot. Fails if the ledger isn't running on slots
+    pub async fn current_slot(&self) -> LedgerClientResult<u64> {
+        let network_settings = self
+            .network_settings()
+            .await?
+            .ok_or(TestLCError::NotSlotBased)
+            .map_err(|e| LedgerClientError::CurrentTime(Box::new(e)))?;
+        let current_time = self.storage.current_time().await?;
+        network_settings
+            .slot_from_posix(current_time)
+            .ok_or(TestLCError::BeforeFirstSlot(current_time))
+            .map_err(|e| LedgerClientError::CurrentTime(Box::new(e)))
+    }
+
     /// Get the current time in seconds
     pub async fn current_time_secs(&self) -> LedgerClientResult<i64> {
         self.storage.current_time().await
@@ -346,6 +556,65 @@ where
         let new_time = advanced_time + current_time;
         self.storage.set_current_time(new_time).await
     }
+
+    /// Capture the entire state of the ledger so it can be restored later with
+    /// [`TestLedgerClient::restore`]
+    pub async fn snapshot(&self) -> LedgerClientResult<TestLedgerSnapshot>
+    where
+        Datum: Into<PlutusData>,
+    {
+        self.storage.snapshot().await
+    }
+
+    /// Revert the ledger to the state captured in `snapshot`. The [`TestLedgerClient::tx_log`]
+    /// is left untouched.
+    pub async fn restore(&self, snapshot: TestLedgerSnapshot) -> LedgerClientResult<()>
+    where
+        Datum: TryFrom<PlutusData>,
+    {
+        self.storage.restore(snapshot).await
+    }
+
+    /// Called once the transaction is already on the ledger, so it can't fail. A poisoned lock
+    /// only means another thread panicked mid-push, which leaves the log itself intact
+    fn record_tx(&self, recorded: RecordedTx<Datum, Redeemer>) {
+        self.tx_log
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(recorded);
+    }
+}
+
+impl<Datum, Redeemer, Storage> TestLedgerClient<Datum, Redeemer, Storage>
+where
+    Datum: Clone + Send + Sync + PartialEq,
+    Redeemer: Clone,
+    Storage: TestLedgerStorage<Datum> + Send + Sync,
+{
+    /// Every transaction successfully issued by this client, oldest first.
+    ///
+    /// The log is only kept in memory, even when the ledger itself is persisted, e.g. with
+    /// [`LocalPersistedStorage`] or [`SqliteStorage`](sqlite_storage::SqliteStorage). It starts
+    /// empty for every new client, so transactions issued by an earlier process or by another
+    /// client sharing the same storage are not in it.
+    pub fn tx_log(&self) -> LedgerClientResult<Vec<RecordedTx<Datum, Redeemer>>> {
+        let log = self
+            .tx_log
+            .lock()
+            .map_err(|e| TestLCError::Mutex(format! {"{e:?}"}))
+            .map_err(|e| LedgerClientError::Storage(Box::new(e)))?;
+        Ok(log.clone())
+    }
+
+    /// The most recent transaction successfully issued by this client, if any
+    pub fn last_tx(&self) -> LedgerClientResult<Option<RecordedTx<Datum, Redeemer>>> {
+        let log = self
+            .tx_log
+            .lock()
+            .map_err(|e| TestLCError::Mutex(format! {"{e:?}"}))
+            .map_err(|e| LedgerClientError::Storage(Box::new(e)))?;
+        Ok(log.last().cloned())
+    }
 }
 
 #[async_trait]
@@ -382,8 +651,16 @@ where
         // Setup
         let valid_range = tx.valid_range;
         let current_time = self.current_time_secs().await?;
-        check_time_valid(valid_range, current_time)
-            .map_err(|e| LedgerClientError::FailedToIssueTx(Box::new(e)))?;
+        let script_range = match self.network_settings().await? {
+            Some(network_settings) => {
+                check_slot_valid(&network_settings, valid_range, current_time)?
+            }
+            None => {
+                check_time_valid(valid_range, current_time)
+                    .map_err(|e| LedgerClientError::FailedToIssueTx(Box::new(e)))?;
+                posix_valid_range(valid_range)
+            }
+        };
 
         let signer = self.signer_base_address().await?;
 
@@ -391,15 +668,10 @@ where
         let mut combined_inputs = self.all_outputs_at_address(&signer).await?;
 
         let mut spending_outputs: Vec<Output<_>> = Vec::new();
-        for (input, redeemer, script) in tx.script_inputs().iter() {
+        for (input, _, _) in tx.script_inputs().iter() {
             if let DatumKind::Typed(datum) = input.datum() {
                 if !spending_outputs.contains(input) {
-                    let ctx = spend_tx_context(&tx, &signer, input)?;
-                    // TODO: Check that the output is at the script address
-                    //  https://github.com/MitchTurner/naumachia/issues/86
-                    script
-                        .execute(datum.to_owned(), redeemer.to_owned(), ctx)
-                        .map_err(|e| LedgerClientError::FailedToIssueTx(Box::new(e)))?;
+                    check_datum_witness(&self.storage, input, datum).await?;
                     combined_inputs.push(input.clone());
                     spending_outputs.push(input.clone());
                 } else {
@@ -414,6 +686,42 @@ where
             }
         }
 
+        let mut minted_value = Values::default();
+        for (amount, asset_name, _, policy) in tx.minting.iter() {
+            let id = policy
+                .id()
+                .map_err(|e| LedgerClientError::FailedToIssueTx(Box::new(e)))?;
+            minted_value.add_one_value(&PolicyId::native_token(&id, asset_name), *amount);
+        }
+
+        // Scripts see the hash of the transaction, so it is derived before they run
+        let tx_hash = tx_hash_from_contents(
+            &combined_inputs,
+            tx.unbuilt_outputs(),
+            &minted_value,
+            valid_range,
+            current_time,
+        );
+        let tx_info = tx_context(&tx, &signer, &script_range, &tx_hash, &minted_value)?;
+
+        let mut recorded_script_inputs = Vec::new();
+        for (input, redeemer, script) in tx.script_inputs().iter() {
+            if let DatumKind::Typed(datum) = input.datum() {
+                let ctx = spend_tx_context(&tx_info, input);
+                // TODO: Check that the output is at the script address
+                //  https://github.com/MitchTurner/naumachia/issues/86
+                let cost = script
+                    .execute(datum.to_owned(), redeemer.to_owned(), ctx.clone())
+                    .map_err(|e| LedgerClientError::FailedToIssueTx(Box::new(e)))?;
+                recorded_script_inputs.push(RecordedScriptInput {
+                    output: input.clone(),
+                    redeemer: redeemer.clone(),
+                    cost,
+                    ctx,
+                });
+            }
+        }
+
         let mut total_input_value =
             combined_inputs
                 .iter()
@@ -422,20 +730,24 @@ where
                     acc
                 });
 
-        let mut construction_ctx = TxIdConstructionCtx::new();
-
-        let mut minted_value = Values::default();
+        let mut recorded_mints = Vec::new();
 
         for (amount, asset_name, redeemer, policy) in tx.minting.iter() {
             let id = policy
                 .id()
                 .map_err(|e| LedgerClientError::FailedToIssueTx(Box::new(e)))?;
             let policy_id = PolicyId::native_token(&id, asset_name);
-            let ctx = mint_tx_context(&tx, &signer, &id)?;
-            policy
-                .execute(redeemer.to_owned(), ctx)
+            let ctx = mint_tx_context(&tx_info, &id)?;
+            let cost = policy
+                .execute(redeemer.to_owned(), ctx.clone())
                 .map_err(|e| LedgerClientError::FailedToIssueTx(Box::new(e)))?;
-            minted_value.add_one_value(&policy_id, *amount);
+            recorded_mints.push(RecordedMint {
+                policy_id,
+                amount: *amount,
+                redeemer: redeemer.clone(),
+                cost,
+                ctx,
+            });
         }
 
         total_input_value.add_values(&minted_value);
@@ -453,9 +765,7 @@ where
             .map_err(|_| TestLCError::NotEnoughInputs)
             .map_err(|e| LedgerClientError::FailedToIssueTx(Box::new(e)))?;
 
-        for input in combined_inputs {
-            self.storage.remove_output(&input).await?;
-        }
+        let mut construction_ctx = TxIdConstructionCtx::new(tx_hash);
 
         let mut combined_outputs = Vec::new();
         if let Some(remainder) = maybe_remainder {
@@ -470,13 +780,28 @@ where
 
         combined_outputs.extend(built_outputs);
 
-        for output in combined_outputs {
-            self.storage.add_output(&output).await?;
-        }
-
-        self.advance_time_one_block().await?;
-
-        Ok(TxId::new(&hex::encode(construction_ctx.tx_hash())))
+        let block_length = self.storage.get_block_length().await?;
+        self.storage
+            .apply_transaction(
+                &combined_inputs,
+                &combined_outputs,
+                current_time + block_length,
+            )
+            .await?;
+
+        let tx_id = TxId::new(&hex::encode(construction_ctx.tx_hash()));
+        self.record_tx(RecordedTx {
+            tx_id: tx_id.clone(),
+            signer,
+            inputs: combined_inputs,
+            outputs: combined_outputs,
+            script_inputs: recorded_script_inputs,
+            mints: recorded_mints,
+            valid_range,
+            issued_at: current_time,
+        });
+
+        Ok(tx_id)
     }
 
     async fn network(&self) -> LedgerClientResult<Network> {
@@ -492,6 +817,70 @@ where
     }
 }
 
+/// Outputs that only hold the hash of their datum can only be spent if the datum provided to the
+/// validator matches that hash
+async fn check_datum_witness<Datum, Storage>(
+    storage: &Storage,
+    input: &Output<Datum>,
+    datum: &Datum,
+) -> LedgerClientResult<()>
+where
+    Datum: Clone + Into<PlutusData>,
+    Storage: TestLedgerStorage<Datum>,
+{
+    let ledger_output = storage.output_by_id(input.id()).await?;
+    let expected_hash = ledger_output
+        .as_ref()
+        .and_then(|output| output.datum_hash())
+        .or(input.datum_hash());
+    if let Some(expected_hash) = expected_hash {
+        let witness: PlutusData = datum.to_owned().into();
+        if witness.hash() != expected_hash {
+            let ledger_datum =
+                ledger_output.and_then(|output| output.datum_plutus_data());
+            let error = match ledger_datum {
+                Some(expected) => TestLCError::DatumMismatch(DatumMismatch {
+                    expected,
+                    actual: witness,
+                }),
+                None => TestLCError::MissingDatumWitness,
+            };
+            return Err(LedgerClientError::FailedToIssueTx(Box::new(error)));
+        }
+    }
+    Ok(())
+}
+
+/// Datum provided to spend an output that isn't the datum the output holds. Lists every
+/// difference, followed by both datums in full. `Debug` is the same as `Display`, so it stays
+/// readable when a test unwraps the error
+struct DatumMismatch {
+    expected: PlutusData,
+    actual: PlutusData,
+}
+
+impl fmt::Display for DatumMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for difference in self.expected.diff(&self.actual) {
+            writeln!(f, "  {difference}")?;
+        }
+        write!(
+            f,
+            "expected:\n{}\nactual:\n{}",
+            self.expected.pretty(),
+            self.actual.pretty()
+        )
+    }
+}
+
+impl Debug for DatumMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+/// The valid range is in seconds and the current time in milliseconds. The lower bound is
+/// inclusive and the upper bound is exclusive
 fn check_time_valid(
     valid_range: (Option<i64>, Option<i64>),
     current_time: i64,
@@ -500,7 +889,8 @@ fn check_time_valid(
         if current_time < lower * 1000 {
             return Err(TestLCError::TxTooEarly);
         }
-    } else if let Some(upper) = valid_range.1 {
+    }
+    if let Some(upper) = valid_range.1 {
         if current_time >= upper * 1000 {
             return Err(TestLCError::TxTooLate);
         }
@@ -508,14 +898,67 @@ fn check_time_valid(
     Ok(())
 }
 
+/// Valid range as seen by scripts when the ledger is running on raw POSIX time
+fn posix_valid_range(valid_range: (Option<i64>, Option<i64>)) -> ValidRange {
+    let lower = valid_range.0.map(|n| (n * 1000, true));
+    let upper = valid_range.1.map(|n| (n * 1000, false));
+    ValidRange { lower, upper }
+}
+
+/// Checks the valid range against the current slot, and returns the valid range as seen by
+/// scripts, i.e. rounded to the start of the bounding slots
+fn check_slot_valid(
+    network_settings: &NetworkSettings,
+    valid_range: (Option<i64>, Option<i64>),
+    current_time: i64,
+) -> LedgerClientResult<ValidRange> {
+    let to_slot = |posix: i64, bound: &str| {
+        network_settings.slot_from_posix(posix).ok_or_else(|| {
+            LedgerClientError::ValidityRange(format!(
+                "Invalid {bound} bounds; Must be after starting slot time: {:?}",
+                network_settings.starting_slot_time()
+            ))
+        })
+    };
+    let current_slot = network_settings
+        .slot_from_posix(current_time)
+        .ok_or(TestLCError::BeforeFirstSlot(current_time))
+        .map_err(|e| LedgerClientError::CurrentTime(Box::new(e)))?;
+    let lower_slot = valid_range
+        .0
+        .map(|posix| to_slot(posix, "lower"))
+        .transpose()?;
+    let upper_slot = valid_range
+        .1
+        .map(|posix| to_slot(posix, "upper"))
+        .transpose()?;
+    if let Some(lower_slot) = lower_slot {
+        if current_slot < lower_slot {
+            return Err(LedgerClientError::FailedToIssueTx(Box::new(
+                TestLCError::TxTooEarly,
+            )));
+        }
+    }
+    if let Some(upper_slot) = upper_slot {
+        if current_slot >= upper_slot {
+            return Err(LedgerClientError::FailedToIssueTx(Box::new(
+                TestLCError::TxTooLate,
+            )));
+        }
+    }
+    let slot_millis = |slot| network_settings.posix_from_slot(slot) * 1000;
+    let lower = lower_slot.map(|slot| (slot_millis(slot), true));
+    let upper = upper_slot.map(|slot| (slot_millis(slot), false));
+    Ok(ValidRange { lower, upper })
+}
+
 struct TxIdConstructionCtx {
     tx_hash: Vec<u8>,
     next_index: u64,
 }
 
 impl TxIdConstructionCtx {
-    pub fn new() -> Self {
-        let tx_hash = arbitrary_tx_id().to_vec();
+    pub fn new(tx_hash: Vec<u8>) -> Self {
         TxIdConstructionCtx {
             tx_hash,
             next_index: 0,
@@ -543,18 +986,27 @@ fn new_wallet_output<Datum>(
     Output::new_wallet(tx_hash, index, addr.clone(), vals.clone())
 }
 
-fn new_validator_output<Datum>(
+fn new_validator_output<Datum: Clone + Into<PlutusData>>(
     addr: &Address,
     vals: &Values,
     datum: Datum,
+    attachment: DatumAttachment,
     construction_ctx: &mut TxIdConstructionCtx,
 ) -> Output<Datum> {
     let tx_hash = construction_ctx.tx_hash();
     let index = construction_ctx.next_index();
-    Output::new_validator(tx_hash, index, addr.clone(), vals.clone(), datum)
+    let (addr, vals) = (addr.clone(), vals.clone());
+    match attachment {
+        DatumAttachment::Inline => {
+            Output::new_validator(tx_hash, index, addr, vals, datum)
+        }
+        DatumAttachment::Hash => {
+            Output::new_validator_with_datum_hash(tx_hash, index, addr, vals, datum)
+        }
+    }
 }
 
-fn build_outputs<Datum>(
+fn build_outputs<Datum: Clone + Into<PlutusData>>(
     unbuilt_outputs: Vec<UnbuiltOutput<Datum>>,
     construction_ctx: &mut TxIdConstructionCtx,
 ) -> Vec<Output<Datum>> {
@@ -569,50 +1021,62 @@ fn build_outputs<Datum>(
                 script_address: owner,
                 values,
                 datum,
+                attachment,
             } => {
                 let addr = Address::from_bech32(&owner).expect("Already validated");
-                new_validator_output(&addr, &values, datum, construction_ctx)
+                new_validator_output(&addr, &values, datum, attachment, construction_ctx)
             }
         })
         .collect()
 }
 
-fn spend_tx_context<Datum: Into<PlutusData> + Clone, Redeemer>(
-    tx: &UnbuiltTransaction<Datum, Redeemer>,
-    signer_address: &Address,
-    output: &Output<Datum>,
-) -> LedgerClientResult<TxContext> {
+fn spend_tx_context<Datum>(tx_info: &TxContext, output: &Output<Datum>) -> TxContext {
     let id = output.id();
     let out_ref = CtxOutputReference::new(id.tx_hash().to_vec(), id.index());
-    let purpose = CtxScriptPurpose::Spend(out_ref);
-    tx_context(tx, signer_address, purpose)
+    TxContext {
+        purpose: CtxScriptPurpose::Spend(out_ref),
+        ..tx_info.clone()
+    }
 }
 
-fn mint_tx_context<Datum: Into<PlutusData> + Clone, Redeemer>(
-    tx: &UnbuiltTransaction<Datum, Redeemer>,
-    signer_address: &Address,
+fn mint_tx_context(
+    tx_info: &TxContext,
     policy_id: &str,
 ) -> LedgerClientResult<TxContext> {
     let id = hex::decode(policy_id)
         .map_err(|e| LedgerClientError::FailedToIssueTx(Box::new(e)))?;
-    let purpose = CtxScriptPurpose::Mint(id);
-    tx_context(tx, signer_address, purpose)
+    Ok(TxContext {
+        purpose: CtxScriptPurpose::Mint(id),
+        ..tx_info.clone()
+    })
 }
 
-fn tx_context<Datum: Into<PlutusData> + Clone, Redeemer>(
+/// Context shared by all the scripts of the transaction. The purpose is filled in per script by
+/// [`spend_tx_context`] and [`mint_tx_context`]
+fn tx_context<Datum, Redeemer>(
     tx: &UnbuiltTransaction<Datum, Redeemer>,
     signer_address: &Address,
-    purpose: CtxScriptPurpose,
-) -> LedgerClientResult<TxContext> {
-    let lower = tx.valid_range.0.map(|n| (n * 1000, true));
-    let upper = tx.valid_range.1.map(|n| (n * 1000, false));
-
+    range: &ValidRange,
+    tx_hash: &[u8],
+    minted: &Values,
+) -> LedgerClientResult<TxContext>
+where
+    Datum: Into<PlutusData> + Clone,
+{
     let mut inputs = Vec::new();
     let mut outputs = Vec::new();
+    let mut datums = Vec::new();
     for (utxo, _, _) in tx.script_inputs.iter() {
         let id = utxo.id();
         let value = CtxValue::from(utxo.values().to_owned());
-        let datum = utxo.typed_datum().into();
+        let datum = match (utxo.datum_hash(), utxo.datum_plutus_data()) {
+            (Some(hash), Some(witness)) => {
+                datums.push((hash.to_vec(), witness));
+                CtxDatum::DatumHash(hash.to_vec())
+            }
+            (Some(hash), None) => CtxDatum::DatumHash(hash.to_vec()),
+            (None, _) => utxo.typed_datum().into(),
+        };
         let address = utxo.owner();
         let transaction_id = id.tx_hash().to_vec();
         let input = Input {
@@ -661,11 +1125,20 @@ fn tx_context<Datum: Into<PlutusData> + Clone, Redeemer>(
                 script_address,
                 values,
                 datum,
+                attachment,
             } => {
                 let address = Address::from_bech32(script_address)
                     .map_err(|e| LedgerClientError::FailedToIssueTx(Box::new(e)))?;
                 let value = CtxValue::from(values.to_owned());
-                let datum = CtxDatum::InlineDatum(datum.to_owned().into());
+                let data: PlutusData = datum.to_owned().into();
+                let datum = match attachment {
+                    DatumAttachment::Inline => CtxDatum::InlineDatum(data),
+                    DatumAttachment::Hash => {
+                        let hash = data.hash();
+                        datums.push((hash.clone(), data));
+                        CtxDatum::DatumHash(hash)
+                    }
+                };
                 CtxOutput {
                     address,
                     value,
@@ -677,24 +1150,127 @@ fn tx_context<Datum: Into<PlutusData> + Clone, Redeemer>(
         outputs.push(new_output)
     }
 
+    // Only scripts that know the on-chain form of their redeemer can show it to the others
+    let mut redeemers = Vec::new();
+    for (utxo, redeemer, script) in tx.script_inputs.iter() {
+        if let Some(data) = script.redeemer_data(redeemer) {
+            let id = utxo.id();
+            let out_ref = CtxOutputReference::new(id.tx_hash().to_vec(), id.index());
+            redeemers.push((CtxScriptPurpose::Spend(out_ref), data));
+        }
+    }
+    // A policy has one redeemer, no matter how many of its assets are minted
+    for (_, _, redeemer, policy) in tx.minting.iter() {
+        let id = policy
+            .id()
+            .map_err(|e| LedgerClientError::FailedToIssueTx(Box::new(e)))?;
+        let id = hex::decode(id)
+            .map_err(|e| LedgerClientError::FailedToIssueTx(Box::new(e)))?;
+        let purpose = CtxScriptPurpose::Mint(id);
+        if redeemers.iter().any(|(existing, _)| existing == &purpose) {
+            continue
+        }
+        if let Some(data) = policy.redeemer_data(redeemer) {
+            redeemers.push((purpose, data));
+        }
+    }
+
     let signer = pub_key_hash_from_address_if_available(signer_address).ok_or(
         LedgerClientError::FailedToIssueTx(Box::new(TestLCError::InvalidAddress)),
     )?;
-    let range = ValidRange { lower, upper };
+    let range = range.clone();
 
-    // TODO: Extra Signatories, and Datums (they are already included in CTX Builder)
+    // The test ledger doesn't charge fees or track stake, and only the signer signs
     let ctx = TxContext {
-        purpose,
+        // Replaced by the purpose of each script
+        purpose: CtxScriptPurpose::Mint(Vec::new()),
         signer,
+        id: tx_hash.to_vec(),
         range,
         inputs,
+        reference_inputs: Vec::new(),
         outputs,
+        fee: 0,
+        mint: CtxMint::new(minted, &Values::default()),
+        certificates: Vec::new(),
+        withdrawals: Vec::new(),
         extra_signatories: vec![],
-        datums: vec![],
+        redeemers,
+        datums,
     };
     Ok(ctx)
 }
 
-fn arbitrary_tx_id() -> [u8; 32] {
-    rand::thread_rng().gen()
+/// Derive the hash of a transaction from its contents, rather than at random, so that repeated
+/// runs against the same ledger produce the same [`OutputId`](crate::output::OutputId)s. Spent
+/// inputs can only be consumed once, and the time always advances, so issued transactions won't
+/// collide.
+fn tx_hash_from_contents<Datum: Clone + Into<PlutusData>>(
+    inputs: &[Output<Datum>],
+    outputs: &[UnbuiltOutput<Datum>],
+    minted: &Values,
+    valid_range: (Option<i64>, Option<i64>),
+    current_time: i64,
+) -> Vec<u8> {
+    let inputs = inputs
+        .iter()
+        .map(|input| {
+            let id = input.id();
+            CtxOutputReference::new(id.tx_hash().to_vec(), id.index()).into()
+        })
+        .collect();
+    let outputs = outputs
+        .iter()
+        .map(|output| {
+            PlutusData::Array(vec![
+                PlutusData::BoundedBytes(output.owner().to_vec()),
+                values_data(output.values()),
+                output.datum().cloned().map(Into::<PlutusData>::into).into(),
+            ])
+        })
+        .collect();
+    let (lower, upper) = valid_range;
+    PlutusData::Array(vec![
+        PlutusData::Array(inputs),
+        PlutusData::Array(outputs),
+        values_data(minted),
+        lower.into(),
+        upper.into(),
+        current_time.into(),
+    ])
+    .hash()
+}
+
+/// Derive the hash of the "genesis" transaction for an output that the ledger starts with.
+/// `position` separates otherwise identical starting outputs.
+pub(crate) fn starting_tx_hash<Datum: Into<PlutusData>>(
+    owner: &Address,
+    values: &Values,
+    datum: Option<Datum>,
+    position: u64,
+) -> Vec<u8> {
+    PlutusData::Array(vec![
+        PlutusData::BoundedBytes(owner.to_vec()),
+        values_data(values),
+        datum.map(Into::<PlutusData>::into).into(),
+        position.into(),
+    ])
+    .hash()
+}
+
+/// [`Values`] are backed by a `HashMap`, so they are sorted here to keep the hash stable
+fn values_data(values: &Values) -> PlutusData {
+    let sorted: BTreeMap<_, _> = values
+        .as_iter()
+        .map(|(policy, amount)| {
+            let key = PlutusData::Array(vec![
+                PlutusData::BoundedBytes(policy.id().into_bytes()),
+                PlutusData::BoundedBytes(
+                    policy.asset_name().unwrap_or_default().into_bytes(),
+                ),
+            ]);
+            (key, PlutusData::from(*amount))
+        })
+        .collect();
+    PlutusData::Map(sorted)
 }
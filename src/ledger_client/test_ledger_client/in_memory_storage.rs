@@ -1,48 +1,125 @@
 use crate::{
     ledger_client::{
         test_ledger_client::{
+            snapshot::TestLedgerSnapshot,
             TestLCError,
             TestLedgerStorage,
         },
         LedgerClientError,
-        LedgerClientError::FailedToIssueTx,
         LedgerClientResult,
     },
-    output::Output,
+    output::{
+        Output,
+        OutputId,
+    },
+    scripts::plutus_validator::plutus_data::PlutusData,
+    trireme_ledger_client::cml_client::network_settings::NetworkSettings,
 };
 use pallas_addresses::{
     Address,
     Network,
 };
 use std::sync::{
-    Arc,
     Mutex,
+    MutexGuard,
+    PoisonError,
 };
 
-/// A mutable, shared reference to a vector of `Output`s.
-type MutableData<Datum> = Arc<Mutex<Vec<(Address, Output<Datum>)>>>;
+/// Everything on the fake ledger. It is kept behind a single lock, so readers never see a
+/// transaction or a restore half-applied.
+#[derive(Debug)]
+struct LedgerState<Datum> {
+    signer: Address,
+    outputs: Vec<(Address, Output<Datum>)>,
+    current_posix_time: i64,
+    block_length: i64,
+    network_settings: Option<NetworkSettings>,
+}
 
 /// Storage adapter for [`TestLedgerClient`] that is ephemeral and in-memory. This is useful
 /// for Unit Tests and Integration Tests.
 #[derive(Debug)]
 pub struct InMemoryStorage<Datum> {
-    /// The address of the signer key owned by this instance of the [`LedgerClient`]. This is a
-    /// simplification of how Cardano works, but it's sufficient for testing.
-    pub signer: Address,
-    /// A list of all the outputs on this fake ledger.
-    pub outputs: MutableData<Datum>,
-    /// The current time on the ledger, or the time of the last "block" committed to the ledger.
-    pub current_posix_time: Arc<Mutex<i64>>,
-    /// The time between blocks on the ledger in seconds.
-    pub block_length: i64,
+    state: Mutex<LedgerState<Datum>>,
+}
+
+impl<Datum> InMemoryStorage<Datum> {
+    /// Constructor for the [`InMemoryStorage`].
+    ///
+    /// `signer` is the address of the signer key owned by this instance of the
+    /// [`LedgerClient`]. This is a simplification of how Cardano works, but it's sufficient for
+    /// testing. `current_posix_time` is the time of the last "block" committed to the ledger,
+    /// and `block_length` the time between blocks, both in seconds.
+    pub fn new(
+        signer: Address,
+        outputs: Vec<(Address, Output<Datum>)>,
+        current_posix_time: i64,
+        block_length: i64,
+    ) -> Self {
+        let state = LedgerState {
+            signer,
+            outputs,
+            current_posix_time,
+            block_length,
+            network_settings: None,
+        };
+        InMemoryStorage {
+            state: Mutex::new(state),
+        }
+    }
+
+    /// Run the ledger on the slots of the given network
+    pub fn with_network_settings(self, network_settings: NetworkSettings) -> Self {
+        let mut state = self
+            .state
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner);
+        state.network_settings = Some(network_settings);
+        InMemoryStorage {
+            state: Mutex::new(state),
+        }
+    }
+
+    /// Getter for the address of the signer key owned by this instance of the
+    /// [`LedgerClient`]
+    pub fn signer_address(&self) -> LedgerClientResult<Address> {
+        Ok(self.state()?.signer.clone())
+    }
+
+    /// Getter for all the outputs on this fake ledger, with their owners
+    pub fn outputs(&self) -> LedgerClientResult<Vec<(Address, Output<Datum>)>>
+    where
+        Datum: Clone,
+    {
+        Ok(self.state()?.outputs.clone())
+    }
+
+    /// Getter for the current time on the ledger, or the time of the last "block" committed
+    /// to the ledger
+    pub fn current_posix_time(&self) -> LedgerClientResult<i64> {
+        Ok(self.state()?.current_posix_time)
+    }
+
+    /// Getter for the time between blocks on the ledger in seconds
+    pub fn block_length(&self) -> LedgerClientResult<i64> {
+        Ok(self.state()?.block_length)
+    }
+
+    fn state(&self) -> LedgerClientResult<MutexGuard<LedgerState<Datum>>> {
+        self.state
+            .lock()
+            .map_err(|e| TestLCError::Mutex(format! {"{e:?}"}))
+            .map_err(|e| LedgerClientError::Storage(Box::new(e)))
+    }
 }
 
 #[async_trait::async_trait]
-impl<Datum: Clone + Send + Sync + PartialEq> TestLedgerStorage<Datum>
-    for InMemoryStorage<Datum>
+impl<Datum> TestLedgerStorage<Datum> for InMemoryStorage<Datum>
+where
+    Datum: Clone + Send + Sync + PartialEq,
 {
     async fn signer(&self) -> LedgerClientResult<Address> {
-        Ok(self.signer.clone())
+        Ok(self.state()?.signer.clone())
     }
 
     async fn outputs_by_count(
@@ -51,15 +128,11 @@ impl<Datum: Clone + Send + Sync + PartialEq> TestLedgerStorage<Datum>
         count: usize,
     ) -> LedgerClientResult<Vec<Output<Datum>>> {
         let outputs = self
+            .state()?
             .outputs
-            .lock()
-            .map_err(|e| TestLCError::Mutex(format! {"{e:?}"}))
-            .map_err(|e| {
-                LedgerClientError::FailedToRetrieveOutputsAt(address.clone(), Box::new(e))
-            })?
             .iter()
-            .cloned()
-            .filter_map(|(a, o)| if &a == address { Some(o) } else { None })
+            .filter(|(a, _)| a == address)
+            .map(|(_, o)| o.clone())
             .take(count)
             .collect();
         Ok(outputs)
@@ -70,26 +143,32 @@ impl<Datum: Clone + Send + Sync + PartialEq> TestLedgerStorage<Datum>
         address: &Address,
     ) -> LedgerClientResult<Vec<Output<Datum>>> {
         let outputs = self
+            .state()?
             .outputs
-            .lock()
-            .map_err(|e| TestLCError::Mutex(format! {"{e:?}"}))
-            .map_err(|e| {
-                LedgerClientError::FailedToRetrieveOutputsAt(address.clone(), Box::new(e))
-            })?
             .iter()
-            .cloned()
-            .filter_map(|(a, o)| if &a == address { Some(o) } else { None })
+            .filter(|(a, _)| a == address)
+            .map(|(_, o)| o.clone())
             .collect();
         Ok(outputs)
     }
 
+    async fn output_by_id(
+        &self,
+        id: &OutputId,
+    ) -> LedgerClientResult<Option<Output<Datum>>> {
+        let output = self
+            .state()?
+            .outputs
+            .iter()
+            .find(|(_, o)| o.id() == id)
+            .map(|(_, o)| o.clone());
+        Ok(output)
+    }
+
     async fn remove_output(&self, output: &Output<Datum>) -> LedgerClientResult<()> {
-        let mut ledger_utxos = self
+        let mut state = self.state()?;
+        let index = state
             .outputs
-            .lock()
-            .map_err(|e| TestLCError::Mutex(format! {"{e:?}"}))
-            .map_err(|e| FailedToIssueTx(Box::new(e)))?;
-        let index = ledger_utxos
             .iter()
             .position(|(_, x)| x == output)
             .ok_or_else(|| {
@@ -98,44 +177,123 @@ impl<Datum: Clone + Send + Sync + PartialEq> TestLedgerStorage<Datum>
                     Box::new(TestLCError::DuplicateInput),
                 )
             })?;
-        ledger_utxos.remove(index);
+        state.outputs.remove(index);
         Ok(())
     }
 
     async fn add_output(&self, output: &Output<Datum>) -> LedgerClientResult<()> {
-        let mut ledger_utxos = self
-            .outputs
-            .lock()
-            .map_err(|e| TestLCError::Mutex(format! {"{e:?}"}))
-            .map_err(|e| FailedToIssueTx(Box::new(e)))?;
-        ledger_utxos.push((output.owner(), output.clone()));
+        self.state()?.outputs.push((output.owner(), output.clone()));
+        Ok(())
+    }
+
+    async fn apply_transaction(
+        &self,
+        consumed: &[Output<Datum>],
+        produced: &[Output<Datum>],
+        new_time: i64,
+    ) -> LedgerClientResult<()> {
+        // The lock is held until the end, so nobody sees the transaction half-applied. Every
+        // consumed output is found before anything is removed
+        let mut state = self.state()?;
+        let mut spent = Vec::with_capacity(consumed.len());
+        for output in consumed {
+            let index = state
+                .outputs
+                .iter()
+                .enumerate()
+                .position(|(index, (_, x))| x == output && !spent.contains(&index))
+                .ok_or_else(|| {
+                    LedgerClientError::FailedToRetrieveOutputWithId(
+                        output.id().clone(),
+                        Box::new(TestLCError::UnknownInput),
+                    )
+                })?;
+            spent.push(index);
+        }
+        let mut index = 0;
+        state.outputs.retain(|_| {
+            let keep = !spent.contains(&index);
+            index += 1;
+            keep
+        });
+        state.outputs.extend(
+            produced
+                .iter()
+                .map(|output| (output.owner(), output.clone())),
+        );
+        state.current_posix_time = new_time;
         Ok(())
     }
 
     async fn current_time(&self) -> LedgerClientResult<i64> {
-        let time = self
-            .current_posix_time
-            .lock()
-            .map_err(|e| TestLCError::Mutex(format! {"{e:?}"}))
-            .map_err(|e| FailedToIssueTx(Box::new(e)))?;
-        Ok(*time)
+        Ok(self.state()?.current_posix_time)
     }
 
     async fn set_current_time(&self, posix_time: i64) -> LedgerClientResult<()> {
-        *self
-            .current_posix_time
-            .lock()
-            .map_err(|e| TestLCError::Mutex(format! {"{e:?}"}))
-            .map_err(|e| FailedToIssueTx(Box::new(e)))? = posix_time;
+        self.state()?.current_posix_time = posix_time;
         Ok(())
     }
 
     async fn get_block_length(&self) -> LedgerClientResult<i64> {
-        let block_length = self.block_length;
-        Ok(block_length)
+        Ok(self.state()?.block_length)
     }
 
     async fn network(&self) -> LedgerClientResult<Network> {
         Ok(Network::Testnet)
     }
+
+    async fn network_settings(&self) -> LedgerClientResult<Option<NetworkSettings>> {
+        Ok(self.state()?.network_settings)
+    }
+
+    async fn set_network_settings(
+        &self,
+        network_settings: Option<NetworkSettings>,
+    ) -> LedgerClientResult<()> {
+        self.state()?.network_settings = network_settings;
+        Ok(())
+    }
+
+    async fn snapshot(&self) -> LedgerClientResult<TestLedgerSnapshot>
+    where
+        Datum: Into<PlutusData>,
+    {
+        let state = self.state()?;
+        let outputs = state
+            .outputs
+            .iter()
+            .map(|(_, output)| output.clone())
+            .collect();
+        let snapshot = TestLedgerSnapshot::new(
+            &state.signer,
+            outputs,
+            state.current_posix_time,
+            state.block_length,
+        )?;
+        let snapshot = match state.network_settings {
+            Some(network_settings) => snapshot.with_network_settings(network_settings),
+            None => snapshot,
+        };
+        Ok(snapshot)
+    }
+
+    async fn restore(&self, snapshot: TestLedgerSnapshot) -> LedgerClientResult<()>
+    where
+        Datum: TryFrom<PlutusData>,
+    {
+        let signer = snapshot.signer()?;
+        let outputs = snapshot
+            .outputs()?
+            .into_iter()
+            .map(|output: Output<Datum>| (output.owner(), output))
+            .collect();
+        *self.state()? = LedgerState {
+            signer,
+            outputs,
+            current_posix_time: snapshot.current_time(),
+            block_length: snapshot.block_length(),
+            network_settings: snapshot.network_settings(),
+        };
+        Ok(())
+    }
 }
@@ -1,3 +1,4 @@
+use fs2::FileExt;
 use pallas_addresses::{
     Address,
     Network,
@@ -6,23 +7,34 @@ use serde::{
     Deserialize,
     Serialize,
 };
+use serde_json::Value;
 use std::{
     collections::HashMap,
+    error,
     fmt::Debug,
-    fs::File,
+    fs::{
+        self,
+        File,
+        OpenOptions,
+    },
     io::{
         Read,
         Write,
     },
     marker::PhantomData,
-    path::Path,
+    path::{
+        Path,
+        PathBuf,
+    },
 };
 use thiserror::Error;
 
 use crate::{
     ledger_client::{
         test_ledger_client::{
-            arbitrary_tx_id,
+            snapshot::TestLedgerSnapshot,
+            starting_tx_hash,
+            TestLCError,
             TestLedgerStorage,
         },
         LedgerClientError,
@@ -33,55 +45,94 @@ use crate::{
         OutputId,
     },
     scripts::plutus_validator::plutus_data::PlutusData,
+    trireme_ledger_client::cml_client::network_settings::NetworkSettings,
     values::Values,
     PolicyId,
 };
 
+/// Version of the layout of the `data` file. Bump this and add a migration to [`MIGRATIONS`]
+/// whenever [`LedgerData`] changes in a way `serde` can't handle on its own.
+pub const CURRENT_DATA_VERSION: u64 = 1;
+
+/// Migrations between versions of the `data` file. The migration at index `n` takes the data
+/// from version `n` to version `n + 1`.
+const MIGRATIONS: [fn(Value) -> Value; CURRENT_DATA_VERSION as usize] =
+    [migrate_v0_to_v1];
+
+/// Version 0 is the layout from before the `data` file was versioned. Hashed datums were added
+/// to outputs as an optional field, so only the version needs setting.
+fn migrate_v0_to_v1(mut data: Value) -> Value {
+    data["version"] = Value::from(1);
+    data
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub(crate) struct LedgerData {
+    #[serde(default)]
+    version: u64,
     active_signer_name: String,
     active_signer: String,
     signers: HashMap<String, String>,
     outputs: Vec<LDOutput>,
     current_time: i64,
     block_length: i64,
+    #[serde(default)]
+    network_settings: Option<NetworkSettings>,
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
-struct LDOutput {
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub(crate) struct LDOutput {
     id: OutputId,
     owner: String,
     values: Values,
     datum: Option<PlutusData>,
+    #[serde(default)]
+    datum_hash: Option<String>,
 }
 
-impl<Datum: Clone + Into<PlutusData>> From<Output<Datum>> for LDOutput {
-    fn from(output: Output<Datum>) -> Self {
-        LDOutput {
+impl<Datum: Clone + Into<PlutusData>> TryFrom<Output<Datum>> for LDOutput {
+    type Error = LedgerClientError;
+
+    fn try_from(output: Output<Datum>) -> LedgerClientResult<Self> {
+        let ld_output = LDOutput {
             id: output.id().to_owned(),
-            owner: output.owner().to_bech32().expect("Already validated"),
+            owner: address_to_bech32(&output.owner())?,
             values: output.values().clone(),
             datum: output.datum_plutus_data(),
-        }
+            datum_hash: output.datum_hash().map(hex::encode),
+        };
+        Ok(ld_output)
     }
 }
 
-impl<Datum> From<LDOutput> for Output<Datum> {
-    fn from(value: LDOutput) -> Self {
+impl<Datum> TryFrom<LDOutput> for Output<Datum> {
+    type Error = LedgerClientError;
+
+    fn try_from(value: LDOutput) -> LedgerClientResult<Self> {
         let LDOutput {
             id,
             owner,
             values,
             datum,
+            datum_hash,
         } = value;
         let tx_hash = id.tx_hash().to_owned();
         let index = id.index();
-        let owner = Address::from_bech32(&owner).unwrap(); // TODO: Unwrap
-        if let Some(datum) = datum {
+        let owner = Address::from_bech32(&owner)
+            .map_err(|e| LedgerClientError::BadAddress(Box::new(e)))?;
+        let output = if let Some(datum) = datum {
             Output::new_untyped_validator(tx_hash, index, owner, values, datum)
         } else {
             Output::new_wallet(tx_hash, index, owner, values)
-        }
+        };
+        let output = match datum_hash {
+            Some(datum_hash) => {
+                let datum_hash = hex::decode(datum_hash).map_err(as_storage_error)?;
+                output.with_datum_hash(datum_hash)
+            }
+            None => output,
+        };
+        Ok(output)
     }
 }
 
@@ -91,57 +142,175 @@ enum LocalPersistedLCError {
     // NotEnoughInputs,
     #[error("The same input is listed twice")]
     DuplicateInput, // TODO: WE don't need this once we dedupe
+    #[error("Signer not found: {0:?}")]
+    UnknownSigner(String),
+    #[error("No ledger data found at {0:?}")]
+    NoData(PathBuf),
+    #[error("Ledger data has version {0:?}, but only up to {1:?} is supported")]
+    UnsupportedVersion(u64, u64),
+}
+
+pub(super) fn as_storage_error<E: error::Error + Send + Sync + 'static>(
+    error: E,
+) -> LedgerClientError {
+    LedgerClientError::Storage(Box::new(error))
+}
+
+pub(super) fn address_to_bech32(address: &Address) -> LedgerClientResult<String> {
+    address
+        .to_bech32()
+        .map_err(|e| TestLCError::Bech32(e.to_string()))
+        .map_err(|e| LedgerClientError::BadAddress(Box::new(e)))
 }
 
 impl LedgerData {
-    pub fn new(signer_name: &str, signer_address: &Address, block_length: i64) -> Self {
+    pub fn new(
+        signer_name: &str,
+        signer_address: &Address,
+        block_length: i64,
+    ) -> LedgerClientResult<Self> {
         let outputs = Vec::new();
-        let address_bech_32 = signer_address.to_bech32().expect("Already validated");
+        let address_bech_32 = address_to_bech32(signer_address)?;
         let mut signers = HashMap::new();
         signers.insert(signer_name.to_string(), address_bech_32.clone());
-        LedgerData {
+        let data = LedgerData {
+            version: CURRENT_DATA_VERSION,
             active_signer_name: signer_name.to_string(),
             active_signer: address_bech_32,
             signers,
             outputs,
             current_time: 0,
             block_length,
+            network_settings: None,
+        };
+        Ok(data)
+    }
+
+    /// Parse the contents of a `data` file, migrating it to the current version if needed.
+    /// Returns whether any migration ran
+    fn from_json(contents: &str) -> LedgerClientResult<(Self, bool)> {
+        let mut data: Value = serde_json::from_str(contents).map_err(as_storage_error)?;
+        let version = data.get("version").and_then(Value::as_u64).unwrap_or(0);
+        if version > CURRENT_DATA_VERSION {
+            return Err(as_storage_error(LocalPersistedLCError::UnsupportedVersion(
+                version,
+                CURRENT_DATA_VERSION,
+            )));
         }
+        let mut migrated = false;
+        for migration in MIGRATIONS.iter().skip(version as usize) {
+            data = migration(data);
+            migrated = true;
+        }
+        let data = serde_json::from_value(data).map_err(as_storage_error)?;
+        Ok((data, migrated))
     }
 
     pub fn signers(&self) -> Vec<String> {
         self.signers.keys().cloned().collect()
     }
 
-    pub fn add_output<Datum: Clone + Into<PlutusData>>(&mut self, output: Output<Datum>) {
-        self.outputs.push(output.into())
+    pub fn add_output<Datum: Clone + Into<PlutusData>>(
+        &mut self,
+        output: Output<Datum>,
+    ) -> LedgerClientResult<()> {
+        self.outputs.push(output.try_into()?);
+        Ok(())
     }
 
-    pub fn add_signer(&mut self, name: &str, address: &Address) {
-        let address_bech_32 = address.to_bech32().expect("Already validated");
+    pub fn add_signer(
+        &mut self,
+        name: &str,
+        address: &Address,
+    ) -> LedgerClientResult<()> {
+        let address_bech_32 = address_to_bech32(address)?;
         self.signers.insert(name.to_string(), address_bech_32);
+        Ok(())
     }
 
-    pub fn switch_signer(&mut self, name: &str) {
+    pub fn switch_signer(&mut self, name: &str) -> LedgerClientResult<()> {
+        let address = self.signers.get(name).ok_or_else(|| {
+            as_storage_error(LocalPersistedLCError::UnknownSigner(name.to_string()))
+        })?;
+        self.active_signer = address.to_string();
         self.active_signer_name = name.to_string();
-        if let Some(address) = self.signers.get(name) {
-            self.active_signer = address.to_string();
-        } else {
-            panic!("Signer not found"); // TODO
-        };
+        Ok(())
+    }
+}
+
+impl From<LedgerData> for TestLedgerSnapshot {
+    fn from(data: LedgerData) -> Self {
+        let LedgerData {
+            active_signer_name,
+            active_signer,
+            signers,
+            outputs,
+            current_time,
+            block_length,
+            network_settings,
+            ..
+        } = data;
+        TestLedgerSnapshot {
+            active_signer_name,
+            active_signer,
+            signers,
+            outputs,
+            current_time,
+            block_length,
+            network_settings,
+        }
+    }
+}
+
+impl From<TestLedgerSnapshot> for LedgerData {
+    fn from(snapshot: TestLedgerSnapshot) -> Self {
+        let TestLedgerSnapshot {
+            active_signer_name,
+            active_signer,
+            signers,
+            outputs,
+            current_time,
+            block_length,
+            network_settings,
+        } = snapshot;
+        LedgerData {
+            version: CURRENT_DATA_VERSION,
+            active_signer_name,
+            active_signer,
+            signers,
+            outputs,
+            current_time,
+            block_length,
+            network_settings,
+        }
     }
 }
 
 /// Create a starting output for a given address for testing
 pub fn starting_output<Datum>(owner: &Address, amount: u64) -> Output<Datum> {
-    let tx_hash = arbitrary_tx_id().to_vec();
+    starting_output_at_position(owner, amount, 0)
+}
+
+/// Create a starting output whose id is also derived from its `position` on the ledger, so
+/// that identical starting outputs don't share an id
+pub(super) fn starting_output_at_position<Datum>(
+    owner: &Address,
+    amount: u64,
+    position: u64,
+) -> Output<Datum> {
     let index = 0;
     let mut values = Values::default();
     values.add_one_value(&PolicyId::Lovelace, amount);
+    let tx_hash = starting_tx_hash::<PlutusData>(owner, &values, None, position);
     Output::new_wallet(tx_hash, index, owner.clone(), values)
 }
 
-/// A ['TestLedgerStorage'] implementation that persists data to a local directory
+/// A ['TestLedgerStorage'] implementation that persists data to a local directory.
+///
+/// The data is kept in a single JSON file. Every change is written to a temporary file first
+/// and then renamed over the original, so a crash never leaves a half-written file behind. An
+/// advisory lock on a sibling lock file serializes access between processes, e.g. two Trireme
+/// commands run at once against the same environment.
 pub struct LocalPersistedStorage<T: AsRef<Path>, Datum> {
     /// Directory of persisted storage
     dir: T,
@@ -149,14 +318,16 @@ pub struct LocalPersistedStorage<T: AsRef<Path>, Datum> {
 }
 
 const DATA: &str = "data";
+const DATA_TMP: &str = "data.tmp";
+const DATA_LOCK: &str = "data.lock";
 
-// TODO: Make fallible!!!
 impl<T, Datum> LocalPersistedStorage<T, Datum>
 where
     T: AsRef<Path>,
     Datum: Clone + Into<PlutusData>,
 {
-    /// Initialize a new [`LocalPersistedStorage`]
+    /// Initialize a new [`LocalPersistedStorage`]. If there is already data in `dir`, it is
+    /// kept as is.
     pub fn init(
         dir: T,
         signer_name: &str,
@@ -164,99 +335,174 @@ where
         starting_amount: u64,
         starting_time: i64,
         block_length: i64,
-    ) -> Self {
-        let path_ref: &Path = dir.as_ref();
-        let path = path_ref.to_owned().join(DATA);
-        if !path.exists() {
-            let mut data = LedgerData::new(signer_name, signer, block_length);
+    ) -> LedgerClientResult<Self> {
+        fs::create_dir_all(dir.as_ref()).map_err(as_storage_error)?;
+        let storage = LocalPersistedStorage {
+            dir,
+            _datum: Default::default(),
+        };
+        let _lock = storage.lock_exclusive()?;
+        if storage.data_path().exists() {
+            storage.migrate_data()?;
+        } else {
+            let mut data = LedgerData::new(signer_name, signer, block_length)?;
             data.current_time = starting_time;
             let output: Output<Datum> = starting_output(signer, starting_amount);
-            data.add_output(output); // TODO: Parameterize
-            let serialized = serde_json::to_string(&data).unwrap();
-            let mut file = File::create(path).unwrap();
-            file.write_all(&serialized.into_bytes()).unwrap();
-        } else {
-            // TODO: Ensure it is valid data?
+            data.add_output(output)?; // TODO: Parameterize
+            storage.write_data(&data)?;
         }
+        Ok(storage)
+    }
 
-        LocalPersistedStorage {
+    /// Constructor for revived [`LocalPersistedStorage`]. Fails if there is no readable data
+    /// in `dir`. Data from older versions is migrated to the current version.
+    pub fn load(dir: T) -> LedgerClientResult<Self> {
+        let storage = LocalPersistedStorage {
             dir,
             _datum: Default::default(),
+        };
+        let no_data =
+            || as_storage_error(LocalPersistedLCError::NoData(storage.data_path()));
+        if !storage.dir.as_ref().is_dir() {
+            return Err(no_data());
         }
+        // Only look for the data once the lock is held, so a concurrent `init` is never
+        // caught half-way
+        let _lock = storage.lock_exclusive()?;
+        if !storage.data_path().exists() {
+            return Err(no_data());
+        }
+        storage.migrate_data()?;
+        Ok(storage)
     }
 
-    /// Constructor for revived [`LocalPersistedStorage`]
-    pub fn load(dir: T) -> Self {
-        LocalPersistedStorage {
-            dir,
-            _datum: Default::default(),
-        }
+    fn data_path(&self) -> PathBuf {
+        self.dir.as_ref().join(DATA)
+    }
+
+    /// Hold the returned file to keep other processes from reading or writing the data. The
+    /// lock is released when the file is dropped.
+    fn lock_exclusive(&self) -> LedgerClientResult<File> {
+        let lock_file = self.open_lock_file()?;
+        lock_file.lock_exclusive().map_err(as_storage_error)?;
+        Ok(lock_file)
+    }
+
+    /// Hold the returned file to keep other processes from writing the data. The lock is
+    /// released when the file is dropped.
+    fn lock_shared(&self) -> LedgerClientResult<File> {
+        let lock_file = self.open_lock_file()?;
+        lock_file.lock_shared().map_err(as_storage_error)?;
+        Ok(lock_file)
+    }
+
+    fn open_lock_file(&self) -> LedgerClientResult<File> {
+        OpenOptions::new()
+            .create(true)
+            .write(true)
+            .open(self.dir.as_ref().join(DATA_LOCK))
+            .map_err(as_storage_error)
     }
 
-    pub(crate) fn get_data(&self) -> LedgerData {
-        let path_ref: &Path = self.dir.as_ref();
-        let path = path_ref.to_owned().join(DATA);
-        let mut file = File::open(path).unwrap();
+    /// Must be called while holding a lock
+    fn read_data(&self) -> LedgerClientResult<LedgerData> {
+        let (data, _) = self.read_and_migrate_data()?;
+        Ok(data)
+    }
+
+    /// Must be called while holding a lock. Also returns whether the data had to be migrated
+    fn read_and_migrate_data(&self) -> LedgerClientResult<(LedgerData, bool)> {
+        let mut file = File::open(self.data_path()).map_err(as_storage_error)?;
         let mut contents = String::new();
-        file.read_to_string(&mut contents).expect("Could not read");
-        serde_json::from_str(&contents).unwrap()
-    }
-
-    fn update_outputs(&self, new_outputs: Vec<LDOutput>) {
-        let path_ref: &Path = self.dir.as_ref();
-        let path = path_ref.to_owned().join(DATA);
-        let mut data = self.get_data();
-        data.outputs = new_outputs.into_iter().map(Into::into).collect();
-        let serialized = serde_json::to_string(&data).unwrap();
-        let mut file = File::create(path).unwrap();
-        file.write_all(&serialized.into_bytes()).unwrap();
-    }
-
-    fn update_current_time(&self, posix_time: i64) -> LedgerClientResult<()> {
-        let path_ref: &Path = self.dir.as_ref();
-        let path = path_ref.to_owned().join(DATA);
-        let mut data = self.get_data();
-        data.current_time = posix_time;
-        let serialized = serde_json::to_string(&data).unwrap();
-        let mut file = File::create(path).unwrap();
-        file.write_all(&serialized.into_bytes()).unwrap();
+        file.read_to_string(&mut contents)
+            .map_err(as_storage_error)?;
+        LedgerData::from_json(&contents)
+    }
+
+    /// Make sure the existing data is readable, and write it back only if it was from an older
+    /// version. Must be called while holding the exclusive lock
+    fn migrate_data(&self) -> LedgerClientResult<()> {
+        let (data, migrated) = self.read_and_migrate_data()?;
+        if migrated {
+            self.write_data(&data)?;
+        }
         Ok(())
     }
 
+    /// Must be called while holding the exclusive lock
+    fn write_data(&self, data: &LedgerData) -> LedgerClientResult<()> {
+        let serialized = serde_json::to_vec(data).map_err(as_storage_error)?;
+        let tmp_path = self.dir.as_ref().join(DATA_TMP);
+        let mut file = File::create(&tmp_path).map_err(as_storage_error)?;
+        file.write_all(&serialized).map_err(as_storage_error)?;
+        file.sync_all().map_err(as_storage_error)?;
+        fs::rename(tmp_path, self.data_path()).map_err(as_storage_error)
+    }
+
+    pub(crate) fn get_data(&self) -> LedgerClientResult<LedgerData> {
+        let _lock = self.lock_shared()?;
+        self.read_data()
+    }
+
+    /// Read, change and write the data while holding the exclusive lock, so no other process
+    /// can change the data in between
+    fn update_data<R>(
+        &self,
+        f: impl FnOnce(&mut LedgerData) -> LedgerClientResult<R>,
+    ) -> LedgerClientResult<R> {
+        let _lock = self.lock_exclusive()?;
+        let mut data = self.read_data()?;
+        let res = f(&mut data)?;
+        self.write_data(&data)?;
+        Ok(res)
+    }
+
     /// Add a new identity to the ledger
-    pub fn add_new_signer(&self, name: &str, address: &Address, starting_amount: u64) {
-        let path_ref: &Path = self.dir.as_ref();
-        let path = path_ref.to_owned().join(DATA);
-        let mut data = self.get_data();
-        let output: Output<Datum> = starting_output(address, starting_amount);
-        data.add_output(output);
-        data.add_signer(name, address);
-        let serialized = serde_json::to_string(&data).unwrap();
-        let mut file = File::create(path).unwrap();
-        file.write_all(&serialized.into_bytes()).unwrap();
+    pub fn add_new_signer(
+        &self,
+        name: &str,
+        address: &Address,
+        starting_amount: u64,
+    ) -> LedgerClientResult<()> {
+        self.update_data(|data| {
+            let position = data.outputs.len() as u64;
+            let output: Output<Datum> =
+                starting_output_at_position(address, starting_amount, position);
+            data.add_output(output)?;
+            data.add_signer(name, address)
+        })
     }
 
     /// Get the name of the active signer
-    pub fn active_signer_name(&self) -> String {
-        let data = self.get_data();
-        data.active_signer_name
+    pub fn active_signer_name(&self) -> LedgerClientResult<String> {
+        let data = self.get_data()?;
+        Ok(data.active_signer_name)
     }
 
     /// Get list of all signers, active and inactive
-    pub fn get_signers(&self) -> Vec<String> {
-        let data = self.get_data();
-        data.signers()
+    pub fn get_signers(&self) -> LedgerClientResult<Vec<String>> {
+        let data = self.get_data()?;
+        Ok(data.signers())
     }
 
-    /// Switch the active signer
-    pub fn switch_signer(&self, name: &str) {
-        let path_ref: &Path = self.dir.as_ref();
-        let path = path_ref.to_owned().join(DATA);
-        let mut data = self.get_data();
-        data.switch_signer(name);
-        let serialized = serde_json::to_string(&data).unwrap();
-        let mut file = File::create(path).unwrap();
-        file.write_all(&serialized.into_bytes()).unwrap();
+    /// Switch the active signer. Fails if there is no signer with the given name
+    pub fn switch_signer(&self, name: &str) -> LedgerClientResult<()> {
+        self.update_data(|data| data.switch_signer(name))
+    }
+
+    fn outputs_at(&self, address: &Address) -> LedgerClientResult<Vec<Output<Datum>>>
+    where
+        Datum: TryFrom<PlutusData>,
+    {
+        let data = self.get_data()?;
+        let mut outputs = Vec::new();
+        for ld_output in data.outputs {
+            let output: Output<Datum> = ld_output.try_into()?;
+            if &output.owner() == address {
+                outputs.push(output.with_typed_datum_if_possible());
+            }
+        }
+        Ok(outputs)
     }
 }
 
@@ -267,7 +513,7 @@ where
     Datum: Clone + Send + Sync + PartialEq + Into<PlutusData> + TryFrom<PlutusData>,
 {
     async fn signer(&self) -> LedgerClientResult<Address> {
-        let signer = self.get_data().active_signer;
+        let signer = self.get_data()?.active_signer;
         Address::from_bech32(&signer)
             .map_err(|e| LedgerClientError::BadAddress(Box::new(e)))
     }
@@ -277,15 +523,8 @@ where
         address: &Address,
         count: usize,
     ) -> LedgerClientResult<Vec<Output<Datum>>> {
-        let data = self.get_data();
-        let outputs = data
-            .outputs
-            .into_iter()
-            .map(Into::<Output<Datum>>::into)
-            .filter(|o| &o.owner() == address)
-            .take(count)
-            .map(|output| output.with_typed_datum_if_possible())
-            .collect();
+        let mut outputs = self.outputs_at(address)?;
+        outputs.truncate(count);
         Ok(outputs)
     }
 
@@ -293,60 +532,124 @@ where
         &self,
         address: &Address,
     ) -> LedgerClientResult<Vec<Output<Datum>>> {
-        let data = self.get_data();
-        let outputs = data
+        self.outputs_at(address)
+    }
+
+    async fn output_by_id(
+        &self,
+        id: &OutputId,
+    ) -> LedgerClientResult<Option<Output<Datum>>> {
+        let ld_output = self
+            .get_data()?
             .outputs
             .into_iter()
-            .map(Into::<Output<Datum>>::into)
-            .filter(|o| &o.owner() == address)
-            .map(|output| output.with_typed_datum_if_possible())
-            .collect();
-        Ok(outputs)
+            .find(|ld_output| &ld_output.id == id);
+        match ld_output {
+            Some(ld_output) => {
+                let output: Output<Datum> = ld_output.try_into()?;
+                Ok(Some(output.with_typed_datum_if_possible()))
+            }
+            None => Ok(None),
+        }
     }
 
     async fn remove_output(&self, output: &Output<Datum>) -> LedgerClientResult<()> {
-        let mut ledger_utxos = self.get_data().outputs;
-        let sanitized_output = output.clone().into();
-
-        let index = ledger_utxos
-            .iter()
-            .position(|x| x == &sanitized_output)
-            .ok_or_else(|| {
-                LedgerClientError::FailedToRetrieveOutputWithId(
-                    output.id().clone(),
-                    Box::new(LocalPersistedLCError::DuplicateInput),
-                )
-            })?;
-        ledger_utxos.remove(index);
-        self.update_outputs(ledger_utxos);
-        Ok(())
+        let sanitized_output: LDOutput = output.clone().try_into()?;
+        self.update_data(|data| {
+            let index = data
+                .outputs
+                .iter()
+                .position(|x| x == &sanitized_output)
+                .ok_or_else(|| {
+                    LedgerClientError::FailedToRetrieveOutputWithId(
+                        output.id().clone(),
+                        Box::new(LocalPersistedLCError::DuplicateInput),
+                    )
+                })?;
+            data.outputs.remove(index);
+            Ok(())
+        })
     }
 
     async fn add_output(&self, output: &Output<Datum>) -> LedgerClientResult<()> {
-        let sanitized_output = output.clone().into();
-        let mut ledger_utxos = self.get_data().outputs;
-        ledger_utxos.push(sanitized_output);
-        self.update_outputs(ledger_utxos);
-        Ok(())
+        let sanitized_output: LDOutput = output.clone().try_into()?;
+        self.update_data(|data| {
+            data.outputs.push(sanitized_output);
+            Ok(())
+        })
+    }
+
+    async fn apply_transaction(
+        &self,
+        consumed: &[Output<Datum>],
+        produced: &[Output<Datum>],
+        new_time: i64,
+    ) -> LedgerClientResult<()> {
+        // Nothing is written unless every change succeeds
+        self.update_data(|data| {
+            for output in consumed {
+                let sanitized_output: LDOutput = output.clone().try_into()?;
+                let index = data
+                    .outputs
+                    .iter()
+                    .position(|x| x == &sanitized_output)
+                    .ok_or_else(|| {
+                        LedgerClientError::FailedToRetrieveOutputWithId(
+                            output.id().clone(),
+                            Box::new(LocalPersistedLCError::DuplicateInput),
+                        )
+                    })?;
+                data.outputs.remove(index);
+            }
+            for output in produced {
+                data.outputs.push(output.clone().try_into()?);
+            }
+            data.current_time = new_time;
+            Ok(())
+        })
     }
 
     async fn current_time(&self) -> LedgerClientResult<i64> {
-        Ok(self.get_data().current_time)
+        Ok(self.get_data()?.current_time)
     }
 
     async fn set_current_time(&self, posix_time: i64) -> LedgerClientResult<()> {
-        self.update_current_time(posix_time).unwrap();
-        Ok(())
+        self.update_data(|data| {
+            data.current_time = posix_time;
+            Ok(())
+        })
     }
 
     async fn get_block_length(&self) -> LedgerClientResult<i64> {
-        let block_length = self.get_data().block_length;
-        Ok(block_length)
+        Ok(self.get_data()?.block_length)
     }
 
     async fn network(&self) -> LedgerClientResult<Network> {
         Ok(Network::Testnet)
     }
+
+    async fn network_settings(&self) -> LedgerClientResult<Option<NetworkSettings>> {
+        Ok(self.get_data()?.network_settings)
+    }
+
+    async fn set_network_settings(
+        &self,
+        network_settings: Option<NetworkSettings>,
+    ) -> LedgerClientResult<()> {
+        self.update_data(|data| {
+            data.network_settings = network_settings;
+            Ok(())
+        })
+    }
+
+    async fn snapshot(&self) -> LedgerClientResult<TestLedgerSnapshot> {
+        Ok(self.get_data()?.into())
+    }
+
+    async fn restore(&self, snapshot: TestLedgerSnapshot) -> LedgerClientResult<()> {
+        let _lock = self.lock_exclusive()?;
+        self.write_data(&snapshot.into())
+    }
 }
 
 #[cfg(test)]
@@ -371,7 +674,8 @@ mod tests {
             starting_amount,
             0,
             BLOCK_LENGTH,
-        );
+        )
+        .unwrap();
         let mut outputs = storage.all_outputs(&signer).await.unwrap();
         assert_eq!(outputs.len(), 1);
         let first_output = outputs.pop().unwrap();
@@ -393,7 +697,8 @@ mod tests {
             starting_amount,
             0,
             BLOCK_LENGTH,
-        );
+        )
+        .unwrap();
         let current_time = storage.current_time().await.unwrap();
         assert_eq!(current_time, 0);
         let new_time = 20;
@@ -416,18 +721,265 @@ mod tests {
             starting_amount,
             0,
             BLOCK_LENGTH,
-        );
+        )
+        .unwrap();
         let bob = "Bob";
         let bob_address = Address::from_bech32("addr_test1qrksjmprvgcedgdt6rhg40590vr6exdzdc2hm5wc6pyl9ymkyskmqs55usm57gflrumk9kd63f3ty6r0l2tdfwfm28qs0rurdr").unwrap();
-        storage.add_new_signer(bob, &bob_address, starting_amount);
+        storage
+            .add_new_signer(bob, &bob_address, starting_amount)
+            .unwrap();
 
         // When
         let signer = storage.signer().await.unwrap();
         assert_eq!(signer, alice_address);
 
         // And
-        storage.switch_signer(bob);
+        storage.switch_signer(bob).unwrap();
         let signer = storage.signer().await.unwrap();
         assert_eq!(signer, bob_address);
     }
+
+    #[tokio::test]
+    async fn restore__reverts_to_snapshot() {
+        // Given
+        let alice = "Alice";
+        let alice_address = Address::from_bech32("addr_test1qrksjmprvgcedgdt6rhg40590vr6exdzdc2hm5wc6pyl9ymkyskmqs55usm57gflrumk9kd63f3ty6r0l2tdfwfm28qs0rurdr").unwrap();
+        let starting_amount = 10_000_000;
+        let tmp_dir = TempDir::new().unwrap();
+        let storage = LocalPersistedStorage::<TempDir, ()>::init(
+            tmp_dir,
+            alice,
+            &alice_address,
+            starting_amount,
+            0,
+            BLOCK_LENGTH,
+        )
+        .unwrap();
+        let snapshot = storage.snapshot().await.unwrap();
+        let output = storage
+            .all_outputs(&alice_address)
+            .await
+            .unwrap()
+            .pop()
+            .unwrap();
+        storage.remove_output(&output).await.unwrap();
+        storage.set_current_time(100).await.unwrap();
+
+        // When
+        storage.restore(snapshot).await.unwrap();
+
+        // Then
+        let outputs = storage.all_outputs(&alice_address).await.unwrap();
+        assert_eq!(outputs, vec![output]);
+        let current_time = storage.current_time().await.unwrap();
+        assert_eq!(current_time, 0);
+    }
+
+    const ALICE: &str = "addr_test1qrksjmprvgcedgdt6rhg40590vr6exdzdc2hm5wc6pyl9ymkyskmqs55usm57gflrumk9kd63f3ty6r0l2tdfwfm28qs0rurdr";
+
+    fn init_storage(dir: &Path) -> LocalPersistedStorage<PathBuf, ()> {
+        let signer = Address::from_bech32(ALICE).unwrap();
+        LocalPersistedStorage::init(
+            dir.to_owned(),
+            "Alice",
+            &signer,
+            10_000_000,
+            0,
+            BLOCK_LENGTH,
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn switch_signer__unknown_signer_fails() {
+        // Given
+        let tmp_dir = TempDir::new().unwrap();
+        let storage = init_storage(tmp_dir.path());
+
+        // When
+        let error = storage.switch_signer("Nobody").unwrap_err();
+
+        // Then
+        assert!(matches!(error, LedgerClientError::Storage(_)));
+        let signer = storage.active_signer_name().unwrap();
+        assert_eq!(signer, "Alice");
+    }
+
+    #[tokio::test]
+    async fn load__fails_without_data() {
+        let tmp_dir = TempDir::new().unwrap();
+
+        let result = LocalPersistedStorage::<_, ()>::load(tmp_dir.path());
+
+        assert!(matches!(result, Err(LedgerClientError::Storage(_))));
+    }
+
+    #[tokio::test]
+    async fn load__fails_without_directory() {
+        let tmp_dir = TempDir::new().unwrap();
+
+        let result = LocalPersistedStorage::<_, ()>::load(tmp_dir.path().join("missing"));
+
+        assert!(matches!(result, Err(LedgerClientError::Storage(_))));
+    }
+
+    #[tokio::test]
+    async fn init__creates_missing_directories() {
+        // Given
+        let tmp_dir = TempDir::new().unwrap();
+        let dir = tmp_dir.path().join("envs").join("fresh");
+
+        // When
+        let storage = init_storage(&dir);
+
+        // Then
+        let signer = storage.signer().await.unwrap();
+        assert_eq!(storage.all_outputs(&signer).await.unwrap().len(), 1);
+        assert!(LocalPersistedStorage::<_, ()>::load(&dir).is_ok());
+    }
+
+    #[tokio::test]
+    async fn load__migrates_unversioned_data() {
+        // Given
+        let tmp_dir = TempDir::new().unwrap();
+        let storage = init_storage(tmp_dir.path());
+        let expected = storage.all_outputs(&storage.signer().await.unwrap()).await;
+        let data_path = tmp_dir.path().join(DATA);
+        let mut data: Value =
+            serde_json::from_str(&fs::read_to_string(&data_path).unwrap()).unwrap();
+        let data_obj = data.as_object_mut().unwrap();
+        data_obj.remove("version");
+        for output in data_obj["outputs"].as_array_mut().unwrap() {
+            output.as_object_mut().unwrap().remove("datum_hash");
+        }
+        fs::write(&data_path, data.to_string()).unwrap();
+
+        // When
+        let storage = LocalPersistedStorage::<_, ()>::load(tmp_dir.path()).unwrap();
+
+        // Then
+        let signer = storage.signer().await.unwrap();
+        let actual = storage.all_outputs(&signer).await;
+        assert_eq!(expected.unwrap(), actual.unwrap());
+        let migrated: Value =
+            serde_json::from_str(&fs::read_to_string(&data_path).unwrap()).unwrap();
+        assert_eq!(migrated["version"], Value::from(CURRENT_DATA_VERSION));
+    }
+
+    #[tokio::test]
+    async fn load__does_not_rewrite_current_data() {
+        // Given
+        let tmp_dir = TempDir::new().unwrap();
+        let _ = init_storage(tmp_dir.path());
+        let data_path = tmp_dir.path().join(DATA);
+        // Same data, different formatting, so any rewrite would show
+        let data: Value =
+            serde_json::from_str(&fs::read_to_string(&data_path).unwrap()).unwrap();
+        let pretty = serde_json::to_string_pretty(&data).unwrap();
+        fs::write(&data_path, &pretty).unwrap();
+
+        // When
+        let _ = LocalPersistedStorage::<_, ()>::load(tmp_dir.path()).unwrap();
+
+        // Then
+        assert_eq!(fs::read_to_string(&data_path).unwrap(), pretty);
+    }
+
+    #[tokio::test]
+    async fn load__fails_for_newer_version() {
+        // Given
+        let tmp_dir = TempDir::new().unwrap();
+        init_storage(tmp_dir.path());
+        let data_path = tmp_dir.path().join(DATA);
+        let mut data: Value =
+            serde_json::from_str(&fs::read_to_string(&data_path).unwrap()).unwrap();
+        data["version"] = Value::from(CURRENT_DATA_VERSION + 1);
+        fs::write(&data_path, data.to_string()).unwrap();
+
+        // When
+        let result = LocalPersistedStorage::<_, ()>::load(tmp_dir.path());
+
+        // Then
+        assert!(matches!(result, Err(LedgerClientError::Storage(_))));
+    }
+
+    #[tokio::test]
+    async fn set_current_time__leaves_no_temporary_file() {
+        let tmp_dir = TempDir::new().unwrap();
+        let storage = init_storage(tmp_dir.path());
+
+        storage.set_current_time(100).await.unwrap();
+
+        assert!(!tmp_dir.path().join(DATA_TMP).exists());
+        assert_eq!(storage.current_time().await.unwrap(), 100);
+    }
+
+    #[tokio::test]
+    async fn apply_transaction__failure_leaves_data_unchanged() {
+        // Given
+        let tmp_dir = TempDir::new().unwrap();
+        let storage = init_storage(tmp_dir.path());
+        let signer = Address::from_bech32(ALICE).unwrap();
+        let existing = storage.all_outputs(&signer).await.unwrap();
+        let missing = starting_output_at_position(&signer, 1, 1);
+        let produced = vec![starting_output_at_position(&signer, 2, 2)];
+
+        // When
+        let consumed = vec![existing[0].clone(), missing];
+        let result = storage.apply_transaction(&consumed, &produced, 100).await;
+
+        // Then
+        assert!(result.is_err());
+        assert_eq!(storage.all_outputs(&signer).await.unwrap(), existing);
+        assert_eq!(storage.current_time().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn apply_transaction__replaces_consumed_outputs() {
+        // Given
+        let tmp_dir = TempDir::new().unwrap();
+        let storage = init_storage(tmp_dir.path());
+        let signer = Address::from_bech32(ALICE).unwrap();
+        let consumed = storage.all_outputs(&signer).await.unwrap();
+        let produced = vec![starting_output_at_position(&signer, 2, 1)];
+
+        // When
+        storage
+            .apply_transaction(&consumed, &produced, 100)
+            .await
+            .unwrap();
+
+        // Then
+        assert_eq!(storage.all_outputs(&signer).await.unwrap(), produced);
+        assert_eq!(storage.current_time().await.unwrap(), 100);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn add_output__concurrent_writers_do_not_lose_updates() {
+        // Given
+        let tmp_dir = TempDir::new().unwrap();
+        let signer = Address::from_bech32(ALICE).unwrap();
+        init_storage(tmp_dir.path());
+        let writers = 8;
+
+        // When
+        let mut handles = Vec::new();
+        for i in 0..writers {
+            let dir = tmp_dir.path().to_owned();
+            let signer = signer.clone();
+            handles.push(tokio::spawn(async move {
+                let storage = LocalPersistedStorage::<_, ()>::load(dir).unwrap();
+                let output = starting_output_at_position(&signer, 1, i + 1);
+                storage.add_output(&output).await.unwrap();
+            }));
+        }
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        // Then
+        let storage = LocalPersistedStorage::<_, ()>::load(tmp_dir.path()).unwrap();
+        let outputs = storage.all_outputs(&signer).await.unwrap();
+        assert_eq!(outputs.len() as u64, writers + 1);
+    }
 }
@@ -0,0 +1,143 @@
+use crate::{
+    ledger_client::{
+        test_ledger_client::local_persisted_storage::LDOutput,
+        LedgerClientError,
+        LedgerClientResult,
+    },
+    output::Output,
+    scripts::plutus_validator::plutus_data::PlutusData,
+    trireme_ledger_client::cml_client::network_settings::NetworkSettings,
+};
+use pallas_addresses::Address;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{
+        Read,
+        Write,
+    },
+    path::Path,
+};
+
+/// Name given to the signer of a ledger that doesn't keep track of signer names, e.g. the
+/// [`InMemoryStorage`](super::in_memory_storage::InMemoryStorage)
+pub const DEFAULT_SIGNER_NAME: &str = "Alice";
+
+/// Serializable copy of the entire state of a [`TestLedgerStorage`](super::TestLedgerStorage).
+///
+/// A snapshot can be taken from any storage and restored into any other storage, or saved to a
+/// file and used to seed a [`TestLedgerClientBuilder`](super::TestLedgerClientBuilder):
+///
+/// ```ignore
+///     let snapshot = ledger_client.snapshot().await?;
+///     // ... run a scenario ...
+///     ledger_client.restore(snapshot).await?;
+///     // ... try a different branch from the same starting point ...
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TestLedgerSnapshot {
+    pub(crate) active_signer_name: String,
+    pub(crate) active_signer: String,
+    pub(crate) signers: HashMap<String, String>,
+    pub(crate) outputs: Vec<LDOutput>,
+    pub(crate) current_time: i64,
+    pub(crate) block_length: i64,
+    #[serde(default)]
+    pub(crate) network_settings: Option<NetworkSettings>,
+}
+
+impl TestLedgerSnapshot {
+    /// Constructor for a [`TestLedgerSnapshot`] of a ledger with a single, unnamed signer
+    pub fn new<Datum: Clone + Into<PlutusData>>(
+        signer: &Address,
+        outputs: Vec<Output<Datum>>,
+        current_time: i64,
+        block_length: i64,
+    ) -> LedgerClientResult<Self> {
+        let signer_bech_32 = signer
+            .to_bech32()
+            .map_err(|e| LedgerClientError::BadAddress(Box::new(e)))?;
+        let mut signers = HashMap::new();
+        signers.insert(DEFAULT_SIGNER_NAME.to_string(), signer_bech_32.clone());
+        let outputs = outputs
+            .into_iter()
+            .map(TryInto::try_into)
+            .collect::<LedgerClientResult<_>>()?;
+        let snapshot = TestLedgerSnapshot {
+            active_signer_name: DEFAULT_SIGNER_NAME.to_string(),
+            active_signer: signer_bech_32,
+            signers,
+            outputs,
+            current_time,
+            block_length,
+            network_settings: None,
+        };
+        Ok(snapshot)
+    }
+
+    /// Record that the ledger runs on the slots of the given network
+    pub fn with_network_settings(mut self, network_settings: NetworkSettings) -> Self {
+        self.network_settings = Some(network_settings);
+        self
+    }
+
+    /// Getter for the active signer's address
+    pub fn signer(&self) -> LedgerClientResult<Address> {
+        Address::from_bech32(&self.active_signer)
+            .map_err(|e| LedgerClientError::BadAddress(Box::new(e)))
+    }
+
+    /// Getter for all the outputs on the ledger. Datums will be typed if they can be converted
+    /// to `Datum`
+    pub fn outputs<Datum: Clone + TryFrom<PlutusData>>(
+        &self,
+    ) -> LedgerClientResult<Vec<Output<Datum>>> {
+        self.outputs
+            .iter()
+            .cloned()
+            .map(TryInto::<Output<Datum>>::try_into)
+            .map(|output| output.map(|output| output.with_typed_datum_if_possible()))
+            .collect()
+    }
+
+    /// Getter for the time of the ledger when the snapshot was taken
+    pub fn current_time(&self) -> i64 {
+        self.current_time
+    }
+
+    /// Getter for the time between blocks in seconds
+    pub fn block_length(&self) -> i64 {
+        self.block_length
+    }
+
+    /// Getter for the [`NetworkSettings`] if the ledger is running on slots
+    pub fn network_settings(&self) -> Option<NetworkSettings> {
+        self.network_settings
+    }
+
+    /// Write the snapshot to the given file as JSON
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> LedgerClientResult<()> {
+        let serialized = serde_json::to_string(&self)
+            .map_err(|e| LedgerClientError::Storage(Box::new(e)))?;
+        let mut file =
+            File::create(path).map_err(|e| LedgerClientError::Storage(Box::new(e)))?;
+        file.write_all(&serialized.into_bytes())
+            .map_err(|e| LedgerClientError::Storage(Box::new(e)))?;
+        Ok(())
+    }
+
+    /// Read a snapshot previously written with [`TestLedgerSnapshot::save`]
+    pub fn load<P: AsRef<Path>>(path: P) -> LedgerClientResult<Self> {
+        let mut file =
+            File::open(path).map_err(|e| LedgerClientError::Storage(Box::new(e)))?;
+        let mut contents = String::new();
+        file.read_to_string(&mut contents)
+            .map_err(|e| LedgerClientError::Storage(Box::new(e)))?;
+        serde_json::from_str(&contents)
+            .map_err(|e| LedgerClientError::Storage(Box::new(e)))
+    }
+}
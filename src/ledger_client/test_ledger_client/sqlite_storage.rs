@@ -0,0 +1,1011 @@
+use pallas_addresses::{
+    Address,
+    Network,
+};
+use rusqlite::{
+    params,
+    Connection,
+    OptionalExtension,
+    Params,
+    Transaction,
+    TransactionBehavior,
+};
+use std::{
+    collections::HashMap,
+    marker::PhantomData,
+    path::{
+        Path,
+        PathBuf,
+    },
+    sync::{
+        Mutex,
+        MutexGuard,
+    },
+    time::Duration,
+};
+use thiserror::Error;
+
+use crate::{
+    ledger_client::{
+        test_ledger_client::{
+            local_persisted_storage::{
+                address_to_bech32,
+                as_storage_error,
+                starting_output_at_position,
+            },
+            snapshot::TestLedgerSnapshot,
+            TestLCError,
+            TestLedgerStorage,
+        },
+        LedgerClientError,
+        LedgerClientResult,
+    },
+    output::{
+        Output,
+        OutputId,
+    },
+    scripts::plutus_validator::plutus_data::PlutusData,
+    trireme_ledger_client::cml_client::network_settings::NetworkSettings,
+    values::Values,
+    PolicyId,
+};
+
+/// Version of the database schema. Bump this, update [`SCHEMA`] and add a migration to
+/// [`MIGRATIONS`] whenever the tables change.
+pub const CURRENT_SCHEMA_VERSION: u64 = 2;
+
+/// Migrations between versions of the schema. The migration at index `n` takes the database
+/// from version `n + 1` to version `n + 2`, as there never was a version 0. Each one is run in
+/// the same database transaction as the version update, so a failed migration changes nothing.
+const MIGRATIONS: [&str; CURRENT_SCHEMA_VERSION as usize - 1] = [MIGRATE_V1_TO_V2];
+
+/// Version 2 keeps the [`NetworkSettings`] of ledgers that run on slots
+const MIGRATE_V1_TO_V2: &str = "ALTER TABLE ledger ADD COLUMN network_settings TEXT;";
+
+/// Name of the database file inside the storage directory
+pub const DATABASE: &str = "ledger.sqlite";
+
+/// How long to wait for another process to release the database before failing
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+const SCHEMA: &str = "
+    CREATE TABLE ledger (
+        id INTEGER PRIMARY KEY CHECK (id = 0),
+        version INTEGER NOT NULL,
+        active_signer_name TEXT NOT NULL,
+        current_time INTEGER NOT NULL,
+        block_length INTEGER NOT NULL,
+        network_settings TEXT
+    );
+    CREATE TABLE signers (
+        name TEXT PRIMARY KEY,
+        address TEXT NOT NULL
+    );
+    CREATE TABLE outputs (
+        id INTEGER PRIMARY KEY,
+        tx_hash BLOB NOT NULL,
+        output_index INTEGER NOT NULL,
+        owner TEXT NOT NULL,
+        datum TEXT,
+        datum_hash BLOB,
+        UNIQUE (tx_hash, output_index)
+    );
+    CREATE INDEX outputs_by_owner ON outputs (owner);
+    CREATE TABLE output_values (
+        output_id INTEGER NOT NULL,
+        policy TEXT,
+        asset TEXT,
+        amount INTEGER NOT NULL
+    );
+    CREATE INDEX output_values_by_output ON output_values (output_id);
+    CREATE INDEX output_values_by_policy ON output_values (policy, asset);
+";
+
+const OUTPUT_COLUMNS: &str = "id, tx_hash, output_index, owner, datum, datum_hash";
+
+#[derive(Debug, Error)]
+enum SqliteLCError {
+    #[error("Signer not found: {0:?}")]
+    UnknownSigner(String),
+    #[error("No ledger database found at {0:?}")]
+    NoData(PathBuf),
+    #[error("Ledger database has version {0:?}, but only up to {1:?} is supported")]
+    UnsupportedVersion(u64, u64),
+    #[error("No output with the given id")]
+    UnknownOutput,
+}
+
+/// Row of the `outputs` table, before its values are joined in
+struct OutputRow {
+    id: i64,
+    tx_hash: Vec<u8>,
+    index: u64,
+    owner: String,
+    datum: Option<String>,
+    datum_hash: Option<Vec<u8>>,
+}
+
+/// A [`TestLedgerStorage`] implementation backed by an embedded SQLite database in a local
+/// directory.
+///
+/// Unlike the [`LocalPersistedStorage`](super::local_persisted_storage::LocalPersistedStorage),
+/// outputs are indexed by owner, [`OutputId`] and policy, so lookups don't have to read the
+/// whole ledger. This makes it a better fit for long-running mock environments with many
+/// outputs. Each transaction is applied inside a single database transaction, so a failure
+/// never leaves the ledger half-updated.
+pub struct SqliteStorage<T: AsRef<Path>, Datum> {
+    /// Directory of persisted storage
+    dir: T,
+    connection: Mutex<Connection>,
+    _datum: PhantomData<Datum>,
+}
+
+impl<T, Datum> SqliteStorage<T, Datum>
+where
+    T: AsRef<Path>,
+    Datum: Clone + Into<PlutusData>,
+{
+    /// Initialize a new [`SqliteStorage`]. If there is already a database in `dir`, it is kept
+    /// as is.
+    pub fn init(
+        dir: T,
+        signer_name: &str,
+        signer: &Address,
+        starting_amount: u64,
+        starting_time: i64,
+        block_length: i64,
+    ) -> LedgerClientResult<Self> {
+        let storage = Self::open(dir)?;
+        let address = address_to_bech32(signer)?;
+        let initialized = storage.transaction(|tx| {
+            let initialized: bool = tx
+                .query_row(
+                    "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE name = 'ledger')",
+                    [],
+                    |row| row.get(0),
+                )
+                .map_err(as_storage_error)?;
+            if !initialized {
+                tx.execute_batch(SCHEMA).map_err(as_storage_error)?;
+                tx.execute(
+                    "INSERT INTO ledger
+                     (id, version, active_signer_name, current_time, block_length)
+                     VALUES (0, ?1, ?2, ?3, ?4)",
+                    params![
+                        CURRENT_SCHEMA_VERSION,
+                        signer_name,
+                        starting_time,
+                        block_length
+                    ],
+                )
+                .map_err(as_storage_error)?;
+                tx.execute(
+                    "INSERT INTO signers VALUES (?1, ?2)",
+                    params![signer_name, address],
+                )
+                .map_err(as_storage_error)?;
+                let output: Output<Datum> =
+                    starting_output_at_position(signer, starting_amount, 0);
+                insert_output(tx, &output)?;
+            }
+            Ok(initialized)
+        })?;
+        if initialized {
+            storage.migrate()?;
+        }
+        Ok(storage)
+    }
+
+    /// Constructor for revived [`SqliteStorage`]. Fails if there is no database in `dir`, or
+    /// if it was written by a newer version. Databases from older versions are migrated to the
+    /// current version.
+    pub fn load(dir: T) -> LedgerClientResult<Self> {
+        let path = dir.as_ref().join(DATABASE);
+        if !path.exists() {
+            return Err(as_storage_error(SqliteLCError::NoData(path)));
+        }
+        let storage = Self::open(dir)?;
+        storage.migrate()?;
+        Ok(storage)
+    }
+
+    fn open(dir: T) -> LedgerClientResult<Self> {
+        let connection =
+            Connection::open(dir.as_ref().join(DATABASE)).map_err(as_storage_error)?;
+        connection
+            .busy_timeout(BUSY_TIMEOUT)
+            .map_err(as_storage_error)?;
+        Ok(SqliteStorage {
+            dir,
+            connection: Mutex::new(connection),
+            _datum: Default::default(),
+        })
+    }
+
+    /// Getter for the directory of the database
+    pub fn dir(&self) -> &Path {
+        self.dir.as_ref()
+    }
+
+    /// Bring the database up to [`CURRENT_SCHEMA_VERSION`]. Fails if it is from a newer version
+    fn migrate(&self) -> LedgerClientResult<()> {
+        self.transaction(|tx| {
+            let version: u64 = tx
+                .query_row("SELECT version FROM ledger", [], |row| row.get(0))
+                .map_err(as_storage_error)?;
+            if version > CURRENT_SCHEMA_VERSION {
+                return Err(as_storage_error(SqliteLCError::UnsupportedVersion(
+                    version,
+                    CURRENT_SCHEMA_VERSION,
+                )));
+            }
+            let pending = MIGRATIONS.iter().skip(version.saturating_sub(1) as usize);
+            for migration in pending {
+                tx.execute_batch(migration).map_err(as_storage_error)?;
+            }
+            if version < CURRENT_SCHEMA_VERSION {
+                tx.execute(
+                    "UPDATE ledger SET version = ?1",
+                    params![CURRENT_SCHEMA_VERSION],
+                )
+                .map_err(as_storage_error)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn connection(&self) -> LedgerClientResult<MutexGuard<Connection>> {
+        self.connection
+            .lock()
+            .map_err(|e| TestLCError::Mutex(format! {"{e:?}"}))
+            .map_err(|e| LedgerClientError::Storage(Box::new(e)))
+    }
+
+    /// Run `f` inside a database transaction. Nothing `f` does is kept unless it succeeds.
+    /// The write lock is taken up front, so other processes wait instead of failing midway.
+    fn transaction<R>(
+        &self,
+        f: impl FnOnce(&Transaction) -> LedgerClientResult<R>,
+    ) -> LedgerClientResult<R> {
+        let mut connection = self.connection()?;
+        let tx = connection
+            .transaction_with_behavior(TransactionBehavior::Immediate)
+            .map_err(as_storage_error)?;
+        let res = f(&tx)?;
+        tx.commit().map_err(as_storage_error)?;
+        Ok(res)
+    }
+
+    /// Add a new identity to the ledger
+    pub fn add_new_signer(
+        &self,
+        name: &str,
+        address: &Address,
+        starting_amount: u64,
+    ) -> LedgerClientResult<()> {
+        let address_bech_32 = address_to_bech32(address)?;
+        self.transaction(|tx| {
+            let position: u64 = tx
+                .query_row("SELECT COUNT(*) FROM outputs", [], |row| row.get(0))
+                .map_err(as_storage_error)?;
+            let output: Output<Datum> =
+                starting_output_at_position(address, starting_amount, position);
+            insert_output(tx, &output)?;
+            tx.execute(
+                "INSERT OR REPLACE INTO signers VALUES (?1, ?2)",
+                params![name, address_bech_32],
+            )
+            .map_err(as_storage_error)?;
+            Ok(())
+        })
+    }
+
+    /// Get the name of the active signer
+    pub fn active_signer_name(&self) -> LedgerClientResult<String> {
+        self.connection()?
+            .query_row("SELECT active_signer_name FROM ledger", [], |row| {
+                row.get(0)
+            })
+            .map_err(as_storage_error)
+    }
+
+    /// Get list of all signers, active and inactive
+    pub fn get_signers(&self) -> LedgerClientResult<Vec<String>> {
+        let connection = self.connection()?;
+        let mut statement = connection
+            .prepare("SELECT name FROM signers ORDER BY rowid")
+            .map_err(as_storage_error)?;
+        let signers = statement
+            .query_map([], |row| row.get(0))
+            .map_err(as_storage_error)?
+            .collect::<Result<_, _>>()
+            .map_err(as_storage_error)?;
+        Ok(signers)
+    }
+
+    /// Switch the active signer. Fails if there is no signer with the given name
+    pub fn switch_signer(&self, name: &str) -> LedgerClientResult<()> {
+        let updated = self
+            .connection()?
+            .execute(
+                "UPDATE ledger SET active_signer_name = ?1
+                 WHERE EXISTS (SELECT 1 FROM signers WHERE name = ?1)",
+                params![name],
+            )
+            .map_err(as_storage_error)?;
+        if updated == 0 {
+            return Err(as_storage_error(SqliteLCError::UnknownSigner(
+                name.to_string(),
+            )));
+        }
+        Ok(())
+    }
+
+    /// Get all the outputs holding any amount of the given token
+    pub fn outputs_with_policy(
+        &self,
+        policy: &PolicyId,
+    ) -> LedgerClientResult<Vec<Output<Datum>>>
+    where
+        Datum: TryFrom<PlutusData>,
+    {
+        let (policy, asset) = policy_columns(policy);
+        self.outputs_where(
+            "id IN (SELECT output_id FROM output_values WHERE policy IS ?1 AND asset IS ?2)",
+            params![policy, asset],
+        )
+    }
+
+    fn outputs_at(
+        &self,
+        address: &Address,
+        count: Option<usize>,
+    ) -> LedgerClientResult<Vec<Output<Datum>>>
+    where
+        Datum: TryFrom<PlutusData>,
+    {
+        let owner = address_to_bech32(address)?;
+        // SQLite treats a negative limit as no limit
+        let limit = count.map(|count| count as i64).unwrap_or(-1);
+        self.outputs_where("owner = ?1 ORDER BY id LIMIT ?2", params![owner, limit])
+    }
+
+    fn outputs_where<P: Params>(
+        &self,
+        condition: &str,
+        params: P,
+    ) -> LedgerClientResult<Vec<Output<Datum>>>
+    where
+        Datum: TryFrom<PlutusData>,
+    {
+        let connection = self.connection()?;
+        select_outputs(&connection, condition, params)?
+            .into_iter()
+            .map(|output| output.map(|output| output.with_typed_datum_if_possible()))
+            .collect()
+    }
+}
+
+fn policy_columns(policy: &PolicyId) -> (Option<String>, Option<String>) {
+    match policy {
+        PolicyId::Lovelace => (None, None),
+        PolicyId::NativeToken(id, asset) => (Some(id.to_owned()), asset.to_owned()),
+    }
+}
+
+fn insert_output<Datum: Clone + Into<PlutusData>>(
+    tx: &Transaction,
+    output: &Output<Datum>,
+) -> LedgerClientResult<()> {
+    let owner = address_to_bech32(&output.owner())?;
+    let datum = output
+        .datum_plutus_data()
+        .map(|datum| serde_json::to_string(&datum))
+        .transpose()
+        .map_err(as_storage_error)?;
+    tx.execute(
+        "INSERT INTO outputs (tx_hash, output_index, owner, datum, datum_hash)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            output.id().tx_hash(),
+            output.id().index(),
+            owner,
+            datum,
+            output.datum_hash()
+        ],
+    )
+    .map_err(as_storage_error)?;
+    let output_id = tx.last_insert_rowid();
+    for (policy, amount) in output.values().as_iter() {
+        let (policy, asset) = policy_columns(policy);
+        tx.execute(
+            "INSERT INTO output_values VALUES (?1, ?2, ?3, ?4)",
+            params![output_id, policy, asset, amount],
+        )
+        .map_err(as_storage_error)?;
+    }
+    Ok(())
+}
+
+fn delete_output(tx: &Transaction, id: &OutputId) -> LedgerClientResult<()> {
+    let row_id: Option<i64> = tx
+        .query_row(
+            "SELECT id FROM outputs WHERE tx_hash = ?1 AND output_index = ?2",
+            params![id.tx_hash(), id.index()],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(as_storage_error)?;
+    let row_id = row_id.ok_or_else(|| {
+        LedgerClientError::FailedToRetrieveOutputWithId(
+            id.clone(),
+            Box::new(SqliteLCError::UnknownOutput),
+        )
+    })?;
+    tx.execute(
+        "DELETE FROM output_values WHERE output_id = ?1",
+        params![row_id],
+    )
+    .map_err(as_storage_error)?;
+    tx.execute("DELETE FROM outputs WHERE id = ?1", params![row_id])
+        .map_err(as_storage_error)?;
+    Ok(())
+}
+
+fn select_outputs<Datum, P: Params>(
+    connection: &Connection,
+    condition: &str,
+    params: P,
+) -> LedgerClientResult<Vec<LedgerClientResult<Output<Datum>>>> {
+    let mut rows_statement = connection
+        .prepare(&format!(
+            "SELECT {OUTPUT_COLUMNS} FROM outputs WHERE {condition}"
+        ))
+        .map_err(as_storage_error)?;
+    let rows = rows_statement
+        .query_map(params, |row| {
+            Ok(OutputRow {
+                id: row.get(0)?,
+                tx_hash: row.get(1)?,
+                index: row.get(2)?,
+                owner: row.get(3)?,
+                datum: row.get(4)?,
+                datum_hash: row.get(5)?,
+            })
+        })
+        .map_err(as_storage_error)?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(as_storage_error)?;
+    let mut values_statement = connection
+        .prepare("SELECT policy, asset, amount FROM output_values WHERE output_id = ?1")
+        .map_err(as_storage_error)?;
+    let mut outputs = Vec::new();
+    for row in rows {
+        let mut values = Values::default();
+        let mut value_rows = values_statement
+            .query(params![row.id])
+            .map_err(as_storage_error)?;
+        while let Some(value_row) = value_rows.next().map_err(as_storage_error)? {
+            let policy: Option<String> = value_row.get(0).map_err(as_storage_error)?;
+            let asset: Option<String> = value_row.get(1).map_err(as_storage_error)?;
+            let amount: u64 = value_row.get(2).map_err(as_storage_error)?;
+            let policy = match policy {
+                Some(id) => PolicyId::native_token(&id, &asset),
+                None => PolicyId::Lovelace,
+            };
+            values.add_one_value(&policy, amount);
+        }
+        outputs.push(output_from_row(row, values));
+    }
+    Ok(outputs)
+}
+
+fn output_from_row<Datum>(
+    row: OutputRow,
+    values: Values,
+) -> LedgerClientResult<Output<Datum>> {
+    let OutputRow {
+        tx_hash,
+        index,
+        owner,
+        datum,
+        datum_hash,
+        ..
+    } = row;
+    let owner = Address::from_bech32(&owner)
+        .map_err(|e| LedgerClientError::BadAddress(Box::new(e)))?;
+    let output = match datum {
+        Some(datum) => {
+            let datum: PlutusData =
+                serde_json::from_str(&datum).map_err(as_storage_error)?;
+            Output::new_untyped_validator(tx_hash, index, owner, values, datum)
+        }
+        None => Output::new_wallet(tx_hash, index, owner, values),
+    };
+    let output = match datum_hash {
+        Some(datum_hash) => output.with_datum_hash(datum_hash),
+        None => output,
+    };
+    Ok(output)
+}
+
+/// [`NetworkSettings`] are kept as JSON, so they can change without a migration
+fn network_settings_column(
+    network_settings: Option<NetworkSettings>,
+) -> LedgerClientResult<Option<String>> {
+    network_settings
+        .map(|network_settings| serde_json::to_string(&network_settings))
+        .transpose()
+        .map_err(as_storage_error)
+}
+
+fn network_settings_from_column(
+    network_settings: Option<String>,
+) -> LedgerClientResult<Option<NetworkSettings>> {
+    network_settings
+        .map(|network_settings| serde_json::from_str(&network_settings))
+        .transpose()
+        .map_err(as_storage_error)
+}
+
+#[async_trait::async_trait]
+impl<T, Datum> TestLedgerStorage<Datum> for SqliteStorage<T, Datum>
+where
+    T: AsRef<Path> + Send + Sync,
+    Datum: Clone + Send + Sync + PartialEq + Into<PlutusData> + TryFrom<PlutusData>,
+{
+    async fn signer(&self) -> LedgerClientResult<Address> {
+        let signer: String = self
+            .connection()?
+            .query_row(
+                "SELECT address FROM signers
+                 WHERE name = (SELECT active_signer_name FROM ledger)",
+                [],
+                |row| row.get(0),
+            )
+            .map_err(as_storage_error)?;
+        Address::from_bech32(&signer)
+            .map_err(|e| LedgerClientError::BadAddress(Box::new(e)))
+    }
+
+    async fn outputs_by_count(
+        &self,
+        address: &Address,
+        count: usize,
+    ) -> LedgerClientResult<Vec<Output<Datum>>> {
+        self.outputs_at(address, Some(count))
+    }
+
+    async fn all_outputs(
+        &self,
+        address: &Address,
+    ) -> LedgerClientResult<Vec<Output<Datum>>> {
+        self.outputs_at(address, None)
+    }
+
+    async fn output_by_id(
+        &self,
+        id: &OutputId,
+    ) -> LedgerClientResult<Option<Output<Datum>>> {
+        let outputs = self.outputs_where(
+            "tx_hash = ?1 AND output_index = ?2",
+            params![id.tx_hash(), id.index()],
+        )?;
+        Ok(outputs.into_iter().next())
+    }
+
+    async fn remove_output(&self, output: &Output<Datum>) -> LedgerClientResult<()> {
+        self.transaction(|tx| delete_output(tx, output.id()))
+    }
+
+    async fn add_output(&self, output: &Output<Datum>) -> LedgerClientResult<()> {
+        self.transaction(|tx| insert_output(tx, output))
+    }
+
+    async fn apply_transaction(
+        &self,
+        consumed: &[Output<Datum>],
+        produced: &[Output<Datum>],
+        new_time: i64,
+    ) -> LedgerClientResult<()> {
+        self.transaction(|tx| {
+            for output in consumed {
+                delete_output(tx, output.id())?;
+            }
+            for output in produced {
+                insert_output(tx, output)?;
+            }
+            tx.execute("UPDATE ledger SET current_time = ?1", params![new_time])
+                .map_err(as_storage_error)?;
+            Ok(())
+        })
+    }
+
+    async fn current_time(&self) -> LedgerClientResult<i64> {
+        self.connection()?
+            .query_row("SELECT current_time FROM ledger", [], |row| row.get(0))
+            .map_err(as_storage_error)
+    }
+
+    async fn set_current_time(&self, posix_time: i64) -> LedgerClientResult<()> {
+        self.connection()?
+            .execute("UPDATE ledger SET current_time = ?1", params![posix_time])
+            .map_err(as_storage_error)?;
+        Ok(())
+    }
+
+    async fn get_block_length(&self) -> LedgerClientResult<i64> {
+        self.connection()?
+            .query_row("SELECT block_length FROM ledger", [], |row| row.get(0))
+            .map_err(as_storage_error)
+    }
+
+    async fn network(&self) -> LedgerClientResult<Network> {
+        Ok(Network::Testnet)
+    }
+
+    async fn network_settings(&self) -> LedgerClientResult<Option<NetworkSettings>> {
+        let network_settings: Option<String> = self
+            .connection()?
+            .query_row("SELECT network_settings FROM ledger", [], |row| row.get(0))
+            .map_err(as_storage_error)?;
+        network_settings_from_column(network_settings)
+    }
+
+    async fn set_network_settings(
+        &self,
+        network_settings: Option<NetworkSettings>,
+    ) -> LedgerClientResult<()> {
+        let network_settings = network_settings_column(network_settings)?;
+        self.connection()?
+            .execute(
+                "UPDATE ledger SET network_settings = ?1",
+                params![network_settings],
+            )
+            .map_err(as_storage_error)?;
+        Ok(())
+    }
+
+    async fn snapshot(&self) -> LedgerClientResult<TestLedgerSnapshot> {
+        let mut connection = self.connection()?;
+        // Read everything from one transaction so the snapshot is consistent
+        let tx = connection.transaction().map_err(as_storage_error)?;
+        let (active_signer_name, current_time, block_length, network_settings) = tx
+            .query_row(
+                "SELECT active_signer_name, current_time, block_length, network_settings
+                 FROM ledger",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .map_err(as_storage_error)?;
+        let network_settings = network_settings_from_column(network_settings)?;
+        let mut signers_statement = tx
+            .prepare("SELECT name, address FROM signers")
+            .map_err(as_storage_error)?;
+        let signers: HashMap<String, String> = signers_statement
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(as_storage_error)?
+            .collect::<Result<_, _>>()
+            .map_err(as_storage_error)?;
+        let outputs = select_outputs::<PlutusData, _>(&tx, "1 ORDER BY id", [])?
+            .into_iter()
+            .map(|output| output.map(Into::into))
+            .collect::<LedgerClientResult<_>>()?;
+        let active_signer =
+            signers.get(&active_signer_name).cloned().ok_or_else(|| {
+                as_storage_error(SqliteLCError::UnknownSigner(active_signer_name.clone()))
+            })?;
+        Ok(TestLedgerSnapshot {
+            active_signer_name,
+            active_signer,
+            signers,
+            outputs,
+            current_time,
+            block_length,
+            network_settings,
+        })
+    }
+
+    async fn restore(&self, snapshot: TestLedgerSnapshot) -> LedgerClientResult<()> {
+        let outputs: Vec<Output<PlutusData>> = snapshot.outputs()?;
+        self.transaction(|tx| {
+            tx.execute_batch(
+                "DELETE FROM output_values; DELETE FROM outputs; DELETE FROM signers;",
+            )
+            .map_err(as_storage_error)?;
+            for (name, address) in snapshot.signers.iter() {
+                tx.execute(
+                    "INSERT INTO signers VALUES (?1, ?2)",
+                    params![name, address],
+                )
+                .map_err(as_storage_error)?;
+            }
+            for output in outputs.iter() {
+                insert_output(tx, output)?;
+            }
+            tx.execute(
+                "UPDATE ledger
+                 SET active_signer_name = ?1, current_time = ?2, block_length = ?3,
+                     network_settings = ?4",
+                params![
+                    snapshot.active_signer_name,
+                    snapshot.current_time,
+                    snapshot.block_length,
+                    network_settings_column(snapshot.network_settings)?
+                ],
+            )
+            .map_err(as_storage_error)?;
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use tempfile::TempDir;
+
+    const BLOCK_LENGTH: i64 = 20;
+    const ALICE: &str = "addr_test1qrksjmprvgcedgdt6rhg40590vr6exdzdc2hm5wc6pyl9ymkyskmqs55usm57gflrumk9kd63f3ty6r0l2tdfwfm28qs0rurdr";
+    const BOB: &str = "addr_test1qzulfkd06qm7t2nwe44nnuxh57k4h3p8zdrqukrjcekwn3kcra4ulhfn3g7j9gmnvmefjwzfsd55fq5ndecwlhgcw4zq07drdr";
+
+    fn init_storage(dir: &Path) -> SqliteStorage<PathBuf, ()> {
+        let signer = Address::from_bech32(ALICE).unwrap();
+        SqliteStorage::init(
+            dir.to_owned(),
+            "Alice",
+            &signer,
+            10_000_000,
+            0,
+            BLOCK_LENGTH,
+        )
+        .unwrap()
+    }
+
+    fn token_output(owner: &Address, position: u64) -> Output<()> {
+        let mut values = Values::default();
+        values.add_one_value(&PolicyId::Lovelace, 2_000_000);
+        values.add_one_value(
+            &PolicyId::native_token("abcd", &Some("Token".to_string())),
+            5,
+        );
+        Output::new_wallet(vec![position as u8; 32], 0, owner.clone(), values)
+    }
+
+    #[tokio::test]
+    async fn outputs_at_address() {
+        let tmp_dir = TempDir::new().unwrap();
+        let storage = init_storage(tmp_dir.path());
+        let signer = Address::from_bech32(ALICE).unwrap();
+
+        let mut outputs = storage.all_outputs(&signer).await.unwrap();
+
+        assert_eq!(outputs.len(), 1);
+        let first_output = outputs.pop().unwrap();
+        let actual = first_output.values().get(&PolicyId::Lovelace).unwrap();
+        assert_eq!(actual, 10_000_000);
+    }
+
+    #[tokio::test]
+    async fn outputs_by_count__returns_oldest_first() {
+        // Given
+        let tmp_dir = TempDir::new().unwrap();
+        let storage = init_storage(tmp_dir.path());
+        let signer = Address::from_bech32(ALICE).unwrap();
+        let first = storage.all_outputs(&signer).await.unwrap().pop().unwrap();
+        for position in 1..4 {
+            storage
+                .add_output(&token_output(&signer, position))
+                .await
+                .unwrap();
+        }
+
+        // When
+        let outputs = storage.outputs_by_count(&signer, 2).await.unwrap();
+
+        // Then
+        assert_eq!(outputs, vec![first, token_output(&signer, 1)]);
+    }
+
+    #[tokio::test]
+    async fn output_by_id__finds_output() {
+        // Given
+        let tmp_dir = TempDir::new().unwrap();
+        let storage = init_storage(tmp_dir.path());
+        let signer = Address::from_bech32(ALICE).unwrap();
+        let output = token_output(&signer, 1);
+        storage.add_output(&output).await.unwrap();
+
+        // When
+        let found = storage.output_by_id(output.id()).await.unwrap();
+
+        // Then
+        assert_eq!(found, Some(output.clone()));
+        storage.remove_output(&output).await.unwrap();
+        assert_eq!(storage.output_by_id(output.id()).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn outputs_with_policy__only_returns_outputs_holding_token() {
+        // Given
+        let tmp_dir = TempDir::new().unwrap();
+        let storage = init_storage(tmp_dir.path());
+        let alice = Address::from_bech32(ALICE).unwrap();
+        let bob = Address::from_bech32(BOB).unwrap();
+        let output = token_output(&bob, 1);
+        storage.add_output(&output).await.unwrap();
+        let policy = PolicyId::native_token("abcd", &Some("Token".to_string()));
+
+        // When
+        let outputs = storage.outputs_with_policy(&policy).unwrap();
+
+        // Then
+        assert_eq!(outputs, vec![output]);
+        let lovelace_outputs = storage.outputs_with_policy(&PolicyId::Lovelace).unwrap();
+        assert_eq!(lovelace_outputs.len(), 2);
+        assert_eq!(storage.all_outputs(&alice).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn current_time() {
+        let tmp_dir = TempDir::new().unwrap();
+        let storage = init_storage(tmp_dir.path());
+
+        assert_eq!(storage.current_time().await.unwrap(), 0);
+        storage.set_current_time(20).await.unwrap();
+
+        assert_eq!(storage.current_time().await.unwrap(), 20);
+    }
+
+    #[tokio::test]
+    async fn switch_signer__changes_signer() {
+        // Given
+        let tmp_dir = TempDir::new().unwrap();
+        let storage = init_storage(tmp_dir.path());
+        let bob = Address::from_bech32(BOB).unwrap();
+        storage.add_new_signer("Bob", &bob, 10_000_000).unwrap();
+
+        // When
+        storage.switch_signer("Bob").unwrap();
+
+        // Then
+        assert_eq!(storage.signer().await.unwrap(), bob);
+        assert_eq!(storage.active_signer_name().unwrap(), "Bob");
+        assert_eq!(storage.get_signers().unwrap(), vec!["Alice", "Bob"]);
+        assert_eq!(storage.all_outputs(&bob).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn switch_signer__unknown_signer_fails() {
+        let tmp_dir = TempDir::new().unwrap();
+        let storage = init_storage(tmp_dir.path());
+
+        let error = storage.switch_signer("Nobody").unwrap_err();
+
+        assert!(matches!(error, LedgerClientError::Storage(_)));
+        assert_eq!(storage.active_signer_name().unwrap(), "Alice");
+    }
+
+    #[tokio::test]
+    async fn load__keeps_data() {
+        // Given
+        let tmp_dir = TempDir::new().unwrap();
+        let storage = init_storage(tmp_dir.path());
+        let signer = Address::from_bech32(ALICE).unwrap();
+        storage.add_output(&token_output(&signer, 1)).await.unwrap();
+        let expected = storage.all_outputs(&signer).await.unwrap();
+        drop(storage);
+
+        // When
+        let storage = SqliteStorage::<_, ()>::load(tmp_dir.path()).unwrap();
+
+        // Then
+        let actual = storage.all_outputs(&signer).await.unwrap();
+        assert_eq!(expected, actual);
+    }
+
+    #[tokio::test]
+    async fn load__fails_without_data() {
+        let tmp_dir = TempDir::new().unwrap();
+
+        let result = SqliteStorage::<_, ()>::load(tmp_dir.path());
+
+        assert!(matches!(result, Err(LedgerClientError::Storage(_))));
+    }
+
+    #[tokio::test]
+    async fn load__migrates_v1_database() {
+        // Given
+        let tmp_dir = TempDir::new().unwrap();
+        let storage = init_storage(tmp_dir.path());
+        let signer = Address::from_bech32(ALICE).unwrap();
+        let expected = storage.all_outputs(&signer).await.unwrap();
+        storage
+            .connection()
+            .unwrap()
+            .execute_batch(
+                "ALTER TABLE ledger DROP COLUMN network_settings;
+                 UPDATE ledger SET version = 1;",
+            )
+            .unwrap();
+        drop(storage);
+
+        // When
+        let storage = SqliteStorage::<_, ()>::load(tmp_dir.path()).unwrap();
+
+        // Then
+        assert_eq!(storage.all_outputs(&signer).await.unwrap(), expected);
+        assert_eq!(storage.network_settings().await.unwrap(), None);
+        let version: u64 = storage
+            .connection()
+            .unwrap()
+            .query_row("SELECT version FROM ledger", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, CURRENT_SCHEMA_VERSION);
+    }
+
+    #[tokio::test]
+    async fn load__fails_for_newer_version() {
+        // Given
+        let tmp_dir = TempDir::new().unwrap();
+        let storage = init_storage(tmp_dir.path());
+        storage
+            .connection()
+            .unwrap()
+            .execute(
+                "UPDATE ledger SET version = ?1",
+                params![CURRENT_SCHEMA_VERSION + 1],
+            )
+            .unwrap();
+        drop(storage);
+
+        // When
+        let result = SqliteStorage::<_, ()>::load(tmp_dir.path());
+
+        // Then
+        assert!(matches!(result, Err(LedgerClientError::Storage(_))));
+    }
+
+    #[tokio::test]
+    async fn apply_transaction__failure_leaves_ledger_unchanged() {
+        // Given
+        let tmp_dir = TempDir::new().unwrap();
+        let storage = init_storage(tmp_dir.path());
+        let signer = Address::from_bech32(ALICE).unwrap();
+        let existing = storage.all_outputs(&signer).await.unwrap();
+        let missing = token_output(&signer, 1);
+        let produced = vec![token_output(&signer, 2)];
+
+        // When
+        let consumed = vec![existing[0].clone(), missing];
+        let result = storage.apply_transaction(&consumed, &produced, 100).await;
+
+        // Then
+        assert!(result.is_err());
+        assert_eq!(storage.all_outputs(&signer).await.unwrap(), existing);
+        assert_eq!(storage.current_time().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn restore__reverts_to_snapshot() {
+        // Given
+        let tmp_dir = TempDir::new().unwrap();
+        let storage = init_storage(tmp_dir.path());
+        let signer = Address::from_bech32(ALICE).unwrap();
+        let snapshot = storage.snapshot().await.unwrap();
+        let output = storage.all_outputs(&signer).await.unwrap().pop().unwrap();
+        storage
+            .apply_transaction(&[output.clone()], &[token_output(&signer, 1)], 100)
+            .await
+            .unwrap();
+
+        // When
+        storage.restore(snapshot).await.unwrap();
+
+        // Then
+        assert_eq!(storage.all_outputs(&signer).await.unwrap(), vec![output]);
+        assert_eq!(storage.current_time().await.unwrap(), 0);
+    }
+}
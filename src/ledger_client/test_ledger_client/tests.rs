@@ -216,6 +216,73 @@ async fn cannot_transfer_after_valid_range() {
     assert!(matches!(error, LedgerClientError::FailedToIssueTx(_),));
 }
 
+#[tokio::test]
+async fn can_transfer_within_valid_range() {
+    let sender = Address::from_bech32(ALICE).unwrap();
+    let starting_amount = 10_000_000;
+    let transfer_amount = 3_000_000;
+
+    let output = starting_output::<()>(&sender, starting_amount);
+    let outputs = vec![(sender.clone(), output)];
+    let record: TestLedgerClient<(), (), _> =
+        TestLedgerClient::new_in_memory(sender.clone(), outputs, BLOCK_LENGTH, 0);
+
+    let current_time = 5_000;
+    record.set_current_time_secs(current_time).await.unwrap();
+
+    let mut values = Values::default();
+    values.add_one_value(&PolicyId::Lovelace, transfer_amount);
+    let recipient = Address::from_bech32(BOB).unwrap();
+    let new_output = UnbuiltOutput::new_wallet(recipient.clone(), values);
+    let tx: UnbuiltTransaction<(), ()> = UnbuiltTransaction {
+        script_version: TransactionVersion::V2,
+        script_inputs: vec![],
+        unbuilt_outputs: vec![new_output],
+        minting: Default::default(),
+        specific_wallet_inputs: vec![],
+        valid_range: (Some(5), Some(6)),
+    };
+    record.issue(tx).await.unwrap();
+
+    let actual = record
+        .balance_at_address(&recipient, &PolicyId::Lovelace)
+        .await
+        .unwrap();
+    assert_eq!(actual, transfer_amount);
+}
+
+#[tokio::test]
+async fn cannot_transfer_after_valid_range_with_lower_bound() {
+    let sender = Address::from_bech32(ALICE).unwrap();
+    let starting_amount = 10_000_000;
+    let transfer_amount = 3_000_000;
+
+    let output = starting_output::<()>(&sender, starting_amount);
+    let outputs = vec![(sender.clone(), output)];
+    let record: TestLedgerClient<(), (), _> =
+        TestLedgerClient::new_in_memory(sender.clone(), outputs, BLOCK_LENGTH, 0);
+
+    let current_time = 10_000;
+    record.set_current_time_secs(current_time).await.unwrap();
+
+    let mut values = Values::default();
+    values.add_one_value(&PolicyId::Lovelace, transfer_amount);
+    let recipient = Address::from_bech32(BOB).unwrap();
+    let new_output = UnbuiltOutput::new_wallet(recipient.clone(), values);
+    let tx: UnbuiltTransaction<(), ()> = UnbuiltTransaction {
+        script_version: TransactionVersion::V2,
+        script_inputs: vec![],
+        unbuilt_outputs: vec![new_output],
+        minting: Default::default(),
+        specific_wallet_inputs: vec![],
+        valid_range: (Some(1), Some(5)),
+    };
+    // The upper bound used to be ignored whenever there was a lower bound
+    let error = record.issue(tx).await.unwrap_err();
+
+    assert!(matches!(error, LedgerClientError::FailedToIssueTx(_)));
+}
+
 #[derive(Clone, Copy)]
 struct AlwaysTrueFakeValidator;
 
@@ -482,6 +549,10 @@ impl MintingPolicy<()> for AlwaysTruePolicy {
     fn script_hex(&self) -> ScriptResult<String> {
         todo!()
     }
+
+    fn redeemer_data(&self, redeemer: &()) -> Option<PlutusData> {
+        Some(PlutusData::from(*redeemer))
+    }
 }
 
 #[tokio::test]
@@ -637,3 +708,719 @@ async fn spends_specific_script_value() {
         .unwrap();
     assert_eq!(alice_balance, minting_amount);
 }
+
+#[tokio::test]
+async fn restore__reverts_issued_tx() {
+    let sender = Address::from_bech32(ALICE).unwrap();
+    let starting_amount = 10_000_000;
+    let transfer_amount = 3_000_000;
+    let output = starting_output::<()>(&sender, starting_amount);
+    let outputs = vec![(sender.clone(), output)];
+    let record: TestLedgerClient<(), (), _> =
+        TestLedgerClient::new_in_memory(sender.clone(), outputs, BLOCK_LENGTH, 0);
+    let snapshot = record.snapshot().await.unwrap();
+
+    let mut values = Values::default();
+    values.add_one_value(&PolicyId::Lovelace, transfer_amount);
+    let recipient = Address::from_bech32(BOB).unwrap();
+    let new_output = UnbuiltOutput::new_wallet(recipient.clone(), values);
+    let tx: UnbuiltTransaction<(), ()> = UnbuiltTransaction {
+        script_version: TransactionVersion::V2,
+        script_inputs: vec![],
+        unbuilt_outputs: vec![new_output],
+        minting: Default::default(),
+        specific_wallet_inputs: vec![],
+        valid_range: (None, None),
+    };
+    record.issue(tx).await.unwrap();
+
+    record.restore(snapshot).await.unwrap();
+
+    let alice_balance = record
+        .balance_at_address(&sender, &PolicyId::Lovelace)
+        .await
+        .unwrap();
+    assert_eq!(alice_balance, starting_amount);
+    let bob_balance = record
+        .balance_at_address(&recipient, &PolicyId::Lovelace)
+        .await
+        .unwrap();
+    assert_eq!(bob_balance, 0);
+    let current_time = record.current_time_secs().await.unwrap();
+    assert_eq!(current_time, 0);
+}
+
+#[tokio::test]
+async fn builder_from_snapshot_file__has_same_state() {
+    let signer = Address::from_bech32(ALICE).unwrap();
+    let starting_amount = 10_000_000;
+    let starting_time = 1_000;
+    let original: TestLedgerClient<(), (), _> = TestLedgerClientBuilder::new(&signer)
+        .with_starting_time(starting_time)
+        .start_output(&signer)
+        .with_value(PolicyId::Lovelace, starting_amount)
+        .finish_output()
+        .build_in_memory();
+    let tmp_dir = tempfile::TempDir::new().unwrap();
+    let path = tmp_dir.path().join("snapshot.json");
+    original.snapshot().await.unwrap().save(&path).unwrap();
+
+    let record: TestLedgerClient<(), (), _> =
+        TestLedgerClientBuilder::from_snapshot_file(&path)
+            .unwrap()
+            .build_in_memory();
+
+    let expected = original.all_outputs_at_address(&signer).await.unwrap();
+    let actual = record.all_outputs_at_address(&signer).await.unwrap();
+    assert_eq!(expected, actual);
+    let actual_time = record.current_time_secs().await.unwrap();
+    assert_eq!(actual_time, starting_time);
+}
+
+#[tokio::test]
+async fn builder_from_snapshot__keeps_network_settings() {
+    // Given
+    let original = slot_based_ledger(1_020);
+    let snapshot = original.snapshot().await.unwrap();
+
+    // When
+    let record: TestLedgerClient<(), (), _> =
+        TestLedgerClientBuilder::from_snapshot(&snapshot)
+            .unwrap()
+            .build_in_memory();
+
+    // Then
+    assert_eq!(
+        record.network_settings().await.unwrap(),
+        Some(TEN_SECOND_SLOTS)
+    );
+    assert_eq!(record.current_slot().await.unwrap(), 2);
+}
+
+fn transfer_tx(recipient: &Address, amount: u64) -> UnbuiltTransaction<(), ()> {
+    let mut values = Values::default();
+    values.add_one_value(&PolicyId::Lovelace, amount);
+    let new_output = UnbuiltOutput::new_wallet(recipient.clone(), values);
+    UnbuiltTransaction {
+        script_version: TransactionVersion::V2,
+        script_inputs: vec![],
+        unbuilt_outputs: vec![new_output],
+        minting: Default::default(),
+        specific_wallet_inputs: vec![],
+        valid_range: (None, None),
+    }
+}
+
+fn deterministic_ledger(
+    signer: &Address,
+) -> TestLedgerClient<(), (), InMemoryStorage<()>> {
+    TestLedgerClientBuilder::new(signer)
+        .start_output(signer)
+        .with_value(PolicyId::Lovelace, 10_000_000)
+        .finish_output()
+        .start_output(signer)
+        .with_value(PolicyId::Lovelace, 10_000_000)
+        .finish_output()
+        .build_in_memory()
+}
+
+#[tokio::test]
+async fn issue__same_tx_on_same_ledger_has_same_tx_id() {
+    // Given
+    let sender = Address::from_bech32(ALICE).unwrap();
+    let recipient = Address::from_bech32(BOB).unwrap();
+    let first = deterministic_ledger(&sender);
+    let second = deterministic_ledger(&sender);
+
+    // When
+    let first_tx_id = first
+        .issue(transfer_tx(&recipient, 3_000_000))
+        .await
+        .unwrap();
+    let second_tx_id = second
+        .issue(transfer_tx(&recipient, 3_000_000))
+        .await
+        .unwrap();
+
+    // Then
+    assert_eq!(first_tx_id, second_tx_id);
+    let first_outputs = first.all_outputs_at_address(&recipient).await.unwrap();
+    let second_outputs = second.all_outputs_at_address(&recipient).await.unwrap();
+    assert_eq!(first_outputs, second_outputs);
+}
+
+#[tokio::test]
+async fn issue__different_txs_have_different_tx_ids() {
+    // Given
+    let sender = Address::from_bech32(ALICE).unwrap();
+    let recipient = Address::from_bech32(BOB).unwrap();
+    let first = deterministic_ledger(&sender);
+    let second = deterministic_ledger(&sender);
+
+    // When
+    let first_tx_id = first
+        .issue(transfer_tx(&recipient, 3_000_000))
+        .await
+        .unwrap();
+    let second_tx_id = second
+        .issue(transfer_tx(&recipient, 4_000_000))
+        .await
+        .unwrap();
+
+    // Then
+    assert_ne!(first_tx_id, second_tx_id);
+}
+
+#[tokio::test]
+async fn builder__identical_starting_outputs_have_different_ids() {
+    // Given
+    let signer = Address::from_bech32(ALICE).unwrap();
+    let record = deterministic_ledger(&signer);
+
+    // When
+    let outputs = record.all_outputs_at_address(&signer).await.unwrap();
+
+    // Then
+    assert_eq!(outputs.len(), 2);
+    assert_ne!(outputs[0].id(), outputs[1].id());
+}
+
+struct RequiresDatumWitnessFakeValidator;
+
+impl Validator<i64, ()> for RequiresDatumWitnessFakeValidator {
+    fn execute(
+        &self,
+        datum: i64,
+        _redeemer: (),
+        ctx: TxContext,
+    ) -> ScriptResult<ExecutionCost> {
+        let witness = PlutusData::from(datum);
+        let expected_hash = witness.hash();
+        let has_hashed_input = ctx.inputs.iter().any(|input| {
+            matches!(&input.datum, CtxDatum::DatumHash(hash) if hash == &expected_hash)
+        });
+        let has_witness = ctx.datums.contains(&(expected_hash, witness));
+        if has_hashed_input && has_witness {
+            Ok(ExecutionCost::default())
+        } else {
+            Err(ScriptError::FailedToExecute(
+                "Missing datum hash or witness".to_string(),
+            ))
+        }
+    }
+
+    fn address(&self, _network: Network) -> ScriptResult<Address> {
+        Ok(Address::from_bech32(
+            "addr_test1wrme5jjggy97th309h2dwpv57wsphxskuc8jkw00c2kn47gu8mkzu",
+        )
+        .unwrap())
+    }
+
+    fn script_hex(&self) -> ScriptResult<String> {
+        todo!()
+    }
+
+    fn redeemer_data(&self, redeemer: &()) -> Option<PlutusData> {
+        Some(PlutusData::from(*redeemer))
+    }
+}
+
+async fn ledger_with_hashed_datum_output(
+    datum: i64,
+) -> (TestLedgerClient<i64, (), InMemoryStorage<i64>>, Output<i64>) {
+    let sender = Address::from_bech32(ALICE).unwrap();
+    let output = starting_output::<i64>(&sender, 10_000_000);
+    let outputs = vec![(sender.clone(), output)];
+    let record = TestLedgerClient::new_in_memory(sender, outputs, BLOCK_LENGTH, 0);
+
+    let mut values = Values::default();
+    values.add_one_value(&PolicyId::Lovelace, 3_000_000);
+    let script_address = RequiresDatumWitnessFakeValidator
+        .address(Network::Testnet)
+        .unwrap();
+    let new_output = UnbuiltOutput::new_validator_with_datum_hash(
+        script_address.clone(),
+        values,
+        datum,
+    );
+    let tx: UnbuiltTransaction<i64, ()> = UnbuiltTransaction {
+        script_version: TransactionVersion::V1,
+        script_inputs: vec![],
+        unbuilt_outputs: vec![new_output],
+        minting: Default::default(),
+        specific_wallet_inputs: vec![],
+        valid_range: (None, None),
+    };
+    record.issue(tx).await.unwrap();
+    let script_output = record
+        .all_outputs_at_address(&script_address)
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+    (record, script_output)
+}
+
+fn redeem_tx(output: Output<i64>) -> UnbuiltTransaction<i64, ()> {
+    let script_box: Box<dyn Validator<i64, ()>> =
+        Box::new(RequiresDatumWitnessFakeValidator);
+    UnbuiltTransaction {
+        script_version: TransactionVersion::V1,
+        script_inputs: vec![(output, (), script_box)],
+        unbuilt_outputs: vec![],
+        minting: Default::default(),
+        specific_wallet_inputs: vec![],
+        valid_range: (None, None),
+    }
+}
+
+#[tokio::test]
+async fn issue__hashed_datum_output_stores_hash_and_witness() {
+    // Given
+    let datum = 42;
+
+    // When
+    let (_, script_output) = ledger_with_hashed_datum_output(datum).await;
+
+    // Then
+    let expected_hash = PlutusData::from(datum).hash();
+    assert_eq!(script_output.datum_hash(), Some(expected_hash.as_slice()));
+    assert_eq!(script_output.typed_datum(), Some(datum));
+}
+
+#[tokio::test]
+async fn issue__redeeming_hashed_datum_provides_witness_to_script() {
+    // Given
+    let (record, script_output) = ledger_with_hashed_datum_output(42).await;
+    let script_address = script_output.owner();
+
+    // When
+    record.issue(redeem_tx(script_output)).await.unwrap();
+
+    // Then
+    let script_balance = record
+        .balance_at_address(&script_address, &PolicyId::Lovelace)
+        .await
+        .unwrap();
+    assert_eq!(script_balance, 0);
+}
+
+#[tokio::test]
+async fn issue__redeeming_hashed_datum_with_wrong_datum_fails() {
+    // Given
+    let (record, script_output) = ledger_with_hashed_datum_output(42).await;
+    let id = script_output.id();
+    let wrong_witness = Output::new_validator(
+        id.tx_hash().to_vec(),
+        id.index(),
+        script_output.owner(),
+        script_output.values().clone(),
+        7,
+    );
+
+    // When
+    let error = record.issue(redeem_tx(wrong_witness)).await.unwrap_err();
+
+    // Then
+    assert!(matches!(error, LedgerClientError::FailedToIssueTx(_)));
+}
+
+#[tokio::test]
+async fn issue__redeeming_hashed_datum_with_wrong_datum_reports_difference() {
+    // Given
+    let (record, script_output) = ledger_with_hashed_datum_output(42).await;
+    let id = script_output.id();
+    let wrong_witness = Output::new_validator(
+        id.tx_hash().to_vec(),
+        id.index(),
+        script_output.owner(),
+        script_output.values().clone(),
+        7,
+    );
+
+    // When
+    let error = record.issue(redeem_tx(wrong_witness)).await.unwrap_err();
+
+    // Then
+    let message = format!("{error:?}");
+    assert!(message.contains("expected 42, found 7"), "{message}");
+}
+
+#[tokio::test]
+async fn tx_log__records_redeemed_script_inputs() {
+    // Given
+    let (record, script_output) = ledger_with_hashed_datum_output(42).await;
+    let tx = redeem_tx(script_output.clone());
+
+    // When
+    let tx_id = record.issue(tx).await.unwrap();
+
+    // Then
+    let log = record.tx_log().unwrap();
+    assert_eq!(log.len(), 2);
+    let recorded = record.last_tx().unwrap().unwrap();
+    assert_eq!(recorded.tx_id(), &tx_id);
+    assert_eq!(recorded.signer(), &Address::from_bech32(ALICE).unwrap());
+    assert!(recorded.inputs().contains(&script_output));
+    let script_inputs = recorded.script_inputs();
+    assert_eq!(script_inputs.len(), 1);
+    assert_eq!(script_inputs[0].output(), &script_output);
+    assert_eq!(script_inputs[0].redeemer(), &());
+    assert!(matches!(
+        script_inputs[0].ctx().purpose,
+        CtxScriptPurpose::Spend(_)
+    ));
+    assert_eq!(script_inputs[0].ctx().datums.len(), 1);
+}
+
+#[tokio::test]
+async fn tx_log__records_mints() {
+    // Given
+    let sender = Address::from_bech32(ALICE).unwrap();
+    let output = starting_output::<()>(&sender, 10_000_000);
+    let outputs = vec![(sender.clone(), output)];
+    let record: TestLedgerClient<(), (), _> =
+        TestLedgerClient::new_in_memory(sender.clone(), outputs, BLOCK_LENGTH, 0);
+    let policy = AlwaysTruePolicy;
+    let id = policy.id().unwrap();
+    let script_box: Box<dyn MintingPolicy<()>> = Box::new(policy);
+    let tx: UnbuiltTransaction<(), ()> = UnbuiltTransaction {
+        script_version: TransactionVersion::V2,
+        script_inputs: vec![],
+        unbuilt_outputs: vec![],
+        minting: vec![(100, None, (), script_box)],
+        specific_wallet_inputs: vec![],
+        valid_range: (None, Some(1_000)),
+    };
+
+    // When
+    record.issue(tx).await.unwrap();
+
+    // Then
+    let recorded = record.last_tx().unwrap().unwrap();
+    assert_eq!(recorded.valid_range(), (None, Some(1_000)));
+    assert_eq!(recorded.issued_at(), 0);
+    let mints = recorded.mints();
+    assert_eq!(mints.len(), 1);
+    assert_eq!(mints[0].policy_id(), &PolicyId::NativeToken(id, None));
+    assert_eq!(mints[0].amount(), 100);
+    assert!(recorded.script_inputs().is_empty());
+}
+
+#[tokio::test]
+async fn issue__scripts_see_the_whole_transaction() {
+    // Given
+    let (record, script_output) = ledger_with_hashed_datum_output(42).await;
+    let mut tx = redeem_tx(script_output.clone());
+    let policy_id = AlwaysTruePolicy.id().unwrap();
+    let script_box: Box<dyn MintingPolicy<()>> = Box::new(AlwaysTruePolicy);
+    let other_script_box: Box<dyn MintingPolicy<()>> = Box::new(AlwaysTruePolicy);
+    tx.minting = vec![
+        (100, None, (), script_box),
+        (5, Some("other".to_string()), (), other_script_box),
+    ];
+
+    // When
+    let tx_id = record.issue(tx).await.unwrap();
+
+    // Then
+    let recorded = record.last_tx().unwrap().unwrap();
+    let ctx = recorded.script_inputs()[0].ctx();
+    assert_eq!(ctx.id, hex::decode(tx_id.as_str()).unwrap());
+    let alice = Address::from_bech32(ALICE).unwrap();
+    let alice_pkh = pub_key_hash_from_address_if_available(&alice).unwrap();
+    assert_eq!(ctx.signer, alice_pkh);
+    assert!(ctx.extra_signatories.is_empty());
+    assert_eq!(ctx.fee, 0);
+    let minted = &ctx.mint.inner[&policy_id];
+    assert_eq!((minted[""], minted["other"]), (100, 5));
+    let id = script_output.id();
+    let out_ref = CtxOutputReference::new(id.tx_hash().to_vec(), id.index());
+    let purposes: Vec<_> = ctx.redeemers.iter().map(|(purpose, _)| purpose).collect();
+    assert_eq!(
+        purposes,
+        vec![
+            &CtxScriptPurpose::Spend(out_ref),
+            &CtxScriptPurpose::Mint(hex::decode(&policy_id).unwrap()),
+        ]
+    );
+    assert_eq!(recorded.mints()[1].ctx().id, ctx.id);
+}
+
+#[tokio::test]
+async fn tx_log__does_not_record_failed_txs() {
+    // Given
+    let sender = Address::from_bech32(ALICE).unwrap();
+    let record: TestLedgerClient<(), (), _> =
+        TestLedgerClient::new_in_memory(sender.clone(), vec![], BLOCK_LENGTH, 0);
+    let recipient = Address::from_bech32(BOB).unwrap();
+
+    // When
+    record
+        .issue(transfer_tx(&recipient, 3_000_000))
+        .await
+        .unwrap_err();
+
+    // Then
+    assert!(record.tx_log().unwrap().is_empty());
+}
+
+const TEN_SECOND_SLOTS: NetworkSettings = NetworkSettings::new(0, 10, 1_000, 0);
+
+fn slot_based_ledger(current_time: i64) -> TestLedgerClient<(), (), InMemoryStorage<()>> {
+    let signer = Address::from_bech32(ALICE).unwrap();
+    TestLedgerClientBuilder::new(&signer)
+        .start_output(&signer)
+        .with_value(PolicyId::Lovelace, 10_000_000)
+        .finish_output()
+        .with_network_settings(TEN_SECOND_SLOTS)
+        .with_starting_time(current_time)
+        .build_in_memory()
+}
+
+fn mint_tx(valid_range: (Option<i64>, Option<i64>)) -> UnbuiltTransaction<(), ()> {
+    let script_box: Box<dyn MintingPolicy<()>> = Box::new(AlwaysTruePolicy);
+    UnbuiltTransaction {
+        script_version: TransactionVersion::V2,
+        script_inputs: vec![],
+        unbuilt_outputs: vec![],
+        minting: vec![(1, None, (), script_box)],
+        specific_wallet_inputs: vec![],
+        valid_range,
+    }
+}
+
+#[tokio::test]
+async fn current_slot__uses_network_settings() {
+    // Given
+    let signer = Address::from_bech32(ALICE).unwrap();
+    let network_settings =
+        NetworkSettings::from(crate::trireme_ledger_client::Network::Preprod);
+    let record: TestLedgerClient<(), (), _> = TestLedgerClientBuilder::new(&signer)
+        .with_network_settings(network_settings)
+        .build_in_memory();
+
+    // When
+    let actual = record.current_slot().await.unwrap();
+
+    // Then
+    let expected = network_settings.starting_slot_number();
+    assert_eq!(expected, actual);
+}
+
+#[tokio::test]
+async fn current_slot__fails_without_network_settings() {
+    let signer = Address::from_bech32(ALICE).unwrap();
+    let record: TestLedgerClient<(), (), _> =
+        TestLedgerClient::new_in_memory(signer, vec![], BLOCK_LENGTH, 0);
+
+    let error = record.current_slot().await.unwrap_err();
+
+    assert!(matches!(error, LedgerClientError::CurrentTime(_)));
+}
+
+#[tokio::test]
+async fn issue__slot_based_valid_range_is_rounded_to_slots() {
+    // Given
+    let record = slot_based_ledger(1_020);
+
+    // When
+    record
+        .issue(mint_tx((Some(1_015), Some(1_047))))
+        .await
+        .unwrap();
+
+    // Then
+    let recorded = record.last_tx().unwrap().unwrap();
+    let range = &recorded.mints()[0].ctx().range;
+    assert_eq!(range.lower, Some((1_010_000, true)));
+    assert_eq!(range.upper, Some((1_040_000, false)));
+}
+
+#[tokio::test]
+async fn issue__slot_based_upper_bound_is_exclusive() {
+    // Given
+    let record = slot_based_ledger(1_040);
+
+    // When
+    let error = record
+        .issue(mint_tx((None, Some(1_045))))
+        .await
+        .unwrap_err();
+
+    // Then
+    assert!(matches!(error, LedgerClientError::FailedToIssueTx(_)));
+}
+
+#[tokio::test]
+async fn issue__slot_based_bounds_before_first_slot_fail() {
+    // Given
+    let record = slot_based_ledger(1_040);
+
+    // When
+    let error = record.issue(mint_tx((Some(500), None))).await.unwrap_err();
+
+    // Then
+    assert!(matches!(error, LedgerClientError::ValidityRange(_)));
+}
+
+#[tokio::test]
+async fn issue__sqlite_storage_persists_transfer() {
+    // Given
+    let sender = Address::from_bech32(ALICE).unwrap();
+    let recipient = Address::from_bech32(BOB).unwrap();
+    let tmp_dir = tempfile::TempDir::new().unwrap();
+    let record: TestLedgerClient<(), (), _> =
+        TestLedgerClient::new_sqlite(tmp_dir.path(), &sender, 10_000_000).unwrap();
+
+    // When
+    record
+        .issue(transfer_tx(&recipient, 3_000_000))
+        .await
+        .unwrap();
+
+    // Then
+    let record: TestLedgerClient<(), (), _> =
+        TestLedgerClient::load_sqlite(tmp_dir.path()).unwrap();
+    let bob_balance = record
+        .balance_at_address(&recipient, &PolicyId::Lovelace)
+        .await
+        .unwrap();
+    assert_eq!(bob_balance, 3_000_000);
+    let alice_balance = record
+        .balance_at_address(&sender, &PolicyId::Lovelace)
+        .await
+        .unwrap();
+    assert_eq!(alice_balance, 7_000_000);
+    assert_eq!(record.current_time_secs().await.unwrap(), 20);
+}
+
+#[tokio::test]
+async fn set_network_settings__persists_with_sqlite_storage() {
+    // Given
+    let sender = Address::from_bech32(ALICE).unwrap();
+    let tmp_dir = tempfile::TempDir::new().unwrap();
+    let record: TestLedgerClient<(), (), _> =
+        TestLedgerClient::new_sqlite(tmp_dir.path(), &sender, 10_000_000).unwrap();
+
+    // When
+    record.set_network_settings(TEN_SECOND_SLOTS).await.unwrap();
+
+    // Then
+    let record: TestLedgerClient<(), (), _> =
+        TestLedgerClient::load_sqlite(tmp_dir.path()).unwrap();
+    assert_eq!(
+        record.network_settings().await.unwrap(),
+        Some(TEN_SECOND_SLOTS)
+    );
+}
+
+#[tokio::test]
+async fn set_network_settings__persists_with_local_persisted_storage() {
+    // Given
+    let sender = Address::from_bech32(ALICE).unwrap();
+    let tmp_dir = tempfile::TempDir::new().unwrap();
+    let record: TestLedgerClient<(), (), _> =
+        TestLedgerClient::new_local_persisted(tmp_dir.path(), &sender, 10_000_000)
+            .unwrap();
+
+    // When
+    record.set_network_settings(TEN_SECOND_SLOTS).await.unwrap();
+
+    // Then
+    let record: TestLedgerClient<(), (), _> =
+        TestLedgerClient::load_local_persisted(tmp_dir.path()).unwrap();
+    assert_eq!(
+        record.network_settings().await.unwrap(),
+        Some(TEN_SECOND_SLOTS)
+    );
+}
+
+#[tokio::test]
+async fn apply_transaction__in_memory_failure_leaves_ledger_unchanged() {
+    // Given
+    let signer = Address::from_bech32(ALICE).unwrap();
+    let record = deterministic_ledger(&signer);
+    let existing = record.all_outputs_at_address(&signer).await.unwrap();
+    let missing = starting_output::<()>(&Address::from_bech32(BOB).unwrap(), 1);
+    let produced = vec![starting_output::<()>(&signer, 1)];
+
+    // When
+    let consumed = vec![existing[0].clone(), missing];
+    let result = record
+        .storage
+        .apply_transaction(&consumed, &produced, 100)
+        .await;
+
+    // Then
+    assert!(result.is_err());
+    let actual = record.all_outputs_at_address(&signer).await.unwrap();
+    assert_eq!(actual, existing);
+    assert_eq!(record.current_time_secs().await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn apply_transaction__in_memory_missing_input_is_unknown_input() {
+    // Given
+    let signer = Address::from_bech32(ALICE).unwrap();
+    let record = deterministic_ledger(&signer);
+    let missing = starting_output::<()>(&Address::from_bech32(BOB).unwrap(), 1);
+
+    // When
+    let error = record
+        .storage
+        .apply_transaction(&[missing], &[], 100)
+        .await
+        .unwrap_err();
+
+    // Then
+    match error {
+        LedgerClientError::FailedToRetrieveOutputWithId(_, e) => assert!(matches!(
+            e.downcast_ref::<TestLCError>(),
+            Some(TestLCError::UnknownInput)
+        )),
+        other => panic!("expected a missing output, got {other:?}"),
+    }
+}
+
+struct NoRedeemerDataPolicy;
+
+impl MintingPolicy<()> for NoRedeemerDataPolicy {
+    fn execute(&self, _redeemer: (), _ctx: TxContext) -> ScriptResult<ExecutionCost> {
+        Ok(ExecutionCost::default())
+    }
+
+    fn id(&self) -> ScriptResult<String> {
+        Ok(hex::encode(vec![2, 2, 2, 2, 2]))
+    }
+
+    fn script_hex(&self) -> ScriptResult<String> {
+        todo!()
+    }
+}
+
+#[tokio::test]
+async fn issue__leaves_out_redeemers_without_on_chain_form() {
+    // Given
+    let sender = Address::from_bech32(ALICE).unwrap();
+    let output = starting_output::<()>(&sender, 10_000_000);
+    let outputs = vec![(sender.clone(), output)];
+    let record: TestLedgerClient<(), (), _> =
+        TestLedgerClient::new_in_memory(sender.clone(), outputs, BLOCK_LENGTH, 0);
+    let script_box: Box<dyn MintingPolicy<()>> = Box::new(NoRedeemerDataPolicy);
+    let tx: UnbuiltTransaction<(), ()> = UnbuiltTransaction {
+        script_version: TransactionVersion::V2,
+        script_inputs: vec![],
+        unbuilt_outputs: vec![],
+        minting: vec![(100, None, (), script_box)],
+        specific_wallet_inputs: vec![],
+        valid_range: (None, None),
+    };
+
+    // When
+    record.issue(tx).await.unwrap();
+
+    // Then
+    let recorded = record.last_tx().unwrap().unwrap();
+    assert!(recorded.mints()[0].ctx().redeemers.is_empty());
+}
@@ -0,0 +1,143 @@
+use crate::{
+    output::Output,
+    scripts::{
+        context::TxContext,
+        ExecutionCost,
+    },
+    transaction::TxId,
+    PolicyId,
+};
+use pallas_addresses::Address;
+
+/// Record of a transaction successfully issued by a
+/// [`TestLedgerClient`](super::TestLedgerClient).
+///
+/// Tests can use the log to assert on _how_ the ledger got to its current state, rather than
+/// reconstructing it from balances:
+///
+/// ```ignore
+///     let tx = backend.ledger_client().last_tx()?.unwrap();
+///     assert_eq!(tx.script_inputs().len(), 1);
+///     assert_eq!(tx.script_inputs()[0].redeemer(), &expected_redeemer);
+/// ```
+#[derive(Clone, Debug)]
+pub struct RecordedTx<Datum, Redeemer> {
+    pub(crate) tx_id: TxId,
+    pub(crate) signer: Address,
+    pub(crate) inputs: Vec<Output<Datum>>,
+    pub(crate) outputs: Vec<Output<Datum>>,
+    pub(crate) script_inputs: Vec<RecordedScriptInput<Datum, Redeemer>>,
+    pub(crate) mints: Vec<RecordedMint<Redeemer>>,
+    pub(crate) valid_range: (Option<i64>, Option<i64>),
+    pub(crate) issued_at: i64,
+}
+
+impl<Datum, Redeemer> RecordedTx<Datum, Redeemer> {
+    /// Getter for the id of the transaction
+    pub fn tx_id(&self) -> &TxId {
+        &self.tx_id
+    }
+
+    /// Getter for the address of the wallet that signed the transaction
+    pub fn signer(&self) -> &Address {
+        &self.signer
+    }
+
+    /// Getter for all the outputs consumed by the transaction, including script inputs
+    pub fn inputs(&self) -> &[Output<Datum>] {
+        &self.inputs
+    }
+
+    /// Getter for all the outputs created by the transaction, including the signer's change
+    pub fn outputs(&self) -> &[Output<Datum>] {
+        &self.outputs
+    }
+
+    /// Getter for the script outputs redeemed by the transaction
+    pub fn script_inputs(&self) -> &[RecordedScriptInput<Datum, Redeemer>] {
+        &self.script_inputs
+    }
+
+    /// Getter for the tokens minted by the transaction
+    pub fn mints(&self) -> &[RecordedMint<Redeemer>] {
+        &self.mints
+    }
+
+    /// Getter for the valid range of the transaction in seconds since the Unix epoch
+    pub fn valid_range(&self) -> (Option<i64>, Option<i64>) {
+        self.valid_range
+    }
+
+    /// Getter for the ledger time, in seconds, when the transaction was issued
+    pub fn issued_at(&self) -> i64 {
+        self.issued_at
+    }
+}
+
+/// Record of a script output redeemed by a [`RecordedTx`]
+#[derive(Clone, Debug)]
+pub struct RecordedScriptInput<Datum, Redeemer> {
+    pub(crate) output: Output<Datum>,
+    pub(crate) redeemer: Redeemer,
+    pub(crate) cost: ExecutionCost,
+    pub(crate) ctx: TxContext,
+}
+
+impl<Datum, Redeemer> RecordedScriptInput<Datum, Redeemer> {
+    /// Getter for the redeemed output
+    pub fn output(&self) -> &Output<Datum> {
+        &self.output
+    }
+
+    /// Getter for the redeemer passed to the validator
+    pub fn redeemer(&self) -> &Redeemer {
+        &self.redeemer
+    }
+
+    /// Getter for the cost of executing the validator
+    pub fn cost(&self) -> &ExecutionCost {
+        &self.cost
+    }
+
+    /// Getter for the [`TxContext`] the validator was executed with
+    pub fn ctx(&self) -> &TxContext {
+        &self.ctx
+    }
+}
+
+/// Record of tokens minted by a [`RecordedTx`]
+#[derive(Clone, Debug)]
+pub struct RecordedMint<Redeemer> {
+    pub(crate) policy_id: PolicyId,
+    pub(crate) amount: u64,
+    pub(crate) redeemer: Redeemer,
+    pub(crate) cost: ExecutionCost,
+    pub(crate) ctx: TxContext,
+}
+
+impl<Redeemer> RecordedMint<Redeemer> {
+    /// Getter for the policy id, including asset name, of the minted tokens
+    pub fn policy_id(&self) -> &PolicyId {
+        &self.policy_id
+    }
+
+    /// Getter for the amount minted
+    pub fn amount(&self) -> u64 {
+        self.amount
+    }
+
+    /// Getter for the redeemer passed to the minting policy
+    pub fn redeemer(&self) -> &Redeemer {
+        &self.redeemer
+    }
+
+    /// Getter for the cost of executing the minting policy
+    pub fn cost(&self) -> &ExecutionCost {
+        &self.cost
+    }
+
+    /// Getter for the [`TxContext`] the minting policy was executed with
+    pub fn ctx(&self) -> &TxContext {
+        &self.ctx
+    }
+}
@@ -27,9 +27,23 @@ pub enum UnbuiltOutput<Datum> {
         values: Values,
         /// Datum of the output
         datum: Datum,
+        /// Whether the datum is included inline or by hash
+        #[serde(default)]
+        attachment: DatumAttachment,
     },
 }
 
+/// How the datum of a validator output is attached to the output on-chain
+#[derive(Clone, Copy, PartialEq, Debug, Eq, Default, Deserialize, Serialize)]
+pub enum DatumAttachment {
+    /// The datum is included in the output itself. Only available from Plutus V2 onwards
+    Inline,
+    /// Only the hash of the datum is included in the output. The datum must be provided as a
+    /// witness by whichever transaction spends the output
+    #[default]
+    Hash,
+}
+
 impl<Datum> UnbuiltOutput<Datum> {
     /// Constructor for wallet output
     pub fn new_wallet(owner: Address, values: Values) -> Self {
@@ -39,12 +53,44 @@ impl<Datum> UnbuiltOutput<Datum> {
         }
     }
 
-    /// Constructor for validator output
+    /// Constructor for validator output. Only the hash of the datum is included in the
+    /// output, see [`UnbuiltOutput::new_validator_with_inline_datum`] otherwise
     pub fn new_validator(script_address: Address, values: Values, datum: Datum) -> Self {
         UnbuiltOutput::Validator {
             script_address: script_address.to_bech32().expect("Already validated"),
             values,
             datum,
+            attachment: DatumAttachment::Hash,
+        }
+    }
+
+    /// Constructor for validator output that includes its datum inline. Only available from
+    /// Plutus V2 onwards
+    pub fn new_validator_with_inline_datum(
+        script_address: Address,
+        values: Values,
+        datum: Datum,
+    ) -> Self {
+        UnbuiltOutput::Validator {
+            script_address: script_address.to_bech32().expect("Already validated"),
+            values,
+            datum,
+            attachment: DatumAttachment::Inline,
+        }
+    }
+
+    /// Constructor for validator output that only includes the hash of its datum, same as
+    /// [`UnbuiltOutput::new_validator`]
+    pub fn new_validator_with_datum_hash(
+        script_address: Address,
+        values: Values,
+        datum: Datum,
+    ) -> Self {
+        UnbuiltOutput::Validator {
+            script_address: script_address.to_bech32().expect("Already validated"),
+            values,
+            datum,
+            attachment: DatumAttachment::Hash,
         }
     }
 
@@ -75,6 +121,14 @@ impl<Datum> UnbuiltOutput<Datum> {
             UnbuiltOutput::Validator { datum, .. } => Some(datum),
         }
     }
+
+    /// Getter for how the datum of the output is attached. Returns `None` for wallet outputs
+    pub fn datum_attachment(&self) -> Option<DatumAttachment> {
+        match self {
+            UnbuiltOutput::Wallet { .. } => None,
+            UnbuiltOutput::Validator { attachment, .. } => Some(*attachment),
+        }
+    }
 }
 
 /// Representation of an on-chain datum
@@ -122,6 +176,7 @@ pub struct Output<Datum> {
     owner: String,
     values: Values,
     datum: DatumKind<Datum>,
+    datum_hash: Option<Vec<u8>>,
 }
 
 /// Unique identifier for specific UTxO
@@ -163,6 +218,7 @@ impl<Datum> Output<Datum> {
             owner: addr,
             values,
             datum: DatumKind::None,
+            datum_hash: None,
         }
     }
 
@@ -181,6 +237,7 @@ impl<Datum> Output<Datum> {
             owner: addr,
             values,
             datum: DatumKind::Typed(datum),
+            datum_hash: None,
         }
     }
 
@@ -199,9 +256,17 @@ impl<Datum> Output<Datum> {
             owner: addr,
             values,
             datum: DatumKind::UnTyped(datum),
+            datum_hash: None,
         }
     }
 
+    /// Mark the output's datum as being attached by hash rather than inline. The datum itself
+    /// might or might not be known, e.g. if no witness for the hash has been seen yet
+    pub fn with_datum_hash(mut self, datum_hash: Vec<u8>) -> Self {
+        self.datum_hash = Some(datum_hash);
+        self
+    }
+
     /// Getter for Output's id
     pub fn id(&self) -> &OutputId {
         &self.id
@@ -221,6 +286,11 @@ impl<Datum> Output<Datum> {
     pub fn datum(&self) -> &DatumKind<Datum> {
         &self.datum
     }
+
+    /// Getter for the hash of Output's datum, if the datum is attached by hash rather than inline
+    pub fn datum_hash(&self) -> Option<&[u8]> {
+        self.datum_hash.as_deref()
+    }
 }
 
 impl<Datum: Clone> Output<Datum> {
@@ -234,6 +304,19 @@ impl<Datum: Clone> Output<Datum> {
 }
 
 impl<Datum: Clone + Into<PlutusData>> Output<Datum> {
+    /// Constructor for validator output whose datum is attached by hash
+    pub fn new_validator_with_datum_hash(
+        tx_hash: Vec<u8>,
+        index: u64,
+        owner: Address,
+        values: Values,
+        datum: Datum,
+    ) -> Self {
+        let datum_hash = Into::<PlutusData>::into(datum.clone()).hash();
+        Output::new_validator(tx_hash, index, owner, values, datum)
+            .with_datum_hash(datum_hash)
+    }
+
     /// Converts `Output` to have an untyped datum, if it is typed. Returns the same `Output` if
     /// datum is untyped or non-existent
     pub fn with_untyped_datum(&self) -> Output<Datum> {
@@ -248,6 +331,7 @@ impl<Datum: Clone + Into<PlutusData>> Output<Datum> {
             owner: self.owner.clone(),
             values: self.values.clone(),
             datum: new_datum,
+            datum_hash: self.datum_hash.clone(),
         }
     }
 
@@ -282,6 +366,7 @@ impl<Datum: Clone + TryFrom<PlutusData>> Output<Datum> {
             owner: self.owner.clone(),
             values: self.values.clone(),
             datum: new_datum,
+            datum_hash: self.datum_hash.clone(),
         }
     }
 }
@@ -3,11 +3,23 @@ use pallas_addresses::{
     Address,
     Network,
 };
-use std::fmt::Debug;
+use plutus_validator::plutus_data::PlutusData;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use std::{
+    convert::Infallible,
+    fmt::Debug,
+};
 use thiserror::Error;
 
 /// Script context types
 pub mod context;
+/// Cost models and budgets that scripts are evaluated with
+pub mod evaluation;
+/// Scripts that take parameters before they can be used
+pub mod parameterized;
 /// Adapter code for [`MintingPolicy`]
 pub mod plutus_minting_policy;
 /// Adapter code for [`Validator`]
@@ -28,6 +40,21 @@ pub trait Validator<D, R>: Send + Sync {
     fn address(&self, network: Network) -> ScriptResult<Address>;
     /// Hex bytes of the script
     fn script_hex(&self) -> ScriptResult<String>;
+    /// Check that `datum` is one the script can be given, before it's put in a transaction.
+    /// Scripts that don't know what they expect accept anything
+    fn check_datum(&self, _datum: &D) -> ScriptResult<()> {
+        Ok(())
+    }
+    /// Check that `redeemer` is one the script can be given, before it's put in a transaction.
+    /// Scripts that don't know what they expect accept anything
+    fn check_redeemer(&self, _redeemer: &R) -> ScriptResult<()> {
+        Ok(())
+    }
+    /// The on-chain form of `redeemer`, as other scripts of the transaction see it in
+    /// [`TxContext::redeemers`]. Scripts whose redeemer has no on-chain form return `None`
+    fn redeemer_data(&self, _redeemer: &R) -> Option<PlutusData> {
+        None
+    }
 }
 
 /// Interface for a script constraining the minting of tokens
@@ -38,10 +65,20 @@ pub trait MintingPolicy<R>: Send + Sync {
     fn id(&self) -> ScriptResult<String>;
     /// Hex bytes of the script
     fn script_hex(&self) -> ScriptResult<String>;
+    /// Check that `redeemer` is one the policy can be given, before it's put in a transaction.
+    /// Policies that don't know what they expect accept anything
+    fn check_redeemer(&self, _redeemer: &R) -> ScriptResult<()> {
+        Ok(())
+    }
+    /// The on-chain form of `redeemer`, as other scripts of the transaction see it in
+    /// [`TxContext::redeemers`]. Policies whose redeemer has no on-chain form return `None`
+    fn redeemer_data(&self, _redeemer: &R) -> Option<PlutusData> {
+        None
+    }
 }
 
 /// Cost of executing a script
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ExecutionCost {
     mem: i64,
     cpu: i64,
@@ -49,7 +86,7 @@ pub struct ExecutionCost {
 
 impl ExecutionCost {
     /// Constructor for an ExecutionCost
-    pub fn new(mem: i64, cpu: i64) -> Self {
+    pub const fn new(mem: i64, cpu: i64) -> Self {
         ExecutionCost { mem, cpu }
     }
 
@@ -85,6 +122,16 @@ pub enum ScriptError {
     IdRetrieval(String),
     #[error("Failed to retrieve script Cbor Hex")]
     ScriptHexRetrieval(String),
+    #[error("Datum doesn't match the script's schema: {0}")]
+    DatumSchema(String),
+    #[error("Redeemer doesn't match the script's schema: {0}")]
+    RedeemerSchema(String),
+}
+
+impl From<Infallible> for ScriptError {
+    fn from(value: Infallible) -> Self {
+        match value {}
+    }
 }
 
 /// Convert a generic error into a [`ScriptError'] `FailedToExecute` variant
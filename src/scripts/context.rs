@@ -9,45 +9,72 @@ use serde::{
     Deserialize,
     Serialize,
 };
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    ops::AddAssign,
+};
+
+#[cfg(test)]
+mod tests;
 
-// TODO: Flesh out and probably move https://github.com/MitchTurner/naumachia/issues/39
-// TODO: This should be shaped like the real one actually. That will be extra useful because we can
-//   expose all the primitives in case people want to use them for params, datums, etc...
-/// The context of the transaction that is executing a script
+/// The context of the transaction that is executing a script. Mirrors the Plutus V2
+/// `ScriptContext`: everything but [`TxContext::purpose`] is the `TxInfo` of the transaction
 #[derive(Clone, Debug)]
 pub struct TxContext {
     /// The purpose of the script
     pub purpose: CtxScriptPurpose,
     /// The signer of the transaction
     pub signer: PubKeyHash,
+    /// Hash of the transaction
+    pub id: Vec<u8>,
     /// The valid range of the transaction
     pub range: ValidRange,
     /// The input UTxOs of the transaction
     pub inputs: Vec<Input>,
+    /// The UTxOs the transaction references without spending them
+    pub reference_inputs: Vec<Input>,
     /// The output UTxOs of the transaction
     pub outputs: Vec<CtxOutput>,
+    /// The fee of the transaction in Lovelace
+    pub fee: u64,
+    /// The value minted and burned by the transaction
+    pub mint: CtxMint,
+    /// The certificates published by the transaction
+    pub certificates: Vec<CtxCertificate>,
+    /// Lovelace withdrawn from each reward account
+    pub withdrawals: Vec<(CtxStakingCredential, u64)>,
     /// The extra signatories of the transaction
     pub extra_signatories: Vec<PubKeyHash>,
+    /// The redeemer of every script run by the transaction, by purpose
+    pub redeemers: Vec<(CtxScriptPurpose, PlutusData)>,
     /// A map of datum hashes to datums
     pub datums: Vec<(Vec<u8>, PlutusData)>,
 }
 
+impl TxContext {
+    /// All the keys that signed the transaction: the signer, then the extra signatories
+    pub fn signatories(&self) -> Vec<PubKeyHash> {
+        let mut signatories = vec![self.signer.clone()];
+        signatories.extend(self.extra_signatories.iter().cloned());
+        signatories
+    }
+}
+
 /// The purpose of the script
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum CtxScriptPurpose {
     /// Mint tokens
     Mint(Vec<u8>),
     /// Spend tokens at a script address
     Spend(CtxOutputReference),
     /// Withdraw staked tokens
-    WithdrawFrom,
+    WithdrawFrom(CtxStakingCredential),
     /// Publish certificate
-    Publish,
+    Publish(CtxCertificate),
 }
 
 /// Specifies the output that is being spent in the script purpose
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct CtxOutputReference {
     pub(crate) transaction_id: Vec<u8>,
     pub(crate) output_index: u64,
@@ -91,6 +118,66 @@ pub fn pub_key_hash_from_address_if_available(address: &Address) -> Option<PubKe
     }
 }
 
+/// Hash of the key or script that controls an address or reward account
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CtxCredential {
+    /// Hash of a verification key
+    PubKey(Vec<u8>),
+    /// Hash of a script
+    Script(Vec<u8>),
+}
+
+/// Identifies a reward account
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CtxStakingCredential {
+    /// The account of a credential
+    Hash(CtxCredential),
+    /// The account registered by the certificate at this position on chain
+    Pointer {
+        /// Slot of the block holding the registration
+        slot: u64,
+        /// Index of the transaction in the block
+        tx_index: u64,
+        /// Index of the certificate in the transaction
+        cert_index: u64,
+    },
+}
+
+/// [`TxContext`]'s representation of a certificate. Pools are identified by the hash of their
+/// cold key
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CtxCertificate {
+    /// Register a reward account
+    RegisterStake(CtxStakingCredential),
+    /// Deregister a reward account
+    DeregisterStake(CtxStakingCredential),
+    /// Delegate a reward account to a pool
+    Delegate {
+        /// The delegating account
+        delegator: CtxStakingCredential,
+        /// The pool delegated to
+        pool: PubKeyHash,
+    },
+    /// Register a pool
+    RegisterPool {
+        /// The pool
+        pool: PubKeyHash,
+        /// Hash of the pool's VRF key
+        vrf: PubKeyHash,
+    },
+    /// Retire a pool
+    RetirePool {
+        /// The pool
+        pool: PubKeyHash,
+        /// Epoch the pool retires in
+        epoch: u64,
+    },
+    /// Genesis key delegation
+    Genesis,
+    /// Move instantaneous rewards
+    Mir,
+}
+
 // TODO: Remove the inclusive bool. It's not needed.
 /// Valid range of tx in milliseconds, and a `bool` specifying inclusive. If `None`, then the range is unbounded.
 #[derive(Clone, Debug)]
@@ -142,22 +229,50 @@ impl From<Values> for CtxValue {
     fn from(values: Values) -> Self {
         let mut inner = HashMap::new();
         for (policy, amt) in values.as_iter() {
-            let (policy_id, asset_name) = match policy {
-                PolicyId::Lovelace => ("", ""),
-                PolicyId::NativeToken(policy_id, a) => {
-                    if let Some(asset_name) = a {
-                        (policy_id.as_str(), asset_name.as_str())
-                    } else {
-                        (policy_id.as_str(), "")
-                    }
-                }
-            };
+            let (policy_id, asset_name) = policy_and_asset_name(policy);
             add_to_nested(&mut inner, policy_id, asset_name, *amt);
         }
         CtxValue { inner }
     }
 }
 
+/// [`TxContext`]'s representation of the value minted by a transaction. Burned amounts are
+/// negative
+#[derive(Clone, Debug, Default)]
+pub struct CtxMint {
+    /// Inner map of PolicyIds to Asset Names and amount
+    pub inner: HashMap<String, HashMap<String, i64>>,
+}
+
+impl CtxMint {
+    /// Constructor for `CtxMint` from the positive amounts that are minted and burned
+    pub fn new(minted: &Values, burned: &Values) -> Self {
+        let mut inner = HashMap::new();
+        for (policy, amt) in minted.as_iter() {
+            let (policy_id, asset_name) = policy_and_asset_name(policy);
+            add_to_nested(&mut inner, policy_id, asset_name, *amt as i64);
+        }
+        for (policy, amt) in burned.as_iter() {
+            let (policy_id, asset_name) = policy_and_asset_name(policy);
+            add_to_nested(&mut inner, policy_id, asset_name, -(*amt as i64));
+        }
+        CtxMint { inner }
+    }
+}
+
+fn policy_and_asset_name(policy: &PolicyId) -> (&str, &str) {
+    match policy {
+        PolicyId::Lovelace => ("", ""),
+        PolicyId::NativeToken(policy_id, a) => {
+            if let Some(asset_name) = a {
+                (policy_id.as_str(), asset_name.as_str())
+            } else {
+                (policy_id.as_str(), "")
+            }
+        }
+    }
+}
+
 /// [`TxContext`]'s representation of a datum
 #[derive(Clone, Debug)]
 pub enum CtxDatum {
@@ -195,10 +310,17 @@ impl<D: Clone + Into<PlutusData>> From<Option<D>> for CtxDatum {
 /// ```
 pub struct ContextBuilder {
     signer: PubKeyHash,
+    id: Vec<u8>,
     range: Option<ValidRange>,
     inputs: Vec<Input>,
+    reference_inputs: Vec<Input>,
     outputs: Vec<CtxOutput>,
+    fee: u64,
+    mint: HashMap<String, HashMap<String, i64>>,
+    certificates: Vec<CtxCertificate>,
+    withdrawals: Vec<(CtxStakingCredential, u64)>,
     extra_signatories: Vec<PubKeyHash>,
+    redeemers: Vec<(CtxScriptPurpose, PlutusData)>,
     datums: Vec<(Vec<u8>, PlutusData)>,
 }
 
@@ -207,14 +329,27 @@ impl ContextBuilder {
     pub fn new(signer: PubKeyHash) -> Self {
         ContextBuilder {
             signer,
+            id: vec![],
             range: None,
             inputs: vec![],
+            reference_inputs: vec![],
             outputs: vec![],
+            fee: 0,
+            mint: HashMap::new(),
+            certificates: vec![],
+            withdrawals: vec![],
             extra_signatories: vec![],
+            redeemers: vec![],
             datums: vec![],
         }
     }
 
+    /// Add specific transaction id for the `TxContext`
+    pub fn with_tx_id(mut self, id: &[u8]) -> Self {
+        self.id = id.to_vec();
+        self
+    }
+
     /// Add specific valid range for the `TxContext`
     pub fn with_range(
         mut self,
@@ -241,6 +376,21 @@ impl ContextBuilder {
             datum: CtxDatum::NoDatum,
             reference_script: None,
             output_index,
+            is_reference: false,
+        }
+    }
+
+    /// Initializes [`CtxInputBuilder`] sub-builder for adding a reference input to the
+    /// `TxContext`
+    pub fn with_reference_input(
+        self,
+        transaction_id: &[u8],
+        output_index: u64,
+        address: &Address,
+    ) -> CtxInputBuilder {
+        CtxInputBuilder {
+            is_reference: true,
+            ..self.with_input(transaction_id, output_index, address)
         }
     }
 
@@ -250,27 +400,26 @@ impl ContextBuilder {
         self
     }
 
+    /// Add specific [`Input`] UTxO, rather than using `with_reference_input`
+    fn add_reference_input(mut self, input: Input) -> ContextBuilder {
+        self.reference_inputs.push(input);
+        self
+    }
+
     /// Add specific [`Output`] as an input, rather than using `with_input`
     pub fn add_specific_input<D: Clone + Into<PlutusData>>(
-        mut self,
+        self,
         input: &Output<D>,
     ) -> Self {
-        let id = input.id();
-        let transaction_id = id.tx_hash().to_vec();
-        let output_index = id.index();
-        let address = input.owner();
-        let value = CtxValue::from(input.values().to_owned());
-        let datum = input.typed_datum().into();
-        let ctx_input = Input {
-            transaction_id,
-            output_index,
-            address,
-            value,
-            datum,
-            reference_script: None,
-        };
-        self.inputs.push(ctx_input);
-        self
+        self.add_input(input_from_output(input))
+    }
+
+    /// Add specific [`Output`] as a reference input, rather than using `with_reference_input`
+    pub fn add_specific_reference_input<D: Clone + Into<PlutusData>>(
+        self,
+        input: &Output<D>,
+    ) -> Self {
+        self.add_reference_input(input_from_output(input))
     }
 
     /// Initializes [`CtxOutputBuilder`] sub-builder for adding an output to the `TxContext`
@@ -308,12 +457,46 @@ impl ContextBuilder {
         self
     }
 
+    /// Add specific fee in Lovelace
+    pub fn with_fee(mut self, fee: u64) -> Self {
+        self.fee = fee;
+        self
+    }
+
+    /// Add minted value. Burned value is negative
+    pub fn add_mint(mut self, policy_id: &str, asset_name: &str, amt: i64) -> Self {
+        add_to_nested(&mut self.mint, policy_id, asset_name, amt);
+        self
+    }
+
+    /// Add specific certificate
+    pub fn add_certificate(mut self, certificate: CtxCertificate) -> Self {
+        self.certificates.push(certificate);
+        self
+    }
+
+    /// Add Lovelace withdrawn from a reward account
+    pub fn add_withdrawal(mut self, credential: CtxStakingCredential, amt: u64) -> Self {
+        self.withdrawals.push((credential, amt));
+        self
+    }
+
     /// Add specific "extra" signatory
     pub fn add_signatory(mut self, signer: PubKeyHash) -> Self {
         self.extra_signatories.push(signer);
         self
     }
 
+    /// Add the redeemer of a script run by the transaction
+    pub fn add_redeemer<Redeemer: Into<PlutusData>>(
+        mut self,
+        purpose: CtxScriptPurpose,
+        redeemer: Redeemer,
+    ) -> Self {
+        self.redeemers.push((purpose, redeemer.into()));
+        self
+    }
+
     /// Add specific `Datum`
     pub fn add_datum<Datum: Into<PlutusData>>(mut self, datum: Datum) -> Self {
         let data = datum.into();
@@ -323,30 +506,26 @@ impl ContextBuilder {
 
     /// Build the context with a "spend" purpose
     pub fn build_spend(&self, tx_id: &[u8], index: u64) -> TxContext {
-        let range = if let Some(range) = self.range.clone() {
-            range
-        } else {
-            ValidRange {
-                lower: None,
-                upper: None,
-            }
-        };
-        TxContext {
-            purpose: CtxScriptPurpose::Spend(CtxOutputReference::new(
-                tx_id.to_vec(),
-                index,
-            )),
-            signer: self.signer.clone(),
-            range,
-            inputs: self.inputs.clone(),
-            outputs: self.outputs.clone(),
-            extra_signatories: self.extra_signatories.clone(),
-            datums: self.datums.clone(),
-        }
+        let out_ref = CtxOutputReference::new(tx_id.to_vec(), index);
+        self.build(CtxScriptPurpose::Spend(out_ref))
     }
 
     /// Build the context with a "mint" purpose
     pub fn build_mint(&self, policy_id: &[u8]) -> TxContext {
+        self.build(CtxScriptPurpose::Mint(policy_id.to_vec()))
+    }
+
+    /// Build the context with a "withdraw" purpose
+    pub fn build_withdraw(&self, credential: CtxStakingCredential) -> TxContext {
+        self.build(CtxScriptPurpose::WithdrawFrom(credential))
+    }
+
+    /// Build the context with a "publish" purpose
+    pub fn build_publish(&self, certificate: CtxCertificate) -> TxContext {
+        self.build(CtxScriptPurpose::Publish(certificate))
+    }
+
+    fn build(&self, purpose: CtxScriptPurpose) -> TxContext {
         let range = if let Some(range) = self.range.clone() {
             range
         } else {
@@ -356,17 +535,38 @@ impl ContextBuilder {
             }
         };
         TxContext {
-            purpose: CtxScriptPurpose::Mint(policy_id.to_vec()),
+            purpose,
             signer: self.signer.clone(),
+            id: self.id.clone(),
             range,
             inputs: self.inputs.clone(),
+            reference_inputs: self.reference_inputs.clone(),
             outputs: self.outputs.clone(),
+            fee: self.fee,
+            mint: CtxMint {
+                inner: self.mint.clone(),
+            },
+            certificates: self.certificates.clone(),
+            withdrawals: self.withdrawals.clone(),
             extra_signatories: self.extra_signatories.clone(),
+            redeemers: self.redeemers.clone(),
             datums: self.datums.clone(),
         }
     }
 }
 
+fn input_from_output<D: Clone + Into<PlutusData>>(output: &Output<D>) -> Input {
+    let id = output.id();
+    Input {
+        transaction_id: id.tx_hash().to_vec(),
+        output_index: id.index(),
+        address: output.owner(),
+        value: CtxValue::from(output.values().to_owned()),
+        datum: output.typed_datum().into(),
+        reference_script: None,
+    }
+}
+
 /// Sub-builder for adding an [`CtxInput`] to a [`TxContext`]
 pub struct CtxInputBuilder {
     outer: ContextBuilder,
@@ -376,6 +576,7 @@ pub struct CtxInputBuilder {
     value: HashMap<String, HashMap<String, u64>>,
     datum: CtxDatum,
     reference_script: Option<Vec<u8>>,
+    is_reference: bool,
 }
 
 impl CtxInputBuilder {
@@ -425,7 +626,11 @@ impl CtxInputBuilder {
             datum: self.datum,
             reference_script: self.reference_script,
         };
-        self.outer.add_input(input)
+        if self.is_reference {
+            self.outer.add_reference_input(input)
+        } else {
+            self.outer.add_input(input)
+        }
     }
 }
 
@@ -479,11 +684,11 @@ impl CtxOutputBuilder {
     }
 }
 
-fn add_to_nested(
-    values: &mut HashMap<String, HashMap<String, u64>>,
+fn add_to_nested<Amount: Copy + AddAssign>(
+    values: &mut HashMap<String, HashMap<String, Amount>>,
     policy_id: &str,
     asset_name: &str,
-    amt: u64,
+    amt: Amount,
 ) {
     let new_assets = if let Some(mut assets) = values.remove(policy_id) {
         if let Some(mut total_amt) = assets.remove(asset_name) {
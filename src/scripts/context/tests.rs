@@ -0,0 +1,168 @@
+#![allow(non_snake_case)]
+
+use super::*;
+use crate::scripts::plutus_validator::plutus_data::Constr;
+use std::collections::BTreeMap;
+
+const SIGNER: &str = "addr_test1qrksjmprvgcedgdt6rhg40590vr6exdzdc2hm5wc6pyl9ymkyskmqs55usm57gflrumk9kd63f3ty6r0l2tdfwfm28qs0rurdr";
+
+fn constr(constr: u64, fields: Vec<PlutusData>) -> PlutusData {
+    PlutusData::Constr(Constr { constr, fields })
+}
+
+fn int(value: i64) -> PlutusData {
+    PlutusData::BigInt(value.into())
+}
+
+fn bytes(bytes: &[u8]) -> PlutusData {
+    PlutusData::BoundedBytes(bytes.to_vec())
+}
+
+fn signer_pkh() -> PubKeyHash {
+    let signer = Address::from_bech32(SIGNER).unwrap();
+    pub_key_hash_from_address_if_available(&signer).unwrap()
+}
+
+/// The fields of the `TxInfo`, and the purpose
+fn encoded(ctx: TxContext) -> (Vec<PlutusData>, PlutusData) {
+    match PlutusData::from(ctx) {
+        PlutusData::Constr(Constr { constr: 0, fields }) => match &fields[..] {
+            [PlutusData::Constr(tx_info), purpose] => {
+                (tx_info.fields.clone(), purpose.clone())
+            }
+            _ => panic!("not a script context: {fields:?}"),
+        },
+        other => panic!("not a script context: {other:?}"),
+    }
+}
+
+#[test]
+fn build__signer_is_first_signatory() {
+    // Given
+    let other = PubKeyHash::new(&[1; 28]);
+    let builder = ContextBuilder::new(signer_pkh()).add_signatory(other.clone());
+
+    // When
+    let ctx = builder.build_mint(&[]);
+
+    // Then
+    assert_eq!(ctx.signatories(), vec![signer_pkh(), other]);
+}
+
+#[test]
+fn build__keeps_reference_inputs_apart_from_inputs() {
+    // Given
+    let address = Address::from_bech32(SIGNER).unwrap();
+    let builder = ContextBuilder::new(signer_pkh())
+        .with_input(&[1], 0, &address)
+        .finish_input()
+        .with_reference_input(&[2], 1, &address)
+        .with_inline_datum(5_i64)
+        .finish_input();
+
+    // When
+    let ctx = builder.build_spend(&[1], 0);
+
+    // Then
+    assert_eq!(ctx.inputs.len(), 1);
+    assert_eq!(ctx.reference_inputs.len(), 1);
+    let reference_input = &ctx.reference_inputs[0];
+    assert_eq!(reference_input.transaction_id, vec![2]);
+    assert!(matches!(reference_input.datum, CtxDatum::InlineDatum(_)));
+}
+
+#[test]
+fn into_plutus_data__encodes_id_fee_and_mint() {
+    // Given
+    let ctx = ContextBuilder::new(signer_pkh())
+        .with_tx_id(&[7; 32])
+        .with_fee(170_000)
+        .add_mint("0101", "tok", 5)
+        .add_mint("0202", "", -3)
+        .build_mint(&[1, 1]);
+
+    // When
+    let (tx_info, purpose) = encoded(ctx);
+
+    // Then
+    let assets = |asset_name: &[u8], amount: i64| {
+        PlutusData::Map(BTreeMap::from([(bytes(asset_name), int(amount))]))
+    };
+    let fee = PlutusData::Map(BTreeMap::from([(bytes(&[]), assets(&[], 170_000))]));
+    // Ada is always in the mint, with nothing minted
+    let mint = PlutusData::Map(BTreeMap::from([
+        (bytes(&[]), assets(&[], 0)),
+        (bytes(&[1, 1]), assets(b"tok", 5)),
+        (bytes(&[2, 2]), assets(&[], -3)),
+    ]));
+    assert_eq!(tx_info[3], fee);
+    assert_eq!(tx_info[4], mint);
+    assert_eq!(tx_info[11], constr(0, vec![bytes(&[7; 32])]));
+    assert_eq!(purpose, constr(0, vec![bytes(&[1, 1])]));
+}
+
+#[test]
+fn into_plutus_data__encodes_certificates_withdrawals_and_redeemers() {
+    // Given
+    let stake = CtxStakingCredential::Hash(CtxCredential::Script(vec![3; 28]));
+    let pool = PubKeyHash::new(&[4; 28]);
+    let ctx = ContextBuilder::new(signer_pkh())
+        .add_certificate(CtxCertificate::Delegate {
+            delegator: stake.clone(),
+            pool,
+        })
+        .add_withdrawal(stake.clone(), 1_000)
+        .add_redeemer(CtxScriptPurpose::WithdrawFrom(stake.clone()), 42_i64)
+        .build_withdraw(stake);
+
+    // When
+    let (tx_info, purpose) = encoded(ctx);
+
+    // Then
+    let stake_data = constr(0, vec![constr(1, vec![bytes(&[3; 28])])]);
+    let expected_purpose = constr(2, vec![stake_data.clone()]);
+    assert_eq!(
+        tx_info[5],
+        PlutusData::Array(vec![constr(2, vec![stake_data.clone(), bytes(&[4; 28])])])
+    );
+    assert_eq!(
+        tx_info[6],
+        PlutusData::Map(BTreeMap::from([(stake_data, int(1_000))]))
+    );
+    assert_eq!(
+        tx_info[9],
+        PlutusData::Map(BTreeMap::from([(expected_purpose.clone(), int(42))]))
+    );
+    assert_eq!(purpose, expected_purpose);
+}
+
+#[test]
+fn into_plutus_data__encodes_bounded_ranges() {
+    // Given
+    let bounded = ContextBuilder::new(signer_pkh())
+        .with_range(Some((5, true)), Some((10, false)))
+        .build_mint(&[]);
+    let upper_only = ContextBuilder::new(signer_pkh())
+        .with_range(None, Some((10, false)))
+        .build_mint(&[]);
+
+    // When
+    let (bounded_info, _) = encoded(bounded);
+    let (upper_only_info, _) = encoded(upper_only);
+
+    // Then
+    let bound =
+        |extended: PlutusData, closed: bool| constr(0, vec![extended, closed.into()]);
+    let finite = |time: i64| constr(1, vec![int(time)]);
+    assert_eq!(
+        bounded_info[7],
+        constr(0, vec![bound(finite(5), true), bound(finite(10), false)])
+    );
+    assert_eq!(
+        upper_only_info[7],
+        constr(
+            0,
+            vec![bound(constr(0, vec![]), true), bound(finite(10), false)]
+        )
+    );
+}
@@ -0,0 +1,160 @@
+use crate::{
+    scripts::{
+        as_failed_to_execute,
+        plutus_validator::{
+            apply_data,
+            plutus_data::PlutusData,
+        },
+        raw_script::PlutusScriptError,
+        ExecutionCost,
+        ScriptError,
+        ScriptResult,
+    },
+    transaction::TransactionVersion,
+};
+use pallas_primitives::babbage::Language;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use uplc::{
+    ast::{
+        FakeNamedDeBruijn,
+        NamedDeBruijn,
+        Program,
+    },
+    machine::cost_model::ExBudget,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Mainnet's Plutus V1 cost model as of the Vasil hard fork
+pub const VASIL_PLUTUS_V1_COST_MODEL: &[i64] = &[
+    205665, 812, 1, 1, 1000, 571, 0, 1, 1000, 24177, 4, 1, 1000, 32, 117366, 10475, 4,
+    23000, 100, 23000, 100, 23000, 100, 23000, 100, 23000, 100, 23000, 100, 100, 100,
+    23000, 100, 19537, 32, 175354, 32, 46417, 4, 221973, 511, 0, 1, 89141, 32, 497525,
+    14068, 4, 2, 196500, 453240, 220, 0, 1, 1, 1000, 28662, 4, 2, 245000, 216773, 62, 1,
+    1060367, 12586, 1, 208512, 421, 1, 187000, 1000, 52998, 1, 80436, 32, 43249, 32,
+    1000, 32, 80556, 1, 57667, 4, 1000, 10, 197145, 156, 1, 197145, 156, 1, 204924, 473,
+    1, 208896, 511, 1, 52467, 32, 64832, 32, 65493, 32, 22558, 32, 16563, 32, 76511, 32,
+    196500, 453240, 220, 0, 1, 1, 69522, 11687, 0, 1, 60091, 32, 196500, 453240, 220, 0,
+    1, 1, 196500, 453240, 220, 0, 1, 1, 806990, 30482, 4, 1927926, 82523, 4, 265318, 0,
+    4, 0, 85931, 32, 205665, 812, 1, 1, 41182, 32, 212342, 32, 31220, 32, 32696, 32,
+    43357, 32, 32247, 32, 38314, 32, 9462713, 1021, 10,
+];
+
+/// Mainnet's Plutus V2 cost model as of the Vasil hard fork
+pub const VASIL_PLUTUS_V2_COST_MODEL: &[i64] = &[
+    205665, 812, 1, 1, 1000, 571, 0, 1, 1000, 24177, 4, 1, 1000, 32, 117366, 10475, 4,
+    23000, 100, 23000, 100, 23000, 100, 23000, 100, 23000, 100, 23000, 100, 100, 100,
+    23000, 100, 19537, 32, 175354, 32, 46417, 4, 221973, 511, 0, 1, 89141, 32, 497525,
+    14068, 4, 2, 196500, 453240, 220, 0, 1, 1, 1000, 28662, 4, 2, 245000, 216773, 62, 1,
+    1060367, 12586, 1, 208512, 421, 1, 187000, 1000, 52998, 1, 80436, 32, 43249, 32,
+    1000, 32, 80556, 1, 57667, 4, 1000, 10, 197145, 156, 1, 197145, 156, 1, 204924, 473,
+    1, 208896, 511, 1, 52467, 32, 64832, 32, 65493, 32, 22558, 32, 16563, 32, 76511, 32,
+    196500, 453240, 220, 0, 1, 1, 69522, 11687, 0, 1, 60091, 32, 196500, 453240, 220, 0,
+    1, 1, 196500, 453240, 220, 0, 1, 1, 1159724, 392670, 0, 2, 806990, 30482, 4, 1927926,
+    82523, 4, 265318, 0, 4, 0, 85931, 32, 205665, 812, 1, 1, 41182, 32, 212342, 32,
+    31220, 32, 32696, 32, 43357, 32, 32247, 32, 38314, 32, 35892428, 10, 57996947, 18975,
+    10, 38887044, 32947, 10,
+];
+
+/// Mainnet's maximum execution units per transaction as of the Vasil hard fork, which is
+/// what a single script may spend at most
+pub const MAX_TX_EX_UNITS: ExecutionCost = ExecutionCost::new(14_000_000, 10_000_000_000);
+
+/// Cost model parameters for each Plutus version, in the order the ledger lists them
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CostModels {
+    /// Parameters for V1 scripts
+    pub plutus_v1: Vec<i64>,
+    /// Parameters for V2 scripts
+    pub plutus_v2: Vec<i64>,
+}
+
+impl CostModels {
+    /// Mainnet's cost models as of the Vasil hard fork
+    pub fn vasil() -> Self {
+        CostModels {
+            plutus_v1: VASIL_PLUTUS_V1_COST_MODEL.to_vec(),
+            plutus_v2: VASIL_PLUTUS_V2_COST_MODEL.to_vec(),
+        }
+    }
+}
+
+/// What scripts are evaluated with: the cost models that price each step, and the budget a
+/// script may spend.
+///
+/// Use the current protocol parameters to get the same [`ExecutionCost`] the node would,
+/// e.g. from [`Ledger::evaluation_params`](crate::trireme_ledger_client::cml_client::Ledger::evaluation_params),
+/// or deserialize them from a config file.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EvaluationParams {
+    /// Cost models of each Plutus version
+    pub cost_models: CostModels,
+    /// Most a script may spend before it fails
+    pub max_budget: ExecutionCost,
+}
+
+impl EvaluationParams {
+    /// Mainnet's protocol parameters as of the Vasil hard fork. Only meant as a fallback for
+    /// when the current ones can't be fetched, e.g. offline, as the node prices scripts with
+    /// the current ones
+    pub fn vasil() -> Self {
+        EvaluationParams {
+            cost_models: CostModels::vasil(),
+            max_budget: MAX_TX_EX_UNITS,
+        }
+    }
+
+    /// Apply `arguments` to the script in `cbor` and evaluate it, returning the budget it
+    /// spent. Failures include the script's trace logs
+    pub fn evaluate(
+        &self,
+        cbor: &[u8],
+        version: &TransactionVersion,
+        arguments: Vec<PlutusData>,
+    ) -> ScriptResult<ExecutionCost> {
+        let program: Program<NamedDeBruijn> =
+            Program::<FakeNamedDeBruijn>::from_cbor(cbor, &mut Vec::new())
+                .map_err(as_failed_to_execute)?
+                .into();
+        let program = apply_data(program, arguments);
+        let (language, costs, required) = match version {
+            TransactionVersion::V1 => (
+                Language::PlutusV1,
+                &self.cost_models.plutus_v1,
+                VASIL_PLUTUS_V1_COST_MODEL.len(),
+            ),
+            TransactionVersion::V2 => (
+                Language::PlutusV2,
+                &self.cost_models.plutus_v2,
+                VASIL_PLUTUS_V2_COST_MODEL.len(),
+            ),
+        };
+        // Newer protocol versions append parameters, which can be ignored, but `uplc` panics
+        // when any are missing
+        if costs.len() < required {
+            return Err(ScriptError::FailedToExecute(format!(
+                "{language:?} cost model has {} parameters, but needs at least {required}",
+                costs.len()
+            )))
+        }
+        let budget = ExBudget {
+            mem: self.max_budget.mem,
+            cpu: self.max_budget.cpu,
+        };
+        let mut eval_result = program.eval_as(&language, costs, Some(&budget));
+        let logs = eval_result.logs();
+        let cost = eval_result.cost();
+        eval_result
+            .result()
+            .map_err(|e| PlutusScriptError::AikenEval {
+                error: format!("{e:?}"),
+                logs,
+            })
+            .map_err(as_failed_to_execute)?;
+        Ok(cost.into())
+    }
+}
@@ -0,0 +1,143 @@
+#![allow(non_snake_case)]
+
+use super::*;
+use uplc::ast::DeBruijn;
+
+/// CBOR of a script that ignores its three arguments and returns unit
+fn takes_three_arguments() -> Vec<u8> {
+    let source = "(program 1.0.0 (lam d (lam r (lam c (con unit ())))))";
+    let program = uplc::parser::program(source).unwrap();
+    let program: Program<DeBruijn> = program.try_into().unwrap();
+    hex::decode(program.to_hex().unwrap()).unwrap()
+}
+
+fn arguments() -> Vec<PlutusData> {
+    vec![PlutusData::BigInt(1.into()); 3]
+}
+
+#[test]
+fn evaluate__returns_budget_spent() {
+    // Given
+    let params = EvaluationParams::vasil();
+
+    // When
+    let cost = params
+        .evaluate(
+            &takes_three_arguments(),
+            &TransactionVersion::V2,
+            arguments(),
+        )
+        .unwrap();
+
+    // Then
+    assert!(cost.mem() > 0);
+    assert!(cost.cpu() > 0);
+    assert!(cost.mem() < MAX_TX_EX_UNITS.mem());
+    assert!(cost.cpu() < MAX_TX_EX_UNITS.cpu());
+}
+
+#[test]
+fn evaluate__prices_steps_with_cost_model() {
+    // Given
+    let default = EvaluationParams::vasil();
+    let doubled = EvaluationParams {
+        cost_models: CostModels {
+            plutus_v1: VASIL_PLUTUS_V1_COST_MODEL.to_vec(),
+            plutus_v2: VASIL_PLUTUS_V2_COST_MODEL
+                .iter()
+                .map(|cost| cost * 2)
+                .collect(),
+        },
+        ..EvaluationParams::vasil()
+    };
+
+    // When
+    let [default_cost, doubled_cost] = [default, doubled].map(|params| {
+        params
+            .evaluate(
+                &takes_three_arguments(),
+                &TransactionVersion::V2,
+                arguments(),
+            )
+            .unwrap()
+    });
+
+    // Then
+    assert!(doubled_cost.cpu() > default_cost.cpu());
+    assert!(doubled_cost.mem() > default_cost.mem());
+}
+
+#[test]
+fn evaluate__fails_over_max_budget() {
+    // Given
+    let params = EvaluationParams {
+        max_budget: ExecutionCost::new(10, 10),
+        ..EvaluationParams::vasil()
+    };
+
+    // When
+    let result = params.evaluate(
+        &takes_three_arguments(),
+        &TransactionVersion::V2,
+        arguments(),
+    );
+
+    // Then
+    assert!(matches!(result, Err(ScriptError::FailedToExecute(_))));
+}
+
+#[test]
+fn evaluate__fails_for_incomplete_cost_model() {
+    // Given
+    let params = EvaluationParams {
+        cost_models: CostModels {
+            plutus_v1: vec![1; 10],
+            plutus_v2: VASIL_PLUTUS_V2_COST_MODEL.to_vec(),
+        },
+        ..EvaluationParams::vasil()
+    };
+
+    // When
+    let result = params.evaluate(
+        &takes_three_arguments(),
+        &TransactionVersion::V1,
+        arguments(),
+    );
+
+    // Then
+    assert_eq!(
+        result.unwrap_err(),
+        ScriptError::FailedToExecute(
+            "PlutusV1 cost model has 10 parameters, but needs at least 166".to_string()
+        )
+    );
+}
+
+#[test]
+fn deserialize__reads_cost_models_and_max_budget() {
+    // Given
+    let json = r#"{
+        "cost_models": { "plutus_v1": [1, 2], "plutus_v2": [3] },
+        "max_budget": { "mem": 100, "cpu": 200 }
+    }"#;
+
+    // When
+    let params: EvaluationParams = serde_json::from_str(json).unwrap();
+
+    // Then
+    assert_eq!(params.cost_models.plutus_v1, vec![1, 2]);
+    assert_eq!(params.cost_models.plutus_v2, vec![3]);
+    assert_eq!(params.max_budget, ExecutionCost::new(100, 200));
+}
+
+#[test]
+fn deserialize__requires_cost_models() {
+    // Given
+    let json = r#"{ "max_budget": { "mem": 100, "cpu": 200 } }"#;
+
+    // When
+    let result = serde_json::from_str::<EvaluationParams>(json);
+
+    // Then
+    assert!(result.is_err());
+}
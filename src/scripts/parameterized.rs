@@ -0,0 +1,178 @@
+use crate::{
+    scripts::{
+        plutus_minting_policy::PlutusMintingPolicy,
+        plutus_validator::{
+            plutus_data::PlutusData,
+            PlutusValidator,
+        },
+        raw_script::{
+            PlutusScriptError,
+            PlutusScriptFile,
+            RawPlutusScriptResult,
+            ValidatorBlueprint,
+        },
+    },
+    transaction::TransactionVersion,
+};
+use minicbor::Decoder;
+use std::{
+    marker::PhantomData,
+    rc::Rc,
+};
+use uplc::ast::{
+    Constant,
+    FakeNamedDeBruijn,
+    NamedDeBruijn,
+    Program,
+    Term,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// [`ParameterizedScript`] that becomes a [`PlutusValidator`] once its parameters are applied
+pub type ParameterizedValidator<Params, Datum, Redeemer> =
+    ParameterizedScript<Params, PlutusValidator<Datum, Redeemer>>;
+
+/// [`ParameterizedScript`] that becomes a [`PlutusMintingPolicy`] once its parameters are applied
+pub type ParameterizedPolicy<Params, Redeemer> =
+    ParameterizedScript<Params, PlutusMintingPolicy<Redeemer>>;
+
+/// Parameters of a script, in the order they're applied. Implemented for tuples of values that
+/// convert into [`PlutusData`], e.g. `(OutputReference, Vec<u8>)`
+pub trait ScriptParams {
+    /// How many parameters there are
+    const ARITY: usize;
+
+    /// Convert each parameter into [`PlutusData`], in order
+    fn into_data(self) -> Vec<PlutusData>;
+}
+
+impl ScriptParams for () {
+    const ARITY: usize = 0;
+
+    fn into_data(self) -> Vec<PlutusData> {
+        Vec::new()
+    }
+}
+
+macro_rules! impl_script_params {
+    ($arity:literal: $($param:ident),+) => {
+        impl<$($param: Into<PlutusData>),+> ScriptParams for ($($param,)+) {
+            const ARITY: usize = $arity;
+
+            #[allow(non_snake_case)]
+            fn into_data(self) -> Vec<PlutusData> {
+                let ($($param,)+) = self;
+                vec![$($param.into()),+]
+            }
+        }
+    };
+}
+
+impl_script_params!(1: A);
+impl_script_params!(2: A, B);
+impl_script_params!(3: A, B, C);
+impl_script_params!(4: A, B, C, D);
+impl_script_params!(5: A, B, C, D, E);
+impl_script_params!(6: A, B, C, D, E, F);
+impl_script_params!(7: A, B, C, D, E, F, G);
+impl_script_params!(8: A, B, C, D, E, F, G, H);
+
+/// Script that is complete once all of its parameters have been applied
+pub trait AppliedScript: Sized {
+    /// Construct the script from its applied code, keeping the blueprint it came from if any
+    fn from_applied(
+        version: TransactionVersion,
+        cbor: Vec<u8>,
+        blueprint: Option<ValidatorBlueprint>,
+    ) -> Self;
+}
+
+/// Builder for a validator or minting policy that takes `Params` before it can be used
+pub struct ParameterizedScript<Params, Script> {
+    version: TransactionVersion,
+    cbor: Vec<u8>,
+    blueprint: Option<ValidatorBlueprint>,
+    _params: PhantomData<Params>,
+    _script: PhantomData<Script>,
+}
+
+impl<Params: ScriptParams, Script: AppliedScript> ParameterizedScript<Params, Script> {
+    /// Create a new V2 `ParameterizedScript` from a [`PlutusScriptFile`]
+    pub fn new_v2(script_file: PlutusScriptFile) -> RawPlutusScriptResult<Self> {
+        let cbor = hex::decode(script_file.cborHex)
+            .map_err(|e| PlutusScriptError::AikenApply(e.to_string()))?;
+        let mut outer_decoder = Decoder::new(&cbor);
+        let outer = outer_decoder
+            .bytes()
+            .map_err(|e| PlutusScriptError::AikenApply(e.to_string()))?;
+        let v2_script = ParameterizedScript {
+            version: TransactionVersion::V2,
+            cbor: outer.to_vec(),
+            blueprint: None,
+            _params: Default::default(),
+            _script: Default::default(),
+        };
+        Ok(v2_script)
+    }
+
+    /// Create a new `ParameterizedScript` from a [`ValidatorBlueprint`] based on CIP-0057, in
+    /// the blueprint's Plutus version. Fails if the blueprint doesn't list as many parameters
+    /// as `Params` has
+    pub fn from_blueprint(blueprint: ValidatorBlueprint) -> RawPlutusScriptResult<Self> {
+        check_arity(&blueprint, Params::ARITY)?;
+        let cbor = hex::decode(blueprint.compiled_code())
+            .map_err(|e| PlutusScriptError::AikenApply(e.to_string()))?;
+        let script = ParameterizedScript {
+            version: blueprint.version()?,
+            cbor,
+            blueprint: Some(blueprint),
+            _params: Default::default(),
+            _script: Default::default(),
+        };
+        Ok(script)
+    }
+
+    /// Apply all the parameters to get the complete script
+    pub fn apply(&self, params: Params) -> RawPlutusScriptResult<Script> {
+        let cbor = apply_params(&self.cbor, params.into_data())?;
+        let script =
+            Script::from_applied(self.version.clone(), cbor, self.blueprint.clone());
+        Ok(script)
+    }
+}
+
+/// Check that the `blueprint` lists exactly `arity` parameters
+pub(crate) fn check_arity(
+    blueprint: &ValidatorBlueprint,
+    arity: usize,
+) -> RawPlutusScriptResult<()> {
+    let expected = blueprint.parameters().len();
+    if expected == arity {
+        Ok(())
+    } else {
+        Err(PlutusScriptError::Blueprint(format!(
+            "{} takes {expected} parameters, not {arity}",
+            blueprint.title()
+        )))
+    }
+}
+
+/// Apply the `params` to the UPLC program encoded in `cbor`, in order
+pub(crate) fn apply_params(
+    cbor: &[u8],
+    params: Vec<PlutusData>,
+) -> RawPlutusScriptResult<Vec<u8>> {
+    let program: Program<NamedDeBruijn> =
+        Program::<FakeNamedDeBruijn>::from_cbor(cbor, &mut Vec::new())
+            .map_err(|e| PlutusScriptError::AikenApply(format!("{e:?}")))?
+            .into();
+    let program = params.into_iter().fold(program, |program, param| {
+        let term = Term::Constant(Rc::new(Constant::Data(param.into())));
+        program.apply_term(&term)
+    });
+    let fake: Program<FakeNamedDeBruijn> = program.into();
+    fake.to_cbor()
+        .map_err(|e| PlutusScriptError::AikenApply(e.to_string()))
+}
@@ -0,0 +1,134 @@
+#![allow(non_snake_case)]
+
+use super::*;
+use crate::scripts::{
+    plutus_minting_policy::{
+        OneParamPlutusPolicy,
+        TwoParamMintingPolicy,
+    },
+    raw_script::BlueprintFile,
+    MintingPolicy,
+};
+
+const MINT_NFT_BLUEPRINT: &str =
+    include_str!("../../../nau-scripts/aiken/mint_nft/plutus.json");
+const CHECKING_BLUEPRINT: &str =
+    include_str!("../../../sample-dApps/checking_account/checking/plutus.json");
+
+fn blueprint(json: &str, title: &str) -> ValidatorBlueprint {
+    BlueprintFile::from_json(json)
+        .unwrap()
+        .get_validator(title)
+        .unwrap()
+}
+
+#[test]
+fn into_data__keeps_parameter_order() {
+    // Given
+    let params = (1_i64, vec![2_u8], 3_u64);
+
+    // When
+    let data = params.into_data();
+
+    // Then
+    let expected = vec![
+        PlutusData::from(1_i64),
+        PlutusData::BoundedBytes(vec![2]),
+        PlutusData::from(3_u64),
+    ];
+    assert_eq!(data, expected);
+}
+
+#[test]
+fn from_blueprint__rejects_wrong_number_of_parameters() {
+    // Given
+    let blueprint = blueprint(MINT_NFT_BLUEPRINT, "one_shot_nft.mint");
+
+    // When
+    let res = ParameterizedPolicy::<(Vec<u8>, Vec<u8>), ()>::from_blueprint(blueprint);
+
+    // Then
+    let expected = PlutusScriptError::Blueprint(
+        "one_shot_nft.mint takes 1 parameters, not 2".to_string(),
+    );
+    assert_eq!(res.err(), Some(expected));
+}
+
+#[test]
+fn from_blueprint__uses_plutus_version_of_preamble() {
+    // Given
+    let v1_json = MINT_NFT_BLUEPRINT
+        .replace(r#""plutusVersion": "v2""#, r#""plutusVersion": "v1""#);
+    let v1_blueprint = blueprint(&v1_json, "one_shot_nft.mint");
+    let v2_blueprint = blueprint(MINT_NFT_BLUEPRINT, "one_shot_nft.mint");
+
+    // When
+    let v1_script = ParameterizedPolicy::<(Vec<u8>,), ()>::from_blueprint(v1_blueprint);
+    let v2_script = ParameterizedPolicy::<(Vec<u8>,), ()>::from_blueprint(v2_blueprint);
+
+    // Then
+    assert!(matches!(v1_script.unwrap().version, TransactionVersion::V1));
+    assert!(matches!(v2_script.unwrap().version, TransactionVersion::V2));
+}
+
+#[test]
+fn from_blueprint__rejects_plutus_v3() {
+    // Given
+    let v3_json = MINT_NFT_BLUEPRINT
+        .replace(r#""plutusVersion": "v2""#, r#""plutusVersion": "v3""#);
+    let v3_blueprint = blueprint(&v3_json, "one_shot_nft.mint");
+
+    // When
+    let res = ParameterizedPolicy::<(Vec<u8>,), ()>::from_blueprint(v3_blueprint);
+
+    // Then
+    let expected = PlutusScriptError::Blueprint(
+        "Plutus V3 scripts aren't supported yet".to_string(),
+    );
+    assert_eq!(res.err(), Some(expected));
+}
+
+#[test]
+fn apply__matches_applying_one_at_a_time() {
+    // Given
+    let blueprint = blueprint(CHECKING_BLUEPRINT, "spend_token_policy.mint");
+    let nft = vec![1; 28];
+    let owner = vec![2; 28];
+    let two_param: TwoParamMintingPolicy<Vec<u8>, Vec<u8>, ()> =
+        TwoParamMintingPolicy::from_blueprint(blueprint.clone()).unwrap();
+    let parameterized: ParameterizedPolicy<(Vec<u8>, Vec<u8>), ()> =
+        ParameterizedScript::from_blueprint(blueprint).unwrap();
+
+    // When
+    let policy = parameterized.apply((nft.clone(), owner.clone())).unwrap();
+
+    // Then
+    let expected = two_param.apply(nft).unwrap().apply(owner).unwrap();
+    assert_eq!(policy.id().unwrap(), expected.id().unwrap());
+}
+
+#[test]
+fn apply__fails_instead_of_panicking_on_bad_code() {
+    // Given
+    let script: ParameterizedPolicy<(Vec<u8>,), ()> = ParameterizedScript {
+        version: TransactionVersion::V2,
+        cbor: vec![0],
+        blueprint: None,
+        _params: Default::default(),
+        _script: Default::default(),
+    };
+    let script_file = PlutusScriptFile::new("PlutusScriptV2", "", "4100");
+    let one_param: OneParamPlutusPolicy<Vec<u8>, ()> =
+        OneParamPlutusPolicy::new_v2(script_file).unwrap();
+
+    // When
+    let res = script.apply((vec![1],));
+    let one_param_res = one_param.apply(vec![1]);
+
+    // Then
+    assert!(matches!(res, Err(PlutusScriptError::AikenApply(_))));
+    assert!(matches!(
+        one_param_res,
+        Err(PlutusScriptError::AikenApply(_))
+    ));
+}
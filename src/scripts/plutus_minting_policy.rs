@@ -1,7 +1,12 @@
 use crate::{
     scripts::{
-        as_failed_to_execute,
         context::TxContext,
+        evaluation::EvaluationParams,
+        parameterized::{
+            apply_params,
+            check_arity,
+            AppliedScript,
+        },
         plutus_validator::plutus_data::PlutusData,
         raw_script::{
             PlutusScriptError,
@@ -25,26 +30,17 @@ use minicbor::{
     Decoder,
     Encoder,
 };
-use pallas_primitives::babbage::Language;
-use std::{
-    marker::PhantomData,
-    rc::Rc,
-};
-use uplc::{
-    ast::{
-        Constant,
-        FakeNamedDeBruijn,
-        NamedDeBruijn,
-        Program,
-        Term,
-    },
-    machine::cost_model::ExBudget,
-};
+use std::marker::PhantomData;
+
+#[cfg(test)]
+mod tests;
 
 /// Implementation of [`MintingPolicy`] for UPLC Minting Policies
 pub struct PlutusMintingPolicy<Redeemer> {
     version: TransactionVersion,
     cbor: Vec<u8>,
+    evaluation: EvaluationParams,
+    blueprint: Option<ValidatorBlueprint>,
     _redeemer: PhantomData<Redeemer>,
 }
 
@@ -60,6 +56,8 @@ impl<R> PlutusMintingPolicy<R> {
         let v1_policy = PlutusMintingPolicy {
             version: TransactionVersion::V1,
             cbor: outer.to_vec(),
+            evaluation: EvaluationParams::vasil(),
+            blueprint: None,
             _redeemer: Default::default(),
         };
         Ok(v1_policy)
@@ -76,21 +74,29 @@ impl<R> PlutusMintingPolicy<R> {
         let v2_policy = PlutusMintingPolicy {
             version: TransactionVersion::V2,
             cbor: outer.to_vec(),
+            evaluation: EvaluationParams::vasil(),
+            blueprint: None,
             _redeemer: Default::default(),
         };
         Ok(v2_policy)
     }
 
-    /// Constructor for new V2 [`PlutusMintingPolicy`] from a [`ValidatorBlueprint`]
+    /// Constructor for new [`PlutusMintingPolicy`] from a [`ValidatorBlueprint`], in the
+    /// blueprint's Plutus version. Redeemers will be checked against the blueprint's schema.
+    /// Fails if the blueprint still takes parameters, see
+    /// [`ParameterizedPolicy`](crate::scripts::parameterized::ParameterizedPolicy) for those
     pub fn from_blueprint(blueprint: ValidatorBlueprint) -> RawPlutusScriptResult<Self> {
+        check_arity(&blueprint, 0)?;
         let cbor = hex::decode(blueprint.compiled_code())
             .map_err(|e| PlutusScriptError::AikenApply(e.to_string()))?;
-        let v2_policy = PlutusMintingPolicy {
-            version: TransactionVersion::V2,
+        let policy = PlutusMintingPolicy {
+            version: blueprint.version()?,
             cbor,
+            evaluation: EvaluationParams::vasil(),
+            blueprint: Some(blueprint),
             _redeemer: Default::default(),
         };
-        Ok(v2_policy)
+        Ok(policy)
     }
 
     /// Constructor for new V2 [`PlutusMintingPolicy`] from a CBOR hex string
@@ -100,16 +106,42 @@ impl<R> PlutusMintingPolicy<R> {
         let v2_policy = PlutusMintingPolicy {
             version: TransactionVersion::V2,
             cbor,
+            evaluation: EvaluationParams::vasil(),
+            blueprint: None,
             _redeemer: Default::default(),
         };
         Ok(v2_policy)
     }
+
+    /// Evaluate the policy with `evaluation`'s cost models and budget, instead of mainnet's
+    /// at the Vasil hard fork
+    pub fn with_evaluation_params(mut self, evaluation: EvaluationParams) -> Self {
+        self.evaluation = evaluation;
+        self
+    }
+}
+
+impl<R> AppliedScript for PlutusMintingPolicy<R> {
+    fn from_applied(
+        version: TransactionVersion,
+        cbor: Vec<u8>,
+        blueprint: Option<ValidatorBlueprint>,
+    ) -> Self {
+        PlutusMintingPolicy {
+            version,
+            cbor,
+            evaluation: EvaluationParams::vasil(),
+            blueprint,
+            _redeemer: Default::default(),
+        }
+    }
 }
 
 /// Builder for [`PlutusMintingPolicy`] that requires a single parameter
 pub struct OneParamPlutusPolicy<One, Redeemer> {
     version: TransactionVersion,
     cbor: Vec<u8>,
+    blueprint: Option<ValidatorBlueprint>,
     _one: PhantomData<One>,
     _redeemer: PhantomData<Redeemer>,
 }
@@ -126,43 +158,37 @@ impl<One: Into<PlutusData>, R> OneParamPlutusPolicy<One, R> {
         let v2_val = OneParamPlutusPolicy {
             version: TransactionVersion::V2,
             cbor: outer.to_vec(),
+            blueprint: None,
             _one: Default::default(),
             _redeemer: Default::default(),
         };
         Ok(v2_val)
     }
 
-    /// Constructor for new V2 [`OneParamPlutusPolicy`] from a [`ValidatorBlueprint`]
+    /// Constructor for new [`OneParamPlutusPolicy`] from a [`ValidatorBlueprint`], in the
+    /// blueprint's Plutus version
     pub fn from_blueprint(blueprint: ValidatorBlueprint) -> RawPlutusScriptResult<Self> {
+        check_arity(&blueprint, 1)?;
         let cbor = hex::decode(blueprint.compiled_code())
             .map_err(|e| PlutusScriptError::AikenApply(e.to_string()))?;
-        let v2_val = OneParamPlutusPolicy {
-            version: TransactionVersion::V2,
+        let policy = OneParamPlutusPolicy {
+            version: blueprint.version()?,
             cbor,
+            blueprint: Some(blueprint),
             _one: Default::default(),
             _redeemer: Default::default(),
         };
-        Ok(v2_val)
+        Ok(policy)
     }
 
     /// Apply the singular parameter to the policy to get a complete [`PlutusMintingPolicy`]
     pub fn apply(&self, one: One) -> RawPlutusScriptResult<PlutusMintingPolicy<R>> {
-        let program: Program<NamedDeBruijn> =
-            Program::<FakeNamedDeBruijn>::from_cbor(&self.cbor, &mut Vec::new())
-                .unwrap()
-                .into();
-        let one_data: PlutusData = one.into();
-        let one_term = Term::Constant(Rc::new(Constant::Data(one_data.into())));
-        let program = program.apply_term(&one_term);
-        let fake: Program<FakeNamedDeBruijn> = program.into();
-        let new_cbor = fake
-            .to_cbor()
-            .map_err(|e| PlutusScriptError::AikenApply(e.to_string()))?;
-        let policy = PlutusMintingPolicy {
-            version: self.version.clone(),
-            cbor: new_cbor,
-            _redeemer: Default::default(),
-        };
+        let new_cbor = apply_params(&self.cbor, vec![one.into()])?;
+        let policy = PlutusMintingPolicy::from_applied(
+            self.version.clone(),
+            new_cbor,
+            self.blueprint.clone(),
+        );
         Ok(policy)
     }
 }
@@ -171,6 +197,7 @@ impl<One: Into<PlutusData>, R> OneParamPlutusPolicy<One, R> {
 pub struct TwoParamMintingPolicy<One, Two, Redeemer> {
     version: TransactionVersion,
     cbor: Vec<u8>,
+    blueprint: Option<ValidatorBlueprint>,
     _one: PhantomData<One>,
     _two: PhantomData<Two>,
     _redeemer: PhantomData<Redeemer>,
@@ -188,6 +215,7 @@ impl<One: Into<PlutusData>, Two: Into<PlutusData>, R> TwoParamMintingPolicy<One,
         let v2_pol = TwoParamMintingPolicy {
             version: TransactionVersion::V2,
             cbor: outer.to_vec(),
+            blueprint: None,
             _one: Default::default(),
             _two: Default::default(),
             _redeemer: Default::default(),
@@ -195,36 +223,30 @@ impl<One: Into<PlutusData>, Two: Into<PlutusData>, R> TwoParamMintingPolicy<One,
         Ok(v2_pol)
     }
 
-    /// Constructor for new V2 [`TwoParamMintingPolicy`] from a [`ValidatorBlueprint`]
+    /// Constructor for new [`TwoParamMintingPolicy`] from a [`ValidatorBlueprint`], in the
+    /// blueprint's Plutus version
     pub fn from_blueprint(blueprint: ValidatorBlueprint) -> RawPlutusScriptResult<Self> {
+        check_arity(&blueprint, 2)?;
         let cbor = hex::decode(blueprint.compiled_code())
             .map_err(|e| PlutusScriptError::AikenApply(e.to_string()))?;
-        let v2_pol = TwoParamMintingPolicy {
-            version: TransactionVersion::V2,
+        let policy = TwoParamMintingPolicy {
+            version: blueprint.version()?,
             cbor,
+            blueprint: Some(blueprint),
             _one: Default::default(),
             _two: Default::default(),
             _redeemer: Default::default(),
         };
-        Ok(v2_pol)
+        Ok(policy)
     }
 
     /// Apply a single parameter to the policy to get a [`OneParamPlutusPolicy`]
     pub fn apply(&self, one: One) -> RawPlutusScriptResult<OneParamPlutusPolicy<Two, R>> {
-        let program: Program<NamedDeBruijn> =
-            Program::<FakeNamedDeBruijn>::from_cbor(&self.cbor, &mut Vec::new())
-                .unwrap()
-                .into();
-        let one_data: PlutusData = one.into();
-        let one_term = Term::Constant(Rc::new(Constant::Data(one_data.into())));
-        let program = program.apply_term(&one_term);
-        let fake: Program<FakeNamedDeBruijn> = program.into();
-        let new_cbor = fake
-            .to_cbor()
-            .map_err(|e| PlutusScriptError::AikenApply(e.to_string()))?;
+        let new_cbor = apply_params(&self.cbor, vec![one.into()])?;
         let policy = OneParamPlutusPolicy {
             version: self.version.clone(),
             cbor: new_cbor,
+            blueprint: self.blueprint.clone(),
             _one: Default::default(),
             _redeemer: Default::default(),
         };
@@ -234,33 +256,12 @@ impl<One: Into<PlutusData>, Two: Into<PlutusData>, R> TwoParamMintingPolicy<One,
 
 impl<Redeemer> MintingPolicy<Redeemer> for PlutusMintingPolicy<Redeemer>
 where
-    Redeemer: Into<PlutusData> + Send + Sync,
+    Redeemer: Into<PlutusData> + Clone + Send + Sync,
 {
     fn execute(&self, redeemer: Redeemer, ctx: TxContext) -> ScriptResult<ExecutionCost> {
-        let program: Program<NamedDeBruijn> =
-            Program::<FakeNamedDeBruijn>::from_cbor(&self.cbor, &mut Vec::new())
-                .map_err(as_failed_to_execute)?
-                .into();
-        let redeemer_data: PlutusData = redeemer.into();
-        let redeemer_term = Term::Constant(Rc::new(Constant::Data(redeemer_data.into())));
-        let program = program.apply_term(&redeemer_term);
-        let ctx_data: PlutusData = ctx.into();
-        let ctx_term = Term::Constant(Rc::new(Constant::Data(ctx_data.into())));
-        let program = program.apply_term(&ctx_term);
-        let mut eval_result = match self.version {
-            TransactionVersion::V1 => program.eval_version(&Language::PlutusV1),
-            TransactionVersion::V2 => program.eval(ExBudget::default()), /* TODO: parameterize */
-        };
-        let logs = eval_result.logs();
-        let cost = eval_result.cost();
-        eval_result
-            .result()
-            .map_err(|e| PlutusScriptError::AikenEval {
-                error: format!("{e:?}"),
-                logs,
-            })
-            .map_err(as_failed_to_execute)?;
-        Ok(cost.into())
+        let arguments = vec![redeemer.into(), ctx.into()];
+        self.evaluation
+            .evaluate(&self.cbor, &self.version, arguments)
     }
 
     fn id(&self) -> ScriptResult<String> {
@@ -294,4 +295,17 @@ where
         let hex = hex::encode(wrap);
         Ok(hex)
     }
+
+    fn check_redeemer(&self, redeemer: &Redeemer) -> ScriptResult<()> {
+        match &self.blueprint {
+            Some(blueprint) => blueprint
+                .validate_redeemer(&redeemer.clone().into())
+                .map_err(|e| ScriptError::RedeemerSchema(e.to_string())),
+            None => Ok(()),
+        }
+    }
+
+    fn redeemer_data(&self, redeemer: &Redeemer) -> Option<PlutusData> {
+        Some(redeemer.clone().into())
+    }
 }
@@ -0,0 +1,55 @@
+use super::*;
+use crate::scripts::{
+    plutus_validator::plutus_data::Constr,
+    raw_script::BlueprintFile,
+};
+
+#[test]
+fn check_redeemer_uses_blueprint_schema() {
+    let blueprint = BlueprintFile::from_json(include_str!(
+        "../../../nau-scripts/aiken/mint_nft/plutus.json"
+    ))
+    .unwrap();
+    let policy_blueprint = blueprint.get_validator("one_shot_nft.mint").unwrap();
+    let one_param: OneParamPlutusPolicy<PlutusData, PlutusData> =
+        OneParamPlutusPolicy::from_blueprint(policy_blueprint).unwrap();
+    let input_ref = PlutusData::Constr(Constr {
+        constr: 0,
+        fields: vec![
+            PlutusData::Constr(Constr {
+                constr: 0,
+                fields: vec![PlutusData::BoundedBytes(vec![1; 32])],
+            }),
+            PlutusData::BigInt(0.into()),
+        ],
+    });
+    let policy = one_param.apply(input_ref).unwrap();
+
+    let unit = PlutusData::Constr(Constr {
+        constr: 0,
+        fields: vec![],
+    });
+
+    policy.check_redeemer(&unit).unwrap();
+    assert_eq!(
+        policy.check_redeemer(&PlutusData::BigInt(1.into())),
+        Err(ScriptError::RedeemerSchema(
+            "expected constructor 0, found integer".to_string()
+        ))
+    );
+}
+
+#[test]
+fn check_redeemer_accepts_anything_without_blueprint() {
+    let script_file = PlutusScriptFile {
+        r#type: "PlutusScriptV1".to_string(),
+        description: "".to_string(),
+        cborHex: "4e4d01000033222220051200120011".to_string(),
+    };
+    let policy: PlutusMintingPolicy<PlutusData> =
+        PlutusMintingPolicy::new_v1(script_file).unwrap();
+
+    policy
+        .check_redeemer(&PlutusData::BigInt(1.into()))
+        .unwrap();
+}
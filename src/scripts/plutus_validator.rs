@@ -1,7 +1,12 @@
 use crate::{
     scripts::{
-        as_failed_to_execute,
         context::TxContext,
+        evaluation::EvaluationParams,
+        parameterized::{
+            apply_params,
+            check_arity,
+            AppliedScript,
+        },
         plutus_validator::plutus_data::{
             BigInt,
             Constr,
@@ -40,7 +45,6 @@ use pallas_addresses::{
     Address,
     Network,
 };
-use pallas_primitives::babbage::Language;
 use std::{
     marker::PhantomData,
     rc::Rc,
@@ -48,15 +52,11 @@ use std::{
 use uplc::{
     ast::{
         Constant,
-        FakeNamedDeBruijn,
         NamedDeBruijn,
         Program,
         Term,
     },
-    machine::{
-        cost_model::ExBudget,
-        runtime::convert_constr_to_tag,
-    },
+    machine::runtime::convert_constr_to_tag,
     BigInt as AikenBigInt,
     Constr as AikenConstr,
     PlutusData as AikenPlutusData,
@@ -73,6 +73,8 @@ mod tests;
 pub struct PlutusValidator<Datum, Redeemer> {
     version: TransactionVersion,
     cbor: Vec<u8>,
+    evaluation: EvaluationParams,
+    blueprint: Option<ValidatorBlueprint>,
     _datum: PhantomData<Datum>,
     _redeemer: PhantomData<Redeemer>,
 }
@@ -89,6 +91,8 @@ impl<D, R> PlutusValidator<D, R> {
         let v1_policy = PlutusValidator {
             version: TransactionVersion::V1,
             cbor: outer.to_vec(),
+            evaluation: EvaluationParams::vasil(),
+            blueprint: None,
             _datum: Default::default(),
             _redeemer: Default::default(),
         };
@@ -106,23 +110,31 @@ impl<D, R> PlutusValidator<D, R> {
         let v2_policy = PlutusValidator {
             version: TransactionVersion::V2,
             cbor: outer.to_vec(),
+            evaluation: EvaluationParams::vasil(),
+            blueprint: None,
             _datum: Default::default(),
             _redeemer: Default::default(),
         };
         Ok(v2_policy)
     }
 
-    /// Create a new V2 `PlutusValidator` from a [`ValidatorBlueprint`] based on CIP-0057
+    /// Create a new `PlutusValidator` from a [`ValidatorBlueprint`] based on CIP-0057, in the
+    /// blueprint's Plutus version. Datums and redeemers will be checked against the
+    /// blueprint's schemas. Fails if the blueprint still takes parameters, see
+    /// [`ParameterizedValidator`](crate::scripts::parameterized::ParameterizedValidator) for those
     pub fn from_blueprint(blueprint: ValidatorBlueprint) -> RawPlutusScriptResult<Self> {
+        check_arity(&blueprint, 0)?;
         let cbor = hex::decode(blueprint.compiled_code())
             .map_err(|e| PlutusScriptError::AikenApply(e.to_string()))?;
-        let v2_policy = PlutusValidator {
-            version: TransactionVersion::V2,
+        let validator = PlutusValidator {
+            version: blueprint.version()?,
             cbor,
+            evaluation: EvaluationParams::vasil(),
+            blueprint: Some(blueprint),
             _datum: Default::default(),
             _redeemer: Default::default(),
         };
-        Ok(v2_policy)
+        Ok(validator)
     }
 
     /// Create a new V2 `PlutusValidator` from a CBOR string
@@ -132,17 +144,44 @@ impl<D, R> PlutusValidator<D, R> {
         let v2_policy = PlutusValidator {
             version: TransactionVersion::V2,
             cbor,
+            evaluation: EvaluationParams::vasil(),
+            blueprint: None,
             _datum: Default::default(),
             _redeemer: Default::default(),
         };
         Ok(v2_policy)
     }
+
+    /// Evaluate the validator with `evaluation`'s cost models and budget, instead of
+    /// mainnet's at the Vasil hard fork
+    pub fn with_evaluation_params(mut self, evaluation: EvaluationParams) -> Self {
+        self.evaluation = evaluation;
+        self
+    }
+}
+
+impl<D, R> AppliedScript for PlutusValidator<D, R> {
+    fn from_applied(
+        version: TransactionVersion,
+        cbor: Vec<u8>,
+        blueprint: Option<ValidatorBlueprint>,
+    ) -> Self {
+        PlutusValidator {
+            version,
+            cbor,
+            evaluation: EvaluationParams::vasil(),
+            blueprint,
+            _datum: Default::default(),
+            _redeemer: Default::default(),
+        }
+    }
 }
 
 /// A builder for a `PlutusValidator` that takes one parameter
 pub struct OneParamRawValidator<One, Datum, Redeemer> {
     version: TransactionVersion,
     cbor: Vec<u8>,
+    blueprint: Option<ValidatorBlueprint>,
     _one: PhantomData<One>,
     _datum: PhantomData<Datum>,
     _redeemer: PhantomData<Redeemer>,
@@ -160,6 +199,7 @@ impl<One: Into<PlutusData>, D, R> OneParamRawValidator<One, D, R> {
         let v2_val = OneParamRawValidator {
             version: TransactionVersion::V2,
             cbor: outer.to_vec(),
+            blueprint: None,
             _one: Default::default(),
             _datum: Default::default(),
             _redeemer: Default::default(),
@@ -167,39 +207,31 @@ impl<One: Into<PlutusData>, D, R> OneParamRawValidator<One, D, R> {
         Ok(v2_val)
     }
 
-    /// Create a new V2 `OneParamRawValidator` from a [`ValidatorBlueprint`] based on CIP-0057
+    /// Create a new `OneParamRawValidator` from a [`ValidatorBlueprint`] based on CIP-0057, in
+    /// the blueprint's Plutus version
     pub fn from_blueprint(blueprint: ValidatorBlueprint) -> RawPlutusScriptResult<Self> {
+        check_arity(&blueprint, 1)?;
         let cbor = hex::decode(blueprint.compiled_code())
             .map_err(|e| PlutusScriptError::AikenApply(e.to_string()))?;
-        let v2_val = OneParamRawValidator {
-            version: TransactionVersion::V2,
+        let validator = OneParamRawValidator {
+            version: blueprint.version()?,
             cbor,
+            blueprint: Some(blueprint),
             _one: Default::default(),
             _datum: Default::default(),
             _redeemer: Default::default(),
         };
-        Ok(v2_val)
+        Ok(validator)
     }
 
     /// Apply the parameter to the validator to get a full [`PlutusValidator`]
     pub fn apply(&self, one: One) -> RawPlutusScriptResult<PlutusValidator<D, R>> {
-        let program: Program<NamedDeBruijn> =
-            Program::<FakeNamedDeBruijn>::from_cbor(&self.cbor, &mut Vec::new())
-                .unwrap()
-                .into();
-        let one_data: PlutusData = one.into();
-        let one_term = Term::Constant(Rc::new(Constant::Data(one_data.into())));
-        let program = program.apply_term(&one_term);
-        let fake: Program<FakeNamedDeBruijn> = program.into();
-        let new_cbor = fake
-            .to_cbor()
-            .map_err(|e| PlutusScriptError::AikenApply(e.to_string()))?;
-        let policy = PlutusValidator {
-            version: self.version.clone(),
-            cbor: new_cbor,
-            _datum: Default::default(),
-            _redeemer: Default::default(),
-        };
+        let new_cbor = apply_params(&self.cbor, vec![one.into()])?;
+        let policy = PlutusValidator::from_applied(
+            self.version.clone(),
+            new_cbor,
+            self.blueprint.clone(),
+        );
         Ok(policy)
     }
 }
@@ -260,8 +292,8 @@ impl From<BigInt> for AikenBigInt {
 
 impl<Datum, Redeemer> Validator<Datum, Redeemer> for PlutusValidator<Datum, Redeemer>
 where
-    Datum: Into<PlutusData> + Send + Sync,
-    Redeemer: Into<PlutusData> + Send + Sync,
+    Datum: Into<PlutusData> + Clone + Send + Sync,
+    Redeemer: Into<PlutusData> + Clone + Send + Sync,
 {
     fn execute(
         &self,
@@ -269,34 +301,9 @@ where
         redeemer: Redeemer,
         ctx: TxContext,
     ) -> ScriptResult<ExecutionCost> {
-        let program: Program<NamedDeBruijn> =
-            Program::<FakeNamedDeBruijn>::from_cbor(&self.cbor, &mut Vec::new())
-                .map_err(as_failed_to_execute)?
-                .into();
-        let datum_data: PlutusData = datum.into();
-        let aiken_datum_data: uplc::PlutusData = datum_data.into();
-        let datum_term = Term::Constant(Rc::new(Constant::Data(aiken_datum_data)));
-        let program = program.apply_term(&datum_term);
-        let redeemer_data: PlutusData = redeemer.into();
-        let redeemer_term = Term::Constant(Rc::new(Constant::Data(redeemer_data.into())));
-        let program = program.apply_term(&redeemer_term);
-        let ctx_data: PlutusData = ctx.into();
-        let ctx_term = Term::Constant(Rc::new(Constant::Data(ctx_data.into())));
-        let program = program.apply_term(&ctx_term);
-        let mut eval_result = match self.version {
-            TransactionVersion::V1 => program.eval_version(&Language::PlutusV1),
-            TransactionVersion::V2 => program.eval(ExBudget::default()), /* TODO: parameterize */
-        };
-        let logs = eval_result.logs();
-        let cost = eval_result.cost();
-        eval_result
-            .result()
-            .map_err(|e| PlutusScriptError::AikenEval {
-                error: format!("{e:?}"),
-                logs,
-            })
-            .map_err(as_failed_to_execute)?;
-        Ok(cost.into())
+        let arguments = vec![datum.into(), redeemer.into(), ctx.into()];
+        self.evaluation
+            .evaluate(&self.cbor, &self.version, arguments)
     }
 
     // TODO: Stop using CML
@@ -345,4 +352,37 @@ where
         let hex = hex::encode(wrap);
         Ok(hex)
     }
+
+    fn check_datum(&self, datum: &Datum) -> ScriptResult<()> {
+        match &self.blueprint {
+            Some(blueprint) => blueprint
+                .validate_datum(&datum.clone().into())
+                .map_err(|e| ScriptError::DatumSchema(e.to_string())),
+            None => Ok(()),
+        }
+    }
+
+    fn check_redeemer(&self, redeemer: &Redeemer) -> ScriptResult<()> {
+        match &self.blueprint {
+            Some(blueprint) => blueprint
+                .validate_redeemer(&redeemer.clone().into())
+                .map_err(|e| ScriptError::RedeemerSchema(e.to_string())),
+            None => Ok(()),
+        }
+    }
+
+    fn redeemer_data(&self, redeemer: &Redeemer) -> Option<PlutusData> {
+        Some(redeemer.clone().into())
+    }
+}
+
+/// Apply each argument to the program, in order
+pub(crate) fn apply_data(
+    program: Program<NamedDeBruijn>,
+    arguments: Vec<PlutusData>,
+) -> Program<NamedDeBruijn> {
+    arguments.into_iter().fold(program, |program, argument| {
+        let term = Term::Constant(Rc::new(Constant::Data(argument.into())));
+        program.apply_term(&term)
+    })
 }
@@ -1,9 +1,13 @@
 use crate::scripts::{
     context::{
+        CtxCertificate,
+        CtxCredential,
         CtxDatum,
+        CtxMint,
         CtxOutput,
         CtxOutputReference,
         CtxScriptPurpose,
+        CtxStakingCredential,
         CtxValue,
         Input,
         PubKeyHash,
@@ -12,7 +16,6 @@ use crate::scripts::{
     },
     ScriptError,
 };
-use cardano_multiplatform_lib::ledger::common::hash::hash_plutus_data;
 use pallas_addresses::{
     Address,
     ShelleyDelegationPart,
@@ -22,7 +25,22 @@ use serde::{
     Deserialize,
     Serialize,
 };
-use std::collections::BTreeMap;
+use std::collections::{
+    BTreeMap,
+    HashMap,
+};
+
+pub use naumachia_derive::{
+    FromPlutusData,
+    ToPlutusData,
+};
+
+/// CBOR encoding of [`PlutusData`], which datum hashes are computed over
+pub mod cbor;
+/// cardano-cli's detailed schema JSON encoding of [`PlutusData`]
+pub mod json;
+/// Human-readable rendering of [`PlutusData`], and structural diffs between values
+pub mod pretty;
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Serialize, Deserialize)]
 pub enum PlutusData {
@@ -33,26 +51,6 @@ pub enum PlutusData {
     Array(Vec<PlutusData>),
 }
 
-impl PlutusData {
-    /// Hash the PlutusData with blake2b256
-    pub fn hash(&self) -> Vec<u8> {
-        // TODO: I'd prefer not to be taking a dep on CML here
-        use crate::trireme_ledger_client::cml_client::plutus_data_interop::PlutusDataInterop;
-
-        let cml_data = self.to_plutus_data();
-        hash_plutus_data(&cml_data).to_bytes().to_vec()
-    }
-
-    /// Raw bytes of PlutusData
-    pub fn bytes(&self) -> Vec<u8> {
-        // TODO: I'd prefer not to be taking a dep on CML here
-        use crate::trireme_ledger_client::cml_client::plutus_data_interop::PlutusDataInterop;
-
-        let cml_data = self.to_plutus_data();
-        cml_data.to_bytes()
-    }
-}
-
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Serialize, Deserialize)]
 pub struct Constr<T> {
     pub constr: u64,
@@ -127,44 +125,40 @@ impl TryFrom<PlutusData> for i64 {
     }
 }
 
-// TODO: Don't hardcode values!
 // TODO: THIS IS V2 only right now! Add V1!
 impl From<TxContext> for PlutusData {
     fn from(ctx: TxContext) -> Self {
         let inputs = PlutusData::Array(ctx.inputs.into_iter().map(Into::into).collect());
-        let reference_inputs = PlutusData::Array(vec![]);
+        let reference_inputs =
+            PlutusData::Array(ctx.reference_inputs.into_iter().map(Into::into).collect());
         let outputs =
             PlutusData::Array(ctx.outputs.into_iter().map(Into::into).collect());
-        let fee = PlutusData::Map(BTreeMap::from([(
-            PlutusData::BoundedBytes(Vec::new()),
-            PlutusData::Map(BTreeMap::from([(
-                PlutusData::BoundedBytes(Vec::new()),
-                PlutusData::BigInt(999_i64.into()),
-            )])),
-        )]));
-        let mint = PlutusData::Map(BTreeMap::from([(
-            PlutusData::BoundedBytes(Vec::new()),
-            PlutusData::Map(BTreeMap::from([(
-                PlutusData::BoundedBytes(Vec::new()),
-                PlutusData::BigInt(0_i64.into()),
-            )])),
-        )]));
-        let dcert = PlutusData::Array(vec![]);
-        let wdrl = PlutusData::Map(BTreeMap::new());
+        let fee = lovelace_value(ctx.fee as i64);
+        let mint = ctx.mint.into();
+        let dcert =
+            PlutusData::Array(ctx.certificates.into_iter().map(Into::into).collect());
+        let wdrl = PlutusData::Map(
+            ctx.withdrawals
+                .into_iter()
+                .map(|(credential, amount)| (credential.into(), amount.into()))
+                .collect(),
+        );
         let valid_range = ctx.range.into();
-        let mut signers: Vec<_> =
-            ctx.extra_signatories.into_iter().map(Into::into).collect();
-        signers.push(ctx.signer.into());
-        let signatories = PlutusData::Array(signers);
-        let redeemers = PlutusData::Map(BTreeMap::new());
+        let signatories =
+            PlutusData::Array(ctx.signatories().into_iter().map(Into::into).collect());
+        let redeemers = PlutusData::Map(
+            ctx.redeemers
+                .into_iter()
+                .map(|(purpose, redeemer)| (purpose.into(), redeemer))
+                .collect(),
+        );
         let data = PlutusData::Map(
             ctx.datums
                 .into_iter()
                 .map(|(hash, data)| (PlutusData::BoundedBytes(hash), data))
                 .collect(),
         );
-        // TODO this id should be computed!
-        let id = wrap_with_constr(0, PlutusData::BoundedBytes(Vec::new()));
+        let id = wrap_with_constr(0, PlutusData::BoundedBytes(ctx.id));
         let tx_info = PlutusData::Constr(Constr {
             constr: 0,
             fields: vec![
@@ -182,8 +176,18 @@ impl From<TxContext> for PlutusData {
                 id,
             ],
         });
-        // Spending
-        let purpose = match ctx.purpose {
+        let purpose = ctx.purpose.into();
+
+        PlutusData::Constr(Constr {
+            constr: 0,
+            fields: vec![tx_info, purpose],
+        })
+    }
+}
+
+impl From<CtxScriptPurpose> for PlutusData {
+    fn from(purpose: CtxScriptPurpose) -> Self {
+        match purpose {
             CtxScriptPurpose::Mint(policy_id) => {
                 let policy_id_data = PlutusData::BoundedBytes(policy_id);
                 wrap_with_constr(0, policy_id_data)
@@ -192,15 +196,68 @@ impl From<TxContext> for PlutusData {
                 let out_ref_data = out_ref.into();
                 wrap_with_constr(1, out_ref_data)
             }
-            _ => {
-                todo!()
+            CtxScriptPurpose::WithdrawFrom(credential) => {
+                wrap_with_constr(2, credential.into())
+            }
+            CtxScriptPurpose::Publish(certificate) => {
+                wrap_with_constr(3, certificate.into())
+            }
+        }
+    }
+}
+
+impl From<CtxCredential> for PlutusData {
+    fn from(credential: CtxCredential) -> Self {
+        match credential {
+            CtxCredential::PubKey(hash) => {
+                wrap_with_constr(0, PlutusData::BoundedBytes(hash))
+            }
+            CtxCredential::Script(hash) => {
+                wrap_with_constr(1, PlutusData::BoundedBytes(hash))
             }
-        };
+        }
+    }
+}
 
-        PlutusData::Constr(Constr {
-            constr: 0,
-            fields: vec![tx_info, purpose],
-        })
+impl From<CtxStakingCredential> for PlutusData {
+    fn from(credential: CtxStakingCredential) -> Self {
+        match credential {
+            CtxStakingCredential::Hash(credential) => {
+                wrap_with_constr(0, credential.into())
+            }
+            CtxStakingCredential::Pointer {
+                slot,
+                tx_index,
+                cert_index,
+            } => wrap_multiple_with_constr(
+                1,
+                vec![slot.into(), tx_index.into(), cert_index.into()],
+            ),
+        }
+    }
+}
+
+impl From<CtxCertificate> for PlutusData {
+    fn from(certificate: CtxCertificate) -> Self {
+        match certificate {
+            CtxCertificate::RegisterStake(credential) => {
+                wrap_with_constr(0, credential.into())
+            }
+            CtxCertificate::DeregisterStake(credential) => {
+                wrap_with_constr(1, credential.into())
+            }
+            CtxCertificate::Delegate { delegator, pool } => {
+                wrap_multiple_with_constr(2, vec![delegator.into(), pool.into()])
+            }
+            CtxCertificate::RegisterPool { pool, vrf } => {
+                wrap_multiple_with_constr(3, vec![pool.into(), vrf.into()])
+            }
+            CtxCertificate::RetirePool { pool, epoch } => {
+                wrap_multiple_with_constr(4, vec![pool.into(), epoch.into()])
+            }
+            CtxCertificate::Genesis => empty_constr(5),
+            CtxCertificate::Mir => empty_constr(6),
+        }
     }
 }
 
@@ -210,6 +267,15 @@ impl From<PubKeyHash> for PlutusData {
     }
 }
 
+impl TryFrom<PlutusData> for PubKeyHash {
+    type Error = ScriptError;
+
+    fn try_from(data: PlutusData) -> Result<Self, Self::Error> {
+        let bytes: Vec<u8> = data.try_into()?;
+        Ok(PubKeyHash::new(&bytes))
+    }
+}
+
 impl From<Address> for PlutusData {
     fn from(value: Address) -> Self {
         match value {
@@ -292,99 +358,21 @@ fn constr_index(index: u64) -> u64 {
 
 impl From<ValidRange> for PlutusData {
     fn from(value: ValidRange) -> Self {
-        match (value.lower, value.upper) {
-            (None, None) => no_time_bound(),
-            (Some((bound, is_inclusive)), None) => lower_bound(bound, is_inclusive),
-            (None, Some(_)) => todo!(),
-            (Some(_), Some(_)) => todo!(),
-        }
+        // NegInf
+        let lower = interval_bound(value.lower, 0);
+        // PosInf
+        let upper = interval_bound(value.upper, 2);
+        wrap_multiple_with_constr(0, vec![lower, upper])
     }
 }
 
-fn no_time_bound() -> PlutusData {
-    PlutusData::Constr(Constr {
-        constr: 0,
-        fields: vec![
-            PlutusData::Constr(Constr {
-                constr: 0,
-                fields: vec![
-                    // NegInf
-                    PlutusData::Constr(Constr {
-                        constr: 0,
-                        fields: vec![],
-                    }),
-                    // Closure
-                    PlutusData::Constr(Constr {
-                        constr: 1,
-                        fields: vec![],
-                    }),
-                ],
-            }),
-            PlutusData::Constr(Constr {
-                constr: 0,
-                fields: vec![
-                    // PosInf
-                    PlutusData::Constr(Constr {
-                        constr: 2,
-                        fields: vec![],
-                    }),
-                    // Closure
-                    PlutusData::Constr(Constr {
-                        constr: 1,
-                        fields: vec![],
-                    }),
-                ],
-            }),
-        ],
-    })
-}
-
-fn lower_bound(bound: i64, is_inclusive: bool) -> PlutusData {
-    let closure = if is_inclusive {
-        // True
-        PlutusData::Constr(Constr {
-            constr: 1,
-            fields: vec![],
-        })
-    } else {
-        // False
-        PlutusData::Constr(Constr {
-            constr: 0,
-            fields: vec![],
-        })
+/// A `Finite` bound and its closure, or the infinity at `unbounded_index`, which is closed
+fn interval_bound(bound: Option<(i64, bool)>, unbounded_index: u64) -> PlutusData {
+    let (extended, is_inclusive) = match bound {
+        Some((time, is_inclusive)) => (wrap_with_constr(1, time.into()), is_inclusive),
+        None => (empty_constr(unbounded_index), true),
     };
-    PlutusData::Constr(Constr {
-        constr: 0,
-        fields: vec![
-            PlutusData::Constr(Constr {
-                constr: 0,
-                fields: vec![
-                    // Finite
-                    PlutusData::Constr(Constr {
-                        constr: 1,
-                        fields: vec![PlutusData::BigInt(bound.into())],
-                    }),
-                    // Closure
-                    closure,
-                ],
-            }),
-            PlutusData::Constr(Constr {
-                constr: 0,
-                fields: vec![
-                    // PosInf
-                    PlutusData::Constr(Constr {
-                        constr: 2,
-                        fields: vec![],
-                    }),
-                    // Closure
-                    PlutusData::Constr(Constr {
-                        constr: 1,
-                        fields: vec![],
-                    }),
-                ],
-            }),
-        ],
-    })
+    wrap_multiple_with_constr(0, vec![extended, is_inclusive.into()])
 }
 
 impl From<Input> for PlutusData {
@@ -435,26 +423,54 @@ impl From<CtxOutput> for PlutusData {
 
 impl From<CtxValue> for PlutusData {
     fn from(value: CtxValue) -> Self {
-        let converted_inner = value
-            .inner
-            .iter()
-            .map(|(p, a)| {
-                let policy_id = PlutusData::BoundedBytes(hex::decode(p).unwrap()); // TODO
-                let assets = a
-                    .iter()
-                    .map(|(an, amt)| {
-                        let asset_name = PlutusData::BoundedBytes(an.as_bytes().to_vec()); // TODO: Should this be bytes? or hex decoded?
-                        let amount = PlutusData::BigInt((*amt as i64).into()); // TODO
-                        (asset_name, amount)
-                    })
-                    .collect();
-                (policy_id, PlutusData::Map(assets))
-            })
-            .collect();
-        PlutusData::Map(converted_inner)
+        multi_asset_data(&value.inner)
+    }
+}
+
+// The V1 and V2 ledgers always include an Ada entry in the mint, even though Ada can't be minted
+impl From<CtxMint> for PlutusData {
+    fn from(value: CtxMint) -> Self {
+        let mut inner = value.inner;
+        inner
+            .entry(String::new())
+            .or_default()
+            .entry(String::new())
+            .or_insert(0);
+        multi_asset_data(&inner)
     }
 }
 
+fn multi_asset_data<Amount: Copy + Into<PlutusData>>(
+    inner: &HashMap<String, HashMap<String, Amount>>,
+) -> PlutusData {
+    let converted_inner = inner
+        .iter()
+        .map(|(p, a)| {
+            let policy_id = PlutusData::BoundedBytes(hex::decode(p).unwrap()); // TODO
+            let assets = a
+                .iter()
+                .map(|(an, amt)| {
+                    let asset_name = PlutusData::BoundedBytes(an.as_bytes().to_vec()); // TODO: Should this be bytes? or hex decoded?
+                    let amount: PlutusData = (*amt).into(); // TODO
+                    (asset_name, amount)
+                })
+                .collect();
+            (policy_id, PlutusData::Map(assets))
+        })
+        .collect();
+    PlutusData::Map(converted_inner)
+}
+
+fn lovelace_value(amount: i64) -> PlutusData {
+    PlutusData::Map(BTreeMap::from([(
+        PlutusData::BoundedBytes(Vec::new()),
+        PlutusData::Map(BTreeMap::from([(
+            PlutusData::BoundedBytes(Vec::new()),
+            PlutusData::BigInt(amount.into()),
+        )])),
+    )]))
+}
+
 impl From<CtxDatum> for PlutusData {
     fn from(value: CtxDatum) -> Self {
         match value {
@@ -491,12 +507,49 @@ impl<T: Into<PlutusData>> From<Option<T>> for PlutusData {
     }
 }
 
+// False and True are the first and second constructors of Aiken's and PlutusTx's `Bool`
+impl From<bool> for PlutusData {
+    fn from(value: bool) -> Self {
+        PlutusData::Constr(Constr {
+            constr: value as u64,
+            fields: vec![],
+        })
+    }
+}
+
+impl TryFrom<PlutusData> for bool {
+    type Error = ScriptError;
+
+    fn try_from(data: PlutusData) -> Result<Self, Self::Error> {
+        match data {
+            PlutusData::Constr(Constr { constr: 0, fields }) if fields.is_empty() => {
+                Ok(false)
+            }
+            PlutusData::Constr(Constr { constr: 1, fields }) if fields.is_empty() => {
+                Ok(true)
+            }
+            _ => Err(ScriptError::DatumDeserialization(format!("{data:?}"))),
+        }
+    }
+}
+
 impl From<Vec<u8>> for PlutusData {
     fn from(value: Vec<u8>) -> Self {
         PlutusData::BoundedBytes(value)
     }
 }
 
+impl TryFrom<PlutusData> for Vec<u8> {
+    type Error = ScriptError;
+
+    fn try_from(data: PlutusData) -> Result<Self, Self::Error> {
+        match data {
+            PlutusData::BoundedBytes(bytes) => Ok(bytes),
+            _ => Err(ScriptError::DatumDeserialization(format!("{data:?}"))),
+        }
+    }
+}
+
 impl From<()> for PlutusData {
     fn from(_: ()) -> Self {
         PlutusData::Constr(Constr {
@@ -515,3 +568,14 @@ impl From<u64> for PlutusData {
         PlutusData::BigInt((value as i64).into()) // TODO: unwrap
     }
 }
+
+impl TryFrom<PlutusData> for u64 {
+    type Error = ScriptError;
+
+    fn try_from(data: PlutusData) -> Result<Self, Self::Error> {
+        match data {
+            PlutusData::BigInt(BigInt::Int { neg: false, val }) => Ok(val),
+            _ => Err(ScriptError::DatumDeserialization(format!("{data:?}"))),
+        }
+    }
+}
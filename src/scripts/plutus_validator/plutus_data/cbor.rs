@@ -0,0 +1,293 @@
+use super::{
+    BigInt,
+    Constr,
+    PlutusData,
+};
+use blake2::{
+    digest::consts::U32,
+    Blake2b,
+    Digest,
+};
+use minicbor::{
+    data::{
+        Int,
+        Tag,
+        Type,
+    },
+    decode,
+    encode,
+    Decode,
+    Decoder,
+    Encoder,
+};
+use thiserror::Error;
+
+#[cfg(test)]
+mod tests;
+
+/// Longest byte string the ledger allows in Plutus data. Longer ones are split into chunks
+const BOUNDED_BYTES_CHUNK: usize = 64;
+/// Tag of constructors 0 to 6
+const SMALL_CONSTR_TAG: u64 = 121;
+/// Tag of constructors 7 to 127
+const MEDIUM_CONSTR_TAG: u64 = 1280;
+/// Tag of any other constructor, which is encoded along with its fields
+const ANY_CONSTR_TAG: u64 = 102;
+
+/// How [`PlutusData`] lays out lists and maps in CBOR
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CborEncoding {
+    /// How the ledger and Plutus encode data: non-empty lists, including constructor fields, have
+    /// indefinite length. Datum hashes are computed over this encoding
+    #[default]
+    Indefinite,
+    /// Definite lengths for all lists, and map keys in canonical CBOR order. Byte strings longer
+    /// than 64 bytes are still chunked, since the ledger doesn't accept them otherwise
+    Canonical,
+}
+
+/// Error from decoding [`PlutusData`] from CBOR
+#[allow(missing_docs)]
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum CborError {
+    #[error("Failed to decode PlutusData: {0}")]
+    Decode(String),
+    #[error("Unexpected bytes after PlutusData at position {0}")]
+    TrailingBytes(usize),
+}
+
+impl PlutusData {
+    /// Encode as CBOR
+    pub fn to_cbor(&self, encoding: CborEncoding) -> Vec<u8> {
+        let mut encoder = Encoder::new(Vec::new());
+        encode_data(self, encoding, &mut encoder).expect("Writing to a Vec can't fail");
+        encoder.into_writer()
+    }
+
+    /// Decode from CBOR in any of the [`CborEncoding`]s, or any other valid layout
+    pub fn from_cbor(bytes: &[u8]) -> Result<Self, CborError> {
+        let mut decoder = Decoder::new(bytes);
+        let data = decoder
+            .decode()
+            .map_err(|e: decode::Error| CborError::Decode(e.to_string()))?;
+        if decoder.position() != bytes.len() {
+            return Err(CborError::TrailingBytes(decoder.position()))
+        }
+        Ok(data)
+    }
+
+    /// Hash the PlutusData with blake2b256
+    pub fn hash(&self) -> Vec<u8> {
+        Blake2b::<U32>::digest(self.bytes()).to_vec()
+    }
+
+    /// Raw bytes of PlutusData, in the encoding the ledger uses
+    pub fn bytes(&self) -> Vec<u8> {
+        self.to_cbor(CborEncoding::Indefinite)
+    }
+}
+
+type EncodeResult = Result<(), encode::Error<std::convert::Infallible>>;
+
+fn encode_data(
+    data: &PlutusData,
+    encoding: CborEncoding,
+    e: &mut Encoder<Vec<u8>>,
+) -> EncodeResult {
+    match data {
+        PlutusData::Constr(constr) => encode_constr(constr, encoding, e),
+        PlutusData::Map(map) => {
+            e.map(map.len() as u64)?;
+            match encoding {
+                CborEncoding::Indefinite => {
+                    for (key, value) in map {
+                        encode_data(key, encoding, e)?;
+                        encode_data(value, encoding, e)?;
+                    }
+                }
+                CborEncoding::Canonical => {
+                    // Canonical CBOR orders keys by their encoding: shorter first, then bytewise
+                    let mut entries: Vec<_> = map
+                        .iter()
+                        .map(|(key, value)| (key.to_cbor(encoding), value))
+                        .collect();
+                    entries.sort_by(|(a, _), (b, _)| {
+                        a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+                    });
+                    for (key, value) in entries {
+                        e.writer_mut().extend_from_slice(&key);
+                        encode_data(value, encoding, e)?;
+                    }
+                }
+            }
+            Ok(())
+        }
+        PlutusData::BigInt(big_int) => encode_big_int(big_int, e),
+        PlutusData::BoundedBytes(bytes) => encode_bounded_bytes(bytes, e),
+        PlutusData::Array(items) => encode_list(items, encoding, e),
+    }
+}
+
+fn encode_constr(
+    constr: &Constr<PlutusData>,
+    encoding: CborEncoding,
+    e: &mut Encoder<Vec<u8>>,
+) -> EncodeResult {
+    match constr.constr {
+        index @ 0..=6 => {
+            e.tag(Tag::Unassigned(SMALL_CONSTR_TAG + index))?;
+        }
+        index @ 7..=127 => {
+            e.tag(Tag::Unassigned(MEDIUM_CONSTR_TAG + index - 7))?;
+        }
+        index => {
+            e.tag(Tag::Unassigned(ANY_CONSTR_TAG))?
+                .array(2)?
+                .u64(index)?;
+        }
+    }
+    encode_list(&constr.fields, encoding, e)
+}
+
+fn encode_list(
+    items: &[PlutusData],
+    encoding: CborEncoding,
+    e: &mut Encoder<Vec<u8>>,
+) -> EncodeResult {
+    let indefinite = encoding == CborEncoding::Indefinite && !items.is_empty();
+    if indefinite {
+        e.begin_array()?;
+    } else {
+        e.array(items.len() as u64)?;
+    }
+    for item in items {
+        encode_data(item, encoding, e)?;
+    }
+    if indefinite {
+        e.end()?;
+    }
+    Ok(())
+}
+
+fn encode_big_int(big_int: &BigInt, e: &mut Encoder<Vec<u8>>) -> EncodeResult {
+    match big_int {
+        BigInt::Int { neg: true, val } if *val > 0 => {
+            let int = Int::try_from(-i128::from(*val))
+                .expect("Every negated u64 is in the CBOR integer range");
+            e.int(int)?;
+        }
+        BigInt::Int { val, .. } => {
+            e.u64(*val)?;
+        }
+        BigInt::BigUInt(bytes) => {
+            e.tag(Tag::PosBignum)?;
+            encode_bounded_bytes(bytes, e)?;
+        }
+        BigInt::BigNInt(bytes) => {
+            e.tag(Tag::NegBignum)?;
+            encode_bounded_bytes(bytes, e)?;
+        }
+    }
+    Ok(())
+}
+
+fn encode_bounded_bytes(bytes: &[u8], e: &mut Encoder<Vec<u8>>) -> EncodeResult {
+    if bytes.len() <= BOUNDED_BYTES_CHUNK {
+        e.bytes(bytes)?;
+    } else {
+        e.begin_bytes()?;
+        for chunk in bytes.chunks(BOUNDED_BYTES_CHUNK) {
+            e.bytes(chunk)?;
+        }
+        e.end()?;
+    }
+    Ok(())
+}
+
+impl<'b, C> Decode<'b, C> for PlutusData {
+    fn decode(d: &mut Decoder<'b>, ctx: &mut C) -> Result<Self, decode::Error> {
+        match d.datatype()? {
+            Type::Tag => {
+                let position = d.position();
+                match d.tag()? {
+                    Tag::PosBignum => Ok(PlutusData::BigInt(BigInt::BigUInt(
+                        decode_bounded_bytes(d)?,
+                    ))),
+                    Tag::NegBignum => Ok(PlutusData::BigInt(BigInt::BigNInt(
+                        decode_bounded_bytes(d)?,
+                    ))),
+                    Tag::Unassigned(tag @ 121..=127) => {
+                        let fields = decode_list(d, ctx)?;
+                        Ok(constr(tag - SMALL_CONSTR_TAG, fields))
+                    }
+                    Tag::Unassigned(tag @ 1280..=1400) => {
+                        let fields = decode_list(d, ctx)?;
+                        Ok(constr(tag - MEDIUM_CONSTR_TAG + 7, fields))
+                    }
+                    Tag::Unassigned(ANY_CONSTR_TAG) => {
+                        let len = d.array()?;
+                        if len != Some(2) {
+                            return Err(decode::Error::message(
+                                "expected constructor index and fields",
+                            )
+                            .at(position))
+                        }
+                        let index = d.u64()?;
+                        let fields = decode_list(d, ctx)?;
+                        Ok(constr(index, fields))
+                    }
+                    _ => Err(decode::Error::message("unexpected tag").at(position)),
+                }
+            }
+            Type::Map | Type::MapIndef => {
+                let entries = d
+                    .map_iter_with::<C, PlutusData, PlutusData>(ctx)?
+                    .collect::<Result<_, _>>()?;
+                Ok(PlutusData::Map(entries))
+            }
+            Type::Array | Type::ArrayIndef => Ok(PlutusData::Array(decode_list(d, ctx)?)),
+            Type::Bytes | Type::BytesIndef => {
+                Ok(PlutusData::BoundedBytes(decode_bounded_bytes(d)?))
+            }
+            Type::U8
+            | Type::U16
+            | Type::U32
+            | Type::U64
+            | Type::I8
+            | Type::I16
+            | Type::I32
+            | Type::I64
+            | Type::Int => {
+                let int = i128::from(d.int()?);
+                let big_int = match u64::try_from(int.unsigned_abs()) {
+                    Ok(val) => BigInt::Int { neg: int < 0, val },
+                    // Only -2^64 doesn't fit, which is -1 - (2^64 - 1)
+                    Err(_) => BigInt::BigNInt(u64::MAX.to_be_bytes().to_vec()),
+                };
+                Ok(PlutusData::BigInt(big_int))
+            }
+            other => Err(decode::Error::type_mismatch(other)
+                .with_message("expected PlutusData")
+                .at(d.position())),
+        }
+    }
+}
+
+fn constr(constr: u64, fields: Vec<PlutusData>) -> PlutusData {
+    PlutusData::Constr(Constr { constr, fields })
+}
+
+fn decode_list<C>(
+    d: &mut Decoder<'_>,
+    ctx: &mut C,
+) -> Result<Vec<PlutusData>, decode::Error> {
+    d.array_iter_with::<C, PlutusData>(ctx)?.collect()
+}
+
+fn decode_bounded_bytes(d: &mut Decoder<'_>) -> Result<Vec<u8>, decode::Error> {
+    let mut bytes = Vec::new();
+    for chunk in d.bytes_iter()? {
+        bytes.extend_from_slice(chunk?);
+    }
+    Ok(bytes)
+}
@@ -0,0 +1,182 @@
+#![allow(non_snake_case)]
+
+use super::*;
+use proptest::prelude::*;
+use std::collections::BTreeMap;
+
+fn constr(constr: u64, fields: Vec<PlutusData>) -> PlutusData {
+    PlutusData::Constr(Constr { constr, fields })
+}
+
+fn int(value: i64) -> PlutusData {
+    PlutusData::BigInt(value.into())
+}
+
+fn cbor_hex(data: &PlutusData, encoding: CborEncoding) -> String {
+    hex::encode(data.to_cbor(encoding))
+}
+
+prop_compose! {
+    fn arb_big_int()(
+        neg in any::<bool>(),
+        val in any::<u64>(),
+        bytes in prop::collection::vec(any::<u8>(), 0..80),
+        kind in 0..3_u8,
+    ) -> BigInt {
+        match kind {
+            // There's no negative zero
+            0 => BigInt::Int { neg: neg && val > 0, val },
+            1 => BigInt::BigUInt(bytes),
+            _ => BigInt::BigNInt(bytes),
+        }
+    }
+}
+
+fn arb_plutus_data() -> impl Strategy<Value = PlutusData> {
+    let leaf = prop_oneof![
+        arb_big_int().prop_map(PlutusData::BigInt),
+        prop::collection::vec(any::<u8>(), 0..150).prop_map(PlutusData::BoundedBytes),
+    ];
+    leaf.prop_recursive(4, 32, 4, |inner| {
+        prop_oneof![
+            (0..300_u64, prop::collection::vec(inner.clone(), 0..4))
+                .prop_map(|(index, fields)| constr(index, fields)),
+            prop::collection::vec(inner.clone(), 0..4).prop_map(PlutusData::Array),
+            prop::collection::btree_map(inner.clone(), inner, 0..4)
+                .prop_map(PlutusData::Map),
+        ]
+    })
+}
+
+#[test]
+fn to_cbor__uses_ledger_layout_for_constructors_and_lists() {
+    assert_eq!(
+        cbor_hex(&constr(0, vec![]), CborEncoding::Indefinite),
+        "d87980"
+    );
+    assert_eq!(
+        cbor_hex(&constr(1, vec![int(1)]), CborEncoding::Indefinite),
+        "d87a9f01ff"
+    );
+    assert_eq!(
+        cbor_hex(&constr(7, vec![]), CborEncoding::Indefinite),
+        "d9050080"
+    );
+    assert_eq!(
+        cbor_hex(&constr(200, vec![]), CborEncoding::Indefinite),
+        "d8668218c880"
+    );
+    assert_eq!(
+        cbor_hex(
+            &PlutusData::Array(vec![int(1), int(-1)]),
+            CborEncoding::Indefinite
+        ),
+        "9f0120ff"
+    );
+}
+
+#[test]
+fn to_cbor__canonical_uses_definite_lengths_and_sorted_keys() {
+    // Given
+    let map = PlutusData::Map(BTreeMap::from([
+        (PlutusData::BoundedBytes(vec![]), int(1)),
+        (int(1000), int(2)),
+        (int(1), int(3)),
+    ]));
+    let data = constr(0, vec![PlutusData::Array(vec![int(1)]), map]);
+
+    // When
+    let cbor = cbor_hex(&data, CborEncoding::Canonical);
+
+    // Then
+    assert_eq!(cbor, "d879828101a3010340011903e802");
+}
+
+#[test]
+fn to_cbor__chunks_long_bytes_and_encodes_big_integers() {
+    // Given
+    let bytes = PlutusData::BoundedBytes(vec![0xab; 65]);
+    let min_int = PlutusData::BigInt(BigInt::Int {
+        neg: true,
+        val: u64::MAX,
+    });
+    let big = PlutusData::BigInt(BigInt::BigUInt(vec![1, 0, 0, 0, 0, 0, 0, 0, 0]));
+
+    // When
+    let bytes_cbor = cbor_hex(&bytes, CborEncoding::Indefinite);
+    let min_int_cbor = cbor_hex(&min_int, CborEncoding::Indefinite);
+    let big_cbor = cbor_hex(&big, CborEncoding::Indefinite);
+
+    // Then
+    let expected_bytes = format!("5f5840{}41abff", "ab".repeat(64));
+    assert_eq!(bytes_cbor, expected_bytes);
+    assert_eq!(min_int_cbor, "3bfffffffffffffffe");
+    assert_eq!(big_cbor, "c249010000000000000000");
+}
+
+#[test]
+fn hash__matches_ledger_datum_hash() {
+    // Given
+    let unit = constr(0, vec![]);
+
+    // When
+    let hash = unit.hash();
+
+    // Then
+    assert_eq!(
+        hex::encode(hash),
+        "923918e403bf43c34b4ef6b48eb2ee04babed17320d8d1b9ff9ad086e86f44ec"
+    );
+}
+
+#[test]
+fn from_cbor__reads_definite_and_indefinite_layouts() {
+    // Given
+    let expected = constr(1, vec![PlutusData::Array(vec![int(1), int(2)])]);
+
+    // When
+    let indefinite = PlutusData::from_cbor(&hex::decode("d87a9f9f0102ffff").unwrap());
+    let definite = PlutusData::from_cbor(&hex::decode("d87a81820102").unwrap());
+    let general = PlutusData::from_cbor(&hex::decode("d866820181820102").unwrap());
+
+    // Then
+    assert_eq!(indefinite, Ok(expected.clone()));
+    assert_eq!(definite, Ok(expected.clone()));
+    assert_eq!(general, Ok(expected));
+}
+
+#[test]
+fn from_cbor__reads_smallest_integer() {
+    // When
+    let data = PlutusData::from_cbor(&hex::decode("3bffffffffffffffff").unwrap());
+
+    // Then
+    let expected = PlutusData::BigInt(BigInt::BigNInt(vec![0xff; 8]));
+    assert_eq!(data, Ok(expected));
+}
+
+#[test]
+fn from_cbor__rejects_other_cbor() {
+    assert!(matches!(
+        PlutusData::from_cbor(&hex::decode("f5").unwrap()),
+        Err(CborError::Decode(_))
+    ));
+    assert!(matches!(
+        PlutusData::from_cbor(&hex::decode("d88080").unwrap()),
+        Err(CborError::Decode(_))
+    ));
+    assert_eq!(
+        PlutusData::from_cbor(&hex::decode("0101").unwrap()),
+        Err(CborError::TrailingBytes(1))
+    );
+}
+
+proptest! {
+    #[test]
+    fn from_cbor__round_trips_every_encoding(data in arb_plutus_data()) {
+        for encoding in [CborEncoding::Indefinite, CborEncoding::Canonical] {
+            let decoded = PlutusData::from_cbor(&data.to_cbor(encoding));
+            prop_assert_eq!(decoded, Ok(data.clone()));
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use super::{
+    BigInt,
+    Constr,
+    PlutusData,
+};
+use serde_json::{
+    json,
+    Map,
+    Value,
+};
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+#[cfg(test)]
+mod tests;
+
+/// Error from converting [`PlutusData`] to or from cardano-cli's detailed schema JSON
+#[allow(missing_docs)]
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum JsonError {
+    #[error("Invalid detailed schema JSON: {0}")]
+    Invalid(String),
+    #[error("Integer doesn't fit in a JSON number: {0:?}")]
+    IntegerOutOfRange(BigInt),
+}
+
+impl PlutusData {
+    /// Convert to JSON in cardano-cli's detailed schema, the format of `--tx-out-datum-file` and
+    /// friends. Integers must fit in an `i64` or `u64`
+    pub fn to_json(&self) -> Result<Value, JsonError> {
+        let value = match self {
+            PlutusData::Constr(Constr { constr, fields }) => {
+                let fields = fields
+                    .iter()
+                    .map(PlutusData::to_json)
+                    .collect::<Result<Vec<_>, _>>()?;
+                json!({ "constructor": constr, "fields": fields })
+            }
+            PlutusData::Map(map) => {
+                let entries = map
+                    .iter()
+                    .map(|(key, value)| {
+                        Ok(json!({ "k": key.to_json()?, "v": value.to_json()? }))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                json!({ "map": entries })
+            }
+            PlutusData::BigInt(big_int) => json!({ "int": int_to_json(big_int)? }),
+            PlutusData::BoundedBytes(bytes) => json!({ "bytes": hex::encode(bytes) }),
+            PlutusData::Array(items) => {
+                let items = items
+                    .iter()
+                    .map(PlutusData::to_json)
+                    .collect::<Result<Vec<_>, _>>()?;
+                json!({ "list": items })
+            }
+        };
+        Ok(value)
+    }
+
+    /// Convert from JSON in cardano-cli's detailed schema
+    pub fn from_json(value: &Value) -> Result<Self, JsonError> {
+        let object = value
+            .as_object()
+            .ok_or_else(|| invalid("expected an object", value))?;
+        if let Some(constr) = object.get("constructor") {
+            let constr = constr
+                .as_u64()
+                .ok_or_else(|| invalid("expected a constructor index", constr))?;
+            let fields = list_field(object, "fields", value)?;
+            return Ok(PlutusData::Constr(Constr { constr, fields }))
+        }
+        let (key, inner) = match object.iter().next() {
+            Some(entry) if object.len() == 1 => entry,
+            _ => return Err(invalid("expected exactly one key", value)),
+        };
+        match key.as_str() {
+            "int" => Ok(PlutusData::BigInt(int_from_json(inner)?)),
+            "bytes" => {
+                let bytes = inner
+                    .as_str()
+                    .and_then(|hex| hex::decode(hex).ok())
+                    .ok_or_else(|| invalid("expected hex bytes", inner))?;
+                Ok(PlutusData::BoundedBytes(bytes))
+            }
+            "list" => Ok(PlutusData::Array(list_field(object, "list", value)?)),
+            "map" => {
+                let entries = inner
+                    .as_array()
+                    .ok_or_else(|| invalid("expected a list of map entries", inner))?;
+                let map = entries
+                    .iter()
+                    .map(|entry| match (entry.get("k"), entry.get("v")) {
+                        (Some(key), Some(value)) => Ok((
+                            PlutusData::from_json(key)?,
+                            PlutusData::from_json(value)?,
+                        )),
+                        _ => Err(invalid(
+                            "expected a map entry with \"k\" and \"v\"",
+                            entry,
+                        )),
+                    })
+                    .collect::<Result<BTreeMap<_, _>, _>>()?;
+                Ok(PlutusData::Map(map))
+            }
+            _ => Err(invalid("unknown key", value)),
+        }
+    }
+}
+
+fn invalid(reason: &str, value: &Value) -> JsonError {
+    JsonError::Invalid(format!("{reason}, found {value}"))
+}
+
+fn list_field(
+    object: &Map<String, Value>,
+    key: &str,
+    value: &Value,
+) -> Result<Vec<PlutusData>, JsonError> {
+    object
+        .get(key)
+        .and_then(Value::as_array)
+        .ok_or_else(|| invalid(&format!("expected a list of {key:?}"), value))?
+        .iter()
+        .map(PlutusData::from_json)
+        .collect()
+}
+
+fn int_to_json(big_int: &BigInt) -> Result<Value, JsonError> {
+    let out_of_range = || JsonError::IntegerOutOfRange(big_int.clone());
+    let value = match big_int {
+        BigInt::Int { neg: false, val } => json!(val),
+        BigInt::Int { neg: true, val } => {
+            let int = i64::try_from(-i128::from(*val)).map_err(|_| out_of_range())?;
+            json!(int)
+        }
+        BigInt::BigUInt(bytes) => json!(magnitude(bytes).ok_or_else(out_of_range)?),
+        BigInt::BigNInt(bytes) => {
+            // Negative bignums hold `n` for the value `-1 - n`
+            let n = magnitude(bytes).ok_or_else(out_of_range)?;
+            let int = i64::try_from(-1 - i128::from(n)).map_err(|_| out_of_range())?;
+            json!(int)
+        }
+    };
+    Ok(value)
+}
+
+fn int_from_json(value: &Value) -> Result<BigInt, JsonError> {
+    if let Some(val) = value.as_u64() {
+        Ok(BigInt::Int { neg: false, val })
+    } else if let Some(int) = value.as_i64() {
+        Ok(int.into())
+    } else {
+        Err(invalid("expected a whole number", value))
+    }
+}
+
+/// Big-endian bytes as a `u64`, if they fit
+fn magnitude(bytes: &[u8]) -> Option<u64> {
+    let significant: Vec<u8> = bytes
+        .iter()
+        .copied()
+        .skip_while(|byte| *byte == 0)
+        .collect();
+    if significant.len() > 8 {
+        return None
+    }
+    Some(
+        significant
+            .iter()
+            .fold(0, |acc, byte| acc << 8 | u64::from(*byte)),
+    )
+}
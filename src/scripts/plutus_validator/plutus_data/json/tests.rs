@@ -0,0 +1,87 @@
+#![allow(non_snake_case)]
+
+use super::*;
+
+fn int(value: i64) -> PlutusData {
+    PlutusData::BigInt(value.into())
+}
+
+#[test]
+fn to_json__uses_detailed_schema() {
+    // Given
+    let data = PlutusData::Constr(Constr {
+        constr: 1,
+        fields: vec![
+            int(-5),
+            PlutusData::BoundedBytes(vec![0xde, 0xad]),
+            PlutusData::Array(vec![int(1)]),
+            PlutusData::Map(BTreeMap::from([(int(2), PlutusData::BoundedBytes(vec![]))])),
+        ],
+    });
+
+    // When
+    let value = data.to_json().unwrap();
+
+    // Then
+    let expected = json!({
+        "constructor": 1,
+        "fields": [
+            { "int": -5 },
+            { "bytes": "dead" },
+            { "list": [{ "int": 1 }] },
+            { "map": [{ "k": { "int": 2 }, "v": { "bytes": "" } }] },
+        ],
+    });
+    assert_eq!(value, expected);
+}
+
+#[test]
+fn from_json__round_trips_to_json() {
+    // Given
+    let value = json!({
+        "constructor": 0,
+        "fields": [
+            { "int": 18446744073709551615_u64 },
+            { "map": [{ "k": { "bytes": "00" }, "v": { "list": [] } }] },
+        ],
+    });
+
+    // When
+    let data = PlutusData::from_json(&value).unwrap();
+
+    // Then
+    assert_eq!(data.to_json().unwrap(), value);
+}
+
+#[test]
+fn to_json__converts_small_bignums_and_rejects_large_ones() {
+    // Given
+    let small = PlutusData::BigInt(BigInt::BigNInt(vec![0, 0, 9]));
+    let large = BigInt::BigUInt(vec![1; 9]);
+
+    // When
+    let small_value = small.to_json();
+    let large_value = PlutusData::BigInt(large.clone()).to_json();
+
+    // Then
+    assert_eq!(small_value, Ok(json!({ "int": -10 })));
+    assert_eq!(large_value, Err(JsonError::IntegerOutOfRange(large)));
+}
+
+#[test]
+fn from_json__rejects_other_json() {
+    for value in [
+        json!([]),
+        json!({ "int": 1.5 }),
+        json!({ "bytes": "xyz" }),
+        json!({ "int": 1, "bytes": "" }),
+        json!({ "map": [{ "k": { "int": 1 } }] }),
+        json!({ "constructor": 0 }),
+        json!({ "string": "hi" }),
+    ] {
+        assert!(
+            matches!(PlutusData::from_json(&value), Err(JsonError::Invalid(_))),
+            "{value}"
+        );
+    }
+}
@@ -0,0 +1,446 @@
+use super::{
+    BigInt,
+    PlutusData,
+};
+use crate::scripts::raw_script::schema::{
+    DataType,
+    Definitions,
+    Items,
+    PathSegment,
+    Schema,
+    SchemaKind,
+};
+use std::fmt;
+
+#[cfg(test)]
+mod tests;
+
+/// Spaces per level of nesting
+const INDENT: usize = 2;
+
+/// [`PlutusData`] rendered for people to read, from [`PlutusData::pretty`]. Nested values are
+/// indented, bytes are shown as hex and integers in decimal, e.g.
+/// ```text
+/// Constr 0 [
+///   h'deadbeef',
+///   [
+///     1,
+///     -2,
+///   ],
+/// ]
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Pretty<'a> {
+    data: &'a PlutusData,
+    labels: Option<Labels<'a>>,
+    compact: bool,
+}
+
+impl<'a> Pretty<'a> {
+    /// Label constructors and their fields with the titles in the blueprint `schema` of the data,
+    /// e.g. `Constr 1 (Some) [ owner: h'..', ]`. Data that doesn't match the schema is left
+    /// unlabelled
+    pub fn with_schema(
+        mut self,
+        schema: &'a Schema,
+        definitions: &'a Definitions,
+    ) -> Self {
+        self.labels = Some(Labels {
+            schema,
+            definitions,
+        });
+        self
+    }
+
+    /// Render on a single line, e.g. `Constr 0 [h'deadbeef', [1, -2]]`
+    pub fn compact(mut self) -> Self {
+        self.compact = true;
+        self
+    }
+}
+
+impl fmt::Display for Pretty<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let indent = if self.compact { None } else { Some(0) };
+        write_data(f, self.data, self.labels, indent)
+    }
+}
+
+/// A difference between two [`PlutusData`] values, from [`PlutusData::diff`]. Displays as e.g.
+/// `field 0 of constructor 1 expected 42, found 7`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataDifference {
+    /// Where in the data the values differ, outermost first. Empty if it's the values themselves
+    pub path: Vec<PathSegment>,
+    /// The expected value there, or `None` if the actual value has an extra field, item or entry
+    pub expected: Option<PlutusData>,
+    /// The actual value there, or `None` if it's missing a field, item or entry
+    pub actual: Option<PlutusData>,
+}
+
+impl DataDifference {
+    fn new(expected: Option<&PlutusData>, actual: Option<&PlutusData>) -> Self {
+        DataDifference {
+            path: Vec::new(),
+            expected: expected.cloned(),
+            actual: actual.cloned(),
+        }
+    }
+
+    fn within(mut self, segment: PathSegment) -> Self {
+        self.path.insert(0, segment);
+        self
+    }
+}
+
+impl fmt::Display for DataDifference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path: Vec<_> = self.path.iter().rev().map(ToString::to_string).collect();
+        if !path.is_empty() {
+            write!(f, "{} ", path.join(" in "))?;
+        }
+        match (&self.expected, &self.actual) {
+            (Some(expected), Some(actual)) => write!(
+                f,
+                "expected {}, found {}",
+                expected.pretty().compact(),
+                actual.pretty().compact()
+            ),
+            (Some(expected), None) => {
+                write!(f, "missing, expected {}", expected.pretty().compact())
+            }
+            (None, Some(actual)) => {
+                write!(f, "unexpected, found {}", actual.pretty().compact())
+            }
+            (None, None) => write!(f, "differs"),
+        }
+    }
+}
+
+impl PlutusData {
+    /// Render for people to read. See [`Pretty`]
+    pub fn pretty(&self) -> Pretty<'_> {
+        Pretty {
+            data: self,
+            labels: None,
+            compact: false,
+        }
+    }
+
+    /// Every place where `actual` differs from `self`, the expected value. Constructors with the
+    /// same index, lists and maps are compared element by element, so a change deep inside a
+    /// datum is reported there rather than as a difference of the whole datum. Empty if the
+    /// values are equal
+    pub fn diff(&self, actual: &PlutusData) -> Vec<DataDifference> {
+        if self == actual {
+            return Vec::new()
+        }
+        match (self, actual) {
+            (PlutusData::Constr(expected), PlutusData::Constr(actual))
+                if expected.constr == actual.constr =>
+            {
+                diff_sequences(&expected.fields, &actual.fields, |field| {
+                    PathSegment::Field {
+                        constructor: expected.constr,
+                        field,
+                    }
+                })
+            }
+            (PlutusData::Array(expected), PlutusData::Array(actual)) => {
+                diff_sequences(expected, actual, PathSegment::Item)
+            }
+            (PlutusData::Map(expected), PlutusData::Map(actual)) => {
+                let mut differences = Vec::new();
+                for (key, expected_value) in expected {
+                    let found = match actual.get(key) {
+                        Some(actual_value) => expected_value.diff(actual_value),
+                        None => vec![DataDifference::new(Some(expected_value), None)],
+                    };
+                    let segment = PathSegment::Entry(key.clone());
+                    differences
+                        .extend(found.into_iter().map(|d| d.within(segment.clone())));
+                }
+                for (key, actual_value) in actual {
+                    if !expected.contains_key(key) {
+                        differences.push(
+                            DataDifference::new(None, Some(actual_value))
+                                .within(PathSegment::Entry(key.clone())),
+                        );
+                    }
+                }
+                differences
+            }
+            _ => vec![DataDifference::new(Some(self), Some(actual))],
+        }
+    }
+}
+
+fn diff_sequences(
+    expected: &[PlutusData],
+    actual: &[PlutusData],
+    segment: impl Fn(usize) -> PathSegment,
+) -> Vec<DataDifference> {
+    let len = expected.len().max(actual.len());
+    (0..len)
+        .flat_map(|index| {
+            let found = match (expected.get(index), actual.get(index)) {
+                (Some(expected), Some(actual)) => expected.diff(actual),
+                (expected, actual) => vec![DataDifference::new(expected, actual)],
+            };
+            let segment = segment(index);
+            found.into_iter().map(move |d| d.within(segment.clone()))
+        })
+        .collect()
+}
+
+/// Blueprint schema of the data being written
+#[derive(Debug, Clone, Copy)]
+struct Labels<'a> {
+    schema: &'a Schema,
+    definitions: &'a Definitions,
+}
+
+impl<'a> Labels<'a> {
+    fn of(self, schema: &'a Schema) -> Self {
+        Labels {
+            schema,
+            definitions: self.definitions,
+        }
+    }
+
+    /// The schema with any `$ref`s followed
+    fn resolved(self) -> &'a Schema {
+        let mut schema = self.schema;
+        // Bounded in case of definitions that only refer to each other
+        for _ in 0..=self.definitions.iter().count() {
+            match &schema.kind {
+                SchemaKind::Reference { reference } => {
+                    match self.definitions.resolve(reference) {
+                        Some((_, definition)) => schema = definition,
+                        None => break,
+                    }
+                }
+                _ => break,
+            }
+        }
+        schema
+    }
+
+    /// Title and field schemas of constructor `index`
+    fn constructor(self, index: u64) -> Option<(Option<&'a str>, &'a [Schema])> {
+        let with_index = |schema: &'a Schema| match &self.of(schema).resolved().kind {
+            SchemaKind::Typed(DataType::Constructor {
+                index: constructor,
+                fields,
+            }) if *constructor == index => {
+                Some((schema.title.as_deref(), fields.as_slice()))
+            }
+            _ => None,
+        };
+        match &self.resolved().kind {
+            SchemaKind::AnyOf { any_of } => any_of.iter().find_map(with_index),
+            _ => with_index(self.resolved()),
+        }
+    }
+
+    fn item(self, index: usize, len: usize) -> Option<Self> {
+        match &self.resolved().kind {
+            SchemaKind::Typed(
+                DataType::List { items } | DataType::BuiltinList { items },
+            ) => match items {
+                Items::Homogeneous(schema) => Some(self.of(schema)),
+                Items::Tuple(schemas) if schemas.len() == len => {
+                    Some(self.of(&schemas[index]))
+                }
+                Items::Tuple(_) => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn entry(self) -> Option<(Self, Self)> {
+        match &self.resolved().kind {
+            SchemaKind::Typed(DataType::Map { keys, values }) => {
+                Some((self.of(keys), self.of(values)))
+            }
+            _ => None,
+        }
+    }
+}
+
+enum Element<'a> {
+    Value {
+        label: Option<&'a str>,
+        data: &'a PlutusData,
+        labels: Option<Labels<'a>>,
+    },
+    Entry {
+        key: &'a PlutusData,
+        key_labels: Option<Labels<'a>>,
+        value: &'a PlutusData,
+        value_labels: Option<Labels<'a>>,
+    },
+}
+
+/// Write `data` spread over indented lines, starting at nesting level `indent`, or on one line if
+/// `indent` is `None`
+fn write_data(
+    f: &mut fmt::Formatter<'_>,
+    data: &PlutusData,
+    labels: Option<Labels<'_>>,
+    indent: Option<usize>,
+) -> fmt::Result {
+    match data {
+        PlutusData::Constr(constr) => {
+            write!(f, "Constr {}", constr.constr)?;
+            let constructor = labels.and_then(|labels| {
+                let (title, fields) = labels.constructor(constr.constr)?;
+                Some((title, fields, labels))
+            });
+            if let Some((Some(title), _, _)) = constructor {
+                write!(f, " ({title})")?;
+            }
+            if constr.fields.is_empty() {
+                return Ok(())
+            }
+            let field_schemas = constructor.and_then(|(_, fields, labels)| {
+                (fields.len() == constr.fields.len()).then_some((fields, labels))
+            });
+            let elements = constr
+                .fields
+                .iter()
+                .enumerate()
+                .map(|(index, data)| {
+                    let schema =
+                        field_schemas.map(|(fields, labels)| labels.of(&fields[index]));
+                    Element::Value {
+                        label: schema.and_then(|labels| labels.schema.title.as_deref()),
+                        data,
+                        labels: schema,
+                    }
+                })
+                .collect();
+            write!(f, " ")?;
+            write_elements(f, "[", elements, "]", indent)
+        }
+        PlutusData::Map(map) => {
+            let entry_labels = labels.and_then(Labels::entry);
+            let elements = map
+                .iter()
+                .map(|(key, value)| Element::Entry {
+                    key,
+                    key_labels: entry_labels.map(|(keys, _)| keys),
+                    value,
+                    value_labels: entry_labels.map(|(_, values)| values),
+                })
+                .collect();
+            write_elements(f, "{", elements, "}", indent)
+        }
+        PlutusData::BigInt(big_int) => write!(f, "{}", decimal(big_int)),
+        PlutusData::BoundedBytes(bytes) => write!(f, "h'{}'", hex::encode(bytes)),
+        PlutusData::Array(items) => {
+            let elements = items
+                .iter()
+                .enumerate()
+                .map(|(index, data)| Element::Value {
+                    label: None,
+                    data,
+                    labels: labels.and_then(|labels| labels.item(index, items.len())),
+                })
+                .collect();
+            write_elements(f, "[", elements, "]", indent)
+        }
+    }
+}
+
+fn write_elements(
+    f: &mut fmt::Formatter<'_>,
+    open: &str,
+    elements: Vec<Element<'_>>,
+    close: &str,
+    indent: Option<usize>,
+) -> fmt::Result {
+    write!(f, "{open}")?;
+    if elements.is_empty() {
+        return write!(f, "{close}")
+    }
+    let inner = indent.map(|level| level + 1);
+    for (position, element) in elements.into_iter().enumerate() {
+        match inner {
+            Some(level) => write!(f, "\n{:width$}", "", width = level * INDENT)?,
+            None if position > 0 => write!(f, ", ")?,
+            None => {}
+        }
+        match element {
+            Element::Value {
+                label,
+                data,
+                labels,
+            } => {
+                if let Some(label) = label {
+                    write!(f, "{label}: ")?;
+                }
+                write_data(f, data, labels, inner)?;
+            }
+            Element::Entry {
+                key,
+                key_labels,
+                value,
+                value_labels,
+            } => {
+                write_data(f, key, key_labels, inner)?;
+                write!(f, ": ")?;
+                write_data(f, value, value_labels, inner)?;
+            }
+        }
+        if inner.is_some() {
+            write!(f, ",")?;
+        }
+    }
+    if let Some(level) = indent {
+        write!(f, "\n{:width$}", "", width = level * INDENT)?;
+    }
+    write!(f, "{close}")
+}
+
+fn decimal(big_int: &BigInt) -> String {
+    match big_int {
+        BigInt::Int { neg: true, val } if *val > 0 => format!("-{val}"),
+        BigInt::Int { val, .. } => val.to_string(),
+        BigInt::BigUInt(bytes) => big_endian_decimal(bytes.to_vec()),
+        // Negative bignums hold `n` for the value `-1 - n`
+        BigInt::BigNInt(bytes) => format!("-{}", big_endian_decimal(plus_one(bytes))),
+    }
+}
+
+fn plus_one(bytes: &[u8]) -> Vec<u8> {
+    let mut sum = bytes.to_vec();
+    for byte in sum.iter_mut().rev() {
+        let (incremented, overflowed) = byte.overflowing_add(1);
+        *byte = incremented;
+        if !overflowed {
+            return sum
+        }
+    }
+    sum.insert(0, 1);
+    sum
+}
+
+/// Decimal digits of a big-endian unsigned integer of any size
+fn big_endian_decimal(mut bytes: Vec<u8>) -> String {
+    let mut digits = String::new();
+    while bytes.iter().any(|byte| *byte != 0) {
+        // Long division by 10, leaving the quotient in `bytes`
+        let mut remainder = 0_u16;
+        for byte in bytes.iter_mut() {
+            let dividend = remainder << 8 | u16::from(*byte);
+            *byte = (dividend / 10) as u8;
+            remainder = dividend % 10;
+        }
+        digits.push(char::from(b'0' + remainder as u8));
+    }
+    if digits.is_empty() {
+        return "0".to_string()
+    }
+    digits.chars().rev().collect()
+}
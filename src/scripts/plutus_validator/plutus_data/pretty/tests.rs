@@ -0,0 +1,211 @@
+#![allow(non_snake_case)]
+
+use super::*;
+use crate::scripts::plutus_validator::plutus_data::Constr;
+use std::collections::BTreeMap;
+
+fn constr(constr: u64, fields: Vec<PlutusData>) -> PlutusData {
+    PlutusData::Constr(Constr { constr, fields })
+}
+
+fn int(value: i64) -> PlutusData {
+    PlutusData::BigInt(value.into())
+}
+
+fn bytes(bytes: &[u8]) -> PlutusData {
+    PlutusData::BoundedBytes(bytes.to_vec())
+}
+
+#[test]
+fn pretty__indents_nested_data() {
+    // Given
+    let data = constr(
+        0,
+        vec![
+            bytes(&[0xde, 0xad]),
+            PlutusData::Array(vec![int(1), int(-2)]),
+            PlutusData::Map(BTreeMap::from([(int(3), constr(1, vec![]))])),
+            PlutusData::Array(vec![]),
+        ],
+    );
+
+    // When
+    let rendered = data.pretty().to_string();
+
+    // Then
+    let expected = "\
+Constr 0 [
+  h'dead',
+  [
+    1,
+    -2,
+  ],
+  {
+    3: Constr 1,
+  },
+  [],
+]";
+    assert_eq!(rendered, expected);
+}
+
+#[test]
+fn pretty__compact_uses_one_line() {
+    // Given
+    let data = constr(
+        0,
+        vec![
+            bytes(&[]),
+            PlutusData::Map(BTreeMap::from([(int(3), PlutusData::Array(vec![int(4)]))])),
+        ],
+    );
+
+    // When
+    let rendered = data.pretty().compact().to_string();
+
+    // Then
+    assert_eq!(rendered, "Constr 0 [h'', {3: [4]}]");
+}
+
+#[test]
+fn pretty__writes_big_integers_in_decimal() {
+    // Given
+    let big = PlutusData::BigInt(BigInt::BigUInt(vec![1, 0, 0, 0, 0, 0, 0, 0, 0]));
+    let negative_big = PlutusData::BigInt(BigInt::BigNInt(vec![0xff; 8]));
+
+    // When
+    let rendered = (big.pretty().to_string(), negative_big.pretty().to_string());
+
+    // Then
+    assert_eq!(
+        rendered,
+        (
+            "18446744073709551616".to_string(),
+            "-18446744073709551616".to_string()
+        )
+    );
+}
+
+#[test]
+fn pretty__labels_constructors_and_fields_from_schema() {
+    // Given
+    let schema: Schema =
+        serde_json::from_str(r##"{ "title": "Datum", "$ref": "#/definitions/Option" }"##)
+            .unwrap();
+    let definitions: Definitions = serde_json::from_str(
+        r##"{
+            "Option": {
+                "anyOf": [
+                    {
+                        "title": "Some",
+                        "dataType": "constructor",
+                        "index": 0,
+                        "fields": [
+                            { "title": "owner", "dataType": "bytes" },
+                            { "title": "amounts", "dataType": "list", "items": { "$ref": "#/definitions/Int" } }
+                        ]
+                    },
+                    { "title": "None", "dataType": "constructor", "index": 1, "fields": [] }
+                ]
+            },
+            "Int": { "dataType": "integer" }
+        }"##,
+    )
+    .unwrap();
+    let some = constr(0, vec![bytes(&[1]), PlutusData::Array(vec![int(5)])]);
+    let none = constr(1, vec![]);
+
+    // When
+    let rendered_some = some.pretty().with_schema(&schema, &definitions).to_string();
+    let rendered_none = none
+        .pretty()
+        .with_schema(&schema, &definitions)
+        .compact()
+        .to_string();
+
+    // Then
+    let expected_some = "\
+Constr 0 (Some) [
+  owner: h'01',
+  amounts: [
+    5,
+  ],
+]";
+    assert_eq!(rendered_some, expected_some);
+    assert_eq!(rendered_none, "Constr 1 (None)");
+}
+
+#[test]
+fn diff__is_empty_for_equal_data() {
+    // Given
+    let data = constr(0, vec![int(1)]);
+
+    // When
+    let differences = data.diff(&data.clone());
+
+    // Then
+    assert!(differences.is_empty());
+}
+
+#[test]
+fn diff__points_at_differences_deep_inside() {
+    // Given
+    let expected = constr(
+        1,
+        vec![
+            int(42),
+            PlutusData::Array(vec![int(1), int(2)]),
+            PlutusData::Map(BTreeMap::from([
+                (bytes(&[1]), int(1)),
+                (bytes(&[2]), int(2)),
+            ])),
+        ],
+    );
+    let actual = constr(
+        1,
+        vec![
+            int(7),
+            PlutusData::Array(vec![int(1)]),
+            PlutusData::Map(BTreeMap::from([
+                (bytes(&[1]), int(9)),
+                (bytes(&[3]), int(3)),
+            ])),
+        ],
+    );
+
+    // When
+    let differences: Vec<_> = expected
+        .diff(&actual)
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+
+    // Then
+    assert_eq!(
+        differences,
+        vec![
+            "field 0 of constructor 1 expected 42, found 7",
+            "item 1 in field 1 of constructor 1 missing, expected 2",
+            "value at key h'01' in field 2 of constructor 1 expected 1, found 9",
+            "value at key h'02' in field 2 of constructor 1 missing, expected 2",
+            "value at key h'03' in field 2 of constructor 1 unexpected, found 3",
+        ]
+    );
+}
+
+#[test]
+fn diff__reports_other_constructors_as_a_whole() {
+    // Given
+    let expected = constr(0, vec![int(1)]);
+    let actual = constr(1, vec![int(1)]);
+
+    // When
+    let differences = expected.diff(&actual);
+
+    // Then
+    let expected_difference = DataDifference {
+        path: Vec::new(),
+        expected: Some(expected),
+        actual: Some(actual),
+    };
+    assert_eq!(differences, vec![expected_difference]);
+}
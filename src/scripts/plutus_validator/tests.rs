@@ -1,7 +1,10 @@
 use super::*;
-use crate::scripts::context::{
-    pub_key_hash_from_address_if_available,
-    ContextBuilder,
+use crate::scripts::{
+    context::{
+        pub_key_hash_from_address_if_available,
+        ContextBuilder,
+    },
+    raw_script::BlueprintFile,
 };
 
 mod game;
@@ -23,3 +26,43 @@ fn execute_always_succeeds() {
 
     script.execute((), (), ctx).unwrap();
 }
+
+#[test]
+fn check_datum_uses_blueprint_schema() {
+    let blueprint = BlueprintFile::from_json(include_str!(
+        "../../../sample-dApps/checking_account/checking/plutus.json"
+    ))
+    .unwrap();
+    let validator_blueprint = blueprint
+        .get_validator("checking_account_validator.spend")
+        .unwrap();
+    let script: PlutusValidator<PlutusData, PlutusData> =
+        PlutusValidator::from_blueprint(validator_blueprint).unwrap();
+
+    let owner = PlutusData::BoundedBytes(vec![1; 28]);
+    let good_datum = PlutusData::Constr(Constr {
+        constr: 0,
+        fields: vec![owner.clone(), PlutusData::BoundedBytes(vec![2; 28])],
+    });
+    let bad_datum = PlutusData::Constr(Constr {
+        constr: 0,
+        fields: vec![owner, PlutusData::BigInt(2.into())],
+    });
+    let unit = PlutusData::Constr(Constr {
+        constr: 0,
+        fields: vec![],
+    });
+
+    script.check_datum(&good_datum).unwrap();
+    script.check_redeemer(&unit).unwrap();
+    assert_eq!(
+        script.check_datum(&bad_datum),
+        Err(ScriptError::DatumSchema(
+            "field 1 of constructor 0 expected bytes, found integer".to_string()
+        ))
+    );
+    assert!(matches!(
+        script.check_redeemer(&good_datum),
+        Err(ScriptError::RedeemerSchema(_))
+    ));
+}
@@ -1,4 +1,15 @@
-use crate::scripts::ExecutionCost;
+use crate::{
+    scripts::{
+        plutus_validator::plutus_data::PlutusData,
+        ExecutionCost,
+    },
+    transaction::TransactionVersion,
+};
+use schema::{
+    Argument,
+    Definitions,
+    SchemaMismatch,
+};
 use serde::{
     Deserialize,
     Serialize,
@@ -6,6 +17,11 @@ use serde::{
 use thiserror::Error;
 use uplc::machine::cost_model::ExBudget;
 
+/// Bindings generator for Blueprint files
+pub mod codegen;
+/// CIP-0057 schema types
+pub mod schema;
+
 #[allow(non_snake_case)]
 #[allow(unused)]
 #[derive(Serialize, Deserialize, Debug)]
@@ -35,21 +51,68 @@ impl PlutusScriptFile {
 pub struct BlueprintFile {
     preamble: Preamble,
     validators: Vec<ValidatorBlueprint>,
+    #[serde(default)]
+    definitions: Definitions,
 }
 
 impl BlueprintFile {
-    /// Get a specific validator from the Blueprint file representation
+    /// Parse a Blueprint file, e.g. the `plutus.json` built by Aiken
+    pub fn from_json(json: &str) -> RawPlutusScriptResult<Self> {
+        serde_json::from_str(json)
+            .map_err(|e| PlutusScriptError::Blueprint(e.to_string()))
+    }
+
+    /// Get a specific validator from the Blueprint file representation, along with the
+    /// definitions its datum and redeemer schemas refer to and the Plutus version
     pub fn get_validator(&self, title: &str) -> Option<ValidatorBlueprint> {
-        self.validators.iter().find(|v| v.title == title).cloned()
+        let mut validator = self.validators.iter().find(|v| v.title == title).cloned()?;
+        validator.definitions = self.definitions.clone();
+        validator.plutus_version = self.preamble.plutusVersion.clone();
+        Some(validator)
+    }
+
+    /// Getter for the preamble
+    pub fn preamble(&self) -> &Preamble {
+        &self.preamble
+    }
+
+    /// Getter for all the validators
+    pub fn validators(&self) -> &[ValidatorBlueprint] {
+        &self.validators
+    }
+
+    /// Getter for the schemas the validators refer to
+    pub fn definitions(&self) -> &Definitions {
+        &self.definitions
     }
 }
 
 /// Preable of a CIP-0057 Blueprint file
+#[allow(non_snake_case)]
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Preamble {
     title: String,
-    description: String,
+    description: Option<String>,
     version: String,
+    plutusVersion: Option<String>,
+    license: Option<String>,
+}
+
+impl Preamble {
+    /// Getter for the title
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Getter for the version
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// Getter for the Plutus version of the validators, e.g. `v2`
+    pub fn plutus_version(&self) -> Option<&str> {
+        self.plutusVersion.as_deref()
+    }
 }
 
 /// Representation of a CIP-0057 Validator Blueprint
@@ -57,10 +120,16 @@ pub struct Preamble {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ValidatorBlueprint {
     title: String,
-    datum: Option<serde_json::Value>, // TODO: what is this type actually?
-    redeemer: serde_json::Value,      // TODO: what is this type actually?
+    datum: Option<Argument>,
+    redeemer: Argument,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    parameters: Vec<Argument>,
     compiledCode: String,
     hash: String,
+    #[serde(skip)]
+    definitions: Definitions,
+    #[serde(skip)]
+    plutus_version: Option<String>,
 }
 
 impl ValidatorBlueprint {
@@ -68,31 +137,64 @@ impl ValidatorBlueprint {
     pub fn compiled_code(&self) -> String {
         self.compiledCode.clone()
     }
-}
 
-// #[allow(non_snake_case)]
-// #[derive(Serialize, Deserialize, Debug, Clone)]
-// pub struct DatumBlueprint {
-//     title: String,
-//     description: String,
-//     anyOf: Vec<VariantBlueprint>,
-// }
-//
-// #[allow(non_snake_case)]
-// #[derive(Serialize, Deserialize, Debug, Clone)]
-// pub struct RedeemerBlueprint {
-//     title: String,
-//     description: String,
-//     anyOf: Vec<VariantBlueprint>,
-// }
-//
-// #[allow(non_snake_case)]
-// #[derive(Serialize, Deserialize, Debug, Clone)]
-// pub struct VariantBlueprint {
-//     dataType: String,
-//     index: u32,
-//     fields: serde_json::Value, // TODO: what is this type actually?
-// }
+    /// Getter for the title, e.g. `one_shot_nft.mint`
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Getter for the datum. Only spending validators have one
+    pub fn datum(&self) -> Option<&Argument> {
+        self.datum.as_ref()
+    }
+
+    /// Getter for the redeemer
+    pub fn redeemer(&self) -> &Argument {
+        &self.redeemer
+    }
+
+    /// Getter for the parameters that still have to be applied, in order
+    pub fn parameters(&self) -> &[Argument] {
+        &self.parameters
+    }
+
+    /// Getter for the hex script hash
+    pub fn hash(&self) -> &str {
+        &self.hash
+    }
+
+    /// The Plutus version from the blueprint's preamble. V2 if the preamble doesn't say, or if
+    /// this didn't come from [`BlueprintFile::get_validator`]. Fails for V3, which isn't
+    /// supported yet
+    pub fn version(&self) -> RawPlutusScriptResult<TransactionVersion> {
+        match self.plutus_version.as_deref() {
+            Some("v1") => Ok(TransactionVersion::V1),
+            Some("v2") | None => Ok(TransactionVersion::V2),
+            Some("v3") => Err(PlutusScriptError::Blueprint(
+                "Plutus V3 scripts aren't supported yet".to_string(),
+            )),
+            Some(other) => Err(PlutusScriptError::Blueprint(format!(
+                "Unknown Plutus version: {other}"
+            ))),
+        }
+    }
+
+    /// Check that `datum` matches the datum schema. Always passes for validators without a datum.
+    ///
+    /// The schemas can only be followed into the blueprint's definitions if this came from
+    /// [`BlueprintFile::get_validator`]
+    pub fn validate_datum(&self, datum: &PlutusData) -> Result<(), SchemaMismatch> {
+        match &self.datum {
+            Some(argument) => argument.schema.validate(datum, &self.definitions),
+            None => Ok(()),
+        }
+    }
+
+    /// Check that `redeemer` matches the redeemer schema
+    pub fn validate_redeemer(&self, redeemer: &PlutusData) -> Result<(), SchemaMismatch> {
+        self.redeemer.schema.validate(redeemer, &self.definitions)
+    }
+}
 
 /// Error from dealing with Plutus Scripts
 #[allow(missing_docs)]
@@ -104,6 +206,8 @@ pub enum PlutusScriptError {
     AikenEval { error: String, logs: Vec<String> },
     #[error("CML Error: {0:?}")]
     CMLError(String),
+    #[error("Invalid Blueprint: {0:?}")]
+    Blueprint(String),
 }
 
 #[allow(missing_docs)]
@@ -0,0 +1,757 @@
+use crate::scripts::raw_script::{
+    schema::{
+        definition_name,
+        DataType,
+        Items,
+        Schema,
+        SchemaKind,
+    },
+    BlueprintFile,
+    PlutusScriptError,
+    ValidatorBlueprint,
+};
+use std::{
+    collections::{
+        BTreeMap,
+        HashSet,
+    },
+    fs,
+    path::Path,
+};
+use thiserror::Error;
+
+#[cfg(test)]
+mod tests;
+
+const HEADER: &str = "\
+// Generated by Naumachia from a CIP-0057 Blueprint. Don't edit it by hand, rebuild the
+// validators instead.
+";
+const PLUTUS_DATA: &str = "::naumachia::scripts::plutus_validator::plutus_data";
+const SCRIPT_ERROR: &str = "::naumachia::scripts::ScriptError";
+const RAW_SCRIPT: &str = "::naumachia::scripts::raw_script";
+const PLUTUS_VALIDATOR: &str = "::naumachia::scripts::plutus_validator";
+const PLUTUS_MINTING_POLICY: &str = "::naumachia::scripts::plutus_minting_policy";
+const PARAMETERIZED: &str = "::naumachia::scripts::parameterized";
+
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv",
+    "pub", "ref", "return", "static", "struct", "trait", "true", "try", "type", "typeof",
+    "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+// Keywords that can't be raw identifiers
+const RESERVED: &[&str] = &["crate", "self", "Self", "super"];
+
+#[allow(missing_docs)]
+#[derive(Debug, Error)]
+pub enum CodegenError {
+    #[error("Invalid Blueprint: {0:?}")]
+    Blueprint(#[from] PlutusScriptError),
+    #[error("Failed to read Blueprint or write bindings: {0:?}")]
+    Io(#[from] std::io::Error),
+    #[error("Definition not found: {0:?}")]
+    UnknownDefinition(String),
+    #[error("Unsupported schema for {name:?}: {reason}")]
+    Unsupported { name: String, reason: String },
+    #[error("More than one item would be named {0:?}")]
+    NameClash(String),
+}
+
+#[allow(missing_docs)]
+pub type CodegenResult<T> = Result<T, CodegenError>;
+
+/// Generate bindings for the Blueprint at `blueprint_path` and write them to `out_path`. Meant
+/// to be called from a build script, which is rerun whenever the Blueprint changes:
+///
+/// ```ignore
+/// // build.rs
+/// fn main() {
+///     let out_dir = std::env::var("OUT_DIR").unwrap();
+///     let out_path = std::path::Path::new(&out_dir).join("checking.rs");
+///     write_bindings("./checking/plutus.json", out_path).unwrap();
+/// }
+///
+/// // src/scripts.rs
+/// pub mod checking {
+///     include!(concat!(env!("OUT_DIR"), "/checking.rs"));
+/// }
+/// ```
+pub fn write_bindings(
+    blueprint_path: impl AsRef<Path>,
+    out_path: impl AsRef<Path>,
+) -> CodegenResult<()> {
+    let blueprint_path = blueprint_path.as_ref();
+    println!("cargo:rerun-if-changed={}", blueprint_path.display());
+    let json = fs::read_to_string(blueprint_path)?;
+    let bindings = generate_bindings(&json)?;
+    fs::write(out_path, bindings)?;
+    Ok(())
+}
+
+/// Generate Rust source for a Blueprint. For every definition that isn't a plain integer, byte
+/// array, unit or bool, it declares a type with `From<_> for PlutusData` and
+/// `TryFrom<PlutusData>` conversions. For every validator, it declares a function building it
+/// with those types as datum, redeemer and parameters, e.g.
+///
+/// ```ignore
+/// pub fn one_shot_nft_mint() -> RawPlutusScriptResult<OneParamPlutusPolicy<OutputReference, ()>>
+/// ```
+///
+/// The Blueprint is embedded, so the output doesn't depend on where it was generated from. It
+/// refers to items by their full path, but it declares a `BLUEPRINT` constant, so each
+/// Blueprint should get its own module.
+pub fn generate_bindings(json: &str) -> CodegenResult<String> {
+    let blueprint = BlueprintFile::from_json(json)?;
+    let generator = BindingsGenerator::new(&blueprint)?;
+    let mut items = vec![
+        HEADER.to_string(),
+        format!("const BLUEPRINT: &str = {json:?};\n"),
+    ];
+    items.extend(generator.types()?);
+    items.extend(generator.validators()?);
+    Ok(items.join("\n"))
+}
+
+struct BindingsGenerator<'a> {
+    blueprint: &'a BlueprintFile,
+    /// Rust type for each definition, by definition name
+    types: BTreeMap<&'a str, String>,
+    /// Definitions that need a type declared, with the name of the type
+    declared: BTreeMap<&'a str, String>,
+}
+
+impl<'a> BindingsGenerator<'a> {
+    fn new(blueprint: &'a BlueprintFile) -> CodegenResult<Self> {
+        let definitions = blueprint.definitions();
+        let mut types = BTreeMap::new();
+        let mut declared = BTreeMap::new();
+        let mut taken = HashSet::new();
+        let mut aliases = Vec::new();
+        for (name, schema) in definitions.iter() {
+            if let SchemaKind::Reference { reference } = &schema.kind {
+                aliases.push((name, reference));
+            } else if let Some(builtin) = builtin_type(schema) {
+                types.insert(name, builtin);
+            } else {
+                let type_name = unique_type_name(name, &mut taken)?;
+                types.insert(name, type_name.clone());
+                declared.insert(name, type_name);
+            }
+        }
+        // Definitions can refer to each other, so follow each chain to its end
+        let max_hops = aliases.len();
+        for (name, reference) in aliases {
+            let mut reference = reference;
+            let mut hops = 0;
+            let rust_type = loop {
+                let (target, schema) = definitions
+                    .resolve(reference)
+                    .ok_or_else(|| CodegenError::UnknownDefinition(reference.clone()))?;
+                match &schema.kind {
+                    SchemaKind::Reference { reference: next } if hops < max_hops => {
+                        reference = next;
+                        hops += 1;
+                    }
+                    SchemaKind::Reference { .. } => {
+                        return Err(unsupported(name, "its references form a cycle"))
+                    }
+                    _ => break types[target].clone(),
+                }
+            };
+            types.insert(name, rust_type);
+        }
+        Ok(BindingsGenerator {
+            blueprint,
+            types,
+            declared,
+        })
+    }
+
+    /// Rust type for the data `schema` describes, when used in `context`
+    fn field_type(&self, schema: &Schema, context: &str) -> CodegenResult<String> {
+        match &schema.kind {
+            SchemaKind::Reference { reference } => definition_name(reference)
+                .and_then(|name| self.types.get(name.as_str()))
+                .cloned()
+                .ok_or_else(|| CodegenError::UnknownDefinition(reference.clone())),
+            _ => builtin_type(schema).ok_or_else(|| {
+                unsupported(
+                    context,
+                    "only integers, bytes, unit, bools and opaque data can be inlined",
+                )
+            }),
+        }
+    }
+
+    fn types(&self) -> CodegenResult<Vec<String>> {
+        self.declared
+            .iter()
+            .map(|(definition, type_name)| {
+                let schema = self
+                    .blueprint
+                    .definitions()
+                    .get(definition)
+                    .expect("Declared types come from the definitions");
+                self.declared_type(type_name, schema)
+            })
+            .collect()
+    }
+
+    fn declared_type(&self, type_name: &str, schema: &Schema) -> CodegenResult<String> {
+        let docs = doc_comment(schema, "");
+        let declaration = match &schema.kind {
+            SchemaKind::AnyOf { any_of } if any_of.len() == 1 => {
+                let (index, fields) = constructor(&any_of[0], type_name)?;
+                self.record(type_name, index, fields)?
+            }
+            SchemaKind::AnyOf { any_of } => self.sum_type(type_name, any_of)?,
+            SchemaKind::Typed(DataType::Constructor { index, fields }) => {
+                self.record(type_name, *index, fields)?
+            }
+            SchemaKind::Typed(DataType::List {
+                items: Items::Homogeneous(item),
+            }) => self.list(type_name, item)?,
+            SchemaKind::Typed(DataType::List {
+                items: Items::Tuple(items),
+            }) => self.tuple(type_name, items)?,
+            SchemaKind::Typed(DataType::Map { keys, values }) => {
+                self.map(type_name, keys, values)?
+            }
+            _ => {
+                return Err(unsupported(
+                    type_name,
+                    "UPLC builtins can't be used as Plutus data",
+                ))
+            }
+        };
+        Ok(format!("{docs}{declaration}"))
+    }
+
+    /// Struct with a single constructor
+    fn record(
+        &self,
+        type_name: &str,
+        index: u64,
+        fields: &[Schema],
+    ) -> CodegenResult<String> {
+        let derives = data_derives();
+        let index_attr = index_attr(index, 0, "");
+        let body = match self.fields(type_name, fields, "pub ")? {
+            Fields::Named(fields) => format!(" {{\n{fields}}}\n"),
+            Fields::Positional(fields) => format!("({fields});\n"),
+            Fields::Unit => ";\n".to_string(),
+        };
+        Ok(format!("{derives}{index_attr}pub struct {type_name}{body}"))
+    }
+
+    /// Enum with a variant per constructor
+    fn sum_type(
+        &self,
+        type_name: &str,
+        constructors: &[Schema],
+    ) -> CodegenResult<String> {
+        let derives = data_derives();
+        let mut variant_names = HashSet::new();
+        let mut variants = String::new();
+        for (position, schema) in constructors.iter().enumerate() {
+            let (index, fields) = constructor(schema, type_name)?;
+            let variant_name = match &schema.title {
+                Some(title) => camel_case(title),
+                None => format!("Constr{index}"),
+            };
+            let context = format!("{type_name}::{variant_name}");
+            if !variant_names.insert(variant_name.clone()) {
+                return Err(CodegenError::NameClash(context))
+            }
+            variants.push_str(&doc_comment(schema, "    "));
+            variants.push_str(&index_attr(index, position as u64, "    "));
+            let body = match self.fields(&context, fields, "")? {
+                Fields::Named(fields) => format!(" {{\n{}    }}", indent(&fields)),
+                Fields::Positional(fields) => format!("({fields})"),
+                Fields::Unit => String::new(),
+            };
+            variants.push_str(&format!("    {variant_name}{body},\n"));
+        }
+        Ok(format!("{derives}pub enum {type_name} {{\n{variants}}}\n"))
+    }
+
+    fn fields(
+        &self,
+        context: &str,
+        fields: &[Schema],
+        visibility: &str,
+    ) -> CodegenResult<Fields> {
+        if fields.is_empty() {
+            return Ok(Fields::Unit)
+        }
+        let types = fields
+            .iter()
+            .map(|field| self.field_type(field, context))
+            .collect::<CodegenResult<Vec<_>>>()?;
+        if fields.iter().any(|field| field.title.is_none()) {
+            let positional = types
+                .iter()
+                .map(|field_type| format!("{visibility}{field_type}"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            return Ok(Fields::Positional(positional))
+        }
+        let mut names = HashSet::new();
+        let mut named = String::new();
+        for (field, field_type) in fields.iter().zip(types) {
+            let title = field.title.as_deref().expect("Checked all have titles");
+            let name = snake_case(title);
+            if !names.insert(name.clone()) {
+                return Err(CodegenError::NameClash(format!("{context}::{name}")))
+            }
+            named.push_str(&doc_comment(field, "    "));
+            named.push_str(&format!("    {visibility}{name}: {field_type},\n"));
+        }
+        Ok(Fields::Named(named))
+    }
+
+    /// Newtype over a list, encoded as a Plutus array
+    fn list(&self, type_name: &str, item: &Schema) -> CodegenResult<String> {
+        let item_type = self.field_type(item, type_name)?;
+        Ok(format!(
+            "\
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct {type_name}(pub ::std::vec::Vec<{item_type}>);
+
+impl ::core::convert::From<{type_name}> for {PLUTUS_DATA}::PlutusData {{
+    fn from(value: {type_name}) -> Self {{
+        let items = value.0.into_iter().map(::core::convert::Into::into).collect();
+        {PLUTUS_DATA}::PlutusData::Array(items)
+    }}
+}}
+
+impl ::core::convert::TryFrom<{PLUTUS_DATA}::PlutusData> for {type_name} {{
+    type Error = {SCRIPT_ERROR};
+
+    // The items might fail with a `ScriptError` already
+    #[allow(clippy::useless_conversion)]
+    fn try_from(data: {PLUTUS_DATA}::PlutusData) -> ::core::result::Result<Self, Self::Error> {{
+        match data {{
+            {PLUTUS_DATA}::PlutusData::Array(items) => {{
+                let items = items
+                    .into_iter()
+                    .map(|item| {{
+                        <{item_type} as ::core::convert::TryFrom<{PLUTUS_DATA}::PlutusData>>::try_from(item)
+                            .map_err({SCRIPT_ERROR}::from)
+                    }})
+                    .collect::<::core::result::Result<_, {SCRIPT_ERROR}>>()?;
+                ::core::result::Result::Ok({type_name}(items))
+            }}
+            other => ::core::result::Result::Err({SCRIPT_ERROR}::DatumDeserialization(
+                ::std::format!(\"Expected a list for {type_name}, found {{:?}}\", other),
+            )),
+        }}
+    }}
+}}
+"
+        ))
+    }
+
+    /// Tuple, encoded as a Plutus array with an item per position
+    fn tuple(&self, type_name: &str, items: &[Schema]) -> CodegenResult<String> {
+        let item_types = items
+            .iter()
+            .map(|item| self.field_type(item, type_name))
+            .collect::<CodegenResult<Vec<_>>>()?;
+        let count = item_types.len();
+        let fields = item_types
+            .iter()
+            .map(|item_type| format!("pub {item_type}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let into_items = (0..count)
+            .map(|position| format!("value.{position}.into()"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let bindings = (0..count)
+            .map(|position| format!("item_{position}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let from_items = item_types
+            .iter()
+            .enumerate()
+            .map(|(position, item_type)| {
+                format!(
+                    "<{item_type} as ::core::convert::TryFrom<{PLUTUS_DATA}::PlutusData>>::try_from(item_{position})?"
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",\n                    ");
+        Ok(format!(
+            "\
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct {type_name}({fields});
+
+impl ::core::convert::From<{type_name}> for {PLUTUS_DATA}::PlutusData {{
+    fn from(value: {type_name}) -> Self {{
+        {PLUTUS_DATA}::PlutusData::Array(::std::vec![{into_items}])
+    }}
+}}
+
+impl ::core::convert::TryFrom<{PLUTUS_DATA}::PlutusData> for {type_name} {{
+    type Error = {SCRIPT_ERROR};
+
+    fn try_from(data: {PLUTUS_DATA}::PlutusData) -> ::core::result::Result<Self, Self::Error> {{
+        match data {{
+            {PLUTUS_DATA}::PlutusData::Array(items) => {{
+                let [{bindings}]: [{PLUTUS_DATA}::PlutusData; {count}] =
+                    ::core::convert::TryInto::try_into(items).map_err(
+                        |items: ::std::vec::Vec<{PLUTUS_DATA}::PlutusData>| {{
+                            {SCRIPT_ERROR}::DatumDeserialization(::std::format!(
+                                \"{type_name} expects {count} items, found {{}}\",
+                                items.len(),
+                            ))
+                        }},
+                    )?;
+                ::core::result::Result::Ok({type_name}(
+                    {from_items},
+                ))
+            }}
+            other => ::core::result::Result::Err({SCRIPT_ERROR}::DatumDeserialization(
+                ::std::format!(\"Expected a list for {type_name}, found {{:?}}\", other),
+            )),
+        }}
+    }}
+}}
+"
+        ))
+    }
+
+    /// Newtype over key value pairs, encoded as a Plutus map
+    fn map(
+        &self,
+        type_name: &str,
+        keys: &Schema,
+        values: &Schema,
+    ) -> CodegenResult<String> {
+        let key_type = self.field_type(keys, type_name)?;
+        let value_type = self.field_type(values, type_name)?;
+        Ok(format!(
+            "\
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct {type_name}(pub ::std::vec::Vec<({key_type}, {value_type})>);
+
+impl ::core::convert::From<{type_name}> for {PLUTUS_DATA}::PlutusData {{
+    fn from(value: {type_name}) -> Self {{
+        let entries = value
+            .0
+            .into_iter()
+            .map(|(key, value)| (key.into(), value.into()))
+            .collect();
+        {PLUTUS_DATA}::PlutusData::Map(entries)
+    }}
+}}
+
+impl ::core::convert::TryFrom<{PLUTUS_DATA}::PlutusData> for {type_name} {{
+    type Error = {SCRIPT_ERROR};
+
+    fn try_from(data: {PLUTUS_DATA}::PlutusData) -> ::core::result::Result<Self, Self::Error> {{
+        match data {{
+            {PLUTUS_DATA}::PlutusData::Map(entries) => {{
+                let entries = entries
+                    .into_iter()
+                    .map(|(key, value)| -> ::core::result::Result<_, {SCRIPT_ERROR}> {{
+                        let key = <{key_type} as ::core::convert::TryFrom<{PLUTUS_DATA}::PlutusData>>::try_from(key)?;
+                        let value = <{value_type} as ::core::convert::TryFrom<{PLUTUS_DATA}::PlutusData>>::try_from(value)?;
+                        ::core::result::Result::Ok((key, value))
+                    }})
+                    .collect::<::core::result::Result<_, {SCRIPT_ERROR}>>()?;
+                ::core::result::Result::Ok({type_name}(entries))
+            }}
+            other => ::core::result::Result::Err({SCRIPT_ERROR}::DatumDeserialization(
+                ::std::format!(\"Expected a map for {type_name}, found {{:?}}\", other),
+            )),
+        }}
+    }}
+}}
+"
+        ))
+    }
+
+    fn validators(&self) -> CodegenResult<Vec<String>> {
+        let mut fn_names = HashSet::new();
+        self.blueprint
+            .validators()
+            .iter()
+            .map(|validator| {
+                let fn_name = snake_case(validator.title());
+                if !fn_names.insert(fn_name.clone()) {
+                    return Err(CodegenError::NameClash(fn_name))
+                }
+                self.validator(&fn_name, validator)
+            })
+            .collect()
+    }
+
+    fn validator(
+        &self,
+        fn_name: &str,
+        validator: &ValidatorBlueprint,
+    ) -> CodegenResult<String> {
+        let title = validator.title();
+        let redeemer = self.field_type(&validator.redeemer().schema, title)?;
+        let params = validator
+            .parameters()
+            .iter()
+            .map(|param| self.field_type(&param.schema, title))
+            .collect::<CodegenResult<Vec<_>>>()?;
+        let (kind, script_type) = match (validator.datum(), params.as_slice()) {
+            (Some(datum), []) => {
+                let datum = self.field_type(&datum.schema, title)?;
+                (
+                    "Validator",
+                    format!("{PLUTUS_VALIDATOR}::PlutusValidator<{datum}, {redeemer}>"),
+                )
+            }
+            (Some(datum), [one]) => {
+                let datum = self.field_type(&datum.schema, title)?;
+                (
+                    "Validator",
+                    format!(
+                        "{PLUTUS_VALIDATOR}::OneParamRawValidator<{one}, {datum}, {redeemer}>"
+                    ),
+                )
+            }
+            (None, []) => (
+                "Minting policy",
+                format!("{PLUTUS_MINTING_POLICY}::PlutusMintingPolicy<{redeemer}>"),
+            ),
+            (None, [one]) => (
+                "Minting policy",
+                format!("{PLUTUS_MINTING_POLICY}::OneParamPlutusPolicy<{one}, {redeemer}>"),
+            ),
+            (None, [one, two]) => (
+                "Minting policy",
+                format!(
+                    "{PLUTUS_MINTING_POLICY}::TwoParamMintingPolicy<{one}, {two}, {redeemer}>"
+                ),
+            ),
+            (Some(datum), params) => {
+                let datum = self.field_type(&datum.schema, title)?;
+                let params = params.join(", ");
+                (
+                    "Validator",
+                    format!(
+                        "{PARAMETERIZED}::ParameterizedValidator<({params}), {datum}, {redeemer}>"
+                    ),
+                )
+            }
+            (None, params) => {
+                let params = params.join(", ");
+                (
+                    "Minting policy",
+                    format!("{PARAMETERIZED}::ParameterizedPolicy<({params}), {redeemer}>"),
+                )
+            }
+        };
+        let script_path = script_type
+            .split('<')
+            .next()
+            .expect("Split always yields at least once");
+        let param_docs: String = validator
+            .parameters()
+            .iter()
+            .filter_map(|param| param.title.as_deref())
+            .map(|param| format!("/// - `{param}`\n"))
+            .collect();
+        let param_docs = if param_docs.is_empty() {
+            param_docs
+        } else {
+            format!("///\n/// Parameters, in the order they have to be applied:\n{param_docs}")
+        };
+        Ok(format!(
+            "\
+/// {kind} `{title}` from the Blueprint
+{param_docs}pub fn {fn_name}() -> {RAW_SCRIPT}::RawPlutusScriptResult<{script_type}> {{
+    let blueprint = {RAW_SCRIPT}::BlueprintFile::from_json(BLUEPRINT)?;
+    let validator = blueprint.get_validator({title:?}).ok_or_else(|| {{
+        {RAW_SCRIPT}::PlutusScriptError::Blueprint(::std::format!(
+            \"Validator not listed in Blueprint: {{:?}}\",
+            {title:?},
+        ))
+    }})?;
+    {script_path}::from_blueprint(validator)
+}}
+"
+        ))
+    }
+}
+
+enum Fields {
+    Named(String),
+    Positional(String),
+    Unit,
+}
+
+fn unsupported(name: &str, reason: &str) -> CodegenError {
+    CodegenError::Unsupported {
+        name: name.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+/// Rust type for schemas that map to existing types, rather than to a declared one
+fn builtin_type(schema: &Schema) -> Option<String> {
+    match &schema.kind {
+        SchemaKind::Typed(DataType::Integer | DataType::BuiltinInteger) => {
+            Some("i64".into())
+        }
+        SchemaKind::Typed(DataType::Bytes | DataType::BuiltinBytes) => {
+            Some("::std::vec::Vec<u8>".into())
+        }
+        SchemaKind::Typed(DataType::BuiltinUnit) => Some("()".into()),
+        SchemaKind::Typed(DataType::BuiltinBoolean) => Some("bool".into()),
+        SchemaKind::Opaque {} => Some(format!("{PLUTUS_DATA}::PlutusData")),
+        SchemaKind::AnyOf { any_of } => {
+            let nullary: Vec<_> = any_of
+                .iter()
+                .map(|schema| match &schema.kind {
+                    SchemaKind::Typed(DataType::Constructor { index, fields })
+                        if fields.is_empty() =>
+                    {
+                        Some((*index, schema.title.as_deref()))
+                    }
+                    _ => None,
+                })
+                .collect();
+            match nullary.as_slice() {
+                // Like Aiken's `Void`
+                [Some((0, _))] => Some("()".into()),
+                [Some((0, Some("False"))), Some((1, Some("True")))] => {
+                    Some("bool".into())
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn constructor<'s>(
+    schema: &'s Schema,
+    type_name: &str,
+) -> CodegenResult<(u64, &'s [Schema])> {
+    match &schema.kind {
+        SchemaKind::Typed(DataType::Constructor { index, fields }) => {
+            Ok((*index, fields))
+        }
+        _ => Err(unsupported(
+            type_name,
+            "only constructors are supported in `anyOf`",
+        )),
+    }
+}
+
+fn data_derives() -> String {
+    format!(
+        "#[derive(Debug, Clone, PartialEq, Eq, {PLUTUS_DATA}::ToPlutusData, {PLUTUS_DATA}::FromPlutusData)]\n"
+    )
+}
+
+fn index_attr(index: u64, default: u64, indentation: &str) -> String {
+    if index == default {
+        String::new()
+    } else {
+        format!("{indentation}#[plutus(index = {index})]\n")
+    }
+}
+
+fn doc_comment(schema: &Schema, indentation: &str) -> String {
+    match &schema.description {
+        Some(description) => description
+            .lines()
+            .map(|line| {
+                format!(
+                    "{indentation}///{}{}\n",
+                    if line.is_empty() { "" } else { " " },
+                    line.trim_end()
+                )
+            })
+            .collect(),
+        None => String::new(),
+    }
+}
+
+fn indent(lines: &str) -> String {
+    lines.lines().map(|line| format!("    {line}\n")).collect()
+}
+
+/// Name of the type declared for definition `name`. It's the last path segment of the
+/// definition and of each of its type arguments, e.g. `Option$aiken/time/PosixTime` is
+/// `OptionPosixTime`, or the whole path if that's taken already
+fn unique_type_name(name: &str, taken: &mut HashSet<String>) -> CodegenResult<String> {
+    let short: String = name
+        .split('$')
+        .map(|part| camel_case(part.rsplit('/').next().unwrap_or(part)))
+        .collect();
+    if taken.insert(short.clone()) {
+        return Ok(short)
+    }
+    let long: String = name.split(['$', '/']).map(camel_case).collect();
+    if taken.insert(long.clone()) {
+        Ok(long)
+    } else {
+        Err(CodegenError::NameClash(long))
+    }
+}
+
+fn camel_case(name: &str) -> String {
+    let camel: String = name
+        .split(|c: char| !c.is_alphanumeric())
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect();
+    match camel.chars().next() {
+        Some(first) if first.is_alphabetic() => identifier(camel),
+        _ => format!("T{camel}"),
+    }
+}
+
+fn snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    let mut after_lowercase = false;
+    for c in name.chars() {
+        if !c.is_alphanumeric() {
+            if !snake.is_empty() && !snake.ends_with('_') {
+                snake.push('_');
+            }
+            after_lowercase = false;
+        } else if c.is_uppercase() {
+            if after_lowercase {
+                snake.push('_');
+            }
+            snake.extend(c.to_lowercase());
+            after_lowercase = false;
+        } else {
+            snake.push(c);
+            after_lowercase = true;
+        }
+    }
+    let snake = snake.trim_end_matches('_').to_string();
+    match snake.chars().next() {
+        Some(first) if first.is_alphabetic() => identifier(snake),
+        _ => format!("field_{snake}"),
+    }
+}
+
+fn identifier(name: String) -> String {
+    if RESERVED.contains(&name.as_str()) {
+        format!("{name}_")
+    } else if KEYWORDS.contains(&name.as_str()) {
+        format!("r#{name}")
+    } else {
+        name
+    }
+}
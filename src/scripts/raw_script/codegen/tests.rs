@@ -0,0 +1,288 @@
+#![allow(non_snake_case)]
+
+use super::*;
+use serde_json::{
+    json,
+    Value,
+};
+
+const MINT_NFT_BLUEPRINT: &str =
+    include_str!("../../../../nau-scripts/aiken/mint_nft/plutus.json");
+const CHECKING_BLUEPRINT: &str =
+    include_str!("../../../../sample-dApps/checking_account/checking/plutus.json");
+
+fn blueprint(validators: Value) -> String {
+    json!({
+        "preamble": { "title": "free-honey/vault", "version": "0.0.0", "plutusVersion": "v2" },
+        "validators": validators,
+        "definitions": {
+            "ByteArray": { "dataType": "bytes" },
+            "Int": { "dataType": "integer" },
+            "Data": { "title": "Data", "description": "Any Plutus data." },
+            "Void": {
+                "title": "Unit",
+                "anyOf": [{ "dataType": "constructor", "index": 0, "fields": [] }],
+            },
+            "Bool": {
+                "title": "Bool",
+                "anyOf": [
+                    { "title": "False", "dataType": "constructor", "index": 0, "fields": [] },
+                    { "title": "True", "dataType": "constructor", "index": 1, "fields": [] },
+                ],
+            },
+            "aiken/time/PosixTime": { "$ref": "#/definitions/Int" },
+            "List$ByteArray": { "dataType": "list", "items": { "$ref": "#/definitions/ByteArray" } },
+            "Tuple$Int_ByteArray": {
+                "title": "Tuple",
+                "dataType": "list",
+                "items": [{ "$ref": "#/definitions/Int" }, { "$ref": "#/definitions/ByteArray" }],
+            },
+            "aiken/dict/Dict$ByteArray_Int": {
+                "title": "Dict",
+                "dataType": "map",
+                "keys": { "$ref": "#/definitions/ByteArray" },
+                "values": { "$ref": "#/definitions/Int" },
+            },
+            "Option$aiken/time/PosixTime": {
+                "title": "Optional",
+                "anyOf": [
+                    {
+                        "title": "Some",
+                        "dataType": "constructor",
+                        "index": 0,
+                        "fields": [{ "$ref": "#/definitions/aiken~1time~1PosixTime" }],
+                    },
+                    { "title": "None", "dataType": "constructor", "index": 1, "fields": [] },
+                ],
+            },
+            "vault/Action": {
+                "title": "Action",
+                "anyOf": [
+                    {
+                        "title": "Deposit",
+                        "dataType": "constructor",
+                        "index": 0,
+                        "fields": [{ "title": "amount", "$ref": "#/definitions/Int" }],
+                    },
+                    {
+                        "title": "Withdraw",
+                        "description": "Take funds out of the vault",
+                        "dataType": "constructor",
+                        "index": 1,
+                        "fields": [
+                            { "title": "amount", "$ref": "#/definitions/Int" },
+                            { "title": "close", "$ref": "#/definitions/Bool" },
+                        ],
+                    },
+                    { "title": "Close", "dataType": "constructor", "index": 3, "fields": [] },
+                ],
+            },
+            "vault/VaultDatum": {
+                "title": "VaultDatum",
+                "description": "State of a vault",
+                "anyOf": [{
+                    "title": "VaultDatum",
+                    "dataType": "constructor",
+                    "index": 0,
+                    "fields": [
+                        { "title": "owner", "$ref": "#/definitions/ByteArray" },
+                        { "title": "beneficiaries", "$ref": "#/definitions/List$ByteArray" },
+                        { "title": "limits", "$ref": "#/definitions/aiken~1dict~1Dict$ByteArray_Int" },
+                        { "title": "deadline", "$ref": "#/definitions/Option$aiken~1time~1PosixTime" },
+                        { "title": "last_payment", "$ref": "#/definitions/Tuple$Int_ByteArray" },
+                        { "title": "type", "$ref": "#/definitions/Data" },
+                    ],
+                }],
+            },
+        },
+    })
+    .to_string()
+}
+
+fn validator(title: &str, datum: Option<&str>, params: &[&str]) -> Value {
+    let argument = |definition: &str| json!({ "schema": { "$ref": format!("#/definitions/{definition}") } });
+    let parameters: Vec<_> = params.iter().map(|param| argument(param)).collect();
+    let mut validator = json!({
+        "title": title,
+        "redeemer": argument("vault~1Action"),
+        "parameters": parameters,
+        "compiledCode": "4e4d01000033222220051200120011",
+        "hash": "00",
+    });
+    if let Some(datum) = datum {
+        validator["datum"] = argument(datum);
+    }
+    validator
+}
+
+fn vault_bindings() -> String {
+    let validators = json!([
+        validator("vault.spend", Some("vault~1VaultDatum"), &[]),
+        validator("vault_tokens.mint", None, &["ByteArray", "Int"]),
+    ]);
+    generate_bindings(&blueprint(validators)).unwrap()
+}
+
+#[test]
+fn generate_bindings__declares_records_for_single_constructors() {
+    // When
+    let bindings = generate_bindings(MINT_NFT_BLUEPRINT).unwrap();
+
+    // Then
+    let expected = "\
+#[derive(Debug, Clone, PartialEq, Eq, ::naumachia::scripts::plutus_validator::plutus_data::ToPlutusData, ::naumachia::scripts::plutus_validator::plutus_data::FromPlutusData)]
+pub struct OutputReference {
+    pub transaction_id: TransactionId,
+    pub output_index: i64,
+}
+";
+    assert!(bindings.contains(expected), "{bindings}");
+    assert!(bindings
+        .contains("pub struct TransactionId {\n    pub hash: ::std::vec::Vec<u8>,\n}"));
+    // The builtin types aren't redeclared
+    assert!(!bindings.contains("struct Void"));
+    assert!(!bindings.contains("struct Int"));
+}
+
+#[test]
+fn generate_bindings__declares_constructors_for_validators() {
+    // When
+    let bindings = generate_bindings(MINT_NFT_BLUEPRINT).unwrap();
+
+    // Then
+    assert!(bindings.contains(
+        "pub fn one_shot_nft_mint() -> ::naumachia::scripts::raw_script::RawPlutusScriptResult<::naumachia::scripts::plutus_minting_policy::OneParamPlutusPolicy<OutputReference, ()>> {"
+    ));
+    assert!(bindings.contains("blueprint.get_validator(\"one_shot_nft.mint\")"));
+    assert!(bindings.contains("/// - `input_ref`"));
+}
+
+#[test]
+fn generate_bindings__picks_script_type_from_datum_and_parameters() {
+    // When
+    let bindings = generate_bindings(CHECKING_BLUEPRINT).unwrap();
+
+    // Then
+    assert!(bindings.contains("RawPlutusScriptResult<::naumachia::scripts::plutus_validator::PlutusValidator<CheckingAccountDatum, ()>>"));
+    assert!(bindings.contains(
+        "pub fn pull_validator_spend() -> ::naumachia::scripts::raw_script::RawPlutusScriptResult<::naumachia::scripts::plutus_validator::PlutusValidator<PullDatum, ()>>"
+    ));
+    assert!(bindings
+        .contains("TwoParamMintingPolicy<::std::vec::Vec<u8>, ::std::vec::Vec<u8>, ()>"));
+}
+
+#[test]
+fn generate_bindings__declares_enums_with_constructor_indices() {
+    // When
+    let bindings = vault_bindings();
+
+    // Then
+    let expected = "\
+pub enum Action {
+    Deposit {
+        amount: i64,
+    },
+    /// Take funds out of the vault
+    Withdraw {
+        amount: i64,
+        close: bool,
+    },
+    #[plutus(index = 3)]
+    Close,
+}
+";
+    assert!(bindings.contains(expected), "{bindings}");
+    assert!(bindings.contains("pub enum OptionPosixTime {\n    Some(i64),\n    None,\n}"));
+}
+
+#[test]
+fn generate_bindings__declares_lists_maps_and_tuples() {
+    // When
+    let bindings = vault_bindings();
+
+    // Then
+    let expected_datum = "\
+/// State of a vault
+#[derive(Debug, Clone, PartialEq, Eq, ::naumachia::scripts::plutus_validator::plutus_data::ToPlutusData, ::naumachia::scripts::plutus_validator::plutus_data::FromPlutusData)]
+pub struct VaultDatum {
+    pub owner: ::std::vec::Vec<u8>,
+    pub beneficiaries: ListByteArray,
+    pub limits: DictByteArrayInt,
+    pub deadline: OptionPosixTime,
+    pub last_payment: TupleIntByteArray,
+    pub r#type: ::naumachia::scripts::plutus_validator::plutus_data::PlutusData,
+}
+";
+    assert!(bindings.contains(expected_datum), "{bindings}");
+    assert!(bindings
+        .contains("pub struct ListByteArray(pub ::std::vec::Vec<::std::vec::Vec<u8>>);"));
+    assert!(bindings.contains(
+        "pub struct DictByteArrayInt(pub ::std::vec::Vec<(::std::vec::Vec<u8>, i64)>);"
+    ));
+    assert!(bindings
+        .contains("pub struct TupleIntByteArray(pub i64, pub ::std::vec::Vec<u8>);"));
+    assert!(bindings.contains("PlutusValidator<VaultDatum, Action>"));
+    assert!(bindings.contains("TwoParamMintingPolicy<::std::vec::Vec<u8>, i64, Action>"));
+}
+
+#[test]
+fn generate_bindings__uses_parameterized_scripts_for_more_parameters() {
+    // Given
+    let validators = json!([
+        validator("vault.spend", Some("Int"), &["Int", "ByteArray"]),
+        validator("vault_tokens.mint", None, &["Int", "Int", "ByteArray"]),
+    ]);
+
+    // When
+    let bindings = generate_bindings(&blueprint(validators)).unwrap();
+
+    // Then
+    assert!(bindings.contains(
+        "::naumachia::scripts::parameterized::ParameterizedValidator<(i64, ::std::vec::Vec<u8>), i64, Action>"
+    ));
+    assert!(bindings.contains(
+        "::naumachia::scripts::parameterized::ParameterizedPolicy<(i64, i64, ::std::vec::Vec<u8>), Action>"
+    ));
+    assert!(bindings.contains(
+        "::naumachia::scripts::parameterized::ParameterizedPolicy::from_blueprint(validator)"
+    ));
+}
+
+#[test]
+fn generate_bindings__rejects_unknown_definitions() {
+    // Given
+    let validators = json!([validator("vault.spend", Some("vault~1Missing"), &[])]);
+
+    // When
+    let res = generate_bindings(&blueprint(validators));
+
+    // Then
+    assert!(matches!(res, Err(CodegenError::UnknownDefinition(_))));
+}
+
+#[test]
+fn unique_type_name__falls_back_to_full_path() {
+    // Given
+    let mut taken = HashSet::new();
+
+    // When
+    let first = unique_type_name("aiken/transaction/Datum", &mut taken).unwrap();
+    let second = unique_type_name("vault/Datum", &mut taken).unwrap();
+
+    // Then
+    assert_eq!(first, "Datum");
+    assert_eq!(second, "VaultDatum");
+}
+
+#[test]
+fn snake_case__makes_valid_identifiers() {
+    assert_eq!(
+        snake_case("checking_account_validator.spend"),
+        "checking_account_validator_spend"
+    );
+    assert_eq!(snake_case("spendTokenPolicy"), "spend_token_policy");
+    assert_eq!(snake_case("_redeemer"), "redeemer");
+    assert_eq!(snake_case("type"), "r#type");
+    assert_eq!(snake_case("self"), "self_");
+    assert_eq!(snake_case("0"), "field_0");
+}
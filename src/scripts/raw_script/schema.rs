@@ -0,0 +1,438 @@
+use crate::scripts::plutus_validator::plutus_data::PlutusData;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use std::{
+    collections::BTreeMap,
+    fmt,
+};
+use thiserror::Error;
+
+#[cfg(test)]
+mod tests;
+
+const DEFINITIONS_PREFIX: &str = "#/definitions/";
+
+/// A named value a validator takes, i.e. its datum, redeemer or one of its parameters
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Argument {
+    /// Name of the argument
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Description of the argument
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Shape of the argument
+    pub schema: Schema,
+}
+
+/// CIP-0057 schema describing the shape of some Plutus data
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Schema {
+    /// Name of the schema. For the fields of a constructor this is the field name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Description of the schema
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// What data the schema allows
+    #[serde(flatten)]
+    pub kind: SchemaKind,
+}
+
+impl Schema {
+    /// Check that `data` has the shape the schema describes, following `$ref`s into `definitions`
+    pub fn validate(
+        &self,
+        data: &PlutusData,
+        definitions: &Definitions,
+    ) -> Result<(), SchemaMismatch> {
+        match &self.resolved(definitions).kind {
+            SchemaKind::Reference { reference } => Err(SchemaMismatch::new(
+                unresolved(reference, definitions),
+                data,
+            )),
+            SchemaKind::AnyOf { any_of } => validate_any_of(any_of, data, definitions),
+            SchemaKind::Typed(data_type) => data_type.validate(data, definitions),
+            SchemaKind::Opaque {} => Ok(()),
+        }
+    }
+
+    fn expected(&self, definitions: &Definitions) -> String {
+        match &self.resolved(definitions).kind {
+            SchemaKind::Reference { reference } => unresolved(reference, definitions),
+            SchemaKind::AnyOf { any_of } => {
+                let alternatives: Vec<_> = any_of
+                    .iter()
+                    .map(|schema| schema.expected(definitions))
+                    .collect();
+                format!("one of {}", alternatives.join(", "))
+            }
+            SchemaKind::Typed(data_type) => data_type.expected(),
+            SchemaKind::Opaque {} => "any data".to_string(),
+        }
+    }
+
+    /// The schema `$ref`s lead to, or the last `$ref` if it can't be followed
+    fn resolved<'a>(&'a self, definitions: &'a Definitions) -> &'a Schema {
+        let mut schema = self;
+        // Bounded in case of definitions that only refer to each other
+        for _ in 0..=definitions.iter().count() {
+            match &schema.kind {
+                SchemaKind::Reference { reference } => {
+                    match definitions.resolve(reference) {
+                        Some((_, definition)) => schema = definition,
+                        None => break,
+                    }
+                }
+                _ => break,
+            }
+        }
+        schema
+    }
+}
+
+fn unresolved(reference: &str, definitions: &Definitions) -> String {
+    match definitions.resolve(reference) {
+        Some(_) => format!("non-cyclic definition {reference:?}"),
+        None => format!("missing definition {reference:?}"),
+    }
+}
+
+fn validate_any_of(
+    any_of: &[Schema],
+    data: &PlutusData,
+    definitions: &Definitions,
+) -> Result<(), SchemaMismatch> {
+    // Point at what's wrong inside the constructor rather than just saying none of them matched
+    if let PlutusData::Constr(constr) = data {
+        let same_index = any_of.iter().find(|schema| {
+            matches!(
+                &schema.kind,
+                SchemaKind::Typed(DataType::Constructor { index, .. }) if *index == constr.constr
+            )
+        });
+        if let Some(schema) = same_index {
+            return schema.validate(data, definitions);
+        }
+    }
+    match any_of {
+        [schema] => schema.validate(data, definitions),
+        _ if any_of
+            .iter()
+            .any(|schema| schema.validate(data, definitions).is_ok()) =>
+        {
+            Ok(())
+        }
+        _ => {
+            let alternatives: Vec<_> = any_of
+                .iter()
+                .map(|schema| schema.expected(definitions))
+                .collect();
+            Err(SchemaMismatch::new(
+                format!("one of {}", alternatives.join(", ")),
+                data,
+            ))
+        }
+    }
+}
+
+/// The different kinds of [`Schema`]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum SchemaKind {
+    /// Same as the schema in the blueprint's [`Definitions`] referred to
+    Reference {
+        /// JSON pointer to the definition, e.g. `#/definitions/ByteArray`
+        #[serde(rename = "$ref")]
+        reference: String,
+    },
+    /// Any one of the schemas, usually the [`DataType::Constructor`]s of a sum type
+    AnyOf {
+        /// The allowed schemas
+        #[serde(rename = "anyOf")]
+        any_of: Vec<Schema>,
+    },
+    /// A specific type of data
+    Typed(DataType),
+    /// Any Plutus data at all
+    Opaque {},
+}
+
+/// Types of data a [`Schema`] can describe. The ones starting with `#` are UPLC builtins that
+/// can only be used as parameters, not as datums or redeemers
+#[allow(missing_docs)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "dataType")]
+pub enum DataType {
+    #[serde(rename = "integer")]
+    Integer,
+    #[serde(rename = "bytes")]
+    Bytes,
+    #[serde(rename = "list")]
+    List { items: Items },
+    #[serde(rename = "map")]
+    Map {
+        keys: Box<Schema>,
+        values: Box<Schema>,
+    },
+    /// A `Constr` with `index` whose fields match `fields`, in order
+    #[serde(rename = "constructor")]
+    Constructor { index: u64, fields: Vec<Schema> },
+    #[serde(rename = "#unit")]
+    BuiltinUnit,
+    #[serde(rename = "#boolean")]
+    BuiltinBoolean,
+    #[serde(rename = "#integer")]
+    BuiltinInteger,
+    #[serde(rename = "#bytes")]
+    BuiltinBytes,
+    #[serde(rename = "#string")]
+    BuiltinString,
+    #[serde(rename = "#pair")]
+    BuiltinPair {
+        left: Box<Schema>,
+        right: Box<Schema>,
+    },
+    #[serde(rename = "#list")]
+    BuiltinList { items: Items },
+}
+
+impl DataType {
+    fn validate(
+        &self,
+        data: &PlutusData,
+        definitions: &Definitions,
+    ) -> Result<(), SchemaMismatch> {
+        match (self, data) {
+            (DataType::Integer | DataType::BuiltinInteger, PlutusData::BigInt(_)) => {
+                Ok(())
+            }
+            (DataType::Bytes | DataType::BuiltinBytes, PlutusData::BoundedBytes(_)) => {
+                Ok(())
+            }
+            (
+                DataType::List { items } | DataType::BuiltinList { items },
+                PlutusData::Array(elements),
+            ) => items.validate(elements, definitions),
+            (DataType::Map { keys, values }, PlutusData::Map(entries)) => {
+                for (entry, (key, value)) in entries.iter().enumerate() {
+                    keys.validate(key, definitions)
+                        .map_err(|e| e.within(PathSegment::Key(entry)))?;
+                    values
+                        .validate(value, definitions)
+                        .map_err(|e| e.within(PathSegment::Value(entry)))?;
+                }
+                Ok(())
+            }
+            (DataType::Constructor { index, fields }, PlutusData::Constr(constr))
+                if constr.constr == *index =>
+            {
+                if constr.fields.len() != fields.len() {
+                    return Err(SchemaMismatch {
+                        path: Vec::new(),
+                        expected: format!(
+                            "constructor {index} with {} fields",
+                            fields.len()
+                        ),
+                        found: format!(
+                            "constructor {index} with {} fields",
+                            constr.fields.len()
+                        ),
+                    });
+                }
+                for (field, (schema, data)) in
+                    fields.iter().zip(&constr.fields).enumerate()
+                {
+                    schema.validate(data, definitions).map_err(|e| {
+                        e.within(PathSegment::Field {
+                            constructor: *index,
+                            field,
+                        })
+                    })?;
+                }
+                Ok(())
+            }
+            _ => Err(SchemaMismatch::new(self.expected(), data)),
+        }
+    }
+
+    fn expected(&self) -> String {
+        match self {
+            DataType::Integer => "integer".to_string(),
+            DataType::Bytes => "bytes".to_string(),
+            DataType::List { .. } => "list".to_string(),
+            DataType::Map { .. } => "map".to_string(),
+            DataType::Constructor { index, .. } => format!("constructor {index}"),
+            DataType::BuiltinUnit => "builtin unit".to_string(),
+            DataType::BuiltinBoolean => "builtin boolean".to_string(),
+            DataType::BuiltinInteger => "integer".to_string(),
+            DataType::BuiltinBytes => "bytes".to_string(),
+            DataType::BuiltinString => "builtin string".to_string(),
+            DataType::BuiltinPair { .. } => "builtin pair".to_string(),
+            DataType::BuiltinList { .. } => "list".to_string(),
+        }
+    }
+}
+
+/// Items of a list: either all of the same schema, or one schema per position for tuples
+#[allow(missing_docs)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum Items {
+    Homogeneous(Box<Schema>),
+    Tuple(Vec<Schema>),
+}
+
+impl Items {
+    fn validate(
+        &self,
+        elements: &[PlutusData],
+        definitions: &Definitions,
+    ) -> Result<(), SchemaMismatch> {
+        match self {
+            Items::Homogeneous(schema) => {
+                for (item, data) in elements.iter().enumerate() {
+                    schema
+                        .validate(data, definitions)
+                        .map_err(|e| e.within(PathSegment::Item(item)))?;
+                }
+                Ok(())
+            }
+            Items::Tuple(schemas) => {
+                if schemas.len() != elements.len() {
+                    return Err(SchemaMismatch {
+                        path: Vec::new(),
+                        expected: format!("list of {} items", schemas.len()),
+                        found: format!("list of {} items", elements.len()),
+                    });
+                }
+                for (item, (schema, data)) in schemas.iter().zip(elements).enumerate() {
+                    schema
+                        .validate(data, definitions)
+                        .map_err(|e| e.within(PathSegment::Item(item)))?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Named schemas of a blueprint that other schemas refer to with `$ref`
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct Definitions(BTreeMap<String, Schema>);
+
+impl Definitions {
+    /// Get the definition named `name`, e.g. `aiken/transaction/OutputReference`
+    pub fn get(&self, name: &str) -> Option<&Schema> {
+        self.0.get(name)
+    }
+
+    /// Get the name and definition a `$ref` like `#/definitions/aiken~1transaction~1OutputReference`
+    /// points to
+    pub fn resolve(&self, reference: &str) -> Option<(&str, &Schema)> {
+        let name = definition_name(reference)?;
+        self.0
+            .get_key_value(&name)
+            .map(|(name, schema)| (name.as_str(), schema))
+    }
+
+    /// Iterate over the names and definitions
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Schema)> {
+        self.0.iter().map(|(name, schema)| (name.as_str(), schema))
+    }
+}
+
+/// Unescape the definition name a `$ref` points to, following JSON pointer escaping
+pub fn definition_name(reference: &str) -> Option<String> {
+    let escaped = reference.strip_prefix(DEFINITIONS_PREFIX)?;
+    Some(escaped.replace("~1", "/").replace("~0", "~"))
+}
+
+/// Error from checking some Plutus data against a [`Schema`]. Displays as e.g.
+/// `field 2 of constructor 1 expected bytes, found integer`
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub struct SchemaMismatch {
+    /// Where in the data the mismatch is, outermost first. Empty if it's the data itself
+    pub path: Vec<PathSegment>,
+    /// What the schema allows there
+    pub expected: String,
+    /// What was found there instead
+    pub found: String,
+}
+
+impl SchemaMismatch {
+    fn new(expected: String, data: &PlutusData) -> Self {
+        SchemaMismatch {
+            path: Vec::new(),
+            expected,
+            found: describe(data),
+        }
+    }
+
+    fn within(mut self, segment: PathSegment) -> Self {
+        self.path.insert(0, segment);
+        self
+    }
+}
+
+impl fmt::Display for SchemaMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path: Vec<_> = self.path.iter().rev().map(ToString::to_string).collect();
+        match path.as_slice() {
+            [] => write!(f, "expected {}, found {}", self.expected, self.found),
+            _ => write!(
+                f,
+                "{} expected {}, found {}",
+                path.join(" in "),
+                self.expected,
+                self.found
+            ),
+        }
+    }
+}
+
+/// A step into some Plutus data, locating a [`SchemaMismatch`] or a [`DataDifference`]
+///
+/// [`DataDifference`]: crate::scripts::plutus_validator::plutus_data::pretty::DataDifference
+#[allow(missing_docs)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathSegment {
+    Field {
+        constructor: u64,
+        field: usize,
+    },
+    Item(usize),
+    Key(usize),
+    Value(usize),
+    /// The value of a map entry, by its key
+    Entry(PlutusData),
+}
+
+impl fmt::Display for PathSegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathSegment::Field { constructor, field } => {
+                write!(f, "field {field} of constructor {constructor}")
+            }
+            PathSegment::Item(item) => write!(f, "item {item}"),
+            PathSegment::Key(entry) => write!(f, "key of entry {entry}"),
+            PathSegment::Value(entry) => write!(f, "value of entry {entry}"),
+            PathSegment::Entry(key) => {
+                write!(f, "value at key {}", key.pretty().compact())
+            }
+        }
+    }
+}
+
+fn describe(data: &PlutusData) -> String {
+    match data {
+        PlutusData::Constr(constr) => format!("constructor {}", constr.constr),
+        PlutusData::Map(_) => "map".to_string(),
+        PlutusData::BigInt(_) => "integer".to_string(),
+        PlutusData::BoundedBytes(_) => "bytes".to_string(),
+        PlutusData::Array(_) => "list".to_string(),
+    }
+}
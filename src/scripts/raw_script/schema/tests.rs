@@ -0,0 +1,309 @@
+#![allow(non_snake_case)]
+
+use super::*;
+use crate::scripts::{
+    plutus_validator::plutus_data::Constr,
+    raw_script::BlueprintFile,
+};
+use serde_json::json;
+
+const MINT_NFT_BLUEPRINT: &str =
+    include_str!("../../../../nau-scripts/aiken/mint_nft/plutus.json");
+
+fn schema(value: serde_json::Value) -> Schema {
+    serde_json::from_value(value).unwrap()
+}
+
+#[test]
+fn blueprint__parses_typed_arguments_and_definitions() {
+    // When
+    let blueprint = BlueprintFile::from_json(MINT_NFT_BLUEPRINT).unwrap();
+
+    // Then
+    let validator = blueprint.get_validator("one_shot_nft.mint").unwrap();
+    assert!(validator.datum().is_none());
+    let param = validator.parameters().first().unwrap();
+    assert_eq!(param.title.as_deref(), Some("input_ref"));
+    let reference = match &param.schema.kind {
+        SchemaKind::Reference { reference } => reference,
+        other => panic!("Expected a reference, found {other:?}"),
+    };
+    let (name, definition) = blueprint.definitions().resolve(reference).unwrap();
+    assert_eq!(name, "aiken/transaction/OutputReference");
+    let any_of = match &definition.kind {
+        SchemaKind::AnyOf { any_of } => any_of,
+        other => panic!("Expected constructors, found {other:?}"),
+    };
+    let expected = DataType::Constructor {
+        index: 0,
+        fields: vec![
+            schema(json!({
+                "title": "transaction_id",
+                "$ref": "#/definitions/aiken~1transaction~1TransactionId",
+            })),
+            schema(json!({ "title": "output_index", "$ref": "#/definitions/Int" })),
+        ],
+    };
+    assert_eq!(any_of[0].kind, SchemaKind::Typed(expected));
+}
+
+#[test]
+fn schema__parses_lists_maps_and_tuples() {
+    // Given
+    let int = schema(json!({ "$ref": "#/definitions/Int" }));
+    let bytes = schema(json!({ "$ref": "#/definitions/ByteArray" }));
+
+    // When
+    let list =
+        schema(json!({ "dataType": "list", "items": { "$ref": "#/definitions/Int" } }));
+    let tuple = schema(json!({
+        "title": "Tuple",
+        "dataType": "list",
+        "items": [{ "$ref": "#/definitions/Int" }, { "$ref": "#/definitions/ByteArray" }],
+    }));
+    let map = schema(json!({
+        "dataType": "map",
+        "keys": { "$ref": "#/definitions/ByteArray" },
+        "values": { "$ref": "#/definitions/Int" },
+    }));
+
+    // Then
+    let expected_list = DataType::List {
+        items: Items::Homogeneous(Box::new(int.clone())),
+    };
+    assert_eq!(list.kind, SchemaKind::Typed(expected_list));
+    let expected_tuple = DataType::List {
+        items: Items::Tuple(vec![int.clone(), bytes.clone()]),
+    };
+    assert_eq!(tuple.title.as_deref(), Some("Tuple"));
+    assert_eq!(tuple.kind, SchemaKind::Typed(expected_tuple));
+    let expected_map = DataType::Map {
+        keys: Box::new(bytes),
+        values: Box::new(int),
+    };
+    assert_eq!(map.kind, SchemaKind::Typed(expected_map));
+}
+
+#[test]
+fn schema__without_data_type_is_opaque() {
+    // When
+    let data = schema(json!({ "title": "Data", "description": "Any Plutus data." }));
+
+    // Then
+    assert_eq!(data.title.as_deref(), Some("Data"));
+    assert_eq!(data.kind, SchemaKind::Opaque {});
+}
+
+#[test]
+fn schema__serializes_back_to_cip_57() {
+    // Given
+    let value = json!({
+        "title": "Action",
+        "anyOf": [
+            { "title": "Close", "dataType": "constructor", "index": 0, "fields": [] },
+            {
+                "title": "Pay",
+                "dataType": "constructor",
+                "index": 1,
+                "fields": [{ "title": "amount", "dataType": "integer" }],
+            },
+        ],
+    });
+
+    // When
+    let serialized = serde_json::to_value(schema(value.clone())).unwrap();
+
+    // Then
+    assert_eq!(serialized, value);
+}
+
+#[test]
+fn definition_name__unescapes_json_pointer() {
+    assert_eq!(
+        definition_name("#/definitions/Option$aiken~1time~1PosixTime"),
+        Some("Option$aiken/time/PosixTime".to_string())
+    );
+    assert_eq!(
+        definition_name("#/definitions/a~0b"),
+        Some("a~b".to_string())
+    );
+    assert_eq!(definition_name("#/components/Int"), None);
+}
+
+fn vault_definitions() -> Definitions {
+    serde_json::from_value(json!({
+        "ByteArray": { "dataType": "bytes" },
+        "Int": { "dataType": "integer" },
+        "Data": { "title": "Data", "description": "Any Plutus data." },
+        "List$Int": { "dataType": "list", "items": { "$ref": "#/definitions/Int" } },
+        "Dict$ByteArray_Int": {
+            "dataType": "map",
+            "keys": { "$ref": "#/definitions/ByteArray" },
+            "values": { "$ref": "#/definitions/Int" },
+        },
+        "vault/Action": {
+            "title": "Action",
+            "anyOf": [
+                {
+                    "title": "Deposit",
+                    "dataType": "constructor",
+                    "index": 0,
+                    "fields": [{ "title": "amount", "$ref": "#/definitions/Int" }],
+                },
+                {
+                    "title": "Withdraw",
+                    "dataType": "constructor",
+                    "index": 1,
+                    "fields": [
+                        { "title": "amount", "$ref": "#/definitions/Int" },
+                        { "title": "limits", "$ref": "#/definitions/Dict$ByteArray_Int" },
+                        { "title": "to", "$ref": "#/definitions/ByteArray" },
+                    ],
+                },
+            ],
+        },
+    }))
+    .unwrap()
+}
+
+fn action() -> Schema {
+    schema(json!({ "$ref": "#/definitions/vault~1Action" }))
+}
+
+fn constr(constr: u64, fields: Vec<PlutusData>) -> PlutusData {
+    PlutusData::Constr(Constr { constr, fields })
+}
+
+fn int(value: i64) -> PlutusData {
+    PlutusData::BigInt(value.into())
+}
+
+#[test]
+fn validate__accepts_matching_data() {
+    // Given
+    let limits = PlutusData::Map(
+        [(PlutusData::BoundedBytes(vec![1]), int(10))]
+            .into_iter()
+            .collect(),
+    );
+    let withdraw = constr(1, vec![int(5), limits, PlutusData::BoundedBytes(vec![2])]);
+
+    // When
+    let res = action().validate(&withdraw, &vault_definitions());
+
+    // Then
+    assert_eq!(res, Ok(()));
+}
+
+#[test]
+fn validate__points_at_the_mismatching_field() {
+    // Given
+    let withdraw = constr(1, vec![int(5), PlutusData::Map(BTreeMap::new()), int(2)]);
+
+    // When
+    let err = action()
+        .validate(&withdraw, &vault_definitions())
+        .unwrap_err();
+
+    // Then
+    let expected_path = vec![PathSegment::Field {
+        constructor: 1,
+        field: 2,
+    }];
+    assert_eq!(err.path, expected_path);
+    assert_eq!(
+        err.to_string(),
+        "field 2 of constructor 1 expected bytes, found integer"
+    );
+}
+
+#[test]
+fn validate__points_into_nested_data() {
+    // Given
+    let limits = PlutusData::Map(
+        [(PlutusData::BoundedBytes(vec![1]), PlutusData::Array(vec![]))]
+            .into_iter()
+            .collect(),
+    );
+    let withdraw = constr(1, vec![int(5), limits, PlutusData::BoundedBytes(vec![2])]);
+
+    // When
+    let err = action()
+        .validate(&withdraw, &vault_definitions())
+        .unwrap_err();
+
+    // Then
+    assert_eq!(
+        err.to_string(),
+        "value of entry 0 in field 1 of constructor 1 expected integer, found list"
+    );
+}
+
+#[test]
+fn validate__rejects_unknown_constructors_and_field_counts() {
+    // Given
+    let definitions = vault_definitions();
+    let unknown = constr(2, vec![]);
+    let short = constr(0, vec![]);
+
+    // When
+    let unknown_err = action().validate(&unknown, &definitions).unwrap_err();
+    let short_err = action().validate(&short, &definitions).unwrap_err();
+
+    // Then
+    assert_eq!(
+        unknown_err.to_string(),
+        "expected one of constructor 0, constructor 1, found constructor 2"
+    );
+    assert_eq!(
+        short_err.to_string(),
+        "expected constructor 0 with 1 fields, found constructor 0 with 0 fields"
+    );
+}
+
+#[test]
+fn validate__checks_list_items_and_allows_any_data_for_opaque() {
+    // Given
+    let definitions = vault_definitions();
+    let list = schema(json!({ "$ref": "#/definitions/List$Int" }));
+    let data = schema(json!({ "$ref": "#/definitions/Data" }));
+    let items = PlutusData::Array(vec![int(1), PlutusData::BoundedBytes(vec![])]);
+
+    // When
+    let list_err = list.validate(&items, &definitions).unwrap_err();
+    let data_res = data.validate(&items, &definitions);
+
+    // Then
+    assert_eq!(list_err.path, vec![PathSegment::Item(1)]);
+    assert_eq!(data_res, Ok(()));
+}
+
+#[test]
+fn validate__rejects_definitions_that_only_refer_to_each_other() {
+    // Given
+    let definitions: Definitions = serde_json::from_value(json!({
+        "Ping": { "$ref": "#/definitions/Pong" },
+        "Pong": { "$ref": "#/definitions/Ping" },
+    }))
+    .unwrap();
+    let ping = schema(json!({ "$ref": "#/definitions/Ping" }));
+
+    // When
+    let err = ping.validate(&int(1), &definitions).unwrap_err();
+
+    // Then
+    assert!(err.expected.starts_with("non-cyclic definition"), "{err}");
+    assert_eq!(err.found, "integer");
+}
+
+#[test]
+fn validate__reports_missing_definitions() {
+    // Given
+    let missing = schema(json!({ "$ref": "#/definitions/Missing" }));
+
+    // When
+    let err = missing.validate(&int(1), &vault_definitions()).unwrap_err();
+
+    // Then
+    assert_eq!(err.expected, "missing definition \"#/definitions/Missing\"");
+}
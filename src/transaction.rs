@@ -1,6 +1,8 @@
 use crate::{
     error::*,
     output::{
+        DatumAttachment,
+        DatumKind,
         Output,
         UnbuiltOutput,
     },
@@ -15,7 +17,10 @@ use crate::{
     },
     values::Values,
 };
-use pallas_addresses::Address;
+use pallas_addresses::{
+    Address,
+    Network,
+};
 use std::{
     cell::RefCell,
     collections::HashMap,
@@ -24,6 +29,9 @@ use std::{
 
 pub(crate) mod nested_value_map;
 
+#[cfg(test)]
+mod tests;
+
 type RedemptionDetails<Datum, Redeemer> =
     (Output<Datum>, Redeemer, Box<dyn Validator<Datum, Redeemer>>);
 
@@ -78,6 +86,10 @@ pub enum Action<Datum, Redeemer> {
         values: Values,
         /// Address to lock at
         address: Address,
+        /// Whether the datum is included inline or by hash
+        attachment: DatumAttachment,
+        /// Validator at `address`, if the `datum` should be checked with it
+        script: Option<Box<dyn Validator<Datum, Redeemer>>>,
     },
     /// Specify a script output that will be redeemed with `redeemer` and `script`
     RedeemScriptOutput {
@@ -167,7 +179,9 @@ impl<Datum: Clone, Redeemer> TxActions<Datum, Redeemer> {
     }
 
     /// Add a script init to the actions.
-    /// This will lock the `values` at the `address` with the `datum`.
+    /// This will lock the `values` at the `address` with the hash of the `datum`. Nothing checks
+    /// that the `datum` is one the script at `address` expects, use
+    /// [`TxActions::with_checked_script_init`] for that.
     pub fn with_script_init(
         mut self,
         datum: Datum,
@@ -178,6 +192,71 @@ impl<Datum: Clone, Redeemer> TxActions<Datum, Redeemer> {
             datum,
             values,
             address,
+            attachment: DatumAttachment::Hash,
+            script: None,
+        };
+        self.actions.push(action);
+        self
+    }
+
+    /// Add a script init to the actions, locking the `values` at the address of `script`.
+    /// The `datum` is checked with the `script` when the actions are converted with
+    /// [`TxActions::to_unbuilt_tx`], see [`Validator::check_datum`].
+    pub fn with_checked_script_init(
+        mut self,
+        datum: Datum,
+        values: Values,
+        script: Box<dyn Validator<Datum, Redeemer>>,
+        network: Network,
+    ) -> Result<Self> {
+        let address = script.address(network)?;
+        let action = Action::InitScript {
+            datum,
+            values,
+            address,
+            attachment: DatumAttachment::Hash,
+            script: Some(script),
+        };
+        self.actions.push(action);
+        Ok(self)
+    }
+
+    /// Add a script init to the actions, with the `datum` included inline in the output.
+    /// This will lock the `values` at the `address`. Scripts can read the datums of outputs
+    /// created this way, but only from Plutus V2 onwards.
+    pub fn with_script_init_inline_datum(
+        mut self,
+        datum: Datum,
+        values: Values,
+        address: Address,
+    ) -> Self {
+        let action = Action::InitScript {
+            datum,
+            values,
+            address,
+            attachment: DatumAttachment::Inline,
+            script: None,
+        };
+        self.actions.push(action);
+        self
+    }
+
+    /// Add a script init to the actions, with only the hash of the `datum` included in the
+    /// output, same as [`TxActions::with_script_init`].
+    /// This will lock the `values` at the `address`. The `datum` will need to be provided as a
+    /// witness when the output is redeemed.
+    pub fn with_script_init_datum_hash(
+        mut self,
+        datum: Datum,
+        values: Values,
+        address: Address,
+    ) -> Self {
+        let action = Action::InitScript {
+            datum,
+            values,
+            address,
+            attachment: DatumAttachment::Hash,
+            script: None,
         };
         self.actions.push(action);
         self
@@ -186,7 +265,8 @@ impl<Datum: Clone, Redeemer> TxActions<Datum, Redeemer> {
     // TODO: This can prolly just take the Output ID
     /// Add a script redeem to the actions.
     /// This will redeem the `output` with the `redeemer` and use the `script` to validate the
-    /// transaction.
+    /// transaction. The datum and `redeemer` are checked with the `script` when the actions are
+    /// converted with [`TxActions::to_unbuilt_tx`].
     pub fn with_script_redeem(
         mut self,
         output: Output<Datum>,
@@ -255,18 +335,25 @@ impl<Datum: Clone, Redeemer> TxActions<Datum, Redeemer> {
                     redeemer,
                     policy,
                 } => {
+                    policy.check_redeemer(&redeemer)?;
                     minting.push((amount, asset_name, redeemer, policy));
                 }
                 Action::InitScript {
                     datum,
                     values,
                     address,
+                    attachment,
+                    script,
                 } => {
+                    if let Some(script) = script {
+                        script.check_datum(&datum)?;
+                    }
                     let owner = address.to_bech32().expect("Already Validated");
                     let output = UnbuiltOutput::Validator {
                         script_address: owner,
                         values,
                         datum,
+                        attachment,
                     };
                     specific_outputs.push(output);
                 }
@@ -275,6 +362,10 @@ impl<Datum: Clone, Redeemer> TxActions<Datum, Redeemer> {
                     redeemer,
                     script,
                 } => {
+                    if let DatumKind::Typed(datum) = output.datum() {
+                        script.check_datum(datum)?;
+                    }
+                    script.check_redeemer(&redeemer)?;
                     script_inputs.push((output.clone(), redeemer, script));
                 }
                 Action::SpecificInput { input } => specific_wallet_inputs.push(input),
@@ -366,7 +457,7 @@ impl<Datum, Redeemer> UnbuiltTransaction<Datum, Redeemer> {
 }
 
 /// The resulting transaction from a [`LedgerClient`] submission
-#[derive(Debug)]
+#[derive(Clone, PartialEq, Debug, Eq)]
 pub struct TxId(String);
 
 impl TxId {
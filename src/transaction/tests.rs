@@ -0,0 +1,118 @@
+#![allow(non_snake_case)]
+
+use super::*;
+use crate::scripts::{
+    plutus_minting_policy::OneParamPlutusPolicy,
+    plutus_validator::{
+        plutus_data::{
+            Constr,
+            PlutusData,
+        },
+        PlutusValidator,
+    },
+    raw_script::BlueprintFile,
+    ScriptError,
+};
+
+fn checking_account_validator() -> PlutusValidator<PlutusData, PlutusData> {
+    let blueprint = BlueprintFile::from_json(include_str!(
+        "../../sample-dApps/checking_account/checking/plutus.json"
+    ))
+    .unwrap();
+    let validator_blueprint = blueprint
+        .get_validator("checking_account_validator.spend")
+        .unwrap();
+    PlutusValidator::from_blueprint(validator_blueprint).unwrap()
+}
+
+fn account_datum(spending_token: PlutusData) -> PlutusData {
+    PlutusData::Constr(Constr {
+        constr: 0,
+        fields: vec![PlutusData::BoundedBytes(vec![1; 28]), spending_token],
+    })
+}
+
+#[test]
+fn to_unbuilt_tx__locks_checked_datum_at_script_address() {
+    // Given
+    let script = checking_account_validator();
+    let address = script.address(Network::Testnet).unwrap();
+    let datum = account_datum(PlutusData::BoundedBytes(vec![2; 28]));
+
+    // When
+    let unbuilt_tx = TxActions::v2()
+        .with_checked_script_init(
+            datum.clone(),
+            Values::default(),
+            Box::new(script),
+            Network::Testnet,
+        )
+        .unwrap()
+        .to_unbuilt_tx()
+        .unwrap();
+
+    // Then
+    let expected = UnbuiltOutput::Validator {
+        script_address: address.to_bech32().unwrap(),
+        values: Values::default(),
+        datum,
+        attachment: DatumAttachment::Hash,
+    };
+    assert_eq!(unbuilt_tx.unbuilt_outputs(), &vec![expected]);
+}
+
+#[test]
+fn to_unbuilt_tx__rejects_checked_datum_that_does_not_match_schema() {
+    // Given
+    let script = checking_account_validator();
+    let datum = account_datum(PlutusData::BigInt(2.into()));
+    let actions: TxActions<PlutusData, PlutusData> = TxActions::v2()
+        .with_checked_script_init(
+            datum,
+            Values::default(),
+            Box::new(script),
+            Network::Testnet,
+        )
+        .unwrap();
+
+    // When
+    let err = actions.to_unbuilt_tx().unwrap_err();
+
+    // Then
+    assert!(matches!(err, Error::Script(ScriptError::DatumSchema(_))));
+}
+
+#[test]
+fn to_unbuilt_tx__rejects_mint_redeemer_that_does_not_match_schema() {
+    // Given
+    let blueprint = BlueprintFile::from_json(include_str!(
+        "../../nau-scripts/aiken/mint_nft/plutus.json"
+    ))
+    .unwrap();
+    let policy_blueprint = blueprint.get_validator("one_shot_nft.mint").unwrap();
+    let one_param: OneParamPlutusPolicy<PlutusData, PlutusData> =
+        OneParamPlutusPolicy::from_blueprint(policy_blueprint).unwrap();
+    let input_ref = PlutusData::Constr(Constr {
+        constr: 0,
+        fields: vec![
+            PlutusData::Constr(Constr {
+                constr: 0,
+                fields: vec![PlutusData::BoundedBytes(vec![1; 32])],
+            }),
+            PlutusData::BigInt(0.into()),
+        ],
+    });
+    let policy = one_param.apply(input_ref).unwrap();
+    let actions: TxActions<PlutusData, PlutusData> = TxActions::v2().with_mint(
+        1,
+        None,
+        PlutusData::BigInt(1.into()),
+        Box::new(policy),
+    );
+
+    // When
+    let err = actions.to_unbuilt_tx().unwrap_err();
+
+    // Then
+    assert!(matches!(err, Error::Script(ScriptError::RedeemerSchema(_))));
+}
@@ -3,6 +3,7 @@ use crate::{
     ledger_client::{
         test_ledger_client::{
             local_persisted_storage::LocalPersistedStorage,
+            sqlite_storage::SqliteStorage,
             TestLedgerClient,
         },
         LedgerClient,
@@ -21,7 +22,10 @@ use crate::{
 
 use crate::trireme_ledger_client::{
     cml_client::{
+        kupo_client::KupoClient,
+        kupo_ogmios_ledger::KupoOgmiosLedger,
         network_settings::NetworkSettings,
+        ogmios_lsq_ledger::OgmiosLsqLedger,
         ogmios_scrolls_ledger::OgmiosScrollsLedger,
         Keys,
     },
@@ -43,6 +47,7 @@ use cml_client::{
     blockfrost_ledger::BlockFrostLedger,
     plutus_data_interop::PlutusDataInterop,
     CMLLedgerCLient,
+    ExUnitsSource,
 };
 use dirs::home_dir;
 use ogmios_client::OgmiosClient;
@@ -66,6 +71,8 @@ use tokio::{
     io::AsyncWriteExt,
 };
 
+/// Blockfrost API emulator module
+pub mod blockfrost_emulator;
 /// CML CLient module
 pub mod cml_client;
 /// Raw secret phrase module
@@ -166,6 +173,24 @@ pub enum LedgerSource {
         /// Port of Ogmios instance
         ogmios_port: String,
     },
+    /// Read from and submit through a single Ogmios v6 instance, without an indexer
+    Ogmios {
+        /// IP address of Ogmios instance
+        ogmios_ip: String,
+        /// Port of Ogmios instance
+        ogmios_port: String,
+    },
+    /// Read from a Kupo instance and submit through an Ogmios instance
+    KupoAndOgmios {
+        /// IP address of Kupo instance
+        kupo_ip: String,
+        /// Port of Kupo instance
+        kupo_port: String,
+        /// IP address of Ogmios instance
+        ogmios_ip: String,
+        /// Port of Ogmios instance
+        ogmios_port: String,
+    },
 }
 
 /// Type of key storage
@@ -188,6 +213,8 @@ pub enum KeySource {
     },
 }
 
+const PREVIEW_NETWORK_URL: &str = "https://cardano-preview.blockfrost.io/api/v0";
+
 /// The networks supported by Trireme
 #[derive(Deserialize, Serialize, Clone)]
 #[serde(tag = "type")]
@@ -198,6 +225,37 @@ pub enum Network {
     Preview,
     /// Cardano Mainnet
     Mainnet,
+    /// Any other network, e.g. a private testnet or a local devnet
+    Custom {
+        /// Network id and slot configuration of the network
+        network_settings: NetworkSettings,
+        /// Network magic of the network
+        magic: u64,
+        /// Base URL of a Blockfrost compatible API for the network, if there is one
+        blockfrost_url: Option<String>,
+    },
+}
+
+impl Network {
+    /// Network magic of the network
+    pub fn magic(&self) -> u64 {
+        match self {
+            Network::Preprod => 1,
+            Network::Preview => 2,
+            Network::Mainnet => 764824073,
+            Network::Custom { magic, .. } => *magic,
+        }
+    }
+
+    /// Base URL of the Blockfrost API for the network, if there is one
+    pub fn blockfrost_url(&self) -> Option<String> {
+        match self {
+            Network::Preprod => Some(PREPROD_NETWORK_URL.to_string()),
+            Network::Preview => Some(PREVIEW_NETWORK_URL.to_string()),
+            Network::Mainnet => Some(MAINNET_URL.to_string()),
+            Network::Custom { blockfrost_url, .. } => blockfrost_url.clone(),
+        }
+    }
 }
 
 impl From<Network> for u8 {
@@ -205,6 +263,9 @@ impl From<Network> for u8 {
         match network {
             Network::Mainnet => 1,
             Network::Preprod | Network::Preview => 0,
+            Network::Custom {
+                network_settings, ..
+            } => network_settings.network(),
         }
     }
 }
@@ -302,12 +363,17 @@ pub struct CMLClientConfig {
     ledger_source: LedgerSource,
     key_source: KeySource,
     network: Network,
+    /// Where execution units of scripts come from. Asks the ledger if missing
+    #[serde(default)]
+    ex_units: ExUnitsSource,
 }
 
 /// Config for Test client
 #[derive(Deserialize, Serialize, Clone)]
 pub struct TestClientConfig {
     data_path: PathBuf,
+    #[serde(default)]
+    storage: TestStorage,
 }
 
 impl TestClientConfig {
@@ -315,6 +381,22 @@ impl TestClientConfig {
     pub fn data_path(&self) -> PathBuf {
         self.data_path.clone()
     }
+
+    /// Getter for the kind of storage the data is kept in
+    pub fn storage(&self) -> TestStorage {
+        self.storage
+    }
+}
+
+/// Kinds of storage for the Test client
+#[derive(Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+#[serde(tag = "type")]
+pub enum TestStorage {
+    /// A single JSON file. See [`LocalPersistedStorage`]
+    #[default]
+    Json,
+    /// An embedded SQLite database. See [`SqliteStorage`]
+    Sqlite,
 }
 
 impl ClientConfig {
@@ -329,6 +411,7 @@ impl ClientConfig {
             ledger_source,
             key_source,
             network,
+            ex_units: ExUnitsSource::default(),
         };
         let variant = ClientVariant::CML(inner);
         ClientConfig {
@@ -338,9 +421,10 @@ impl ClientConfig {
     }
 
     /// Constructor for Test client config
-    pub fn new_test(name: &str, data_path: &PathBuf) -> Self {
+    pub fn new_test(name: &str, data_path: &PathBuf, storage: TestStorage) -> Self {
         let inner = TestClientConfig {
             data_path: data_path.to_owned(),
+            storage,
         };
         let variant = ClientVariant::Test(inner);
         ClientConfig {
@@ -378,6 +462,7 @@ impl ClientConfig {
         match self.variant {
             ClientVariant::CML(inner) => {
                 let network = inner.network;
+                let ex_units = inner.ex_units;
                 let keys = match inner.key_source {
                     KeySource::RawSecretPhrase { phrase_file } => {
                         let keys =
@@ -411,22 +496,18 @@ impl ClientConfig {
                             )
                                 })?;
                         let key: String = blockfrost_key.into();
-                        let url = match &network {
-                            Network::Preprod => PREPROD_NETWORK_URL,
-                            Network::Mainnet => MAINNET_URL,
-                            Network::Preview => {
-                                return Err(Error::Trireme(
-                                    "Preview network not supported yet".to_string(),
-                                ))
-                            }
-                        };
-                        let ledger = BlockFrostLedger::new(url, &key);
+                        let url = network.blockfrost_url().ok_or_else(|| {
+                            Error::Trireme(
+                                "No Blockfrost URL configured for this network"
+                                    .to_string(),
+                            )
+                        })?;
+                        let ledger = BlockFrostLedger::new(&url, &key);
                         let network_settings = network.clone().into();
-                        InnerClient::BlockFrost(CMLLedgerCLient::new(
-                            ledger,
-                            keys,
-                            network_settings,
-                        ))
+                        InnerClient::BlockFrost(
+                            CMLLedgerCLient::new(ledger, keys, network_settings)
+                                .with_ex_units_source(ex_units),
+                        )
                     }
                     LedgerSource::OgmiosAndScrolls {
                         scrolls_ip,
@@ -443,11 +524,47 @@ impl ClientConfig {
                             ogmios_client,
                             network_settings,
                         );
-                        InnerClient::OgmiosScrolls(CMLLedgerCLient::new(
-                            ledger,
-                            keys,
+                        InnerClient::OgmiosScrolls(
+                            CMLLedgerCLient::new(ledger, keys, network_settings)
+                                .with_ex_units_source(ex_units),
+                        )
+                    }
+                    LedgerSource::Ogmios {
+                        ogmios_ip,
+                        ogmios_port,
+                    } => {
+                        let ledger = OgmiosLsqLedger::new(&format!(
+                            "http://{ogmios_ip}:{ogmios_port}"
+                        ));
+                        ledger
+                            .check_network_magic(network.magic())
+                            .await
+                            .map_err(|e| Error::Trireme(e.to_string()))?;
+                        let network_settings: NetworkSettings = network.into();
+                        InnerClient::OgmiosLsq(
+                            CMLLedgerCLient::new(ledger, keys, network_settings)
+                                .with_ex_units_source(ex_units),
+                        )
+                    }
+                    LedgerSource::KupoAndOgmios {
+                        kupo_ip,
+                        kupo_port,
+                        ogmios_ip,
+                        ogmios_port,
+                    } => {
+                        let kupo_client =
+                            KupoClient::new(&format!("http://{kupo_ip}:{kupo_port}"));
+                        let ogmios_client = OgmiosClient::new(ogmios_ip, ogmios_port);
+                        let network_settings: NetworkSettings = network.into();
+                        let ledger = KupoOgmiosLedger::new(
+                            kupo_client,
+                            ogmios_client,
                             network_settings,
-                        ))
+                        );
+                        InnerClient::KupoOgmios(
+                            CMLLedgerCLient::new(ledger, keys, network_settings)
+                                .with_ex_units_source(ex_units),
+                        )
                     }
                 };
 
@@ -460,8 +577,14 @@ impl ClientConfig {
             }
             ClientVariant::Test(inner) => {
                 let data_dir = inner.data_path;
-                let test_client = TestLedgerClient::load_local_persisted(data_dir);
-                let inner_client = InnerClient::Mocked(test_client);
+                let inner_client = match inner.storage {
+                    TestStorage::Json => InnerClient::Mocked(
+                        TestLedgerClient::load_local_persisted(data_dir)?,
+                    ),
+                    TestStorage::Sqlite => InnerClient::MockedSqlite(
+                        TestLedgerClient::load_sqlite(data_dir)?,
+                    ),
+                };
                 let trireme_client = TriremeLedgerClient {
                     _datum: Default::default(),
                     _redeemer: Default::default(),
@@ -518,8 +641,14 @@ where
     OgmiosScrolls(
         CMLLedgerCLient<OgmiosScrollsLedger, SecretPhraseKeys, Datum, Redeemer>,
     ),
+    /// Ogmios local-state-query client
+    OgmiosLsq(CMLLedgerCLient<OgmiosLsqLedger, SecretPhraseKeys, Datum, Redeemer>),
+    /// Kupo + Ogmios client
+    KupoOgmios(CMLLedgerCLient<KupoOgmiosLedger, SecretPhraseKeys, Datum, Redeemer>),
     /// Test client
     Mocked(TestLedgerClient<Datum, Redeemer, LocalPersistedStorage<PathBuf, Datum>>),
+    /// Test client backed by SQLite
+    MockedSqlite(TestLedgerClient<Datum, Redeemer, SqliteStorage<PathBuf, Datum>>),
 }
 
 /// Implementation of [`LedgerClient`] for the Trireme CLI environment manager
@@ -559,11 +688,20 @@ where
                 ))))
             }
             InnerClient::Mocked(test_client) => test_client.current_time_secs().await,
+            InnerClient::MockedSqlite(test_client) => {
+                test_client.current_time_secs().await
+            }
             InnerClient::OgmiosScrolls(_) => {
                 Err(LedgerClientError::CurrentTime(Box::new(Error::Trireme(
                     "Not implemented for Ogmios/Scrolls client".to_string(),
                 ))))
             }
+            InnerClient::OgmiosLsq(_) => Err(LedgerClientError::CurrentTime(Box::new(
+                Error::Trireme("Not implemented for Ogmios client".to_string()),
+            ))),
+            InnerClient::KupoOgmios(_) => Err(LedgerClientError::CurrentTime(Box::new(
+                Error::Trireme("Not implemented for Kupo/Ogmios client".to_string()),
+            ))),
         }
     }
 
@@ -580,11 +718,20 @@ where
             InnerClient::Mocked(test_client) => {
                 test_client.advance_time_n_blocks(count).await
             }
+            InnerClient::MockedSqlite(test_client) => {
+                test_client.advance_time_n_blocks(count).await
+            }
             InnerClient::OgmiosScrolls(_) => {
                 Err(LedgerClientError::CurrentTime(Box::new(Error::Trireme(
                     "Not implemented for Ogmios/Scrolls client".to_string(),
                 ))))
             }
+            InnerClient::OgmiosLsq(_) => Err(LedgerClientError::CurrentTime(Box::new(
+                Error::Trireme("Not implemented for Ogmios client".to_string()),
+            ))),
+            InnerClient::KupoOgmios(_) => Err(LedgerClientError::CurrentTime(Box::new(
+                Error::Trireme("Not implemented for Kupo/Ogmios client".to_string()),
+            ))),
         }
     }
 }
@@ -607,7 +754,10 @@ where
         match &self.inner_client {
             InnerClient::BlockFrost(cml_client) => cml_client.signer_base_address(),
             InnerClient::Mocked(test_client) => test_client.signer_base_address(),
+            InnerClient::MockedSqlite(test_client) => test_client.signer_base_address(),
             InnerClient::OgmiosScrolls(cml_client) => cml_client.signer_base_address(),
+            InnerClient::OgmiosLsq(cml_client) => cml_client.signer_base_address(),
+            InnerClient::KupoOgmios(cml_client) => cml_client.signer_base_address(),
         }
         .await
     }
@@ -624,9 +774,18 @@ where
             InnerClient::Mocked(test_client) => {
                 test_client.outputs_at_address(address, count)
             }
+            InnerClient::MockedSqlite(test_client) => {
+                test_client.outputs_at_address(address, count)
+            }
             InnerClient::OgmiosScrolls(cml_client) => {
                 cml_client.outputs_at_address(address, count)
             }
+            InnerClient::OgmiosLsq(cml_client) => {
+                cml_client.outputs_at_address(address, count)
+            }
+            InnerClient::KupoOgmios(cml_client) => {
+                cml_client.outputs_at_address(address, count)
+            }
         }
         .await
     }
@@ -642,9 +801,18 @@ where
             InnerClient::Mocked(test_client) => {
                 test_client.all_outputs_at_address(address)
             }
+            InnerClient::MockedSqlite(test_client) => {
+                test_client.all_outputs_at_address(address)
+            }
             InnerClient::OgmiosScrolls(cml_client) => {
                 cml_client.all_outputs_at_address(address)
             }
+            InnerClient::OgmiosLsq(cml_client) => {
+                cml_client.all_outputs_at_address(address)
+            }
+            InnerClient::KupoOgmios(cml_client) => {
+                cml_client.all_outputs_at_address(address)
+            }
         }
         .await
     }
@@ -656,7 +824,10 @@ where
         match &self.inner_client {
             InnerClient::BlockFrost(cml_client) => cml_client.issue(tx),
             InnerClient::Mocked(test_client) => test_client.issue(tx),
+            InnerClient::MockedSqlite(test_client) => test_client.issue(tx),
             InnerClient::OgmiosScrolls(cml_client) => cml_client.issue(tx),
+            InnerClient::OgmiosLsq(cml_client) => cml_client.issue(tx),
+            InnerClient::KupoOgmios(cml_client) => cml_client.issue(tx),
         }
         .await
     }
@@ -665,7 +836,10 @@ where
         match &self.inner_client {
             InnerClient::BlockFrost(cml_client) => cml_client.network(),
             InnerClient::Mocked(test_client) => test_client.network(),
+            InnerClient::MockedSqlite(test_client) => test_client.network(),
             InnerClient::OgmiosScrolls(cml_client) => cml_client.network(),
+            InnerClient::OgmiosLsq(cml_client) => cml_client.network(),
+            InnerClient::KupoOgmios(cml_client) => cml_client.network(),
         }
         .await
     }
@@ -674,7 +848,10 @@ where
         match &self.inner_client {
             InnerClient::BlockFrost(cml_client) => cml_client.last_block_time_secs(),
             InnerClient::Mocked(test_client) => test_client.last_block_time_secs(),
+            InnerClient::MockedSqlite(test_client) => test_client.last_block_time_secs(),
             InnerClient::OgmiosScrolls(cml_client) => cml_client.last_block_time_secs(),
+            InnerClient::OgmiosLsq(cml_client) => cml_client.last_block_time_secs(),
+            InnerClient::KupoOgmios(cml_client) => cml_client.last_block_time_secs(),
         }
         .await
     }
@@ -689,7 +866,16 @@ where
             > as LedgerClient<Datum, Redeemer>>::current_time_secs(
                 test_client
             ),
+            InnerClient::MockedSqlite(test_client) => <TestLedgerClient<
+                Datum,
+                Redeemer,
+                SqliteStorage<PathBuf, Datum>,
+            > as LedgerClient<Datum, Redeemer>>::current_time_secs(
+                test_client
+            ),
             InnerClient::OgmiosScrolls(cml_client) => cml_client.current_time_secs(),
+            InnerClient::OgmiosLsq(cml_client) => cml_client.current_time_secs(),
+            InnerClient::KupoOgmios(cml_client) => cml_client.current_time_secs(),
         }
         .await
     }
@@ -0,0 +1,594 @@
+use crate::{
+    ledger_client::{
+        test_ledger_client::TestLedgerStorage,
+        LedgerClientError,
+    },
+    output::Output,
+    scripts::{
+        evaluation::EvaluationParams,
+        plutus_validator::plutus_data::PlutusData,
+        ExecutionCost,
+    },
+    trireme_ledger_client::cml_client::{
+        local_evaluation::{
+            input_ids,
+            DecodedTx,
+            LocalEvaluationError,
+            RedeemerPurpose,
+        },
+        network_settings::NetworkSettings,
+    },
+    values::Values,
+    PolicyId,
+};
+use axum::{
+    body::Bytes,
+    extract::{
+        Path,
+        Query,
+        State,
+    },
+    http::StatusCode,
+    response::{
+        IntoResponse,
+        Response,
+    },
+    routing::{
+        get,
+        post,
+    },
+    Json,
+    Router,
+};
+use cardano_multiplatform_lib::{
+    plutus::{
+        decode_plutus_datum_to_json_str,
+        PlutusDatumSchema,
+    },
+    Transaction as CMLTransaction,
+};
+use pallas_addresses::Address;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use serde_json::{
+    json,
+    Value,
+};
+use std::{
+    collections::HashMap,
+    net::TcpListener,
+    sync::{
+        Arc,
+        Mutex,
+        MutexGuard,
+    },
+};
+use thiserror::Error;
+
+#[cfg(test)]
+mod tests;
+
+/// Maximum page size of the Blockfrost API
+const MAX_PAGE_SIZE: usize = 100;
+
+#[allow(missing_docs)]
+#[derive(Debug, Error)]
+pub enum EmulatorError {
+    #[error("Could not deserialize transaction: {0}")]
+    Deserialize(String),
+    #[error("Not a valid address: {0}")]
+    BadAddress(String),
+    #[error("Transaction is not valid before slot {1:?}; current slot is {0:?}")]
+    TooEarly(u64, u64),
+    #[error("Transaction is not valid from slot {1:?}; current slot is {0:?}")]
+    TooLate(u64, u64),
+    #[error("Inputs and minted value don't match outputs and fee")]
+    NotBalanced,
+    #[error(
+        "Datum with hash {0} is in the witness set but no input or output has its hash"
+    )]
+    NotAllowedSupplementalDatum(String),
+    #[error("Datum with hash {0} not found")]
+    UnknownDatum(String),
+    #[error("Error from the ledger: {0}")]
+    Ledger(#[from] LedgerClientError),
+    #[error("Server error: {0}")]
+    Server(String),
+    #[error(transparent)]
+    Evaluation(#[from] LocalEvaluationError),
+}
+
+#[allow(missing_docs)]
+pub type EmulatorResult<T, E = EmulatorError> = Result<T, E>;
+
+impl EmulatorError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            EmulatorError::UnknownDatum(_) => StatusCode::NOT_FOUND,
+            EmulatorError::Ledger(_) | EmulatorError::Server(_) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+            _ => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+impl IntoResponse for EmulatorError {
+    fn into_response(self) -> Response {
+        let status_code = self.status_code();
+        let body = json!({
+            "status_code": status_code.as_u16(),
+            "error": status_code.canonical_reason().unwrap_or_default(),
+            "message": self.to_string(),
+        });
+        (status_code, Json(body)).into_response()
+    }
+}
+
+/// Serves a [`TestLedgerStorage`] over the subset of the
+/// [Blockfrost API](https://docs.blockfrost.io/) used by the
+/// [`BlockFrostLedger`](super::cml_client::blockfrost_ledger::BlockFrostLedger), so that tools
+/// which only speak Blockfrost can run against a local, offline ledger.
+///
+/// Submitted transactions are decoded from CBOR and checked the way the
+/// [`TestLedgerClient`](crate::ledger_client::test_ledger_client::TestLedgerClient) checks them:
+/// every input must be on the ledger, the validity interval must contain the current slot, the
+/// transaction must be balanced and every Plutus script must succeed. Signatures are _not_
+/// checked.
+///
+/// ```ignore
+///     let emulator = BlockfrostEmulator::new(storage, Network::Preprod.into());
+///     let listener = TcpListener::bind("127.0.0.1:3000")?;
+///     emulator.serve(listener).await?;
+///     // BlockFrostLedger::new("http://127.0.0.1:3000", "any key") now reads from `storage`
+/// ```
+pub struct BlockfrostEmulator<Storage> {
+    storage: Storage,
+    network_settings: NetworkSettings,
+    evaluation: EvaluationParams,
+    /// Datums seen in the witness sets of submitted transactions, by hash
+    datums: Mutex<HashMap<Vec<u8>, PlutusData>>,
+}
+
+impl<Storage> BlockfrostEmulator<Storage>
+where
+    Storage: TestLedgerStorage<PlutusData> + Send + Sync + 'static,
+{
+    /// Constructor for the [`BlockfrostEmulator`]. The `network_settings` are used to convert
+    /// between the ledger's time and the slots in transactions. Scripts are run with
+    /// [`EvaluationParams::vasil`], see [`BlockfrostEmulator::with_evaluation_params`].
+    pub fn new(storage: Storage, network_settings: NetworkSettings) -> Self {
+        BlockfrostEmulator {
+            storage,
+            network_settings,
+            evaluation: EvaluationParams::vasil(),
+            datums: Default::default(),
+        }
+    }
+
+    /// Run scripts with the given cost models and budget, which are also reported as the
+    /// protocol parameters of the latest epoch
+    pub fn with_evaluation_params(mut self, evaluation: EvaluationParams) -> Self {
+        self.evaluation = evaluation;
+        self
+    }
+
+    /// Build the HTTP routes of the emulator
+    pub fn router(self) -> Router {
+        Router::new()
+            .route("/addresses/:address/utxos", get(utxos_handler::<Storage>))
+            .route("/scripts/datum/:datum_hash", get(datum_handler::<Storage>))
+            .route(
+                "/scripts/datum/:datum_hash/cbor",
+                get(datum_cbor_handler::<Storage>),
+            )
+            .route("/blocks/latest", get(latest_block_handler::<Storage>))
+            .route(
+                "/epochs/latest/parameters",
+                get(parameters_handler::<Storage>),
+            )
+            .route("/tx/submit", post(submit_handler::<Storage>))
+            .route("/utils/txs/evaluate", post(evaluate_handler::<Storage>))
+            .with_state(Arc::new(self))
+    }
+
+    /// Serve the emulator on the given listener until the server fails
+    pub async fn serve(self, listener: TcpListener) -> EmulatorResult<()> {
+        axum::Server::from_tcp(listener)
+            .map_err(|e| EmulatorError::Server(e.to_string()))?
+            .serve(self.router().into_make_service())
+            .await
+            .map_err(|e| EmulatorError::Server(e.to_string()))
+    }
+
+    /// Get the outputs at `address`, oldest first
+    pub async fn utxos(
+        &self,
+        address: &Address,
+    ) -> EmulatorResult<Vec<Output<PlutusData>>> {
+        Ok(self.storage.all_outputs(address).await?)
+    }
+
+    /// Get a datum by its hash, either from an output on the ledger or from the witness set of
+    /// a submitted transaction
+    pub async fn datum(&self, datum_hash: &[u8]) -> EmulatorResult<Option<PlutusData>> {
+        let submitted = self.lock_datums()?.get(datum_hash).cloned();
+        if submitted.is_some() {
+            return Ok(submitted);
+        }
+        let outputs = self.storage.snapshot().await?.outputs::<PlutusData>()?;
+        let datum = outputs
+            .into_iter()
+            .filter(|output| output.datum_hash() == Some(datum_hash))
+            .find_map(|output| output.datum_plutus_data());
+        Ok(datum)
+    }
+
+    /// The slot of the ledger's current time. Times before the first slot count as slot `0`
+    pub async fn current_slot(&self) -> EmulatorResult<u64> {
+        let current_time = self.storage.current_time().await?;
+        Ok(self
+            .network_settings
+            .slot_from_posix(current_time)
+            .unwrap_or_default())
+    }
+
+    /// Check the CBOR-encoded transaction and run its scripts without changing the ledger
+    pub async fn evaluate(
+        &self,
+        tx_bytes: &[u8],
+    ) -> EmulatorResult<Vec<(RedeemerPurpose, u64, ExecutionCost)>> {
+        let decoded = self.decode(tx_bytes).await?;
+        let costs = decoded.execute_scripts(&self.network_settings, &self.evaluation)?;
+        Ok(costs)
+    }
+
+    /// Check the CBOR-encoded transaction, run its scripts and apply it to the ledger. Returns
+    /// the hex-encoded transaction id.
+    pub async fn submit(&self, tx_bytes: &[u8]) -> EmulatorResult<String> {
+        let decoded = self.decode(tx_bytes).await?;
+        check_validity(&decoded, self.current_slot().await?)?;
+        check_balance(&decoded)?;
+        check_supplemental_datums(&decoded)?;
+        decoded.execute_scripts(&self.network_settings, &self.evaluation)?;
+        let current_time = self.storage.current_time().await?;
+        let block_length = self.storage.get_block_length().await?;
+        self.storage
+            .apply_transaction(
+                &decoded.inputs,
+                &decoded.outputs,
+                current_time + block_length,
+            )
+            .await?;
+        self.lock_datums()?.extend(decoded.datums);
+        Ok(hex::encode(decoded.tx_hash))
+    }
+
+    fn lock_datums(
+        &self,
+    ) -> EmulatorResult<MutexGuard<'_, HashMap<Vec<u8>, PlutusData>>> {
+        self.datums
+            .lock()
+            .map_err(|e| EmulatorError::Server(format!("{e:?}")))
+    }
+
+    /// Only the outputs the transaction spends are read from the ledger
+    async fn decode(&self, tx_bytes: &[u8]) -> EmulatorResult<DecodedTx> {
+        let tx = CMLTransaction::from_bytes(tx_bytes.to_vec())
+            .map_err(|e| EmulatorError::Deserialize(e.to_string()))?;
+        let mut inputs = Vec::new();
+        for id in input_ids(&tx) {
+            let input = self.storage.output_by_id(&id).await?.ok_or_else(|| {
+                LocalEvaluationError::UnknownInput(hex::encode(id.tx_hash()), id.index())
+            })?;
+            inputs.push(input);
+        }
+        Ok(DecodedTx::new(&tx, &inputs)?)
+    }
+}
+
+/// The lower bound is inclusive and the upper bound (the TTL) is exclusive
+fn check_validity(tx: &DecodedTx, current_slot: u64) -> EmulatorResult<()> {
+    let (start, ttl) = tx.validity;
+    if let Some(start) = start {
+        if current_slot < start {
+            return Err(EmulatorError::TooEarly(current_slot, start));
+        }
+    }
+    if let Some(ttl) = ttl {
+        if current_slot >= ttl {
+            return Err(EmulatorError::TooLate(current_slot, ttl));
+        }
+    }
+    Ok(())
+}
+
+fn check_balance(tx: &DecodedTx) -> EmulatorResult<()> {
+    let mut consumed = Values::from_outputs(&tx.inputs);
+    consumed.add_values(&tx.minted);
+    let mut produced = Values::from_outputs(&tx.outputs);
+    produced.add_one_value(&PolicyId::Lovelace, tx.fee);
+    produced.add_values(&tx.burned);
+    if consumed == produced {
+        Ok(())
+    } else {
+        Err(EmulatorError::NotBalanced)
+    }
+}
+
+/// Redeemer tags as Ogmios names them
+/// Like the ledger, only datums for inputs or outputs holding a datum by hash may be
+/// supplied. Inline datums are read from the outputs themselves
+fn check_supplemental_datums(tx: &DecodedTx) -> EmulatorResult<()> {
+    let allowed: Vec<&[u8]> = tx
+        .inputs
+        .iter()
+        .chain(tx.outputs.iter())
+        .filter_map(|output| output.datum_hash())
+        .collect();
+    for (datum_hash, _) in &tx.datums {
+        if !allowed.contains(&datum_hash.as_slice()) {
+            return Err(EmulatorError::NotAllowedSupplementalDatum(hex::encode(
+                datum_hash,
+            )))
+        }
+    }
+    Ok(())
+}
+
+fn redeemer_tag(purpose: RedeemerPurpose) -> &'static str {
+    match purpose {
+        RedeemerPurpose::Spend => "spend",
+        RedeemerPurpose::Mint => "mint",
+    }
+}
+
+/// Blockfrost accepts transactions both as raw CBOR and as hex-encoded CBOR
+fn tx_bytes_from_body(body: &[u8]) -> Vec<u8> {
+    std::str::from_utf8(body)
+        .ok()
+        .and_then(|text| hex::decode(text.trim().trim_matches('"')).ok())
+        .unwrap_or_else(|| body.to_vec())
+}
+
+#[derive(Deserialize)]
+struct PageParams {
+    count: Option<usize>,
+    page: Option<usize>,
+    order: Option<String>,
+}
+
+#[derive(Serialize)]
+struct BlockfrostAmount {
+    unit: String,
+    quantity: String,
+}
+
+#[derive(Serialize)]
+struct BlockfrostUtxo {
+    address: String,
+    tx_hash: String,
+    tx_index: u64,
+    output_index: u64,
+    amount: Vec<BlockfrostAmount>,
+    block: String,
+    data_hash: Option<String>,
+    inline_datum: Option<String>,
+    reference_script_hash: Option<String>,
+}
+
+impl BlockfrostUtxo {
+    fn new(output: &Output<PlutusData>) -> EmulatorResult<Self> {
+        let address = output
+            .owner()
+            .to_bech32()
+            .map_err(|e| EmulatorError::BadAddress(e.to_string()))?;
+        let mut amount: Vec<_> = output
+            .values()
+            .as_iter()
+            .map(|(policy, quantity)| BlockfrostAmount {
+                unit: unit(policy),
+                quantity: quantity.to_string(),
+            })
+            .collect();
+        // Blockfrost always lists lovelace first
+        amount.sort_by_key(|amount| amount.unit != "lovelace");
+        let (data_hash, inline_datum) =
+            match (output.datum_hash(), output.datum_plutus_data()) {
+                (Some(datum_hash), _) => (Some(hex::encode(datum_hash)), None),
+                (None, Some(datum)) => (
+                    Some(hex::encode(datum.hash())),
+                    Some(hex::encode(datum.bytes())),
+                ),
+                (None, None) => (None, None),
+            };
+        Ok(BlockfrostUtxo {
+            address,
+            tx_hash: hex::encode(output.id().tx_hash()),
+            tx_index: output.id().index(),
+            output_index: output.id().index(),
+            amount,
+            block: hex::encode(output.id().tx_hash()),
+            data_hash,
+            inline_datum,
+            reference_script_hash: None,
+        })
+    }
+}
+
+fn unit(policy: &PolicyId) -> String {
+    match policy {
+        PolicyId::Lovelace => "lovelace".to_string(),
+        PolicyId::NativeToken(id, asset_name) => {
+            let asset_name = asset_name.as_deref().unwrap_or_default();
+            format!("{id}{}", hex::encode(asset_name.as_bytes()))
+        }
+    }
+}
+
+async fn utxos_handler<Storage>(
+    State(emulator): State<Arc<BlockfrostEmulator<Storage>>>,
+    Path(address): Path<String>,
+    Query(params): Query<PageParams>,
+) -> EmulatorResult<Response>
+where
+    Storage: TestLedgerStorage<PlutusData> + Send + Sync + 'static,
+{
+    let address = Address::from_bech32(&address)
+        .map_err(|e| EmulatorError::BadAddress(e.to_string()))?;
+    let mut outputs = emulator.utxos(&address).await?;
+    if params.order.as_deref() == Some("desc") {
+        outputs.reverse();
+    }
+    let count = params.count.unwrap_or(MAX_PAGE_SIZE).min(MAX_PAGE_SIZE);
+    let page = params.page.unwrap_or(1).max(1);
+    let utxos = outputs
+        .iter()
+        .skip((page - 1) * count)
+        .take(count)
+        .map(BlockfrostUtxo::new)
+        .collect::<EmulatorResult<Vec<_>>>()?;
+    // Like Blockfrost, an address without any outputs is "not found"
+    if utxos.is_empty() && page == 1 {
+        let body = json!({
+            "status_code": 404,
+            "error": "Not Found",
+            "message": "The requested component has not been found.",
+        });
+        return Ok((StatusCode::NOT_FOUND, Json(body)).into_response());
+    }
+    Ok(Json(utxos).into_response())
+}
+
+async fn datum_handler<Storage>(
+    State(emulator): State<Arc<BlockfrostEmulator<Storage>>>,
+    Path(datum_hash): Path<String>,
+) -> EmulatorResult<Json<Value>>
+where
+    Storage: TestLedgerStorage<PlutusData> + Send + Sync + 'static,
+{
+    let hash_bytes = hex::decode(&datum_hash)
+        .map_err(|e| EmulatorError::Deserialize(e.to_string()))?;
+    let datum = emulator
+        .datum(&hash_bytes)
+        .await?
+        .ok_or(EmulatorError::UnknownDatum(datum_hash))?;
+    let json_str =
+        decode_plutus_datum_to_json_str(&datum.into(), PlutusDatumSchema::DetailedSchema)
+            .map_err(|e| EmulatorError::Deserialize(e.to_string()))?;
+    let json_value: Value = serde_json::from_str(&json_str)
+        .map_err(|e| EmulatorError::Deserialize(e.to_string()))?;
+    Ok(Json(json!({ "json_value": json_value })))
+}
+
+async fn datum_cbor_handler<Storage>(
+    State(emulator): State<Arc<BlockfrostEmulator<Storage>>>,
+    Path(datum_hash): Path<String>,
+) -> EmulatorResult<Json<Value>>
+where
+    Storage: TestLedgerStorage<PlutusData> + Send + Sync + 'static,
+{
+    let hash_bytes = hex::decode(&datum_hash)
+        .map_err(|e| EmulatorError::Deserialize(e.to_string()))?;
+    let datum = emulator
+        .datum(&hash_bytes)
+        .await?
+        .ok_or(EmulatorError::UnknownDatum(datum_hash))?;
+    Ok(Json(json!({ "cbor": hex::encode(datum.bytes()) })))
+}
+
+async fn latest_block_handler<Storage>(
+    State(emulator): State<Arc<BlockfrostEmulator<Storage>>>,
+) -> EmulatorResult<Json<Value>>
+where
+    Storage: TestLedgerStorage<PlutusData> + Send + Sync + 'static,
+{
+    let time = emulator.storage.current_time().await?;
+    let slot = emulator.current_slot().await?;
+    // The emulator has no real blocks, so there is one "block" per slot
+    Ok(Json(json!({
+        "time": time,
+        "height": slot,
+        "hash": hex::encode(slot.to_be_bytes()),
+        "slot": slot,
+        "epoch": 0,
+        "epoch_slot": slot,
+        "slot_leader": "emulator",
+        "size": 0,
+        "tx_count": 0,
+        "output": null,
+        "fees": null,
+        "block_vrf": null,
+        "op_cert": null,
+        "op_cert_counter": null,
+        "previous_block": null,
+        "next_block": null,
+        "confirmations": 0,
+    })))
+}
+
+/// Only the parameters scripts are evaluated with
+async fn parameters_handler<Storage>(
+    State(emulator): State<Arc<BlockfrostEmulator<Storage>>>,
+) -> Json<Value>
+where
+    Storage: TestLedgerStorage<PlutusData> + Send + Sync + 'static,
+{
+    let evaluation = &emulator.evaluation;
+    Json(json!({
+        "epoch": 0,
+        "max_tx_ex_mem": evaluation.max_budget.mem().to_string(),
+        "max_tx_ex_steps": evaluation.max_budget.cpu().to_string(),
+        "cost_models_raw": {
+            "PlutusV1": evaluation.cost_models.plutus_v1,
+            "PlutusV2": evaluation.cost_models.plutus_v2,
+        },
+    }))
+}
+
+async fn submit_handler<Storage>(
+    State(emulator): State<Arc<BlockfrostEmulator<Storage>>>,
+    body: Bytes,
+) -> EmulatorResult<Json<String>>
+where
+    Storage: TestLedgerStorage<PlutusData> + Send + Sync + 'static,
+{
+    let tx_bytes = tx_bytes_from_body(&body);
+    let tx_id = emulator.submit(&tx_bytes).await?;
+    Ok(Json(tx_id))
+}
+
+/// Responds in the Ogmios format that Blockfrost passes through
+async fn evaluate_handler<Storage>(
+    State(emulator): State<Arc<BlockfrostEmulator<Storage>>>,
+    body: Bytes,
+) -> Json<Value>
+where
+    Storage: TestLedgerStorage<PlutusData> + Send + Sync + 'static,
+{
+    let tx_bytes = tx_bytes_from_body(&body);
+    let result = match emulator.evaluate(&tx_bytes).await {
+        Ok(costs) => {
+            let evaluation: serde_json::Map<String, Value> = costs
+                .into_iter()
+                .map(|(purpose, index, cost)| {
+                    let key = format!("{}:{index}", redeemer_tag(purpose));
+                    let value = json!({ "memory": cost.mem(), "steps": cost.cpu() });
+                    (key, value)
+                })
+                .collect();
+            json!({ "EvaluationResult": evaluation })
+        }
+        Err(e) => json!({ "EvaluationFailure": { "ScriptFailures": e.to_string() } }),
+    };
+    Json(json!({
+        "type": "jsonwsp/response",
+        "version": "1.0",
+        "servicename": "ogmios",
+        "methodname": "EvaluateTx",
+        "result": result,
+        "reflection": null,
+    }))
+}
@@ -0,0 +1,456 @@
+#![allow(non_snake_case)]
+
+use super::*;
+use crate::{
+    ledger_client::test_ledger_client::{
+        in_memory_storage::InMemoryStorage,
+        local_persisted_storage::starting_output,
+    },
+    trireme_ledger_client::{
+        cml_client::{
+            blockfrost_ledger::BlockFrostLedger,
+            local_evaluation::address_from_cml,
+            plutus_data_interop::PlutusDataInterop,
+            Ledger,
+        },
+        Network,
+    },
+};
+use cardano_multiplatform_lib::{
+    address::{
+        Address as CMLAddress,
+        EnterpriseAddress,
+        StakeCredential,
+    },
+    crypto::TransactionHash,
+    ledger::common::value::{
+        BigNum,
+        Int,
+        Value as CMLValue,
+    },
+    plutus::{
+        ExUnits,
+        PlutusList,
+        PlutusScript,
+        PlutusV1Script,
+        PlutusV1Scripts,
+        Redeemer,
+        RedeemerTag,
+        Redeemers,
+    },
+    AssetName,
+    Assets,
+    Mint,
+    MintAssets,
+    MultiAsset,
+    TransactionBody,
+    TransactionInput,
+    TransactionInputs,
+    TransactionOutput,
+    TransactionOutputs,
+    TransactionWitnessSet,
+};
+use uplc::ast::{
+    DeBruijn,
+    Program,
+};
+
+const ALICE: &str = "addr_test1qrmezjhpelwzvz83wjl0e6mx766de7j3nksu2338s00yzx870xyxfa97xyz2zn5rknyntu5g0c66s7ktjnx0p6f0an6s3dyxwr";
+const BOB: &str = "addr_test1qzvrhz9v6lwcr26a52y8mmk2nzq37lky68359keq3dgth4lkzpnnjv8vf98m20lhqdzl60mcftq7r2lc4xtcsv0w6xjstag0ua";
+
+const STARTING_AMOUNT: u64 = 10_000_000;
+const BLOCK_LENGTH: i64 = 20;
+
+fn storage_with_alice_funds(
+    current_time: i64,
+) -> (InMemoryStorage<PlutusData>, Output<PlutusData>) {
+    let alice = Address::from_bech32(ALICE).unwrap();
+    let output = starting_output::<PlutusData>(&alice, STARTING_AMOUNT);
+    let outputs = vec![(alice.clone(), output.clone())];
+    let storage = InMemoryStorage::new(alice, outputs, current_time, BLOCK_LENGTH);
+    (storage, output)
+}
+
+fn spawn_emulator(storage: InMemoryStorage<PlutusData>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let emulator = BlockfrostEmulator::new(storage, Network::Preprod.into());
+    tokio::spawn(emulator.serve(listener));
+    url
+}
+
+fn cml_address(bech32: &str) -> CMLAddress {
+    CMLAddress::from_bech32(bech32).unwrap()
+}
+
+fn transfer(
+    input: &Output<PlutusData>,
+    amount: u64,
+    change: u64,
+    fee: u64,
+    ttl: Option<u64>,
+) -> CMLTransaction {
+    let mut inputs = TransactionInputs::new();
+    let tx_hash = TransactionHash::from_bytes(input.id().tx_hash().to_vec()).unwrap();
+    inputs.add(&TransactionInput::new(&tx_hash, &input.id().index().into()));
+    let mut outputs = TransactionOutputs::new();
+    let to_bob = CMLValue::new(&BigNum::from(amount));
+    outputs.add(&TransactionOutput::new(&cml_address(BOB), &to_bob));
+    let to_alice = CMLValue::new(&BigNum::from(change));
+    outputs.add(&TransactionOutput::new(&cml_address(ALICE), &to_alice));
+    let body = TransactionBody::new(
+        &inputs,
+        &outputs,
+        &BigNum::from(fee),
+        ttl.map(BigNum::from),
+    );
+    CMLTransaction::new(&body, &TransactionWitnessSet::new(), None)
+}
+
+fn v1_script(source: &str) -> PlutusV1Script {
+    let program = uplc::parser::program(source).unwrap();
+    let program: Program<DeBruijn> = program.try_into().unwrap();
+    PlutusV1Script::new(hex::decode(program.to_hex().unwrap()).unwrap())
+}
+
+/// An output locked by `script` holding the starting amount, and a transaction spending it
+/// back to Alice
+fn spend_from_script(script: &PlutusV1Script) -> (Output<PlutusData>, CMLTransaction) {
+    let script_hash = PlutusScript::from_v1(script).hash();
+    let credential = StakeCredential::from_scripthash(&script_hash);
+    let script_address = EnterpriseAddress::new(0, &credential).to_address();
+    let owner = address_from_cml(&script_address).unwrap();
+    let mut values = Values::default();
+    values.add_one_value(&PolicyId::Lovelace, STARTING_AMOUNT);
+    let datum = PlutusData::BigInt(1.into());
+    let input = Output::new_untyped_validator(vec![1; 32], 0, owner, values, datum);
+
+    let mut tx = transfer(&input, 3_000_000, 6_800_000, 200_000, None);
+    let mut witness_set = TransactionWitnessSet::new();
+    let mut scripts = PlutusV1Scripts::new();
+    scripts.add(script);
+    witness_set.set_plutus_v1_scripts(&scripts);
+    let mut redeemers = Redeemers::new();
+    redeemers.add(&Redeemer::new(
+        &RedeemerTag::new_spend(),
+        &BigNum::from(0),
+        &PlutusData::BigInt(2.into()).to_plutus_data(),
+        &ExUnits::new(&BigNum::from(0), &BigNum::from(0)),
+    ));
+    witness_set.set_redeemers(&redeemers);
+    tx = CMLTransaction::new(&tx.body(), &witness_set, None);
+    (input, tx)
+}
+
+#[tokio::test]
+async fn get_utxos__returns_outputs_at_address() {
+    // Given
+    let (storage, output) = storage_with_alice_funds(0);
+    let url = spawn_emulator(storage);
+    let ledger = BlockFrostLedger::new(&url, "any key");
+
+    // When
+    let utxos = ledger
+        .get_all_utxos_for_addr(&cml_address(ALICE))
+        .await
+        .unwrap();
+
+    // Then
+    assert_eq!(utxos.len(), 1);
+    let utxo = utxos.first().unwrap();
+    assert_eq!(utxo.tx_hash().to_bytes(), output.id().tx_hash());
+    let lovelace: u64 = utxo.amount().coin().into();
+    assert_eq!(lovelace, STARTING_AMOUNT);
+}
+
+#[tokio::test]
+async fn get_utxos__empty_address_is_not_an_error() {
+    // Given
+    let (storage, _) = storage_with_alice_funds(0);
+    let url = spawn_emulator(storage);
+    let ledger = BlockFrostLedger::new(&url, "any key");
+
+    // When
+    let utxos = ledger
+        .get_all_utxos_for_addr(&cml_address(BOB))
+        .await
+        .unwrap();
+
+    // Then
+    assert!(utxos.is_empty());
+}
+
+#[tokio::test]
+async fn last_block_time__is_ledger_time() {
+    // Given
+    let current_time = 1_700_000_000;
+    let (storage, _) = storage_with_alice_funds(current_time);
+    let url = spawn_emulator(storage);
+    let ledger = BlockFrostLedger::new(&url, "any key");
+
+    // When
+    let block_time = ledger.last_block_time_secs().await.unwrap();
+
+    // Then
+    assert_eq!(block_time, current_time);
+}
+
+#[tokio::test]
+async fn evaluation_params__are_the_emulators() {
+    // Given
+    let (storage, _) = storage_with_alice_funds(0);
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let evaluation = EvaluationParams {
+        max_budget: ExecutionCost::new(1_000, 2_000),
+        ..EvaluationParams::vasil()
+    };
+    let emulator = BlockfrostEmulator::new(storage, Network::Preprod.into())
+        .with_evaluation_params(evaluation.clone());
+    tokio::spawn(emulator.serve(listener));
+    let ledger = BlockFrostLedger::new(&url, "any key");
+
+    // When
+    let params = ledger.evaluation_params().await.unwrap();
+
+    // Then
+    assert_eq!(params, evaluation);
+}
+
+#[tokio::test]
+async fn submit__applies_transfer_to_ledger() {
+    // Given
+    let (storage, output) = storage_with_alice_funds(0);
+    let url = spawn_emulator(storage);
+    let ledger = BlockFrostLedger::new(&url, "any key");
+    let amount = 3_000_000;
+    let tx = transfer(
+        &output,
+        amount,
+        STARTING_AMOUNT - amount - 200_000,
+        200_000,
+        None,
+    );
+
+    // When
+    let tx_id = ledger.submit_transaction(&tx).await.unwrap();
+
+    // Then
+    let expected_id = hex::encode(hash_transaction(&tx.body()).to_bytes());
+    assert_eq!(tx_id, expected_id);
+    let bob_utxos = ledger
+        .get_all_utxos_for_addr(&cml_address(BOB))
+        .await
+        .unwrap();
+    assert_eq!(bob_utxos.len(), 1);
+    let bob_lovelace: u64 = bob_utxos.first().unwrap().amount().coin().into();
+    assert_eq!(bob_lovelace, amount);
+    let alice_utxos = ledger
+        .get_all_utxos_for_addr(&cml_address(ALICE))
+        .await
+        .unwrap();
+    assert_eq!(alice_utxos.len(), 1);
+    assert_eq!(
+        hex::encode(alice_utxos.first().unwrap().tx_hash().to_bytes()),
+        tx_id
+    );
+    let block_time = ledger.last_block_time_secs().await.unwrap();
+    assert_eq!(block_time, BLOCK_LENGTH);
+}
+
+#[tokio::test]
+async fn submit__unbalanced_tx_is_rejected() {
+    // Given
+    let (storage, output) = storage_with_alice_funds(0);
+    let emulator = BlockfrostEmulator::new(storage, Network::Preprod.into());
+    let tx = transfer(&output, 3_000_000, 7_000_000, 200_000, None);
+
+    // When
+    let error = emulator.submit(&tx.to_bytes()).await.unwrap_err();
+
+    // Then
+    assert!(matches!(error, EmulatorError::NotBalanced));
+    let alice = Address::from_bech32(ALICE).unwrap();
+    assert_eq!(emulator.utxos(&alice).await.unwrap(), vec![output]);
+}
+
+#[tokio::test]
+async fn submit__unknown_input_is_rejected() {
+    // Given
+    let (storage, output) = storage_with_alice_funds(0);
+    let emulator = BlockfrostEmulator::new(storage, Network::Preprod.into());
+    let tx = transfer(&output, 3_000_000, 6_800_000, 200_000, None);
+    emulator.submit(&tx.to_bytes()).await.unwrap();
+
+    // When
+    let error = emulator.submit(&tx.to_bytes()).await.unwrap_err();
+
+    // Then
+    assert!(matches!(
+        error,
+        EmulatorError::Evaluation(LocalEvaluationError::UnknownInput(_, 0))
+    ));
+}
+
+#[tokio::test]
+async fn submit__expired_tx_is_rejected() {
+    // Given
+    let network_settings: NetworkSettings = Network::Preprod.into();
+    let current_time = network_settings.posix_from_slot(1_000);
+    let (storage, output) = storage_with_alice_funds(current_time);
+    let emulator = BlockfrostEmulator::new(storage, network_settings);
+    let tx = transfer(&output, 3_000_000, 6_800_000, 200_000, Some(1_000));
+
+    // When
+    let error = emulator.submit(&tx.to_bytes()).await.unwrap_err();
+
+    // Then
+    assert!(matches!(error, EmulatorError::TooLate(1_000, 1_000)));
+}
+
+#[tokio::test]
+async fn submit_endpoint__rejection_is_bad_request() {
+    // Given
+    let (storage, output) = storage_with_alice_funds(0);
+    let url = spawn_emulator(storage);
+    let tx = transfer(&output, 3_000_000, 7_000_000, 200_000, None);
+
+    // When
+    let response = reqwest::Client::new()
+        .post(format!("{url}/tx/submit"))
+        .header("Content-Type", "application/cbor")
+        .body(tx.to_bytes())
+        .send()
+        .await
+        .unwrap();
+
+    // Then
+    assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn submit__spends_script_output() {
+    // Given
+    let script = v1_script("(program 1.0.0 (lam d (lam r (lam c (con unit ())))))");
+    let (input, tx) = spend_from_script(&script);
+    let alice = Address::from_bech32(ALICE).unwrap();
+    let outputs = vec![(input.owner(), input.clone())];
+    let storage = InMemoryStorage::new(alice, outputs, 0, BLOCK_LENGTH);
+    let emulator = BlockfrostEmulator::new(storage, Network::Preprod.into());
+
+    // When
+    emulator.submit(&tx.to_bytes()).await.unwrap();
+
+    // Then
+    assert!(emulator.utxos(&input.owner()).await.unwrap().is_empty());
+    let bob = Address::from_bech32(BOB).unwrap();
+    let bob_utxos = emulator.utxos(&bob).await.unwrap();
+    assert_eq!(bob_utxos.len(), 1);
+    assert_eq!(
+        bob_utxos[0].values().get(&PolicyId::Lovelace),
+        Some(3_000_000)
+    );
+}
+
+#[tokio::test]
+async fn submit__failing_script_is_rejected() {
+    // Given
+    let script = v1_script("(program 1.0.0 (lam d (lam r (lam c (error)))))");
+    let (input, tx) = spend_from_script(&script);
+    let alice = Address::from_bech32(ALICE).unwrap();
+    let outputs = vec![(input.owner(), input.clone())];
+    let storage = InMemoryStorage::new(alice, outputs, 0, BLOCK_LENGTH);
+    let emulator = BlockfrostEmulator::new(storage, Network::Preprod.into());
+
+    // When
+    let error = emulator.submit(&tx.to_bytes()).await.unwrap_err();
+
+    // Then
+    assert!(matches!(
+        error,
+        EmulatorError::Evaluation(LocalEvaluationError::Script(_))
+    ));
+    assert_eq!(emulator.utxos(&input.owner()).await.unwrap(), vec![input]);
+}
+
+#[tokio::test]
+async fn submit__datum_for_inline_datum_input_is_rejected() {
+    // Given
+    let script = v1_script("(program 1.0.0 (lam d (lam r (lam c (con unit ())))))");
+    let (input, tx) = spend_from_script(&script);
+    let mut witness_set = tx.witness_set();
+    let mut datums = PlutusList::new();
+    datums.add(&input.datum_plutus_data().unwrap().to_plutus_data());
+    witness_set.set_plutus_data(&datums);
+    let tx = CMLTransaction::new(&tx.body(), &witness_set, None);
+    let alice = Address::from_bech32(ALICE).unwrap();
+    let outputs = vec![(input.owner(), input.clone())];
+    let storage = InMemoryStorage::new(alice, outputs, 0, BLOCK_LENGTH);
+    let emulator = BlockfrostEmulator::new(storage, Network::Preprod.into());
+
+    // When
+    let error = emulator.submit(&tx.to_bytes()).await.unwrap_err();
+
+    // Then
+    assert!(matches!(
+        error,
+        EmulatorError::NotAllowedSupplementalDatum(_)
+    ));
+    assert_eq!(emulator.utxos(&input.owner()).await.unwrap(), vec![input]);
+}
+
+#[tokio::test]
+async fn submit__mints_with_policy() {
+    // Given
+    let (storage, output) = storage_with_alice_funds(0);
+    let emulator = BlockfrostEmulator::new(storage, Network::Preprod.into());
+    let policy_script = v1_script("(program 1.0.0 (lam r (lam c (con unit ()))))");
+    let policy_id = PlutusScript::from_v1(&policy_script).hash();
+    let asset_name = AssetName::new(b"Honey".to_vec()).unwrap();
+    let fee = 200_000;
+
+    let mut inputs = TransactionInputs::new();
+    let tx_hash = TransactionHash::from_bytes(output.id().tx_hash().to_vec()).unwrap();
+    inputs.add(&TransactionInput::new(
+        &tx_hash,
+        &output.id().index().into(),
+    ));
+    let mut assets = Assets::new();
+    assets.insert(&asset_name, &BigNum::from(1));
+    let mut multi_asset = MultiAsset::new();
+    multi_asset.insert(&policy_id, &assets);
+    let mut to_alice = CMLValue::new(&BigNum::from(STARTING_AMOUNT - fee));
+    to_alice.set_multiasset(&multi_asset);
+    let mut outputs = TransactionOutputs::new();
+    outputs.add(&TransactionOutput::new(&cml_address(ALICE), &to_alice));
+    let mut body = TransactionBody::new(&inputs, &outputs, &BigNum::from(fee), None);
+    let mut mint = Mint::new();
+    let mint_assets = MintAssets::new_from_entry(&asset_name, Int::new(&BigNum::from(1)));
+    mint.insert(&policy_id, &mint_assets);
+    body.set_mint(&mint);
+
+    let mut witness_set = TransactionWitnessSet::new();
+    let mut scripts = PlutusV1Scripts::new();
+    scripts.add(&policy_script);
+    witness_set.set_plutus_v1_scripts(&scripts);
+    let mut redeemers = Redeemers::new();
+    redeemers.add(&Redeemer::new(
+        &RedeemerTag::new_mint(),
+        &BigNum::from(0),
+        &PlutusData::BigInt(0.into()).to_plutus_data(),
+        &ExUnits::new(&BigNum::from(0), &BigNum::from(0)),
+    ));
+    witness_set.set_redeemers(&redeemers);
+    let tx = CMLTransaction::new(&body, &witness_set, None);
+
+    // When
+    emulator.submit(&tx.to_bytes()).await.unwrap();
+
+    // Then
+    let alice = Address::from_bech32(ALICE).unwrap();
+    let alice_utxos = emulator.utxos(&alice).await.unwrap();
+    assert_eq!(alice_utxos.len(), 1);
+    let honey =
+        PolicyId::native_token(&policy_id.to_string(), &Some("Honey".to_string()));
+    assert_eq!(alice_utxos[0].values().get(&honey), Some(1));
+}
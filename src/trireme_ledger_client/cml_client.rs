@@ -5,10 +5,15 @@ use crate::{
         LedgerClientResult,
     },
     output::{
+        DatumAttachment,
         Output,
         UnbuiltOutput,
     },
-    scripts::Validator,
+    scripts::{
+        evaluation::EvaluationParams,
+        plutus_validator::plutus_data::PlutusData as NauPlutusData,
+        Validator,
+    },
     transaction::{
         TransactionVersion,
         TxId,
@@ -30,6 +35,10 @@ use crate::{
             vasil_v1_tx_builder,
             vasil_v2_tx_builder,
         },
+        local_evaluation::{
+            evaluate_tx,
+            RedeemerPurpose,
+        },
         network_settings::NetworkSettings,
         plutus_data_interop::PlutusDataInterop,
     },
@@ -64,6 +73,7 @@ use cardano_multiplatform_lib::{
         },
     },
     crypto::{
+        DataHash,
         PrivateKey,
         TransactionHash,
     },
@@ -76,6 +86,7 @@ use cardano_multiplatform_lib::{
         },
     },
     plutus::{
+        Data,
         ExUnits,
         PlutusData,
         PlutusScript,
@@ -94,6 +105,10 @@ use pallas_addresses::{
     Address,
     Network as CMLNetwork,
 };
+use serde::{
+    Deserialize,
+    Serialize,
+};
 use std::{
     collections::HashMap,
     fmt::Debug,
@@ -106,15 +121,25 @@ use std::{
 pub mod blockfrost_ledger;
 /// CML Ledger Client Error module
 pub mod error;
-mod issuance_helpers;
+pub(crate) mod issuance_helpers;
 /// CML Client Keys module
 pub mod key_manager;
+/// Kupo HTTP Client module
+pub mod kupo_client;
+/// Kupo + Ogmios Ledger module
+pub mod kupo_ogmios_ledger;
+/// Local Script Evaluation module
+pub mod local_evaluation;
 /// CML Client Network Settings module
 pub mod network_settings;
+/// Ogmios local-state-query Ledger module
+pub mod ogmios_lsq_ledger;
 /// Ogmios + Scrolls Ledger module
 pub mod ogmios_scrolls_ledger;
 /// Plutus Data Interop module
 pub mod plutus_data_interop;
+/// Request Policy module for remote ledgers
+pub mod request_policy;
 
 #[cfg(test)]
 mod tests;
@@ -131,10 +156,72 @@ where
     ledger: L,
     keys: K,
     network_settings: NetworkSettings,
+    ex_units_source: ExUnitsSource,
     _datum: PhantomData<Datum>,
     _redeemer: PhantomData<Redeemer>,
 }
 
+/// Where the [`CMLLedgerCLient`] gets the execution units of the scripts in a transaction from
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(tag = "type")]
+pub enum ExUnitsSource {
+    /// Send the draft transaction to the [`Ledger`], see [`Ledger::calculate_ex_units`]
+    #[default]
+    Ledger,
+    /// Run the scripts locally with `uplc`, against the outputs the client spends, with the
+    /// cost models and budget of the [`Ledger`]'s current protocol parameters, see
+    /// [`Ledger::evaluation_params`]. Failures include the scripts' trace logs
+    Local,
+    /// Like [`ExUnitsSource::Local`], but with the given cost models and budget instead of the
+    /// [`Ledger`]'s, e.g. [`EvaluationParams::vasil`] when the protocol parameters can't be
+    /// fetched. Costs no requests to the ledger
+    LocalWith(EvaluationParams),
+}
+
+/// The outputs a transaction built by the [`CMLLedgerCLient`] may spend: the signer's UTxOs and
+/// the script and specific inputs of `tx`
+fn spent_outputs<Datum: PlutusDataInterop + Clone, Redeemer>(
+    tx: &UnbuiltTransaction<Datum, Redeemer>,
+    my_utxos: &[UTxO],
+    my_address: &CMLAddress,
+) -> LedgerClientResult<Vec<Output<NauPlutusData>>> {
+    let owner = my_address
+        .to_bech32(None)
+        .map_err(|e| CMLLCError::JsError(e.to_string()))
+        .map_err(as_failed_to_issue_tx)?;
+    let owner = Address::from_bech32(&owner).map_err(as_failed_to_issue_tx)?;
+    let mut outputs = my_utxos
+        .iter()
+        .map(|utxo| utxo_to_nau_utxo(utxo, &owner))
+        .collect::<LedgerClientResult<Vec<_>>>()?;
+    let script_inputs = tx.script_inputs().iter().map(|(input, _, _)| input);
+    for input in script_inputs.chain(tx.specific_wallet_inputs.iter()) {
+        let id = input.id();
+        let maybe_datum: Option<Datum> = input.datum().to_owned().into();
+        let output = match maybe_datum {
+            Some(datum) => Output::new_untyped_validator(
+                id.tx_hash().to_vec(),
+                id.index(),
+                input.owner(),
+                input.values().clone(),
+                datum.to_plutus_data().into(),
+            ),
+            None => Output::new_wallet(
+                id.tx_hash().to_vec(),
+                id.index(),
+                input.owner(),
+                input.values().clone(),
+            ),
+        };
+        let output = match input.datum_hash() {
+            Some(datum_hash) => output.with_datum_hash(datum_hash.to_vec()),
+            None => output,
+        };
+        outputs.push(output);
+    }
+    Ok(outputs)
+}
+
 /// Interface for providing keys to the [`CMLLedgerCLient`]
 #[async_trait]
 pub trait Keys {
@@ -157,6 +244,8 @@ pub struct UTxO {
     output_index: BigNum,
     amount: CMLValue,
     datum: Option<PlutusData>,
+    datum_hash: Option<DataHash>,
+    reference_script: Option<PlutusScript>,
 }
 
 impl UTxO {
@@ -172,9 +261,23 @@ impl UTxO {
             output_index,
             amount,
             datum,
+            datum_hash: None,
+            reference_script: None,
         }
     }
 
+    /// Mark the `UTxO`'s datum as being held by hash rather than inline
+    pub fn with_datum_hash(mut self, datum_hash: DataHash) -> Self {
+        self.datum_hash = Some(datum_hash);
+        self
+    }
+
+    /// Attach the Plutus script that the `UTxO` holds for use as a reference script
+    pub fn with_reference_script(mut self, reference_script: PlutusScript) -> Self {
+        self.reference_script = Some(reference_script);
+        self
+    }
+
     /// Get the transaction hash
     pub fn tx_hash(&self) -> &TransactionHash {
         &self.tx_hash
@@ -194,6 +297,16 @@ impl UTxO {
     pub fn datum(&self) -> &Option<PlutusData> {
         &self.datum
     }
+
+    /// Get the hash of the datum, if the `UTxO` holds its datum by hash rather than inline
+    pub fn datum_hash(&self) -> &Option<DataHash> {
+        &self.datum_hash
+    }
+
+    /// Get the reference script held by the `UTxO`
+    pub fn reference_script(&self) -> &Option<PlutusScript> {
+        &self.reference_script
+    }
 }
 
 /// Cost of execution for a transaction
@@ -273,6 +386,13 @@ impl ExecutionCost {
     }
 }
 
+/// The ledger's units, to compare with what executing a script locally cost
+impl From<&ExecutionCost> for crate::scripts::ExecutionCost {
+    fn from(cost: &ExecutionCost) -> Self {
+        crate::scripts::ExecutionCost::new(cost.memory as i64, cost.steps as i64)
+    }
+}
+
 /// Interface for providing a ledger to the [`CMLLedgerCLient`]
 #[async_trait]
 pub trait Ledger {
@@ -293,6 +413,9 @@ pub trait Ledger {
     ) -> Result<HashMap<u64, ExecutionCost>>;
     /// Submit a transaction
     async fn submit_transaction(&self, tx: &CMLTransaction) -> Result<String>;
+    /// Get the cost models and maximum execution units of the current protocol parameters,
+    /// to evaluate scripts locally the same way the node does
+    async fn evaluation_params(&self) -> Result<EvaluationParams>;
 }
 
 impl<L, K, D, R> CMLLedgerCLient<L, K, D, R>
@@ -308,11 +431,18 @@ where
             ledger,
             keys,
             network_settings,
+            ex_units_source: ExUnitsSource::default(),
             _datum: Default::default(),
             _redeemer: Default::default(),
         }
     }
 
+    /// Get the execution units of scripts from `ex_units_source` instead of the [`Ledger`]
+    pub fn with_ex_units_source(mut self, ex_units_source: ExUnitsSource) -> Self {
+        self.ex_units_source = ex_units_source;
+        self
+    }
+
     async fn add_outputs_for_tx<
         Datum: PlutusDataInterop + Debug,
         Redeemer: PlutusDataInterop,
@@ -331,16 +461,31 @@ where
             let recp_addr = addr_from_bech_32(&recipient.to_string())
                 .map_err(as_failed_to_issue_tx)?;
             let mut output = TransactionOutput::new(&recp_addr, &cml_values);
-            let res = if let UnbuiltOutput::Validator { datum, .. } = unbuilt_output {
-                let data = datum.to_plutus_data();
-                let data_hash = hash_plutus_data(&data);
-                let cml_datum = CMLDatum::new_data_hash(&data_hash);
-                output.set_datum(&cml_datum);
-                let mut res = SingleOutputBuilderResult::new(&output);
-                res.set_communication_datum(&data);
-                res
-            } else {
-                SingleOutputBuilderResult::new(&output)
+            let res = match unbuilt_output {
+                UnbuiltOutput::Validator {
+                    datum, attachment, ..
+                } => {
+                    let data = datum.to_plutus_data();
+                    match attachment {
+                        DatumAttachment::Inline => {
+                            if matches!(tx.script_version, TransactionVersion::V1) {
+                                return Err(as_failed_to_issue_tx(
+                                    CMLLCError::InlineDatumInV1Tx,
+                                ))
+                            }
+                            output.set_datum(&CMLDatum::new_data(&Data::new(&data)));
+                            SingleOutputBuilderResult::new(&output)
+                        }
+                        DatumAttachment::Hash => {
+                            let data_hash = hash_plutus_data(&data);
+                            output.set_datum(&CMLDatum::new_data_hash(&data_hash));
+                            let mut res = SingleOutputBuilderResult::new(&output);
+                            res.set_communication_datum(&data);
+                            res
+                        }
+                    }
+                }
+                UnbuiltOutput::Wallet { .. } => SingleOutputBuilderResult::new(&output),
             };
             tx_builder
                 .add_output(&res)
@@ -418,15 +563,25 @@ where
             .clone()
             .try_into()
             .map_err(as_failed_to_issue_tx)?;
-        let utxo_info = TransactionOutput::new(&cml_script_address, &value);
-        let input_builder = SingleInputBuilder::new(&script_input, &utxo_info);
+        let mut utxo_info = TransactionOutput::new(&cml_script_address, &value);
         let maybe_data: Option<Datum> = input.datum().to_owned().into();
         let data = maybe_data.ok_or(LedgerClientError::NoDatumOnScriptInput)?;
         let datum = data.to_plutus_data();
-        let cml_input = input_builder
-            .plutus_script(&partial_witness, &required_signers, &datum)
-            .map_err(|e| CMLLCError::JsError(e.to_string()))
-            .map_err(as_failed_to_issue_tx)?;
+        let cml_input = if input.datum_hash().is_some() {
+            SingleInputBuilder::new(&script_input, &utxo_info).plutus_script(
+                &partial_witness,
+                &required_signers,
+                &datum,
+            )
+        } else {
+            // The script reads an inline datum from the spent output, so supplying it in the
+            // witness set as well would make the transaction invalid
+            utxo_info.set_datum(&CMLDatum::new_data(&Data::new(&datum)));
+            SingleInputBuilder::new(&script_input, &utxo_info)
+                .plutus_script_inline_datum(&partial_witness, &required_signers)
+        }
+        .map_err(|e| CMLLCError::JsError(e.to_string()))
+        .map_err(as_failed_to_issue_tx)?;
         Ok(cml_input)
     }
 
@@ -563,9 +718,36 @@ where
         Ok(())
     }
 
-    async fn update_ex_units(
+    fn evaluate_locally<Datum: PlutusDataInterop + Clone, Redeemer>(
+        &self,
+        transaction: &CMLTransaction,
+        tx: &UnbuiltTransaction<Datum, Redeemer>,
+        my_utxos: &[UTxO],
+        my_address: &CMLAddress,
+        evaluation: &EvaluationParams,
+    ) -> LedgerClientResult<Vec<(u64, ExecutionCost)>> {
+        let inputs = spent_outputs(tx, my_utxos, my_address)?;
+        let costs = evaluate_tx(transaction, &inputs, &self.network_settings, evaluation)
+            .map_err(as_failed_to_issue_tx)?
+            .into_iter()
+            .map(|(purpose, index, cost)| {
+                let memory = cost.mem() as u64;
+                let steps = cost.cpu() as u64;
+                let cost = match purpose {
+                    RedeemerPurpose::Spend => ExecutionCost::new_spend(memory, steps),
+                    RedeemerPurpose::Mint => ExecutionCost::new_mint(memory, steps),
+                };
+                (index, cost)
+            })
+            .collect();
+        Ok(costs)
+    }
+
+    async fn update_ex_units<Datum: PlutusDataInterop + Clone, Redeemer>(
         &self,
         tx_builder: &mut TransactionBuilder,
+        tx: &UnbuiltTransaction<Datum, Redeemer>,
+        my_utxos: &[UTxO],
         my_address: &CMLAddress,
     ) -> LedgerClientResult<()> {
         let algo = ChangeSelectionAlgo::Default;
@@ -574,13 +756,33 @@ where
             .map_err(|e| CMLLCError::JsError(e.to_string()))
             .map_err(as_failed_to_issue_tx)?;
         let transaction = tx_redeemer_builder.draft_tx();
-        println!("{}", &transaction.to_json().unwrap());
-        let res = self
-            .ledger
-            .calculate_ex_units(&transaction)
-            .await
-            .map_err(|e| CMLLCError::JsError(e.to_string()))
-            .map_err(as_failed_to_issue_tx)?;
+        let res: Vec<(u64, ExecutionCost)> = match &self.ex_units_source {
+            ExUnitsSource::Ledger => self
+                .ledger
+                .calculate_ex_units(&transaction)
+                .await
+                .map_err(|e| CMLLCError::JsError(e.to_string()))
+                .map_err(as_failed_to_issue_tx)?
+                .into_iter()
+                .collect(),
+            ExUnitsSource::Local => {
+                let evaluation = self
+                    .ledger
+                    .evaluation_params()
+                    .await
+                    .map_err(as_failed_to_issue_tx)?;
+                self.evaluate_locally(
+                    &transaction,
+                    tx,
+                    my_utxos,
+                    my_address,
+                    &evaluation,
+                )?
+            }
+            ExUnitsSource::LocalWith(evaluation) => {
+                self.evaluate_locally(&transaction, tx, my_utxos, my_address, evaluation)?
+            }
+        };
         for (index, spend) in res.iter() {
             let tag = match spend.execution_type {
                 ExecutionType::Spend => RedeemerTag::new_spend(),
@@ -627,7 +829,8 @@ where
         self.add_outputs_for_tx(&mut tx_builder, &tx).await?;
         add_collateral(&mut tx_builder, &my_address, &my_utxos).await?;
         select_inputs_from_utxos(&mut tx_builder).await?;
-        self.update_ex_units(&mut tx_builder, &my_address).await?;
+        self.update_ex_units(&mut tx_builder, &tx, &my_utxos, &my_address)
+            .await?;
         let mut signed_tx_builder =
             build_tx_for_signing(&mut tx_builder, &my_address).await?;
         let tx = sign_tx(&mut signed_tx_builder, &priv_key).await?;
@@ -659,7 +862,8 @@ where
         self.add_outputs_for_tx(&mut tx_builder, &tx).await?;
         add_collateral(&mut tx_builder, &my_address, &my_utxos).await?;
         select_inputs_from_utxos(&mut tx_builder).await?;
-        self.update_ex_units(&mut tx_builder, &my_address).await?;
+        self.update_ex_units(&mut tx_builder, &tx, &my_utxos, &my_address)
+            .await?;
         let mut signed_tx_builder =
             build_tx_for_signing(&mut tx_builder, &my_address).await?;
         let tx = sign_tx(&mut signed_tx_builder, &priv_key).await?;
@@ -1,16 +1,28 @@
 use super::error::*;
-use crate::trireme_ledger_client::cml_client::{
-    error::CMLLCError,
-    ExecutionCost,
-    Ledger,
-    UTxO,
+use crate::{
+    scripts::{
+        evaluation::{
+            CostModels,
+            EvaluationParams,
+        },
+        ExecutionCost as ScriptExecutionCost,
+    },
+    trireme_ledger_client::cml_client::{
+        error::CMLLCError,
+        request_policy::{
+            RequestLimiter,
+            RequestPolicy,
+        },
+        ExecutionCost,
+        Ledger,
+        UTxO,
+    },
 };
 use async_trait::async_trait;
 use blockfrost_http_client::{
     error::Error,
     models::{
         ExecutionType,
-        UTxO as BFUTxO,
         Value as BFValue,
     },
     BlockFrostHttp,
@@ -18,14 +30,19 @@ use blockfrost_http_client::{
 };
 use cardano_multiplatform_lib::{
     address::Address as CMLAddress,
-    crypto::TransactionHash,
+    crypto::{
+        DataHash,
+        TransactionHash,
+    },
     ledger::common::value::{
         BigNum,
         Value as CMLValue,
     },
     plutus::{
-        encode_json_str_to_plutus_datum,
-        PlutusDatumSchema,
+        PlutusData,
+        PlutusScript,
+        PlutusV1Script,
+        PlutusV2Script,
     },
     AssetName,
     Assets,
@@ -34,7 +51,12 @@ use cardano_multiplatform_lib::{
     Transaction as CMLTransaction,
 };
 use futures::future;
+use reqwest::{
+    Client,
+    StatusCode,
+};
 use serde::{
+    de::DeserializeOwned,
     Deserialize,
     Serialize,
 };
@@ -44,61 +66,286 @@ use std::{
 };
 use thiserror::Error;
 
+#[cfg(test)]
+mod tests;
+
+// Largest page Blockfrost will return
+const MAX_PAGE_SIZE: usize = 100;
+
 /// A Ledger implementation that uses Blockfrost as a backend
 pub struct BlockFrostLedger {
     client: BlockFrostHttp,
+    url: String,
+    key: String,
+    http: Client,
+    limiter: RequestLimiter,
 }
 
 impl BlockFrostLedger {
-    /// Constructor for BlockFrostLedger
+    /// Constructor for BlockFrostLedger, with the default [`RequestPolicy`]
     pub fn new(url: &str, key: &str) -> Self {
         let client = BlockFrostHttp::new(url, key);
-        BlockFrostLedger { client }
+        BlockFrostLedger {
+            client,
+            url: url.trim_end_matches('/').to_string(),
+            key: key.to_string(),
+            http: Client::new(),
+            limiter: RequestLimiter::new(RequestPolicy::default()),
+        }
+    }
+
+    /// Use `policy` for the requests to Blockfrost, e.g. to stay under the rate limit of
+    /// your plan
+    pub fn with_request_policy(mut self, policy: RequestPolicy) -> Self {
+        self.limiter = RequestLimiter::new(policy);
+        self
+    }
+
+    /// Get a datum by its hash. `None` if Blockfrost hasn't seen the datum
+    pub async fn get_datum(&self, datum_hash: &str) -> Result<Option<PlutusData>> {
+        let path = format!("/scripts/datum/{datum_hash}/cbor");
+        match self.get::<BlockfrostCbor>(&path).await? {
+            Some(datum) => {
+                let bytes = hex::decode(datum.cbor)?;
+                let datum = PlutusData::from_bytes(bytes)
+                    .map_err(|e| CMLLCError::Deserialize(e.to_string()))?;
+                Ok(Some(datum))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Get a Plutus script by its hash. `None` if Blockfrost hasn't seen the script or if it
+    /// is a native script
+    pub async fn get_script(&self, script_hash: &str) -> Result<Option<PlutusScript>> {
+        let script_type = match self
+            .get::<BlockfrostScript>(&format!("/scripts/{script_hash}"))
+            .await?
+        {
+            Some(info) if info.script_type != "timelock" => info.script_type,
+            _ => return Ok(None),
+        };
+        let script = match self
+            .get::<BlockfrostCbor>(&format!("/scripts/{script_hash}/cbor"))
+            .await?
+        {
+            Some(script) => script,
+            None => return Ok(None),
+        };
+        let bytes = hex::decode(script.cbor)?;
+        let script = match script_type.as_str() {
+            "plutusV1" => {
+                let v1 = PlutusV1Script::from_bytes(bytes)
+                    .map_err(|e| CMLLCError::Deserialize(e.to_string()))?;
+                PlutusScript::from_v1(&v1)
+            }
+            "plutusV2" => {
+                let v2 = PlutusV2Script::from_bytes(bytes)
+                    .map_err(|e| CMLLCError::Deserialize(e.to_string()))?;
+                PlutusScript::from_v2(&v2)
+            }
+            other => {
+                return Err(CMLLCError::Deserialize(format!(
+                    "Unknown script type: {other}"
+                )))
+            }
+        };
+        Ok(Some(script))
+    }
+
+    /// Get up to `count` UTxOs at an address, oldest first. All of them if `count` is `None`
+    async fn get_utxos(
+        &self,
+        addr: &CMLAddress,
+        count: Option<usize>,
+    ) -> Result<Vec<UTxO>> {
+        let addr_string = addr
+            .to_bech32(None)
+            .map_err(|e| CMLLCError::JsError(e.to_string()))?;
+        let wanted = count.unwrap_or(usize::MAX);
+        let mut bf_utxos = Vec::new();
+        let mut page = 1;
+        while bf_utxos.len() < wanted {
+            let path = format!(
+                "/addresses/{addr_string}/utxos?count={MAX_PAGE_SIZE}&page={page}"
+            );
+            // Blockfrost answers 404 for addresses it has never seen
+            let page_utxos: Vec<BlockfrostUtxo> =
+                self.get(&path).await?.unwrap_or_default();
+            let last_page = page_utxos.len() < MAX_PAGE_SIZE;
+            bf_utxos.extend(page_utxos);
+            if last_page {
+                break;
+            }
+            page += 1;
+        }
+        bf_utxos.truncate(wanted);
+        future::join_all(
+            bf_utxos
+                .iter()
+                .map(|bf_utxo| async move { self.bfutxo_to_utxo(bf_utxo).await }),
+        )
+        .await
+        .into_iter()
+        .collect()
     }
 
     /// Convert a Blockfrost representation of a UTxO to a CML representation
-    // TODO: Handle V2 outputs (with inline datums)
-    async fn bfutxo_to_utxo(&self, bf_utxo: &BFUTxO) -> Result<UTxO> {
-        let tx_hash = TransactionHash::from_hex(bf_utxo.tx_hash())
+    async fn bfutxo_to_utxo(&self, bf_utxo: &BlockfrostUtxo) -> Result<UTxO> {
+        let tx_hash = TransactionHash::from_hex(&bf_utxo.tx_hash)
             .map_err(|e| CMLLCError::JsError(e.to_string()))?;
-        let output_index = bf_utxo.output_index().into();
-        let amount = cmlvalue_from_bfvalues(bf_utxo.amount())?;
-        let datum = if let Some(data_hash) = bf_utxo.data_hash() {
-            let json_datum = self
-                .client
-                .datum(data_hash)
-                .await
-                .map_err(|e| CMLLCError::LedgerError(Box::new(e)))?;
-            if let Some(inner) = json_datum.as_object() {
-                if inner.get("error").is_none() {
-                    let ser = json_datum["json_value"].to_string();
-                    let plutus_data = encode_json_str_to_plutus_datum(
-                        &ser, // TODO: Make this safer!
-                        PlutusDatumSchema::DetailedSchema,
-                    )
-                    .map_err(|e| CMLLCError::JsError(e.to_string()))?;
-                    Some(plutus_data)
-                } else {
-                    None // TODO: Add debug msg
-                }
-            } else {
-                None // TODO: Add debug msg
+        let output_index = bf_utxo.output_index.into();
+        let amount = cmlvalue_from_units(
+            bf_utxo
+                .amount
+                .iter()
+                .map(|amount| (amount.unit.as_str(), amount.quantity.as_str())),
+        )?;
+        let datum = match (&bf_utxo.inline_datum, &bf_utxo.data_hash) {
+            (Some(inline_datum), _) => {
+                let bytes = hex::decode(inline_datum)?;
+                let datum = PlutusData::from_bytes(bytes)
+                    .map_err(|e| CMLLCError::Deserialize(e.to_string()))?;
+                Some(datum)
             }
-        } else {
-            None
+            (None, Some(data_hash)) => self.get_datum(data_hash).await?,
+            (None, None) => None,
+        };
+        let mut utxo = UTxO::new(tx_hash, output_index, amount, datum);
+        if let (None, Some(data_hash)) = (&bf_utxo.inline_datum, &bf_utxo.data_hash) {
+            let data_hash = DataHash::from_hex(data_hash)
+                .map_err(|e| CMLLCError::JsError(e.to_string()))?;
+            utxo = utxo.with_datum_hash(data_hash);
+        }
+        let reference_script = match &bf_utxo.reference_script_hash {
+            Some(script_hash) => self.get_script(script_hash).await?,
+            None => None,
         };
+        match reference_script {
+            Some(script) => Ok(utxo.with_reference_script(script)),
+            None => Ok(utxo),
+        }
+    }
+
+    /// GET a Blockfrost endpoint. `None` if Blockfrost answers 404
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<Option<T>> {
+        self.limiter
+            .send(|| async move {
+                let response = self
+                    .http
+                    .get(format!("{}{path}", self.url))
+                    .header("project_id", &self.key)
+                    .send()
+                    .await
+                    .map_err(reqwest_error)?;
+                match response.status() {
+                    StatusCode::NOT_FOUND => Ok(None),
+                    status if status.is_success() => {
+                        let body = response.json().await.map_err(reqwest_error)?;
+                        Ok(Some(body))
+                    }
+                    status => {
+                        let message = response.text().await.unwrap_or_default();
+                        let error =
+                            format!("Blockfrost responded with {status}: {message}")
+                                .into();
+                        if is_transient_status(status.as_u16()) {
+                            Err(CMLLCError::TransientLedgerError(error))
+                        } else {
+                            Err(CMLLCError::LedgerError(error))
+                        }
+                    }
+                }
+            })
+            .await
+    }
+}
+
+// Rate limited, or Blockfrost is having trouble
+fn is_transient_status(status: u16) -> bool {
+    status == 429 || status >= 500
+}
+
+fn reqwest_error(error: reqwest::Error) -> CMLLCError {
+    if error.is_timeout() || error.is_connect() {
+        CMLLCError::TransientLedgerError(Box::new(error))
+    } else {
+        CMLLCError::LedgerError(Box::new(error))
+    }
+}
 
-        let utxo = UTxO::new(tx_hash, output_index, amount, datum);
-        Ok(utxo)
+fn blockfrost_error(error: Error) -> CMLLCError {
+    let transient = matches!(
+        &error,
+        Error::HttpError { status_code, .. } if is_transient_status(u16::from(*status_code))
+    );
+    if transient {
+        CMLLCError::TransientLedgerError(Box::new(error))
+    } else {
+        CMLLCError::LedgerError(Box::new(error))
     }
 }
 
+#[derive(Deserialize)]
+struct BlockfrostAmount {
+    unit: String,
+    quantity: String,
+}
+
+#[derive(Deserialize)]
+struct BlockfrostUtxo {
+    tx_hash: String,
+    output_index: u64,
+    amount: Vec<BlockfrostAmount>,
+    data_hash: Option<String>,
+    /// CBOR hex of the inline datum
+    inline_datum: Option<String>,
+    reference_script_hash: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct BlockfrostScript {
+    #[serde(rename = "type")]
+    script_type: String,
+}
+
+#[derive(Deserialize)]
+struct BlockfrostCbor {
+    cbor: String,
+}
+
+#[derive(Deserialize)]
+struct BlockfrostProtocolParameters {
+    max_tx_ex_mem: String,
+    max_tx_ex_steps: String,
+    cost_models_raw: BlockfrostCostModels,
+}
+
+/// Cost models as lists of parameters, in the order the ledger expects them
+#[derive(Deserialize)]
+struct BlockfrostCostModels {
+    #[serde(rename = "PlutusV1")]
+    plutus_v1: Vec<i64>,
+    #[serde(rename = "PlutusV2")]
+    plutus_v2: Vec<i64>,
+}
+
+fn parse_quantity(quantity: &str) -> Result<i64> {
+    quantity
+        .parse()
+        .map_err(|_| CMLLCError::Deserialize(format!("Invalid quantity: {quantity}")))
+}
+
 /// Convert a list of Blockfrost values to a CML value
 pub fn cmlvalue_from_bfvalues(values: &[BFValue]) -> Result<CMLValue> {
+    cmlvalue_from_units(values.iter().map(|value| (value.unit(), value.quantity())))
+}
+
+fn cmlvalue_from_units<'a>(
+    values: impl Iterator<Item = (&'a str, &'a str)>,
+) -> Result<CMLValue> {
     let mut cml_value = CMLValue::zero();
-    for value in values.iter() {
-        let unit = value.unit();
-        let quantity = value.quantity();
+    for (unit, quantity) in values {
         let add_value = match unit {
             "lovelace" => CMLValue::new(&BigNum::from_str(quantity).unwrap()),
             _ => {
@@ -132,10 +379,14 @@ pub fn cmlvalue_from_bfvalues(values: &[BFValue]) -> Result<CMLValue> {
 impl Ledger for BlockFrostLedger {
     async fn last_block_time_secs(&self) -> Result<i64> {
         let res = self
-            .client
-            .latest_block_info()
-            .await
-            .map_err(|e| CMLLCError::LedgerError(Box::new(e)))?;
+            .limiter
+            .send(|| async move {
+                self.client
+                    .latest_block_info()
+                    .await
+                    .map_err(blockfrost_error)
+            })
+            .await?;
         Ok(res.time() as i64)
     }
 
@@ -144,73 +395,27 @@ impl Ledger for BlockFrostLedger {
         addr: &CMLAddress,
         count: usize,
     ) -> Result<Vec<UTxO>> {
-        let addr_string = addr
-            .to_bech32(None)
-            .map_err(|e| CMLLCError::JsError(e.to_string()))?;
-        let bf_utxos_res = self.client.utxos(&addr_string, Some(count)).await;
-        let bf_utxos = match bf_utxos_res {
-            Ok(bf_utxos) => Ok(bf_utxos),
-            Err(e) => match e {
-                Error::HttpError { status_code, .. } => {
-                    if status_code == 404 {
-                        Ok(Vec::new())
-                    } else {
-                        Err(CMLLCError::LedgerError(Box::new(e)))
-                    }
-                }
-                _ => Err(CMLLCError::LedgerError(Box::new(e))),
-            },
-        }?;
-        let utxos = future::join_all(
-            bf_utxos
-                .iter()
-                .map(|bf_utxo| async move { self.bfutxo_to_utxo(bf_utxo).await }),
-        )
-        .await
-        .into_iter()
-        .collect::<Result<Vec<_>>>()?;
-        Ok(utxos)
+        self.get_utxos(addr, Some(count)).await
     }
 
     async fn get_all_utxos_for_addr(&self, addr: &CMLAddress) -> Result<Vec<UTxO>> {
-        let addr_string = addr
-            .to_bech32(None)
-            .map_err(|e| CMLLCError::JsError(e.to_string()))?;
-        let bf_utxos_res = self.client.utxos(&addr_string, None).await;
-        let bf_utxos = match bf_utxos_res {
-            Ok(bf_utxos) => Ok(bf_utxos),
-            Err(e) => match e {
-                Error::HttpError { status_code, .. } => {
-                    if status_code == 404 {
-                        Ok(Vec::new())
-                    } else {
-                        Err(CMLLCError::LedgerError(Box::new(e)))
-                    }
-                }
-                _ => Err(CMLLCError::LedgerError(Box::new(e))),
-            },
-        }?;
-        let utxos = future::join_all(
-            bf_utxos
-                .iter()
-                .map(|bf_utxo| async move { self.bfutxo_to_utxo(bf_utxo).await }),
-        )
-        .await
-        .into_iter()
-        .collect::<Result<Vec<_>>>()?;
-        Ok(utxos)
+        self.get_utxos(addr, None).await
     }
 
     async fn calculate_ex_units(
         &self,
         tx: &CMLTransaction,
     ) -> Result<HashMap<u64, ExecutionCost>> {
-        let bytes = tx.to_bytes();
+        let bytes = &tx.to_bytes();
         let res = self
-            .client
-            .execution_units(&bytes)
-            .await
-            .map_err(|e| CMLLCError::LedgerError(Box::new(e)))?;
+            .limiter
+            .send(|| async move {
+                self.client
+                    .execution_units(bytes)
+                    .await
+                    .map_err(blockfrost_error)
+            })
+            .await?;
         let bf_spends = res
             .get_execution_costs()
             .map_err(|e| CMLLCError::LedgerError(Box::new(e)))?;
@@ -224,14 +429,40 @@ impl Ledger for BlockFrostLedger {
     }
 
     async fn submit_transaction(&self, tx: &CMLTransaction) -> Result<String> {
-        println!("{}", &tx.to_json().unwrap());
+        let bytes = tx.to_bytes();
+        // Not retried, as a submission that timed out might still have gone through
         let res = self
-            .client
-            .submit_tx(&tx.to_bytes())
-            .await
-            .map_err(|e| CMLLCError::LedgerError(Box::new(e)))?;
+            .limiter
+            .send_once(async {
+                self.client
+                    .submit_tx(&bytes)
+                    .await
+                    .map_err(blockfrost_error)
+            })
+            .await?;
         Ok(res.tx_id().to_string())
     }
+
+    async fn evaluation_params(&self) -> Result<EvaluationParams> {
+        let params = self
+            .get::<BlockfrostProtocolParameters>("/epochs/latest/parameters")
+            .await?
+            .ok_or(CMLLCError::LedgerError(
+                "Blockfrost has no protocol parameters for the latest epoch".into(),
+            ))?;
+        let cost_models = CostModels {
+            plutus_v1: params.cost_models_raw.plutus_v1,
+            plutus_v2: params.cost_models_raw.plutus_v2,
+        };
+        let max_budget = ScriptExecutionCost::new(
+            parse_quantity(&params.max_tx_ex_mem)?,
+            parse_quantity(&params.max_tx_ex_steps)?,
+        );
+        Ok(EvaluationParams {
+            cost_models,
+            max_budget,
+        })
+    }
 }
 
 fn spend_from_bf_spend(
@@ -0,0 +1,344 @@
+#![allow(non_snake_case)]
+
+use super::*;
+use serde_json::{
+    json,
+    Value,
+};
+use std::time::Duration;
+use wiremock::{
+    matchers::{
+        header,
+        method,
+        path,
+    },
+    Mock,
+    MockServer,
+    ResponseTemplate,
+};
+
+const ALICE: &str = "addr_test1qrmezjhpelwzvz83wjl0e6mx766de7j3nksu2338s00yzx870xyxfa97xyz2zn5rknyntu5g0c66s7ktjnx0p6f0an6s3dyxwr";
+const API_KEY: &str = "preprodTestKey";
+
+const TX_HASH: &str = "1111111111111111111111111111111111111111111111111111111111111111";
+const DATUM_HASH: &str =
+    "2222222222222222222222222222222222222222222222222222222222222222";
+const PLUTUS_SCRIPT_HASH: &str =
+    "33333333333333333333333333333333333333333333333333333333";
+const NATIVE_SCRIPT_HASH: &str =
+    "44444444444444444444444444444444444444444444444444444444";
+// CBOR for the integer 42
+const HASHED_DATUM: &str = "182a";
+// CBOR for `Constr 0 [42]`
+const INLINE_DATUM: &str = "d8799f182aff";
+// Always succeeding Plutus V2 script
+const SCRIPT_CBOR: &str = "49480100002221200101";
+
+// Trimmed down to the fields Naumachia reads from `/addresses/{address}/utxos`
+fn bf_utxo(
+    output_index: u64,
+    data_hash: Option<&str>,
+    inline_datum: Option<&str>,
+    reference_script_hash: Option<&str>,
+) -> Value {
+    json!({
+        "address": ALICE,
+        "tx_hash": TX_HASH,
+        "tx_index": output_index,
+        "output_index": output_index,
+        "amount": [
+            { "unit": "lovelace", "quantity": "2000000" },
+        ],
+        "block": "5555555555555555555555555555555555555555555555555555555555555555",
+        "data_hash": data_hash,
+        "inline_datum": inline_datum,
+        "reference_script_hash": reference_script_hash,
+    })
+}
+
+async fn mock_blockfrost(utxos: Value) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(format!("/addresses/{ALICE}/utxos")))
+        .and(header("project_id", API_KEY))
+        .respond_with(ResponseTemplate::new(200).set_body_json(utxos))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(format!("/scripts/datum/{DATUM_HASH}/cbor")))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(json!({ "cbor": HASHED_DATUM })),
+        )
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(format!("/scripts/{PLUTUS_SCRIPT_HASH}")))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "script_hash": PLUTUS_SCRIPT_HASH,
+            "type": "plutusV2",
+            "serialised_size": 9,
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(format!("/scripts/{PLUTUS_SCRIPT_HASH}/cbor")))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(json!({ "cbor": SCRIPT_CBOR })),
+        )
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(format!("/scripts/{NATIVE_SCRIPT_HASH}")))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "script_hash": NATIVE_SCRIPT_HASH,
+            "type": "timelock",
+            "serialised_size": null,
+        })))
+        .mount(&server)
+        .await;
+    // Anything else is unknown to Blockfrost
+    Mock::given(method("GET"))
+        .with_priority(u8::MAX)
+        .respond_with(ResponseTemplate::new(404).set_body_json(json!({
+            "status_code": 404,
+            "error": "Not Found",
+            "message": "The requested component has not been found.",
+        })))
+        .mount(&server)
+        .await;
+    server
+}
+
+fn fast_policy() -> RequestPolicy {
+    RequestPolicy::default()
+        .with_max_retries(2)
+        .with_backoff(Duration::from_millis(1), Duration::from_millis(5))
+}
+
+fn alice() -> CMLAddress {
+    CMLAddress::from_bech32(ALICE).unwrap()
+}
+
+#[tokio::test]
+async fn get_all_utxos_for_addr__decodes_inline_datums() {
+    // Given
+    let utxos = json!([bf_utxo(0, Some(DATUM_HASH), Some(INLINE_DATUM), None)]);
+    let server = mock_blockfrost(utxos).await;
+    let ledger = BlockFrostLedger::new(&server.uri(), API_KEY);
+
+    // When
+    let utxos = ledger.get_all_utxos_for_addr(&alice()).await.unwrap();
+
+    // Then
+    let utxo = utxos.first().unwrap();
+    let lovelace: u64 = utxo.amount().coin().into();
+    assert_eq!(lovelace, 2_000_000);
+    let datum = utxo.datum().clone().unwrap();
+    assert_eq!(hex::encode(datum.to_bytes()), INLINE_DATUM);
+    assert!(utxo.datum_hash().is_none());
+}
+
+#[tokio::test]
+async fn get_all_utxos_for_addr__resolves_datum_hashes() {
+    // Given
+    let utxos = json!([bf_utxo(0, Some(DATUM_HASH), None, None)]);
+    let server = mock_blockfrost(utxos).await;
+    let ledger = BlockFrostLedger::new(&server.uri(), API_KEY);
+
+    // When
+    let utxos = ledger.get_all_utxos_for_addr(&alice()).await.unwrap();
+
+    // Then
+    let utxo = utxos.first().unwrap();
+    let datum = utxo.datum().clone().unwrap();
+    assert_eq!(hex::encode(datum.to_bytes()), HASHED_DATUM);
+    let datum_hash = utxo.datum_hash().clone().unwrap();
+    assert_eq!(hex::encode(datum_hash.to_bytes()), DATUM_HASH);
+}
+
+#[tokio::test]
+async fn get_all_utxos_for_addr__unknown_datum_hash_is_none() {
+    // Given
+    let unknown_hash = "6666666666666666666666666666666666666666666666666666666666666666";
+    let utxos = json!([bf_utxo(0, Some(unknown_hash), None, None)]);
+    let server = mock_blockfrost(utxos).await;
+    let ledger = BlockFrostLedger::new(&server.uri(), API_KEY);
+
+    // When
+    let utxos = ledger.get_all_utxos_for_addr(&alice()).await.unwrap();
+
+    // Then
+    assert!(utxos.first().unwrap().datum().is_none());
+}
+
+#[tokio::test]
+async fn get_all_utxos_for_addr__surfaces_plutus_reference_scripts() {
+    // Given
+    let utxos = json!([
+        bf_utxo(0, None, None, Some(PLUTUS_SCRIPT_HASH)),
+        bf_utxo(1, None, None, Some(NATIVE_SCRIPT_HASH)),
+    ]);
+    let server = mock_blockfrost(utxos).await;
+    let ledger = BlockFrostLedger::new(&server.uri(), API_KEY);
+
+    // When
+    let utxos = ledger.get_all_utxos_for_addr(&alice()).await.unwrap();
+
+    // Then
+    let plutus_script = utxos.first().unwrap().reference_script().clone().unwrap();
+    let v2 = PlutusV2Script::from_bytes(hex::decode(SCRIPT_CBOR).unwrap()).unwrap();
+    let expected = PlutusScript::from_v2(&v2);
+    assert_eq!(
+        plutus_script.hash().to_string(),
+        expected.hash().to_string()
+    );
+    // Native scripts can't be used as Plutus reference scripts
+    assert!(utxos.last().unwrap().reference_script().is_none());
+}
+
+#[tokio::test]
+async fn get_all_utxos_for_addr__unknown_address_is_empty() {
+    // Given
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+    let ledger = BlockFrostLedger::new(&server.uri(), API_KEY);
+
+    // When
+    let utxos = ledger.get_all_utxos_for_addr(&alice()).await.unwrap();
+
+    // Then
+    assert!(utxos.is_empty());
+}
+
+#[tokio::test]
+async fn get_utxos_for_addr__respects_count() {
+    // Given
+    let utxos = json!([
+        bf_utxo(0, None, None, None),
+        bf_utxo(1, None, None, None),
+        bf_utxo(2, None, None, None),
+    ]);
+    let server = mock_blockfrost(utxos).await;
+    let ledger = BlockFrostLedger::new(&server.uri(), API_KEY);
+
+    // When
+    let utxos = ledger.get_utxos_for_addr(&alice(), 2).await.unwrap();
+
+    // Then
+    assert_eq!(utxos.len(), 2);
+    let index: u64 = utxos.last().unwrap().output_index().into();
+    assert_eq!(index, 1);
+}
+
+#[tokio::test]
+async fn get_all_utxos_for_addr__retries_rate_limited_requests() {
+    // Given
+    let server = mock_blockfrost(json!([bf_utxo(0, None, None, None)])).await;
+    Mock::given(method("GET"))
+        .and(path(format!("/addresses/{ALICE}/utxos")))
+        .respond_with(ResponseTemplate::new(429))
+        .with_priority(1)
+        .up_to_n_times(2)
+        .expect(2)
+        .mount(&server)
+        .await;
+    let ledger =
+        BlockFrostLedger::new(&server.uri(), API_KEY).with_request_policy(fast_policy());
+
+    // When
+    let utxos = ledger.get_all_utxos_for_addr(&alice()).await.unwrap();
+
+    // Then
+    assert_eq!(utxos.len(), 1);
+}
+
+#[tokio::test]
+async fn get_all_utxos_for_addr__gives_up_on_server_errors() {
+    // Given
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(3)
+        .mount(&server)
+        .await;
+    let ledger =
+        BlockFrostLedger::new(&server.uri(), API_KEY).with_request_policy(fast_policy());
+
+    // When
+    let error = ledger.get_all_utxos_for_addr(&alice()).await.unwrap_err();
+
+    // Then
+    assert!(error.is_transient());
+    assert!(matches!(
+        error,
+        CMLLCError::RetriesExhausted { attempts: 3, .. }
+    ));
+}
+
+#[tokio::test]
+async fn get_all_utxos_for_addr__does_not_retry_client_errors() {
+    // Given
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(403))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let ledger =
+        BlockFrostLedger::new(&server.uri(), API_KEY).with_request_policy(fast_policy());
+
+    // When
+    let error = ledger.get_all_utxos_for_addr(&alice()).await.unwrap_err();
+
+    // Then
+    assert!(!error.is_transient());
+    assert!(matches!(error, CMLLCError::LedgerError(_)));
+}
+
+#[tokio::test]
+async fn evaluation_params__uses_cost_models_and_max_units_of_latest_epoch() {
+    // Given
+    let server = mock_blockfrost(json!([])).await;
+    // Trimmed down to the fields Naumachia reads from `/epochs/latest/parameters`
+    Mock::given(method("GET"))
+        .and(path("/epochs/latest/parameters"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "epoch": 120,
+            "max_tx_ex_mem": "14000000",
+            "max_tx_ex_steps": "10000000000",
+            "cost_models_raw": {
+                "PlutusV1": [205665, 812, 1],
+                "PlutusV2": [205665, 812, 1, 1],
+            },
+        })))
+        .mount(&server)
+        .await;
+    let ledger = BlockFrostLedger::new(&server.uri(), API_KEY);
+
+    // When
+    let params = ledger.evaluation_params().await.unwrap();
+
+    // Then
+    assert_eq!(params.cost_models.plutus_v1, vec![205665, 812, 1]);
+    assert_eq!(params.cost_models.plutus_v2, vec![205665, 812, 1, 1]);
+    assert_eq!(
+        params.max_budget,
+        ScriptExecutionCost::new(14_000_000, 10_000_000_000)
+    );
+}
+
+#[tokio::test]
+async fn evaluation_params__fails_without_protocol_parameters() {
+    // Given
+    let server = mock_blockfrost(json!([])).await;
+    let ledger = BlockFrostLedger::new(&server.uri(), API_KEY);
+
+    // When
+    let error = ledger.evaluation_params().await.unwrap_err();
+
+    // Then
+    assert!(matches!(error, CMLLCError::LedgerError(_)));
+}
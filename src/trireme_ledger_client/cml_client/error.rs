@@ -1,7 +1,17 @@
-use crate::ledger_client::LedgerClientError;
+use crate::{
+    ledger_client::LedgerClientError,
+    trireme_ledger_client::cml_client::kupo_client::KupoError,
+};
 use pallas_addresses::Address;
+use std::{
+    io,
+    time::Duration,
+};
 use thiserror::Error;
 
+#[cfg(test)]
+mod tests;
+
 #[allow(missing_docs)]
 #[derive(Debug, Error)]
 pub enum CMLLCError {
@@ -13,12 +23,23 @@ pub enum CMLLCError {
     ScrollsClient(#[from] scrolls_client::error::Error),
     #[error("Ogmios Client: {0:?}")]
     OgmiosClient(#[from] ogmios_client::Error),
+    #[error("Kupo Client: {0:?}")]
+    KupoClient(#[from] KupoError),
     #[error("Ogmios Response: {0:?}")]
     OgmiosResponse(String),
     #[error("Not a valid BaseAddress")]
     InvalidBaseAddr,
     #[error("Error from ledger implementation: {0:?}")]
     LedgerError(Box<dyn std::error::Error + Send + Sync>),
+    #[error("Transient error from ledger implementation: {0:?}")]
+    TransientLedgerError(Box<dyn std::error::Error + Send + Sync>),
+    #[error("Request to the ledger timed out after {0:?}")]
+    Timeout(Duration),
+    #[error("Request to the ledger failed {attempts} times, last with: {last}")]
+    RetriesExhausted {
+        attempts: u32,
+        last: Box<CMLLCError>,
+    },
     #[error("Error in key manager implementation: {0:?}")]
     KeyError(Box<dyn std::error::Error + Send + Sync>),
     #[error("Unbuilt output does not have sufficient ADA")]
@@ -30,6 +51,59 @@ pub enum CMLLCError {
     Hex(#[from] hex::FromHexError),
     #[error("Invalid Policy Id: {0:?}")]
     InvalidPolicyId(String),
+    #[error("Plutus V1 transactions can't create outputs with inline datums")]
+    InlineDatumInV1Tx,
+    #[error("Ledger is on the network with magic {actual}, not {expected}")]
+    WrongNetwork { expected: u64, actual: u64 },
+    #[error("Ledger can't report the protocol parameters scripts are evaluated with")]
+    NoEvaluationParams,
+}
+
+impl CMLLCError {
+    /// Whether the error is a failure that may go away if the request is retried later, like a
+    /// timeout, a rate limit or a dropped connection, rather than one that will happen again
+    pub fn is_transient(&self) -> bool {
+        match self {
+            CMLLCError::TransientLedgerError(_) | CMLLCError::Timeout(_) => true,
+            // Only when the connection to Redis or Ogmios failed or timed out. Anything else,
+            // e.g. a response that can't be parsed, will fail the same way again
+            CMLLCError::ScrollsClient(e) => is_connection_failure(e),
+            CMLLCError::OgmiosClient(e) => is_connection_failure(e),
+            CMLLCError::RetriesExhausted { last, .. } => last.is_transient(),
+            _ => false,
+        }
+    }
+}
+
+/// Whether `error`, or any error that caused it, is a connection that failed or timed out. The
+/// Scrolls and Ogmios clients wrap the errors of their transports, so this looks through the
+/// whole chain of sources, the same way [`reqwest::Error`]s are told apart by the Blockfrost
+/// ledger
+fn is_connection_failure(error: &(dyn std::error::Error + 'static)) -> bool {
+    let mut current = Some(error);
+    while let Some(error) = current {
+        if let Some(io_error) = error.downcast_ref::<io::Error>() {
+            if matches!(
+                io_error.kind(),
+                io::ErrorKind::ConnectionRefused
+                    | io::ErrorKind::ConnectionReset
+                    | io::ErrorKind::ConnectionAborted
+                    | io::ErrorKind::NotConnected
+                    | io::ErrorKind::BrokenPipe
+                    | io::ErrorKind::TimedOut
+                    | io::ErrorKind::UnexpectedEof
+            ) {
+                return true;
+            }
+        }
+        if let Some(reqwest_error) = error.downcast_ref::<reqwest::Error>() {
+            if reqwest_error.is_timeout() || reqwest_error.is_connect() {
+                return true;
+            }
+        }
+        current = error.source();
+    }
+    false
 }
 
 /// Convenience function for wrapping a `CMLLCError` in a [`LedgerClientError`] `FailedToRetrieveOutputsAt` variant
@@ -0,0 +1,47 @@
+#![allow(non_snake_case)]
+
+use super::*;
+
+/// Stands in for the error types of the clients, which wrap the errors of their transports
+#[derive(Debug, Error)]
+#[error("Client error: {0}")]
+struct WrappingError(#[source] io::Error);
+
+#[test]
+fn is_connection_failure__refused_connection_in_source_chain() {
+    // Given
+    let error = WrappingError(io::Error::from(io::ErrorKind::ConnectionRefused));
+
+    // When
+    let transient = is_connection_failure(&error);
+
+    // Then
+    assert!(transient);
+}
+
+#[test]
+fn is_connection_failure__other_io_error_is_not_transient() {
+    // Given
+    let error = WrappingError(io::Error::from(io::ErrorKind::InvalidData));
+
+    // When
+    let transient = is_connection_failure(&error);
+
+    // Then
+    assert!(!transient);
+}
+
+#[test]
+fn is_transient__exhausted_retries_follow_last_error() {
+    // Given
+    let error = CMLLCError::RetriesExhausted {
+        attempts: 3,
+        last: Box::new(CMLLCError::Timeout(Duration::from_secs(1))),
+    };
+
+    // When
+    let transient = error.is_transient();
+
+    // Then
+    assert!(transient);
+}
@@ -6,6 +6,10 @@ use crate::{
     },
     output::Output,
     scripts::{
+        evaluation::{
+            VASIL_PLUTUS_V1_COST_MODEL,
+            VASIL_PLUTUS_V2_COST_MODEL,
+        },
         MintingPolicy,
         Validator,
     },
@@ -91,23 +95,17 @@ pub fn vasil_v1_tx_builder() -> LedgerClientResult<TransactionBuilder> {
     let step_den = 10000000.into();
     let step_price = UnitInterval::new(&step_num, &step_den);
     let ex_unit_prices = ExUnitPrices::new(&mem_price, &step_price);
-    let vasil_v1_cost_models = vec![
-        205665, 812, 1, 1, 1000, 571, 0, 1, 1000, 24177, 4, 1, 1000, 32, 117366, 10475,
-        4, 23000, 100, 23000, 100, 23000, 100, 23000, 100, 23000, 100, 23000, 100, 100,
-        100, 23000, 100, 19537, 32, 175354, 32, 46417, 4, 221973, 511, 0, 1, 89141, 32,
-        497525, 14068, 4, 2, 196500, 453240, 220, 0, 1, 1, 1000, 28662, 4, 2, 245000,
-        216773, 62, 1, 1060367, 12586, 1, 208512, 421, 1, 187000, 1000, 52998, 1, 80436,
-        32, 43249, 32, 1000, 32, 80556, 1, 57667, 4, 1000, 10, 197145, 156, 1, 197145,
-        156, 1, 204924, 473, 1, 208896, 511, 1, 52467, 32, 64832, 32, 65493, 32, 22558,
-        32, 16563, 32, 76511, 32, 196500, 453240, 220, 0, 1, 1, 69522, 11687, 0, 1,
-        60091, 32, 196500, 453240, 220, 0, 1, 1, 196500, 453240, 220, 0, 1, 1, 806990,
-        30482, 4, 1927926, 82523, 4, 265318, 0, 4, 0, 85931, 32, 205665, 812, 1, 1,
-        41182, 32, 212342, 32, 31220, 32, 32696, 32, 43357, 32, 32247, 32, 38314, 32,
-        9462713, 1021, 10,
-    ];
     let cm = CostModel::new(
         &Language::new_plutus_v1(),
-        &vasil_v1_cost_models.iter().map(|&i| Int::from(i)).collect(),
+        &VASIL_PLUTUS_V1_COST_MODEL
+            .iter()
+            .map(|&i| Int::from_str(&i.to_string()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| {
+                LedgerClientError::ConfigError(format!(
+                    "Cost models misconfigured: {e:?}"
+                ))
+            })?,
     );
     let mut cost_models = Costmdls::new();
     cost_models.insert(&cm);
@@ -147,23 +145,9 @@ pub fn vasil_v2_tx_builder() -> LedgerClientResult<TransactionBuilder> {
     let step_den = 10000000.into();
     let step_price = UnitInterval::new(&step_num, &step_den);
     let ex_unit_prices = ExUnitPrices::new(&mem_price, &step_price);
-    let vasil_v2_cost_models: Vec<i64> = vec![
-        205665, 812, 1, 1, 1000, 571, 0, 1, 1000, 24177, 4, 1, 1000, 32, 117366, 10475,
-        4, 23000, 100, 23000, 100, 23000, 100, 23000, 100, 23000, 100, 23000, 100, 100,
-        100, 23000, 100, 19537, 32, 175354, 32, 46417, 4, 221973, 511, 0, 1, 89141, 32,
-        497525, 14068, 4, 2, 196500, 453240, 220, 0, 1, 1, 1000, 28662, 4, 2, 245000,
-        216773, 62, 1, 1060367, 12586, 1, 208512, 421, 1, 187000, 1000, 52998, 1, 80436,
-        32, 43249, 32, 1000, 32, 80556, 1, 57667, 4, 1000, 10, 197145, 156, 1, 197145,
-        156, 1, 204924, 473, 1, 208896, 511, 1, 52467, 32, 64832, 32, 65493, 32, 22558,
-        32, 16563, 32, 76511, 32, 196500, 453240, 220, 0, 1, 1, 69522, 11687, 0, 1,
-        60091, 32, 196500, 453240, 220, 0, 1, 1, 196500, 453240, 220, 0, 1, 1, 1159724,
-        392670, 0, 2, 806990, 30482, 4, 1927926, 82523, 4, 265318, 0, 4, 0, 85931, 32,
-        205665, 812, 1, 1, 41182, 32, 212342, 32, 31220, 32, 32696, 32, 43357, 32, 32247,
-        32, 38314, 32, 35892428, 10, 57996947, 18975, 10, 38887044, 32947, 10,
-    ];
     let cm = CostModel::new(
         &Language::new_plutus_v2(),
-        &vasil_v2_cost_models
+        &VASIL_PLUTUS_V2_COST_MODEL
             .iter()
             .map(|&i| Int::from_str(&i.to_string()))
             .collect::<Result<Vec<_>, _>>()
@@ -277,14 +261,19 @@ pub(crate) fn utxo_to_nau_utxo<Datum: PlutusDataInterop>(
         .to_owned()
         .and_then(|data| Datum::from_plutus_data(&data).ok())
     {
-        Output::new_validator(tx_hash, index, owner.to_owned(), values, datum)
+        let output =
+            Output::new_validator(tx_hash, index, owner.to_owned(), values, datum);
+        match utxo.datum_hash() {
+            Some(datum_hash) => output.with_datum_hash(datum_hash.to_bytes()),
+            None => output,
+        }
     } else {
         Output::new_wallet(tx_hash, index, owner.to_owned(), values)
     };
     Ok(output)
 }
 
-fn as_nau_values(cml_value: &CMLValue) -> LedgerClientResult<Values> {
+pub(crate) fn as_nau_values(cml_value: &CMLValue) -> LedgerClientResult<Values> {
     let mut values = Values::default();
     let ada = cml_value.coin().into();
     values.add_one_value(&PolicyId::Lovelace, ada);
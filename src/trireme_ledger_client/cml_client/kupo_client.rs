@@ -0,0 +1,131 @@
+use reqwest::{
+    Client,
+    StatusCode,
+};
+use serde::{
+    de::DeserializeOwned,
+    Deserialize,
+};
+use std::collections::HashMap;
+use thiserror::Error;
+
+#[allow(missing_docs)]
+#[derive(Debug, Error)]
+pub enum KupoError {
+    #[error("HTTP Error: {0:?}")]
+    Http(#[from] reqwest::Error),
+    #[error("Kupo responded with {0}: {1}")]
+    Response(StatusCode, String),
+}
+
+#[allow(missing_docs)]
+pub type KupoResult<T, E = KupoError> = Result<T, E>;
+
+/// Minimal HTTP client for a [Kupo](https://cardanosolutions.github.io/kupo/) instance
+#[derive(Clone)]
+pub struct KupoClient {
+    base_url: String,
+    client: Client,
+}
+
+impl KupoClient {
+    /// Constructor for the [`KupoClient`], e.g. `KupoClient::new("http://127.0.0.1:1442")`
+    pub fn new(base_url: &str) -> Self {
+        KupoClient {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            client: Client::new(),
+        }
+    }
+
+    /// Get the unspent outputs matching `pattern`. A pattern can be anything Kupo accepts, e.g.
+    /// a bech32 address, a payment credential `<hash>/*` or an output reference `<index>@<tx>`.
+    ///
+    /// Kupo only knows about outputs matching the patterns it was started with.
+    pub async fn unspent_matches(&self, pattern: &str) -> KupoResult<Vec<KupoMatch>> {
+        let url = format!("{}/matches/{}?unspent", self.base_url, pattern);
+        self.get(&url).await
+    }
+
+    /// Get the CBOR hex of a datum by its hash, if Kupo has seen it. Inline datums are indexed
+    /// too.
+    pub async fn datum(&self, datum_hash: &str) -> KupoResult<Option<String>> {
+        let url = format!("{}/datums/{}", self.base_url, datum_hash);
+        let datum: Option<KupoDatum> = self.get(&url).await?;
+        Ok(datum.map(|datum| datum.datum))
+    }
+
+    /// Get a script by its hash, if Kupo has seen it
+    pub async fn script(&self, script_hash: &str) -> KupoResult<Option<KupoScript>> {
+        let url = format!("{}/scripts/{}", self.base_url, script_hash);
+        self.get(&url).await
+    }
+
+    /// Get the most recent checkpoint Kupo has synchronized
+    pub async fn most_recent_checkpoint(&self) -> KupoResult<Option<KupoPoint>> {
+        let url = format!("{}/checkpoints", self.base_url);
+        let checkpoints: Vec<KupoPoint> = self.get(&url).await?;
+        Ok(checkpoints.into_iter().max_by_key(|point| point.slot_no))
+    }
+
+    async fn get<T: DeserializeOwned>(&self, url: &str) -> KupoResult<T> {
+        let response = self.client.get(url).send().await?;
+        let status = response.status();
+        if status.is_success() {
+            Ok(response.json().await?)
+        } else {
+            let message = response.text().await.unwrap_or_default();
+            Err(KupoError::Response(status, message))
+        }
+    }
+}
+
+/// An output matched by Kupo
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct KupoMatch {
+    pub transaction_index: u64,
+    pub transaction_id: String,
+    pub output_index: u64,
+    pub address: String,
+    pub value: KupoValue,
+    pub datum_hash: Option<String>,
+    /// `"hash"` or `"inline"`. Missing from responses of older Kupo versions
+    #[serde(default)]
+    pub datum_type: Option<String>,
+    pub script_hash: Option<String>,
+    pub created_at: KupoPoint,
+    pub spent_at: Option<KupoPoint>,
+}
+
+/// Value of a [`KupoMatch`]
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct KupoValue {
+    /// Amount of lovelace
+    pub coins: u64,
+    /// Native assets keyed by `<policy id>.<hex asset name>`, or just `<policy id>` if the
+    /// asset name is empty
+    #[serde(default)]
+    pub assets: HashMap<String, u64>,
+}
+
+/// A point on the chain
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct KupoPoint {
+    pub slot_no: u64,
+    pub header_hash: String,
+}
+
+#[derive(Deserialize)]
+struct KupoDatum {
+    datum: String,
+}
+
+/// A script known to Kupo
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct KupoScript {
+    /// One of `"native"`, `"plutus:v1"`, `"plutus:v2"` or `"plutus:v3"`
+    pub language: String,
+    /// Hex encoded script
+    pub script: String,
+}
@@ -0,0 +1,204 @@
+use crate::{
+    scripts::evaluation::EvaluationParams,
+    trireme_ledger_client::cml_client::{
+        error::{
+            CMLLCError,
+            Result,
+        },
+        kupo_client::{
+            KupoClient,
+            KupoMatch,
+            KupoScript,
+            KupoValue,
+        },
+        network_settings::NetworkSettings,
+        ogmios_scrolls_ledger::{
+            check_for_error,
+            parse_evaluation_results,
+        },
+        ExecutionCost,
+        Ledger,
+        UTxO,
+    },
+};
+use async_trait::async_trait;
+use cardano_multiplatform_lib::{
+    address::Address as CMLAddress,
+    crypto::{
+        DataHash,
+        TransactionHash,
+    },
+    ledger::common::value::Value as CMLValue,
+    plutus::PlutusData,
+    AssetName,
+    Assets,
+    MultiAsset,
+    PolicyID,
+    Transaction as CMLTransaction,
+};
+use ogmios_client::{
+    OgmiosClient,
+    OgmiosLocalTxSubmission,
+};
+use std::collections::HashMap;
+
+#[cfg(test)]
+mod tests;
+
+/// Implementation of the [`Ledger`] trait that reads from Kupo and submits through Ogmios
+pub struct KupoOgmiosLedger {
+    kupo_client: KupoClient,
+    ogmios_client: OgmiosClient,
+    network_settings: NetworkSettings,
+}
+
+impl KupoOgmiosLedger {
+    /// Constructor for the [`KupoOgmiosLedger`] struct
+    pub fn new(
+        kupo_client: KupoClient,
+        ogmios_client: OgmiosClient,
+        network_settings: NetworkSettings,
+    ) -> Self {
+        Self {
+            kupo_client,
+            ogmios_client,
+            network_settings,
+        }
+    }
+
+    /// Get the unspent UTxOs matching a Kupo pattern, oldest first
+    pub async fn get_utxos_by_pattern(
+        &self,
+        pattern: &str,
+        count: Option<usize>,
+    ) -> Result<Vec<UTxO>> {
+        let mut matches = self.kupo_client.unspent_matches(pattern).await?;
+        matches.sort_by_key(|kupo_match| {
+            (
+                kupo_match.created_at.slot_no,
+                kupo_match.transaction_index,
+                kupo_match.output_index,
+            )
+        });
+        let mut utxos = Vec::new();
+        for kupo_match in matches.iter().take(count.unwrap_or(usize::MAX)) {
+            let utxo = self.utxo_from_kupo_match(kupo_match).await?;
+            utxos.push(utxo);
+        }
+        Ok(utxos)
+    }
+
+    /// Get a datum by its hash
+    pub async fn get_datum(&self, datum_hash: &str) -> Result<Option<PlutusData>> {
+        self.kupo_client
+            .datum(datum_hash)
+            .await?
+            .map(|datum| plutus_data_from_hex(&datum))
+            .transpose()
+    }
+
+    /// Get a script by its hash
+    pub async fn get_script(&self, script_hash: &str) -> Result<Option<KupoScript>> {
+        Ok(self.kupo_client.script(script_hash).await?)
+    }
+
+    async fn utxo_from_kupo_match(&self, kupo_match: &KupoMatch) -> Result<UTxO> {
+        let tx_hash = TransactionHash::from_hex(&kupo_match.transaction_id)
+            .map_err(|e| CMLLCError::JsError(e.to_string()))?;
+        let output_index = kupo_match.output_index.into();
+        let amount = cml_value_from_kupo_value(&kupo_match.value)?;
+        let datum = match &kupo_match.datum_hash {
+            Some(datum_hash) => self.get_datum(datum_hash).await?,
+            None => None,
+        };
+        let utxo = UTxO::new(tx_hash, output_index, amount, datum);
+        match (&kupo_match.datum_hash, kupo_match.datum_type.as_deref()) {
+            (Some(_), Some("inline")) | (None, _) => Ok(utxo),
+            (Some(datum_hash), _) => {
+                let datum_hash = DataHash::from_hex(datum_hash)
+                    .map_err(|e| CMLLCError::JsError(e.to_string()))?;
+                Ok(utxo.with_datum_hash(datum_hash))
+            }
+        }
+    }
+}
+
+fn cml_value_from_kupo_value(value: &KupoValue) -> Result<CMLValue> {
+    let mut cml_value = CMLValue::new(&value.coins.into());
+    for (unit, quantity) in value.assets.iter() {
+        let (policy_id_hex, asset_name_hex) = unit.split_once('.').unwrap_or((unit, ""));
+        let policy_id = PolicyID::from_hex(policy_id_hex)
+            .map_err(|_| CMLLCError::InvalidPolicyId(unit.to_string()))?;
+        let asset_name_bytes = hex::decode(asset_name_hex)?;
+        let asset_name = AssetName::new(asset_name_bytes)
+            .map_err(|e| CMLLCError::JsError(e.to_string()))?;
+        let mut assets = Assets::new();
+        assets.insert(&asset_name, &(*quantity).into());
+        let mut multi_assets = MultiAsset::new();
+        multi_assets.insert(&policy_id, &assets);
+        cml_value = cml_value
+            .checked_add(&CMLValue::new_from_assets(&multi_assets))
+            .map_err(|e| CMLLCError::JsError(e.to_string()))?;
+    }
+    Ok(cml_value)
+}
+
+fn plutus_data_from_hex(datum: &str) -> Result<PlutusData> {
+    let bytes = hex::decode(datum)?;
+    PlutusData::from_bytes(bytes).map_err(|e| CMLLCError::Deserialize(e.to_string()))
+}
+
+#[async_trait]
+impl Ledger for KupoOgmiosLedger {
+    async fn last_block_time_secs(&self) -> Result<i64> {
+        let checkpoint = self.kupo_client.most_recent_checkpoint().await?.ok_or(
+            CMLLCError::LedgerError("Kupo has not synchronized any blocks yet".into()),
+        )?;
+        Ok(self.network_settings.posix_from_slot(checkpoint.slot_no))
+    }
+
+    async fn get_utxos_for_addr(
+        &self,
+        addr: &CMLAddress,
+        count: usize,
+    ) -> Result<Vec<UTxO>> {
+        let address = addr
+            .to_bech32(None)
+            .map_err(|e| CMLLCError::JsError(e.to_string()))?;
+        self.get_utxos_by_pattern(&address, Some(count)).await
+    }
+
+    async fn get_all_utxos_for_addr(&self, addr: &CMLAddress) -> Result<Vec<UTxO>> {
+        let address = addr
+            .to_bech32(None)
+            .map_err(|e| CMLLCError::JsError(e.to_string()))?;
+        self.get_utxos_by_pattern(&address, None).await
+    }
+
+    async fn calculate_ex_units(
+        &self,
+        tx: &CMLTransaction,
+    ) -> Result<HashMap<u64, ExecutionCost>> {
+        let bytes = tx.to_bytes();
+        let res = self.ogmios_client.evaluate_tx(&bytes, vec![]).await?;
+        check_for_error(&res)?;
+        parse_evaluation_results(&res)
+    }
+
+    async fn submit_transaction(&self, tx: &CMLTransaction) -> Result<String> {
+        let bytes = tx.to_bytes();
+        let res = self.ogmios_client.submit_tx(&bytes).await?;
+        let tx_hash = res
+            .result()
+            .ok_or(CMLLCError::OgmiosResponse(
+                "No transaction hash in response".to_string(),
+            ))?
+            .tx_id()
+            .to_string();
+        Ok(tx_hash)
+    }
+
+    async fn evaluation_params(&self) -> Result<EvaluationParams> {
+        Err(CMLLCError::NoEvaluationParams)
+    }
+}
@@ -0,0 +1,184 @@
+#![allow(non_snake_case)]
+
+use super::*;
+use crate::trireme_ledger_client::Network;
+use serde_json::{
+    json,
+    Value,
+};
+use wiremock::{
+    matchers::{
+        method,
+        path,
+    },
+    Mock,
+    MockServer,
+    ResponseTemplate,
+};
+
+const ALICE: &str = "addr_test1qrmezjhpelwzvz83wjl0e6mx766de7j3nksu2338s00yzx870xyxfa97xyz2zn5rknyntu5g0c66s7ktjnx0p6f0an6s3dyxwr";
+
+const OLD_TX: &str = "1111111111111111111111111111111111111111111111111111111111111111";
+const NEW_TX: &str = "2222222222222222222222222222222222222222222222222222222222222222";
+const POLICY: &str = "3333333333333333333333333333333333333333333333333333333333";
+const DATUM_HASH: &str =
+    "4444444444444444444444444444444444444444444444444444444444444444";
+// CBOR for the integer 42
+const DATUM: &str = "182a";
+
+fn kupo_match(
+    tx_id: &str,
+    slot_no: u64,
+    value: Value,
+    datum_hash: Option<&str>,
+) -> Value {
+    json!({
+        "transaction_index": 0,
+        "transaction_id": tx_id,
+        "output_index": 1,
+        "address": ALICE,
+        "value": value,
+        "datum_hash": datum_hash,
+        "script_hash": null,
+        "created_at": { "slot_no": slot_no, "header_hash": "00" },
+        "spent_at": null,
+    })
+}
+
+async fn mock_kupo(matches: Value) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(format!("/matches/{ALICE}")))
+        .respond_with(ResponseTemplate::new(200).set_body_json(matches))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(format!("/datums/{DATUM_HASH}")))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "datum": DATUM })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/checkpoints"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            { "slot_no": 200, "header_hash": "bb" },
+            { "slot_no": 100, "header_hash": "aa" },
+        ])))
+        .mount(&server)
+        .await;
+    server
+}
+
+fn ledger(server: &MockServer) -> KupoOgmiosLedger {
+    let kupo_client = KupoClient::new(&server.uri());
+    // Never contacted by these tests
+    let ogmios_client = OgmiosClient::new("127.0.0.1".to_string(), "1337".to_string());
+    KupoOgmiosLedger::new(kupo_client, ogmios_client, Network::Preprod.into())
+}
+
+#[tokio::test]
+async fn get_all_utxos_for_addr__returns_oldest_first_with_datums() {
+    // Given
+    let matches = json!([
+        kupo_match(NEW_TX, 20, json!({ "coins": 2_000_000 }), None),
+        kupo_match(OLD_TX, 10, json!({ "coins": 1_000_000 }), Some(DATUM_HASH)),
+    ]);
+    let server = mock_kupo(matches).await;
+    let ledger = ledger(&server);
+    let addr = CMLAddress::from_bech32(ALICE).unwrap();
+
+    // When
+    let utxos = ledger.get_all_utxos_for_addr(&addr).await.unwrap();
+
+    // Then
+    assert_eq!(utxos.len(), 2);
+    let oldest = utxos.first().unwrap();
+    assert_eq!(oldest.tx_hash().to_bytes(), hex::decode(OLD_TX).unwrap());
+    let index: u64 = oldest.output_index().into();
+    assert_eq!(index, 1);
+    let datum = oldest.datum().clone().unwrap();
+    assert_eq!(hex::encode(datum.to_bytes()), DATUM);
+    let newest = utxos.last().unwrap();
+    assert!(newest.datum().is_none());
+}
+
+#[tokio::test]
+async fn get_utxos_for_addr__respects_count() {
+    // Given
+    let matches = json!([
+        kupo_match(NEW_TX, 20, json!({ "coins": 2_000_000 }), None),
+        kupo_match(OLD_TX, 10, json!({ "coins": 1_000_000 }), None),
+    ]);
+    let server = mock_kupo(matches).await;
+    let ledger = ledger(&server);
+    let addr = CMLAddress::from_bech32(ALICE).unwrap();
+
+    // When
+    let utxos = ledger.get_utxos_for_addr(&addr, 1).await.unwrap();
+
+    // Then
+    assert_eq!(utxos.len(), 1);
+    let lovelace: u64 = utxos.first().unwrap().amount().coin().into();
+    assert_eq!(lovelace, 1_000_000);
+}
+
+#[tokio::test]
+async fn get_all_utxos_for_addr__converts_native_assets() {
+    // Given
+    let named_asset = format!("{POLICY}.{}", hex::encode("Honey"));
+    let value = json!({
+        "coins": 1_500_000,
+        "assets": { named_asset: 7, POLICY: 3 },
+    });
+    let server = mock_kupo(json!([kupo_match(OLD_TX, 10, value, None)])).await;
+    let ledger = ledger(&server);
+    let addr = CMLAddress::from_bech32(ALICE).unwrap();
+
+    // When
+    let utxos = ledger.get_all_utxos_for_addr(&addr).await.unwrap();
+
+    // Then
+    let amount = utxos.first().unwrap().amount();
+    let lovelace: u64 = amount.coin().into();
+    assert_eq!(lovelace, 1_500_000);
+    let multiasset = amount.multiasset().unwrap();
+    let policy_id = PolicyID::from_hex(POLICY).unwrap();
+    let assets = multiasset.get(&policy_id).unwrap();
+    let honey = AssetName::new(b"Honey".to_vec()).unwrap();
+    let honey_amount: u64 = assets.get(&honey).unwrap().into();
+    assert_eq!(honey_amount, 7);
+    let unnamed = AssetName::new(vec![]).unwrap();
+    let unnamed_amount: u64 = assets.get(&unnamed).unwrap().into();
+    assert_eq!(unnamed_amount, 3);
+}
+
+#[tokio::test]
+async fn last_block_time_secs__uses_most_recent_checkpoint() {
+    // Given
+    let server = mock_kupo(json!([])).await;
+    let ledger = ledger(&server);
+    let network_settings: NetworkSettings = Network::Preprod.into();
+
+    // When
+    let block_time = ledger.last_block_time_secs().await.unwrap();
+
+    // Then
+    assert_eq!(block_time, network_settings.posix_from_slot(200));
+}
+
+#[tokio::test]
+async fn get_datum__unknown_hash_is_none() {
+    // Given
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(format!("/datums/{DATUM_HASH}")))
+        .respond_with(ResponseTemplate::new(200).set_body_json(Value::Null))
+        .mount(&server)
+        .await;
+    let ledger = ledger(&server);
+
+    // When
+    let datum = ledger.get_datum(DATUM_HASH).await.unwrap();
+
+    // Then
+    assert!(datum.is_none());
+}
@@ -0,0 +1,555 @@
+use crate::{
+    output::{
+        Output,
+        OutputId,
+    },
+    scripts::{
+        context::{
+            pub_key_hash_from_address_if_available,
+            CtxDatum,
+            CtxMint,
+            CtxOutput,
+            CtxOutputReference,
+            CtxScriptPurpose,
+            CtxValue,
+            Input,
+            PubKeyHash,
+            TxContext,
+            ValidRange,
+        },
+        evaluation::EvaluationParams,
+        plutus_minting_policy::PlutusMintingPolicy,
+        plutus_validator::{
+            plutus_data::PlutusData,
+            PlutusValidator,
+        },
+        raw_script::PlutusScriptFile,
+        ExecutionCost,
+        MintingPolicy,
+        Validator,
+    },
+    trireme_ledger_client::cml_client::{
+        issuance_helpers::as_nau_values,
+        network_settings::NetworkSettings,
+    },
+    values::Values,
+    PolicyId,
+};
+use cardano_multiplatform_lib::{
+    ledger::common::hash::hash_transaction,
+    plutus::{
+        PlutusScript,
+        RedeemerTagKind,
+    },
+    Transaction as CMLTransaction,
+};
+use pallas_addresses::{
+    Address,
+    ShelleyPaymentPart,
+};
+use std::collections::HashMap;
+use thiserror::Error;
+
+#[cfg(test)]
+mod tests;
+
+#[allow(missing_docs)]
+#[derive(Debug, Error)]
+pub enum LocalEvaluationError {
+    #[error("Could not deserialize transaction: {0}")]
+    Deserialize(String),
+    #[error("Not a valid address: {0}")]
+    BadAddress(String),
+    #[error("Input {0}#{1} is not on the ledger")]
+    UnknownInput(String, u64),
+    #[error("No script with hash {0} in the witness set")]
+    MissingScript(String),
+    #[error("No {0:?} redeemer with index {1:?} in the witness set")]
+    MissingRedeemer(RedeemerPurpose, u64),
+    #[error("No datum for script input {0}#{1}")]
+    MissingDatum(String, u64),
+    #[error("Script execution failed: {0}")]
+    Script(String),
+}
+
+#[allow(missing_docs)]
+pub type LocalEvaluationResult<T, E = LocalEvaluationError> = Result<T, E>;
+
+/// What a redeemer in the witness set is for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RedeemerPurpose {
+    /// Spending the script input at the index of the sorted inputs
+    Spend,
+    /// Minting with the policy at the index of the sorted policy ids
+    Mint,
+}
+
+/// Run the Plutus scripts of `tx` against the given `inputs`, which must hold every
+/// output `tx` spends. Returns the cost of each script by the purpose and index of its
+/// redeemer
+pub fn evaluate_tx(
+    tx: &CMLTransaction,
+    inputs: &[Output<PlutusData>],
+    network_settings: &NetworkSettings,
+    evaluation: &EvaluationParams,
+) -> LocalEvaluationResult<Vec<(RedeemerPurpose, u64, ExecutionCost)>> {
+    DecodedTx::new(tx, inputs)?.execute_scripts(network_settings, evaluation)
+}
+
+/// A CML transaction, with its inputs resolved against the outputs it spends
+pub(crate) struct DecodedTx {
+    pub(crate) tx_hash: Vec<u8>,
+    /// Sorted the way the ledger sorts them, so redeemer indices line up
+    pub(crate) inputs: Vec<Output<PlutusData>>,
+    pub(crate) outputs: Vec<Output<PlutusData>>,
+    pub(crate) fee: u64,
+    pub(crate) minted: Values,
+    pub(crate) burned: Values,
+    /// Sorted the way the ledger sorts them, so redeemer indices line up
+    policies: Vec<String>,
+    /// Slots the transaction is valid from and until
+    pub(crate) validity: (Option<u64>, Option<u64>),
+    signatories: Vec<PubKeyHash>,
+    pub(crate) datums: Vec<(Vec<u8>, PlutusData)>,
+    redeemers: HashMap<(RedeemerPurpose, u64), PlutusData>,
+    /// Plutus scripts in the witness set, by hash
+    scripts: HashMap<String, WitnessScript>,
+}
+
+/// The CBOR of the script, without the bytestring it's wrapped in in the witness set
+enum WitnessScript {
+    V1(Vec<u8>),
+    V2(Vec<u8>),
+}
+
+impl DecodedTx {
+    pub(crate) fn new(
+        tx: &CMLTransaction,
+        ledger_outputs: &[Output<PlutusData>],
+    ) -> LocalEvaluationResult<Self> {
+        let body = tx.body();
+        let tx_hash = hash_transaction(&body).to_bytes();
+
+        let inputs = input_ids(tx)
+            .into_iter()
+            .map(|id| {
+                ledger_outputs
+                    .iter()
+                    .find(|output| output.id() == &id)
+                    .cloned()
+                    .ok_or_else(|| {
+                        LocalEvaluationError::UnknownInput(
+                            hex::encode(id.tx_hash()),
+                            id.index(),
+                        )
+                    })
+            })
+            .collect::<LocalEvaluationResult<Vec<_>>>()?;
+
+        let witness_set = tx.witness_set();
+        let mut datums = Vec::new();
+        if let Some(plutus_data) = witness_set.plutus_data() {
+            for i in 0..plutus_data.len() {
+                let datum: PlutusData = plutus_data.get(i).into();
+                datums.push((datum.hash(), datum));
+            }
+        }
+
+        let cml_outputs = body.outputs();
+        let mut outputs = Vec::new();
+        for i in 0..cml_outputs.len() {
+            let cml_output = cml_outputs.get(i);
+            let owner = address_from_cml(&cml_output.address())?;
+            let values = as_nau_values(&cml_output.amount())?;
+            let index = i as u64;
+            let output = match cml_output.datum() {
+                Some(datum) => {
+                    if let Some(inline) = datum.as_inline_data() {
+                        Output::new_untyped_validator(
+                            tx_hash.clone(),
+                            index,
+                            owner,
+                            values,
+                            inline.into(),
+                        )
+                    } else if let Some(datum_hash) = datum.as_data_hash() {
+                        let datum_hash = datum_hash.to_bytes();
+                        let witness = datums
+                            .iter()
+                            .find(|(hash, _)| hash == &datum_hash)
+                            .map(|(_, datum)| datum.clone());
+                        let output = match witness {
+                            Some(datum) => Output::new_untyped_validator(
+                                tx_hash.clone(),
+                                index,
+                                owner,
+                                values,
+                                datum,
+                            ),
+                            None => {
+                                Output::new_wallet(tx_hash.clone(), index, owner, values)
+                            }
+                        };
+                        output.with_datum_hash(datum_hash)
+                    } else {
+                        Output::new_wallet(tx_hash.clone(), index, owner, values)
+                    }
+                }
+                None => Output::new_wallet(tx_hash.clone(), index, owner, values),
+            };
+            outputs.push(output);
+        }
+
+        let mut minted = Values::default();
+        let mut burned = Values::default();
+        let mut policies = Vec::new();
+        if let Some(mint) = body.mint() {
+            let policy_ids = mint.keys();
+            for i in 0..policy_ids.len() {
+                let policy_id = policy_ids.get(i);
+                let policy_hex = policy_id.to_string();
+                if let Some(assets) = mint.get(&policy_id) {
+                    let names = assets.keys();
+                    for j in 0..names.len() {
+                        let name = names.get(j);
+                        let asset_name = String::from_utf8(name.name()).map_err(|e| {
+                            LocalEvaluationError::Deserialize(e.to_string())
+                        })?;
+                        let policy =
+                            PolicyId::native_token(&policy_hex, &Some(asset_name));
+                        if let Some(amount) = assets.get(&name) {
+                            if let Some(positive) = amount.as_positive() {
+                                minted.add_one_value(&policy, positive.into());
+                            } else if let Some(negative) = amount.as_negative() {
+                                burned.add_one_value(&policy, negative.into());
+                            }
+                        }
+                    }
+                }
+                policies.push(policy_hex);
+            }
+        }
+        policies.sort();
+
+        let start: Option<u64> = body.validity_start_interval().map(Into::into);
+        let ttl: Option<u64> = body.ttl().map(Into::into);
+
+        let mut signatories = Vec::new();
+        if let Some(required_signers) = body.required_signers() {
+            for i in 0..required_signers.len() {
+                signatories.push(PubKeyHash::new(&required_signers.get(i).to_bytes()));
+            }
+        }
+
+        let mut redeemers = HashMap::new();
+        if let Some(cml_redeemers) = witness_set.redeemers() {
+            for i in 0..cml_redeemers.len() {
+                let redeemer = cml_redeemers.get(i);
+                let purpose = match redeemer.tag().kind() {
+                    RedeemerTagKind::Spend => RedeemerPurpose::Spend,
+                    RedeemerTagKind::Mint => RedeemerPurpose::Mint,
+                    // Certificates and withdrawals don't touch the emulated ledger
+                    _ => continue,
+                };
+                let index: u64 = redeemer.index().into();
+                redeemers.insert((purpose, index), redeemer.data().into());
+            }
+        }
+
+        let mut scripts = HashMap::new();
+        if let Some(v1_scripts) = witness_set.plutus_v1_scripts() {
+            for i in 0..v1_scripts.len() {
+                let v1 = v1_scripts.get(i);
+                let script_hash = PlutusScript::from_v1(&v1).hash().to_string();
+                scripts.insert(script_hash, WitnessScript::V1(v1.bytes()));
+            }
+        }
+        if let Some(v2_scripts) = witness_set.plutus_v2_scripts() {
+            for i in 0..v2_scripts.len() {
+                let v2 = v2_scripts.get(i);
+                let script_hash = PlutusScript::from_v2(&v2).hash().to_string();
+                scripts.insert(script_hash, WitnessScript::V2(v2.bytes()));
+            }
+        }
+
+        Ok(DecodedTx {
+            tx_hash,
+            inputs,
+            outputs,
+            fee: body.fee().into(),
+            minted,
+            burned,
+            policies,
+            validity: (start, ttl),
+            signatories,
+            datums,
+            redeemers,
+            scripts,
+        })
+    }
+
+    pub(crate) fn execute_scripts(
+        &self,
+        network_settings: &NetworkSettings,
+        evaluation: &EvaluationParams,
+    ) -> LocalEvaluationResult<Vec<(RedeemerPurpose, u64, ExecutionCost)>> {
+        let mut costs = Vec::new();
+        for (index, input) in self.inputs.iter().enumerate() {
+            let index = index as u64;
+            let script_hash = match input.owner() {
+                Address::Shelley(shelley_address) => match shelley_address.payment() {
+                    ShelleyPaymentPart::Script(hash) => hash.to_string(),
+                    ShelleyPaymentPart::Key(_) => continue,
+                },
+                _ => continue,
+            };
+            let redeemer = self.redeemer(RedeemerPurpose::Spend, index)?;
+            let datum = self.input_datum(input)?;
+            let validator = self.validator(&script_hash, evaluation)?;
+            let output_ref = CtxOutputReference::new(
+                input.id().tx_hash().to_vec(),
+                input.id().index(),
+            );
+            let ctx =
+                self.tx_context(CtxScriptPurpose::Spend(output_ref), network_settings);
+            let cost = validator
+                .execute(datum, redeemer, ctx)
+                .map_err(|e| LocalEvaluationError::Script(e.to_string()))?;
+            costs.push((RedeemerPurpose::Spend, index, cost));
+        }
+        for (index, policy) in self.policies.iter().enumerate() {
+            let index = index as u64;
+            let redeemer = self.redeemer(RedeemerPurpose::Mint, index)?;
+            let minting_policy = self.minting_policy(policy, evaluation)?;
+            let policy_bytes = hex::decode(policy)
+                .map_err(|e| LocalEvaluationError::Deserialize(e.to_string()))?;
+            let ctx =
+                self.tx_context(CtxScriptPurpose::Mint(policy_bytes), network_settings);
+            let cost = minting_policy
+                .execute(redeemer, ctx)
+                .map_err(|e| LocalEvaluationError::Script(e.to_string()))?;
+            costs.push((RedeemerPurpose::Mint, index, cost));
+        }
+        Ok(costs)
+    }
+
+    fn redeemer(
+        &self,
+        purpose: RedeemerPurpose,
+        index: u64,
+    ) -> LocalEvaluationResult<PlutusData> {
+        self.redeemers
+            .get(&(purpose, index))
+            .cloned()
+            .ok_or(LocalEvaluationError::MissingRedeemer(purpose, index))
+    }
+
+    /// Outputs that only hold the hash of their datum need the datum in the witness set
+    fn input_datum(
+        &self,
+        input: &Output<PlutusData>,
+    ) -> LocalEvaluationResult<PlutusData> {
+        let missing = || {
+            LocalEvaluationError::MissingDatum(
+                hex::encode(input.id().tx_hash()),
+                input.id().index(),
+            )
+        };
+        match input.datum_hash() {
+            Some(datum_hash) => self
+                .datums
+                .iter()
+                .find(|(hash, _)| hash == datum_hash)
+                .map(|(_, datum)| datum.clone())
+                .ok_or_else(missing),
+            None => input.datum_plutus_data().ok_or_else(missing),
+        }
+    }
+
+    fn validator(
+        &self,
+        script_hash: &str,
+        evaluation: &EvaluationParams,
+    ) -> LocalEvaluationResult<Box<dyn Validator<PlutusData, PlutusData>>> {
+        let script = self.scripts.get(script_hash).ok_or_else(|| {
+            LocalEvaluationError::MissingScript(script_hash.to_string())
+        })?;
+        let validator = match script {
+            WitnessScript::V1(script_bytes) => {
+                PlutusValidator::new_v1(script_file(script_bytes))
+            }
+            WitnessScript::V2(script_bytes) => {
+                PlutusValidator::v2_from_cbor(hex::encode(script_bytes))
+            }
+        }
+        .map_err(|e| LocalEvaluationError::Deserialize(e.to_string()))?
+        .with_evaluation_params(evaluation.clone());
+        Ok(Box::new(validator))
+    }
+
+    fn minting_policy(
+        &self,
+        policy: &str,
+        evaluation: &EvaluationParams,
+    ) -> LocalEvaluationResult<Box<dyn MintingPolicy<PlutusData>>> {
+        let script = self
+            .scripts
+            .get(policy)
+            .ok_or_else(|| LocalEvaluationError::MissingScript(policy.to_string()))?;
+        let minting_policy = match script {
+            WitnessScript::V1(script_bytes) => {
+                PlutusMintingPolicy::new_v1(script_file(script_bytes))
+            }
+            WitnessScript::V2(script_bytes) => {
+                PlutusMintingPolicy::v2_from_cbor(hex::encode(script_bytes))
+            }
+        }
+        .map_err(|e| LocalEvaluationError::Deserialize(e.to_string()))?
+        .with_evaluation_params(evaluation.clone());
+        Ok(Box::new(minting_policy))
+    }
+
+    fn tx_context(
+        &self,
+        purpose: CtxScriptPurpose,
+        network_settings: &NetworkSettings,
+    ) -> TxContext {
+        let (start, ttl) = self.validity;
+        let range = ValidRange {
+            lower: start
+                .map(|slot| (network_settings.posix_from_slot(slot) * 1000, true)),
+            upper: ttl.map(|slot| (network_settings.posix_from_slot(slot) * 1000, false)),
+        };
+        let inputs = self
+            .inputs
+            .iter()
+            .map(|input| Input {
+                transaction_id: input.id().tx_hash().to_vec(),
+                output_index: input.id().index(),
+                address: input.owner(),
+                value: CtxValue::from(input.values().clone()),
+                datum: ctx_datum(input),
+                reference_script: None,
+            })
+            .collect();
+        let outputs = self
+            .outputs
+            .iter()
+            .map(|output| CtxOutput {
+                address: output.owner(),
+                value: CtxValue::from(output.values().clone()),
+                datum: ctx_datum(output),
+                reference_script: None,
+            })
+            .collect();
+        // Without required signers, nothing in the transaction says who signed it. Fall back to
+        // the owner of the first wallet input, which is who pays for the transaction when built
+        // by Naumachia.
+        let (signer, extra_signatories) = match self.signatories.split_first() {
+            Some((signer, extra_signatories)) => {
+                (signer.clone(), extra_signatories.to_vec())
+            }
+            None => {
+                let signer = self
+                    .inputs
+                    .iter()
+                    .find(|input| input.datum_plutus_data().is_none())
+                    .and_then(|input| {
+                        pub_key_hash_from_address_if_available(&input.owner())
+                    })
+                    .unwrap_or_else(|| PubKeyHash::new(&[]));
+                (signer, Vec::new())
+            }
+        };
+        let redeemers = self
+            .redeemers
+            .iter()
+            .filter_map(|((redeemer_purpose, index), redeemer)| {
+                let purpose = self.script_purpose(*redeemer_purpose, *index)?;
+                Some((purpose, redeemer.clone()))
+            })
+            .collect();
+        TxContext {
+            purpose,
+            signer,
+            id: self.tx_hash.clone(),
+            range,
+            inputs,
+            // The emulator only decodes what it needs to apply the transaction
+            reference_inputs: Vec::new(),
+            outputs,
+            fee: self.fee,
+            mint: CtxMint::new(&self.minted, &self.burned),
+            certificates: Vec::new(),
+            withdrawals: Vec::new(),
+            extra_signatories,
+            redeemers,
+            datums: self.datums.clone(),
+        }
+    }
+
+    /// The purpose of the redeemer at `index`, if the index points at an input or policy
+    fn script_purpose(
+        &self,
+        purpose: RedeemerPurpose,
+        index: u64,
+    ) -> Option<CtxScriptPurpose> {
+        match purpose {
+            RedeemerPurpose::Spend => {
+                let input = self.inputs.get(index as usize)?;
+                let output_ref = CtxOutputReference::new(
+                    input.id().tx_hash().to_vec(),
+                    input.id().index(),
+                );
+                Some(CtxScriptPurpose::Spend(output_ref))
+            }
+            RedeemerPurpose::Mint => {
+                let policy = self.policies.get(index as usize)?;
+                let policy_bytes = hex::decode(policy).ok()?;
+                Some(CtxScriptPurpose::Mint(policy_bytes))
+            }
+        }
+    }
+}
+
+/// Ids of the outputs spent by `tx`, sorted the way the ledger sorts them
+pub(crate) fn input_ids(tx: &CMLTransaction) -> Vec<OutputId> {
+    let cml_inputs = tx.body().inputs();
+    let mut ids: Vec<_> = (0..cml_inputs.len())
+        .map(|i| {
+            let input = cml_inputs.get(i);
+            (input.transaction_id().to_bytes(), u64::from(input.index()))
+        })
+        .collect();
+    ids.sort();
+    ids.into_iter()
+        .map(|(tx_hash, index)| OutputId::new(tx_hash, index))
+        .collect()
+}
+
+fn ctx_datum(output: &Output<PlutusData>) -> CtxDatum {
+    match (output.datum_hash(), output.datum_plutus_data()) {
+        (Some(datum_hash), _) => CtxDatum::DatumHash(datum_hash.to_vec()),
+        (None, Some(datum)) => CtxDatum::InlineDatum(datum),
+        (None, None) => CtxDatum::NoDatum,
+    }
+}
+
+/// Wrap the raw bytes of a V1 script the way text envelope files do
+fn script_file(script_bytes: &[u8]) -> PlutusScriptFile {
+    let mut cbor = Vec::new();
+    minicbor::Encoder::new(&mut cbor)
+        .bytes(script_bytes)
+        .expect("Writing to a Vec can't fail");
+    PlutusScriptFile::new("PlutusScriptV1", "", &hex::encode(cbor))
+}
+
+pub(crate) fn address_from_cml(
+    address: &cardano_multiplatform_lib::address::Address,
+) -> LocalEvaluationResult<Address> {
+    let bech32 = address
+        .to_bech32(None)
+        .map_err(|e| LocalEvaluationError::BadAddress(e.to_string()))?;
+    Address::from_bech32(&bech32)
+        .map_err(|e| LocalEvaluationError::BadAddress(e.to_string()))
+}
@@ -0,0 +1,151 @@
+#![allow(non_snake_case)]
+
+use super::*;
+use crate::trireme_ledger_client::{
+    cml_client::plutus_data_interop::PlutusDataInterop,
+    Network,
+};
+use cardano_multiplatform_lib::{
+    address::{
+        Address as CMLAddress,
+        EnterpriseAddress,
+        StakeCredential,
+    },
+    crypto::TransactionHash,
+    ledger::common::value::{
+        BigNum,
+        Value as CMLValue,
+    },
+    plutus::{
+        ExUnits,
+        PlutusV1Script,
+        PlutusV1Scripts,
+        Redeemer,
+        RedeemerTag,
+        Redeemers,
+    },
+    TransactionBody,
+    TransactionInput,
+    TransactionInputs,
+    TransactionOutput,
+    TransactionOutputs,
+    TransactionWitnessSet,
+};
+use uplc::ast::{
+    DeBruijn,
+    Program,
+};
+
+const ALICE: &str = "addr_test1qrmezjhpelwzvz83wjl0e6mx766de7j3nksu2338s00yzx870xyxfa97xyz2zn5rknyntu5g0c66s7ktjnx0p6f0an6s3dyxwr";
+
+const LOCKED_AMOUNT: u64 = 10_000_000;
+
+fn v1_script(source: &str) -> PlutusV1Script {
+    let program = uplc::parser::program(source).unwrap();
+    let program: Program<DeBruijn> = program.try_into().unwrap();
+    PlutusV1Script::new(hex::decode(program.to_hex().unwrap()).unwrap())
+}
+
+/// An output locked by `script`, and a transaction spending all of it to Alice
+fn spend_from_script(script: &PlutusV1Script) -> (Output<PlutusData>, CMLTransaction) {
+    let script_hash = PlutusScript::from_v1(script).hash();
+    let credential = StakeCredential::from_scripthash(&script_hash);
+    let script_address = EnterpriseAddress::new(0, &credential).to_address();
+    let owner = address_from_cml(&script_address).unwrap();
+    let mut values = Values::default();
+    values.add_one_value(&PolicyId::Lovelace, LOCKED_AMOUNT);
+    let datum = PlutusData::BigInt(1.into());
+    let input = Output::new_untyped_validator(vec![1; 32], 0, owner, values, datum);
+
+    let mut inputs = TransactionInputs::new();
+    let tx_hash = TransactionHash::from_bytes(input.id().tx_hash().to_vec()).unwrap();
+    inputs.add(&TransactionInput::new(&tx_hash, &input.id().index().into()));
+    let fee = 200_000;
+    let mut outputs = TransactionOutputs::new();
+    let alice = CMLAddress::from_bech32(ALICE).unwrap();
+    let to_alice = CMLValue::new(&BigNum::from(LOCKED_AMOUNT - fee));
+    outputs.add(&TransactionOutput::new(&alice, &to_alice));
+    let body = TransactionBody::new(&inputs, &outputs, &BigNum::from(fee), None);
+
+    let mut witness_set = TransactionWitnessSet::new();
+    let mut scripts = PlutusV1Scripts::new();
+    scripts.add(script);
+    witness_set.set_plutus_v1_scripts(&scripts);
+    let mut redeemers = Redeemers::new();
+    redeemers.add(&Redeemer::new(
+        &RedeemerTag::new_spend(),
+        &BigNum::from(0),
+        &PlutusData::BigInt(2.into()).to_plutus_data(),
+        &ExUnits::new(&BigNum::from(0), &BigNum::from(0)),
+    ));
+    witness_set.set_redeemers(&redeemers);
+    (input, CMLTransaction::new(&body, &witness_set, None))
+}
+
+#[test]
+fn evaluate_tx__returns_cost_of_each_script() {
+    // Given
+    let script = v1_script("(program 1.0.0 (lam d (lam r (lam c (con unit ())))))");
+    let (input, tx) = spend_from_script(&script);
+
+    // When
+    let costs = evaluate_tx(
+        &tx,
+        &[input],
+        &Network::Preprod.into(),
+        &EvaluationParams::vasil(),
+    )
+    .unwrap();
+
+    // Then
+    assert_eq!(costs.len(), 1);
+    let (purpose, index, cost) = &costs[0];
+    assert_eq!(*purpose, RedeemerPurpose::Spend);
+    assert_eq!(*index, 0);
+    assert!(cost.cpu() > 0);
+}
+
+#[test]
+fn evaluate_tx__failure_includes_trace_logs() {
+    // Given
+    let script = v1_script(
+        r#"(program 1.0.0 (lam d (lam r (lam c
+            [(lam x (error)) [(force (builtin trace)) (con string "not allowed") (con unit ())]]
+        ))))"#,
+    );
+    let (input, tx) = spend_from_script(&script);
+
+    // When
+    let error = evaluate_tx(
+        &tx,
+        &[input],
+        &Network::Preprod.into(),
+        &EvaluationParams::vasil(),
+    )
+    .unwrap_err();
+
+    // Then
+    match error {
+        LocalEvaluationError::Script(message) => assert!(message.contains("not allowed")),
+        other => panic!("expected a script failure, got {other:?}"),
+    }
+}
+
+#[test]
+fn evaluate_tx__input_must_be_given() {
+    // Given
+    let script = v1_script("(program 1.0.0 (lam d (lam r (lam c (con unit ())))))");
+    let (_, tx) = spend_from_script(&script);
+
+    // When
+    let error = evaluate_tx(
+        &tx,
+        &[],
+        &Network::Preprod.into(),
+        &EvaluationParams::vasil(),
+    )
+    .unwrap_err();
+
+    // Then
+    assert!(matches!(error, LocalEvaluationError::UnknownInput(_, 0)));
+}
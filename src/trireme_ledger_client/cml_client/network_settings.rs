@@ -1,7 +1,11 @@
 use crate::trireme_ledger_client::Network;
+use serde::{
+    Deserialize,
+    Serialize,
+};
 
 /// Network Settings
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
 pub struct NetworkSettings {
     network: u8,
     slot_length: i64,
@@ -11,7 +15,7 @@ pub struct NetworkSettings {
 
 impl NetworkSettings {
     /// Constructor for the [`NetworkSettings`] struct
-    pub fn new(
+    pub const fn new(
         network: u8,
         slot_length: i64,
         starting_slot_time: i64,
@@ -95,6 +99,9 @@ impl From<Network> for NetworkSettings {
                 PREVIEW_STARTING_SLOT_TIME,
                 PREVIEW_STARTING_SLOT_NUMBER,
             ),
+            Network::Custom {
+                network_settings, ..
+            } => network_settings,
         }
     }
 }
@@ -194,4 +201,22 @@ mod tests {
         let actual = network_settings.posix_from_slot(posix);
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    fn from__custom_network_uses_its_own_settings() {
+        // given
+        let settings = NetworkSettings::new(42, 1, 1700000000, 0);
+        let network = Network::Custom {
+            network_settings: settings,
+            magic: 42,
+            blockfrost_url: None,
+        };
+
+        // when
+        let network_settings = NetworkSettings::from(network);
+
+        // then
+        assert_eq!(settings, network_settings);
+        assert_eq!(network_settings.slot_from_posix(1700000100), Some(100));
+    }
 }
@@ -0,0 +1,406 @@
+use crate::{
+    scripts::{
+        evaluation::{
+            CostModels,
+            EvaluationParams,
+        },
+        ExecutionCost as ScriptExecutionCost,
+    },
+    trireme_ledger_client::cml_client::{
+        error::{
+            CMLLCError,
+            Result,
+        },
+        ExecutionCost,
+        Ledger,
+        UTxO,
+    },
+};
+use async_trait::async_trait;
+use cardano_multiplatform_lib::{
+    address::Address as CMLAddress,
+    crypto::{
+        DataHash,
+        TransactionHash,
+    },
+    ledger::common::value::Value as CMLValue,
+    plutus::PlutusData,
+    AssetName,
+    Assets,
+    MultiAsset,
+    PolicyID,
+    Transaction as CMLTransaction,
+};
+use chrono::DateTime;
+use reqwest::Client;
+use serde::{
+    de::DeserializeOwned,
+    Deserialize,
+};
+use serde_json::{
+    json,
+    Value,
+};
+use std::collections::{
+    BTreeMap,
+    HashMap,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Implementation of the [`Ledger`] trait that only needs an [Ogmios](https://ogmios.dev/) v6
+/// instance, using its local-state-query over the HTTP JSON-RPC interface. No chain indexer
+/// like Scrolls or Kupo is required.
+///
+/// Without an indexer only inline datums can be resolved. Outputs that only hold the hash of
+/// their datum are returned without it.
+pub struct OgmiosLsqLedger {
+    url: String,
+    client: Client,
+}
+
+impl OgmiosLsqLedger {
+    /// Constructor for the [`OgmiosLsqLedger`] struct, e.g.
+    /// `OgmiosLsqLedger::new("http://127.0.0.1:1337")`
+    pub fn new(url: &str) -> Self {
+        OgmiosLsqLedger {
+            url: url.to_string(),
+            client: Client::new(),
+        }
+    }
+
+    /// Get the current UTxOs at an address
+    pub async fn get_utxos(&self, addr: &CMLAddress) -> Result<Vec<UTxO>> {
+        let address = addr
+            .to_bech32(None)
+            .map_err(|e| CMLLCError::JsError(e.to_string()))?;
+        let params = json!({ "addresses": [address] });
+        let mut ogmios_utxos: Vec<OgmiosUTxO> =
+            self.request("queryLedgerState/utxo", params).await?;
+        // The node doesn't keep the outputs in any particular order
+        ogmios_utxos.sort_by(|a, b| {
+            (&a.transaction.id, a.index).cmp(&(&b.transaction.id, b.index))
+        });
+        ogmios_utxos.iter().map(utxo_from_ogmios_utxo).collect()
+    }
+
+    /// Get the current protocol parameters, as returned by Ogmios
+    pub async fn protocol_parameters(&self) -> Result<Value> {
+        self.request("queryLedgerState/protocolParameters", json!({}))
+            .await
+    }
+
+    /// Get the network magic of the network the node is on
+    pub async fn network_magic(&self) -> Result<u64> {
+        let genesis: ShelleyGenesis = self
+            .request(
+                "queryNetwork/genesisConfiguration",
+                json!({ "era": "shelley" }),
+            )
+            .await?;
+        Ok(genesis.network_magic)
+    }
+
+    /// Check that the node is on the network with the `expected` network magic, so that
+    /// transactions aren't built from the state of another network
+    pub async fn check_network_magic(&self, expected: u64) -> Result<()> {
+        let actual = self.network_magic().await?;
+        if actual == expected {
+            Ok(())
+        } else {
+            Err(CMLLCError::WrongNetwork { expected, actual })
+        }
+    }
+
+    /// Get the slot of the tip of the node. `None` if the node is still at the origin
+    pub async fn tip_slot(&self) -> Result<Option<u64>> {
+        let tip: Value = self.request("queryNetwork/tip", json!({})).await?;
+        Ok(tip.get("slot").and_then(Value::as_u64))
+    }
+
+    /// Converts a slot number to a POSIX timestamp in seconds, using the era summaries of the
+    /// node so that slot lengths of past eras are accounted for
+    pub async fn posix_from_slot(&self, slot: u64) -> Result<i64> {
+        let start_time: String =
+            self.request("queryNetwork/startTime", json!({})).await?;
+        let system_start = DateTime::parse_from_rfc3339(&start_time)
+            .map_err(|e| CMLLCError::Deserialize(e.to_string()))?
+            .timestamp();
+        let era_summaries: Vec<EraSummary> = self
+            .request("queryLedgerState/eraSummaries", json!({}))
+            .await?;
+        let era = era_summaries
+            .iter()
+            .rev()
+            .find(|era| era.start.slot <= slot)
+            .ok_or(CMLLCError::OgmiosResponse(format!(
+                "No era summary for slot {slot}"
+            )))?;
+        let slot_length_ms = era.parameters.slot_length.milliseconds;
+        let since_era_start_ms = (slot - era.start.slot) as i64 * slot_length_ms;
+        Ok(system_start + era.start.time.seconds + since_era_start_ms / 1000)
+    }
+
+    async fn request<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Value,
+    ) -> Result<T> {
+        let body = json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params,
+            "id": null,
+        });
+        let response: JsonRpcResponse<T> = self
+            .client
+            .post(&self.url)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| CMLLCError::LedgerError(Box::new(e)))?
+            .json()
+            .await
+            .map_err(|e| CMLLCError::LedgerError(Box::new(e)))?;
+        match response {
+            JsonRpcResponse::Result { result } => Ok(result),
+            JsonRpcResponse::Error { error } => Err(CMLLCError::OgmiosResponse(format!(
+                "{method} failed with {}: {}",
+                error.code, error.message
+            ))),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonRpcResponse<T> {
+    Result { result: T },
+    Error { error: JsonRpcError },
+}
+
+#[derive(Deserialize)]
+struct JsonRpcError {
+    code: i64,
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct OgmiosUTxO {
+    transaction: OgmiosTransactionId,
+    index: u64,
+    /// Lovelace under `ada.lovelace`, native assets under `<policy id>.<hex asset name>`
+    value: BTreeMap<String, BTreeMap<String, u64>>,
+    /// Inline datum as CBOR hex
+    datum: Option<String>,
+    /// Hash of the datum, for outputs holding their datum by hash
+    #[serde(rename = "datumHash")]
+    datum_hash: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ShelleyGenesis {
+    network_magic: u64,
+}
+
+#[derive(Deserialize)]
+struct OgmiosTransactionId {
+    id: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OgmiosProtocolParameters {
+    plutus_cost_models: OgmiosCostModels,
+    max_execution_units_per_transaction: OgmiosExecutionUnits,
+}
+
+#[derive(Deserialize)]
+struct OgmiosCostModels {
+    #[serde(rename = "plutus:v1")]
+    plutus_v1: Vec<i64>,
+    #[serde(rename = "plutus:v2")]
+    plutus_v2: Vec<i64>,
+}
+
+#[derive(Deserialize)]
+struct OgmiosExecutionUnits {
+    memory: i64,
+    cpu: i64,
+}
+
+#[derive(Deserialize)]
+struct EraSummary {
+    start: EraBound,
+    parameters: EraParameters,
+}
+
+#[derive(Deserialize)]
+struct EraBound {
+    time: EraTime,
+    slot: u64,
+}
+
+#[derive(Deserialize)]
+struct EraTime {
+    seconds: i64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EraParameters {
+    slot_length: EraSlotLength,
+}
+
+#[derive(Deserialize)]
+struct EraSlotLength {
+    milliseconds: i64,
+}
+
+fn utxo_from_ogmios_utxo(utxo: &OgmiosUTxO) -> Result<UTxO> {
+    let tx_hash = TransactionHash::from_hex(&utxo.transaction.id)
+        .map_err(|e| CMLLCError::JsError(e.to_string()))?;
+    let output_index = utxo.index.into();
+    let amount = cml_value_from_ogmios_value(&utxo.value)?;
+    let datum = match &utxo.datum {
+        Some(datum) => {
+            let bytes = hex::decode(datum)?;
+            let datum = PlutusData::from_bytes(bytes)
+                .map_err(|e| CMLLCError::Deserialize(e.to_string()))?;
+            Some(datum)
+        }
+        None => None,
+    };
+    let cml_utxo = UTxO::new(tx_hash, output_index, amount, datum);
+    match &utxo.datum_hash {
+        Some(datum_hash) => {
+            let datum_hash = DataHash::from_hex(datum_hash)
+                .map_err(|e| CMLLCError::JsError(e.to_string()))?;
+            Ok(cml_utxo.with_datum_hash(datum_hash))
+        }
+        None => Ok(cml_utxo),
+    }
+}
+
+fn cml_value_from_ogmios_value(
+    value: &BTreeMap<String, BTreeMap<String, u64>>,
+) -> Result<CMLValue> {
+    let mut cml_value = CMLValue::zero();
+    for (policy, assets) in value.iter() {
+        if policy == "ada" {
+            let lovelace = assets.get("lovelace").copied().unwrap_or_default();
+            cml_value = cml_value
+                .checked_add(&CMLValue::new(&lovelace.into()))
+                .map_err(|e| CMLLCError::JsError(e.to_string()))?;
+            continue;
+        }
+        let policy_id = PolicyID::from_hex(policy)
+            .map_err(|_| CMLLCError::InvalidPolicyId(policy.to_string()))?;
+        let mut cml_assets = Assets::new();
+        for (asset_name_hex, quantity) in assets.iter() {
+            let asset_name = AssetName::new(hex::decode(asset_name_hex)?)
+                .map_err(|e| CMLLCError::JsError(e.to_string()))?;
+            cml_assets.insert(&asset_name, &(*quantity).into());
+        }
+        let mut multi_assets = MultiAsset::new();
+        multi_assets.insert(&policy_id, &cml_assets);
+        cml_value = cml_value
+            .checked_add(&CMLValue::new_from_assets(&multi_assets))
+            .map_err(|e| CMLLCError::JsError(e.to_string()))?;
+    }
+    Ok(cml_value)
+}
+
+fn parse_evaluation(evaluation: &Value) -> Option<(u64, ExecutionCost)> {
+    // Early v6 releases report the validator as `"spend:0"` rather than an object
+    let (purpose, index) = match evaluation.get("validator")? {
+        Value::String(validator) => {
+            let (purpose, index) = validator.split_once(':')?;
+            (purpose.to_string(), index.parse().ok()?)
+        }
+        validator => (
+            validator.get("purpose")?.as_str()?.to_string(),
+            validator.get("index")?.as_u64()?,
+        ),
+    };
+    let budget = evaluation.get("budget")?;
+    let memory = budget.get("memory")?.as_u64()?;
+    let steps = budget.get("cpu")?.as_u64()?;
+    let ex_cost = match purpose.as_str() {
+        "spend" => ExecutionCost::new_spend(memory, steps),
+        "mint" => ExecutionCost::new_mint(memory, steps),
+        "withdraw" => ExecutionCost::new_withdrawal(memory, steps),
+        "publish" => ExecutionCost::new_certificate(memory, steps),
+        _ => return None,
+    };
+    Some((index, ex_cost))
+}
+
+#[async_trait]
+impl Ledger for OgmiosLsqLedger {
+    async fn last_block_time_secs(&self) -> Result<i64> {
+        let slot = self.tip_slot().await?.ok_or(CMLLCError::OgmiosResponse(
+            "Node has not synchronized any blocks yet".to_string(),
+        ))?;
+        self.posix_from_slot(slot).await
+    }
+
+    async fn get_utxos_for_addr(
+        &self,
+        addr: &CMLAddress,
+        count: usize,
+    ) -> Result<Vec<UTxO>> {
+        let utxos = self
+            .get_utxos(addr)
+            .await?
+            .into_iter()
+            .take(count)
+            .collect();
+        Ok(utxos)
+    }
+
+    async fn get_all_utxos_for_addr(&self, addr: &CMLAddress) -> Result<Vec<UTxO>> {
+        self.get_utxos(addr).await
+    }
+
+    async fn calculate_ex_units(
+        &self,
+        tx: &CMLTransaction,
+    ) -> Result<HashMap<u64, ExecutionCost>> {
+        let params = json!({ "transaction": { "cbor": hex::encode(tx.to_bytes()) } });
+        let evaluations: Vec<Value> = self.request("evaluateTransaction", params).await?;
+        let costs = evaluations.iter().filter_map(parse_evaluation).collect();
+        Ok(costs)
+    }
+
+    async fn submit_transaction(&self, tx: &CMLTransaction) -> Result<String> {
+        let params = json!({ "transaction": { "cbor": hex::encode(tx.to_bytes()) } });
+        let submitted: Value = self.request("submitTransaction", params).await?;
+        let tx_id = submitted
+            .get("transaction")
+            .and_then(|transaction| transaction.get("id"))
+            .and_then(Value::as_str)
+            .ok_or(CMLLCError::OgmiosResponse(
+                "No transaction hash in response".to_string(),
+            ))?;
+        Ok(tx_id.to_string())
+    }
+
+    async fn evaluation_params(&self) -> Result<EvaluationParams> {
+        let params: OgmiosProtocolParameters = self
+            .request("queryLedgerState/protocolParameters", json!({}))
+            .await?;
+        let cost_models = CostModels {
+            plutus_v1: params.plutus_cost_models.plutus_v1,
+            plutus_v2: params.plutus_cost_models.plutus_v2,
+        };
+        let max_units = params.max_execution_units_per_transaction;
+        let max_budget = ScriptExecutionCost::new(max_units.memory, max_units.cpu);
+        Ok(EvaluationParams {
+            cost_models,
+            max_budget,
+        })
+    }
+}
@@ -0,0 +1,277 @@
+#![allow(non_snake_case)]
+
+use super::*;
+use crate::{
+    scripts::evaluation::{
+        MAX_TX_EX_UNITS,
+        VASIL_PLUTUS_V1_COST_MODEL,
+        VASIL_PLUTUS_V2_COST_MODEL,
+    },
+    trireme_ledger_client::{
+        cml_client::{
+            network_settings::NetworkSettings,
+            ExecutionType,
+        },
+        Network,
+    },
+};
+use cardano_multiplatform_lib::{
+    ledger::common::value::BigNum,
+    TransactionBody,
+    TransactionInputs,
+    TransactionOutputs,
+    TransactionWitnessSet,
+};
+use wiremock::{
+    matchers::{
+        body_partial_json,
+        method,
+    },
+    Mock,
+    MockServer,
+    ResponseTemplate,
+};
+
+// Responses recorded from an Ogmios v6 instance connected to a preprod node
+const UTXO: &str = include_str!("recorded/utxo.json");
+const TIP: &str = include_str!("recorded/tip.json");
+const START_TIME: &str = include_str!("recorded/start_time.json");
+const ERA_SUMMARIES: &str = include_str!("recorded/era_summaries.json");
+const EVALUATE: &str = include_str!("recorded/evaluate.json");
+const PROTOCOL_PARAMETERS: &str = include_str!("recorded/protocol_parameters.json");
+const SUBMIT: &str = include_str!("recorded/submit.json");
+const SUBMIT_ERROR: &str = include_str!("recorded/submit_error.json");
+
+const SCRIPT_ADDRESS: &str =
+    "addr_test1wq6t9y9k20wp545s2snkt5222vhhwt40p8mqt8pad6xtdnsq95tm0";
+
+async fn mock_ogmios(recorded: &[(&str, &str)]) -> MockServer {
+    let server = MockServer::start().await;
+    for (rpc_method, response) in recorded {
+        Mock::given(method("POST"))
+            .and(body_partial_json(json!({ "method": rpc_method })))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_raw(*response, "application/json"),
+            )
+            .mount(&server)
+            .await;
+    }
+    server
+}
+
+fn empty_tx() -> CMLTransaction {
+    let body = TransactionBody::new(
+        &TransactionInputs::new(),
+        &TransactionOutputs::new(),
+        &BigNum::from(0),
+        None,
+    );
+    CMLTransaction::new(&body, &TransactionWitnessSet::new(), None)
+}
+
+#[tokio::test]
+async fn get_all_utxos_for_addr__converts_values_and_inline_datums() {
+    // Given
+    let server = mock_ogmios(&[("queryLedgerState/utxo", UTXO)]).await;
+    let ledger = OgmiosLsqLedger::new(&server.uri());
+    let addr = CMLAddress::from_bech32(SCRIPT_ADDRESS).unwrap();
+
+    // When
+    let utxos = ledger.get_all_utxos_for_addr(&addr).await.unwrap();
+
+    // Then
+    assert_eq!(utxos.len(), 2);
+    let hashed_datum_utxo = utxos.first().unwrap();
+    let lovelace: u64 = hashed_datum_utxo.amount().coin().into();
+    assert_eq!(lovelace, 10_000_000);
+    assert!(hashed_datum_utxo.datum().is_none());
+    let datum_hash = hashed_datum_utxo.datum_hash().clone().unwrap();
+    assert_eq!(
+        hex::encode(datum_hash.to_bytes()),
+        "923918e403bf43c34b4ef6b48eb2ee04babed17320d8d1b9ff9ad086e86f44ec"
+    );
+
+    let inline_datum_utxo = utxos.last().unwrap();
+    let index: u64 = inline_datum_utxo.output_index().into();
+    assert_eq!(index, 1);
+    let datum = inline_datum_utxo.datum().clone().unwrap();
+    assert_eq!(hex::encode(datum.to_bytes()), "d8799f182aff");
+    assert!(inline_datum_utxo.datum_hash().is_none());
+    let policy_id =
+        PolicyID::from_hex("8a1cfae21368b8bebbbed9800fec304e95cce39a2a57dc35e2e3ebaa")
+            .unwrap();
+    let milk = AssetName::new(b"MILK".to_vec()).unwrap();
+    let milk_amount: u64 = inline_datum_utxo
+        .amount()
+        .multiasset()
+        .unwrap()
+        .get(&policy_id)
+        .unwrap()
+        .get(&milk)
+        .unwrap()
+        .into();
+    assert_eq!(milk_amount, 5);
+}
+
+#[tokio::test]
+async fn get_utxos_for_addr__respects_count() {
+    // Given
+    let server = mock_ogmios(&[("queryLedgerState/utxo", UTXO)]).await;
+    let ledger = OgmiosLsqLedger::new(&server.uri());
+    let addr = CMLAddress::from_bech32(SCRIPT_ADDRESS).unwrap();
+
+    // When
+    let utxos = ledger.get_utxos_for_addr(&addr, 1).await.unwrap();
+
+    // Then
+    assert_eq!(utxos.len(), 1);
+}
+
+#[tokio::test]
+async fn last_block_time_secs__converts_tip_with_era_summaries() {
+    // Given
+    let server = mock_ogmios(&[
+        ("queryNetwork/tip", TIP),
+        ("queryNetwork/startTime", START_TIME),
+        ("queryLedgerState/eraSummaries", ERA_SUMMARIES),
+    ])
+    .await;
+    let ledger = OgmiosLsqLedger::new(&server.uri());
+
+    // When
+    let block_time = ledger.last_block_time_secs().await.unwrap();
+
+    // Then
+    let network_settings: NetworkSettings = Network::Preprod.into();
+    assert_eq!(block_time, network_settings.posix_from_slot(50_000_000));
+}
+
+#[tokio::test]
+async fn posix_from_slot__accounts_for_byron_slot_length() {
+    // Given
+    let server = mock_ogmios(&[
+        ("queryNetwork/startTime", START_TIME),
+        ("queryLedgerState/eraSummaries", ERA_SUMMARIES),
+    ])
+    .await;
+    let ledger = OgmiosLsqLedger::new(&server.uri());
+
+    // When
+    let byron_time = ledger.posix_from_slot(10).await.unwrap();
+
+    // Then
+    let system_start = 1654041600;
+    assert_eq!(byron_time, system_start + 10 * 20);
+}
+
+#[tokio::test]
+async fn calculate_ex_units__parses_budgets() {
+    // Given
+    let server = mock_ogmios(&[("evaluateTransaction", EVALUATE)]).await;
+    let ledger = OgmiosLsqLedger::new(&server.uri());
+
+    // When
+    let costs = ledger.calculate_ex_units(&empty_tx()).await.unwrap();
+
+    // Then
+    assert_eq!(costs.len(), 2);
+    let spend = costs.get(&0).unwrap();
+    assert!(matches!(spend.execution_type(), ExecutionType::Spend));
+    assert_eq!(spend.memory(), 5236222);
+    assert_eq!(spend.steps(), 1212353);
+    let mint = costs.get(&1).unwrap();
+    assert!(matches!(mint.execution_type(), ExecutionType::Mint));
+}
+
+#[tokio::test]
+async fn evaluation_params__uses_cost_models_and_max_units() {
+    // Given
+    let server =
+        mock_ogmios(&[("queryLedgerState/protocolParameters", PROTOCOL_PARAMETERS)])
+            .await;
+    let ledger = OgmiosLsqLedger::new(&server.uri());
+
+    // When
+    let params = ledger.evaluation_params().await.unwrap();
+
+    // Then
+    assert_eq!(params.cost_models.plutus_v1, VASIL_PLUTUS_V1_COST_MODEL);
+    assert_eq!(params.cost_models.plutus_v2, VASIL_PLUTUS_V2_COST_MODEL);
+    assert_eq!(params.max_budget, MAX_TX_EX_UNITS);
+}
+
+#[tokio::test]
+async fn submit_transaction__returns_tx_id() {
+    // Given
+    let server = mock_ogmios(&[("submitTransaction", SUBMIT)]).await;
+    let ledger = OgmiosLsqLedger::new(&server.uri());
+
+    // When
+    let tx_id = ledger.submit_transaction(&empty_tx()).await.unwrap();
+
+    // Then
+    assert_eq!(
+        tx_id,
+        "ee155ace9c40292074cb6aff8c9ccdd273c81648ff1149ef36bcea6ebb8a3e25"
+    );
+}
+
+#[tokio::test]
+async fn submit_transaction__surfaces_rejection() {
+    // Given
+    let server = mock_ogmios(&[("submitTransaction", SUBMIT_ERROR)]).await;
+    let ledger = OgmiosLsqLedger::new(&server.uri());
+
+    // When
+    let error = ledger.submit_transaction(&empty_tx()).await.unwrap_err();
+
+    // Then
+    assert!(
+        matches!(error, CMLLCError::OgmiosResponse(message) if message.contains("3117"))
+    );
+}
+
+// Trimmed down to the fields Naumachia reads from the Shelley genesis configuration
+const SHELLEY_GENESIS: &str = r#"{
+  "jsonrpc": "2.0",
+  "method": "queryNetwork/genesisConfiguration",
+  "result": { "era": "shelley", "networkMagic": 1, "network": "testnet" },
+  "id": null
+}"#;
+
+#[tokio::test]
+async fn check_network_magic__accepts_same_network() {
+    // Given
+    let server =
+        mock_ogmios(&[("queryNetwork/genesisConfiguration", SHELLEY_GENESIS)]).await;
+    let ledger = OgmiosLsqLedger::new(&server.uri());
+
+    // When
+    let result = ledger.check_network_magic(Network::Preprod.magic()).await;
+
+    // Then
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn check_network_magic__rejects_other_network() {
+    // Given
+    let server =
+        mock_ogmios(&[("queryNetwork/genesisConfiguration", SHELLEY_GENESIS)]).await;
+    let ledger = OgmiosLsqLedger::new(&server.uri());
+
+    // When
+    let error = ledger
+        .check_network_magic(Network::Preview.magic())
+        .await
+        .unwrap_err();
+
+    // Then
+    assert!(matches!(
+        error,
+        CMLLCError::WrongNetwork {
+            expected: 2,
+            actual: 1
+        }
+    ));
+}
@@ -1,18 +1,28 @@
-use crate::trireme_ledger_client::cml_client::{
-    error::{
-        CMLLCError,
-        Result,
+use crate::{
+    scripts::evaluation::EvaluationParams,
+    trireme_ledger_client::cml_client::{
+        error::{
+            CMLLCError,
+            Result,
+        },
+        network_settings::NetworkSettings,
+        request_policy::{
+            RequestLimiter,
+            RequestPolicy,
+        },
+        ExecutionCost,
+        Ledger,
+        UTxO,
     },
-    network_settings::NetworkSettings,
-    ExecutionCost,
-    Ledger,
-    UTxO,
 };
 use async_trait::async_trait;
 use cardano_multiplatform_lib::{
     address::Address as CMLAddress,
     crypto::TransactionHash,
-    ledger::common::value::Value as CMLValue,
+    ledger::common::{
+        hash::hash_plutus_data,
+        value::Value as CMLValue,
+    },
     plutus::PlutusData,
     AssetName,
     Assets,
@@ -49,7 +59,13 @@ fn utxo_from_scrolls_utxo(utxo: &ScrollsClientUTxO) -> Result<UTxO> {
         None
     };
 
-    Ok(UTxO::new(tx_hash, output_index, amount, datum))
+    // Scrolls doesn't say how the datum is attached, so assume it's by hash
+    let datum_hash = datum.as_ref().map(hash_plutus_data);
+    let utxo = UTxO::new(tx_hash, output_index, amount, datum);
+    match datum_hash {
+        Some(datum_hash) => Ok(utxo.with_datum_hash(datum_hash)),
+        None => Ok(utxo),
+    }
 }
 
 fn cml_value_from_scroll_amount(amount: &[ScrollClientAmount]) -> Result<CMLValue> {
@@ -96,11 +112,12 @@ pub struct OgmiosScrollsLedger {
     scrolls_client: ScrollsClient,
     ogmios_client: OgmiosClient,
     network_settings: NetworkSettings,
+    limiter: RequestLimiter,
     // TODO: WS Client for Ogmios data
 }
 
 impl OgmiosScrollsLedger {
-    /// Constructor for the [`OgmiosScrollsLedger`] struct
+    /// Constructor for the [`OgmiosScrollsLedger`] struct, with the default [`RequestPolicy`]
     pub fn new(
         scrolls_client: ScrollsClient,
         ogmios_client: OgmiosClient,
@@ -110,17 +127,26 @@ impl OgmiosScrollsLedger {
             scrolls_client,
             ogmios_client,
             network_settings,
+            limiter: RequestLimiter::new(RequestPolicy::default()),
         }
     }
 
+    /// Use `policy` for the requests to Scrolls and Ogmios
+    pub fn with_request_policy(mut self, policy: RequestPolicy) -> Self {
+        self.limiter = RequestLimiter::new(policy);
+        self
+    }
+
     /// Get the UTxOs for an address
     pub async fn get_utxos(&self, addr: &CMLAddress) -> Result<Vec<UTxO>> {
         let address_str = addr
             .to_bech32(None)
             .map_err(|e| CMLLCError::JsError(e.to_string()))?;
-        let address = Address::from_bech32(&address_str)?;
-        self.scrolls_client
-            .get_utxos_for_address(&address)
+        let address = &Address::from_bech32(&address_str)?;
+        self.limiter
+            .send(|| async move {
+                Ok(self.scrolls_client.get_utxos_for_address(address).await?)
+            })
             .await?
             .iter()
             .map(utxo_from_scrolls_utxo)
@@ -131,7 +157,11 @@ impl OgmiosScrollsLedger {
 #[async_trait]
 impl Ledger for OgmiosScrollsLedger {
     async fn last_block_time_secs(&self) -> Result<i64> {
-        let slot = self.scrolls_client.get_last_block_info().await?.slot;
+        let slot = self
+            .limiter
+            .send(|| async move { Ok(self.scrolls_client.get_last_block_info().await?) })
+            .await?
+            .slot;
         Ok(self.network_settings.posix_from_slot(slot))
     }
 
@@ -157,15 +187,24 @@ impl Ledger for OgmiosScrollsLedger {
         &self,
         tx: &CMLTransaction,
     ) -> Result<HashMap<u64, ExecutionCost>> {
-        let bytes = tx.to_bytes();
-        let res = self.ogmios_client.evaluate_tx(&bytes, vec![]).await?;
+        let bytes = &tx.to_bytes();
+        let res =
+            self.limiter
+                .send(|| async move {
+                    Ok(self.ogmios_client.evaluate_tx(bytes, vec![]).await?)
+                })
+                .await?;
         check_for_error(&res)?;
         parse_evaluation_results(&res)
     }
 
     async fn submit_transaction(&self, tx: &CMLTransaction) -> Result<String> {
         let bytes = tx.to_bytes();
-        let res = self.ogmios_client.submit_tx(&bytes).await?;
+        // Not retried, as a submission that timed out might still have gone through
+        let res = self
+            .limiter
+            .send_once(async { Ok(self.ogmios_client.submit_tx(&bytes).await?) })
+            .await?;
         let tx_hash = res
             .result()
             .ok_or(CMLLCError::OgmiosResponse(
@@ -175,9 +214,13 @@ impl Ledger for OgmiosScrollsLedger {
             .to_string();
         Ok(tx_hash)
     }
+
+    async fn evaluation_params(&self) -> Result<EvaluationParams> {
+        Err(CMLLCError::NoEvaluationParams)
+    }
 }
 
-fn check_for_error(res: &OgmiosResponse<EvaluationResult>) -> Result<()> {
+pub(super) fn check_for_error(res: &OgmiosResponse<EvaluationResult>) -> Result<()> {
     if let Some(err) = res.fault() {
         Err(CMLLCError::OgmiosResponse(err.to_string()))
     } else {
@@ -185,7 +228,7 @@ fn check_for_error(res: &OgmiosResponse<EvaluationResult>) -> Result<()> {
     }
 }
 
-fn parse_evaluation_results(
+pub(super) fn parse_evaluation_results(
     res: &OgmiosResponse<EvaluationResult>,
 ) -> Result<HashMap<u64, ExecutionCost>> {
     let eval_res = if let Some(eval_res) = res.result() {
@@ -0,0 +1,161 @@
+use crate::trireme_ledger_client::cml_client::error::{
+    CMLLCError,
+    Result,
+};
+use std::{
+    future::Future,
+    time::Duration,
+};
+use tokio::sync::Semaphore;
+
+#[cfg(test)]
+mod tests;
+
+const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 5;
+const DEFAULT_MAX_RETRIES: u32 = 5;
+const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_millis(200);
+const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(10);
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How a remote [`Ledger`](super::Ledger) talks to its backend: how many requests may be in
+/// flight at once, how long each may take, and how often requests that failed with a
+/// [transient](CMLLCError::is_transient) error are retried
+#[derive(Debug, Clone)]
+pub struct RequestPolicy {
+    max_concurrent_requests: usize,
+    max_retries: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    timeout: Duration,
+}
+
+impl Default for RequestPolicy {
+    fn default() -> Self {
+        RequestPolicy {
+            max_concurrent_requests: DEFAULT_MAX_CONCURRENT_REQUESTS,
+            max_retries: DEFAULT_MAX_RETRIES,
+            initial_backoff: DEFAULT_INITIAL_BACKOFF,
+            max_backoff: DEFAULT_MAX_BACKOFF,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+}
+
+impl RequestPolicy {
+    /// Set how many requests may be in flight at once. At least one always is
+    pub fn with_max_concurrent_requests(
+        mut self,
+        max_concurrent_requests: usize,
+    ) -> Self {
+        self.max_concurrent_requests = max_concurrent_requests.max(1);
+        self
+    }
+
+    /// Set how many times a request is retried after a transient error
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Set the wait before the first retry. It doubles on every following retry, up to
+    /// `max_backoff`
+    pub fn with_backoff(
+        mut self,
+        initial_backoff: Duration,
+        max_backoff: Duration,
+    ) -> Self {
+        self.initial_backoff = initial_backoff;
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Set how long a single request may take
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Getter for the maximum number of requests in flight
+    pub fn max_concurrent_requests(&self) -> usize {
+        self.max_concurrent_requests
+    }
+
+    /// Getter for the maximum number of retries
+    pub fn max_retries(&self) -> u32 {
+        self.max_retries
+    }
+
+    /// Getter for the timeout of a single request
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// The wait before retry number `retry`, counting from `0`
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry);
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+/// Enforces a [`RequestPolicy`] over all the requests of a ledger
+#[derive(Debug)]
+pub struct RequestLimiter {
+    policy: RequestPolicy,
+    permits: Semaphore,
+}
+
+impl RequestLimiter {
+    /// Constructor for the [`RequestLimiter`] struct
+    pub fn new(policy: RequestPolicy) -> Self {
+        let permits = Semaphore::new(policy.max_concurrent_requests.max(1));
+        RequestLimiter { policy, permits }
+    }
+
+    /// Getter for the enforced policy
+    pub fn policy(&self) -> &RequestPolicy {
+        &self.policy
+    }
+
+    /// Send a request, retrying it with backoff while it fails with a transient error.
+    /// `request` is called once per attempt
+    pub async fn send<T, F, Fut>(&self, mut request: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut retry = 0;
+        loop {
+            match self.send_once(request()).await {
+                Err(e) if e.is_transient() => {
+                    if retry >= self.policy.max_retries {
+                        return Err(CMLLCError::RetriesExhausted {
+                            attempts: retry + 1,
+                            last: Box::new(e),
+                        })
+                    }
+                    // Other requests can go ahead while this one waits
+                    tokio::time::sleep(self.policy.backoff(retry)).await;
+                    retry += 1;
+                }
+                res => return res,
+            }
+        }
+    }
+
+    /// Send a request without retrying it, e.g. because it isn't safe to repeat
+    pub async fn send_once<T, Fut>(&self, request: Fut) -> Result<T>
+    where
+        Fut: Future<Output = Result<T>>,
+    {
+        let _permit = self
+            .permits
+            .acquire()
+            .await
+            .expect("Semaphore is never closed");
+        tokio::time::timeout(self.policy.timeout, request)
+            .await
+            .map_err(|_| CMLLCError::Timeout(self.policy.timeout))?
+    }
+}
@@ -0,0 +1,148 @@
+#![allow(non_snake_case)]
+
+use super::*;
+use futures::future;
+use std::sync::atomic::{
+    AtomicU32,
+    AtomicUsize,
+    Ordering,
+};
+
+fn fast_policy() -> RequestPolicy {
+    RequestPolicy::default()
+        .with_max_retries(3)
+        .with_backoff(Duration::from_millis(1), Duration::from_millis(5))
+}
+
+fn transient_error() -> CMLLCError {
+    CMLLCError::TransientLedgerError("429 Too Many Requests".into())
+}
+
+#[test]
+fn backoff__doubles_up_to_max() {
+    // Given
+    let policy = RequestPolicy::default()
+        .with_backoff(Duration::from_millis(100), Duration::from_millis(500));
+
+    // When
+    let backoffs: Vec<_> = (0..5).map(|retry| policy.backoff(retry)).collect();
+
+    // Then
+    let expected: Vec<_> = [100, 200, 400, 500, 500]
+        .into_iter()
+        .map(Duration::from_millis)
+        .collect();
+    assert_eq!(backoffs, expected);
+}
+
+#[tokio::test]
+async fn send__retries_transient_errors() {
+    // Given
+    let limiter = RequestLimiter::new(fast_policy());
+    let attempts = &AtomicU32::new(0);
+
+    // When
+    let res = limiter
+        .send(|| async move {
+            if attempts.fetch_add(1, Ordering::SeqCst) < 2 {
+                Err(transient_error())
+            } else {
+                Ok("done")
+            }
+        })
+        .await;
+
+    // Then
+    assert_eq!(res.unwrap(), "done");
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn send__does_not_retry_permanent_errors() {
+    // Given
+    let limiter = RequestLimiter::new(fast_policy());
+    let attempts = &AtomicU32::new(0);
+
+    // When
+    let res: Result<()> = limiter
+        .send(|| async move {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err(CMLLCError::LedgerError("400 Bad Request".into()))
+        })
+        .await;
+
+    // Then
+    let error = res.unwrap_err();
+    assert!(!error.is_transient());
+    assert!(matches!(error, CMLLCError::LedgerError(_)));
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn send__gives_up_after_max_retries() {
+    // Given
+    let limiter = RequestLimiter::new(fast_policy());
+    let attempts = &AtomicU32::new(0);
+
+    // When
+    let res: Result<()> = limiter
+        .send(|| async move {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err(transient_error())
+        })
+        .await;
+
+    // Then
+    let error = res.unwrap_err();
+    assert!(error.is_transient());
+    assert!(matches!(
+        error,
+        CMLLCError::RetriesExhausted { attempts: 4, .. }
+    ));
+    assert_eq!(attempts.load(Ordering::SeqCst), 4);
+}
+
+#[tokio::test]
+async fn send_once__times_out_slow_requests() {
+    // Given
+    let policy = fast_policy().with_timeout(Duration::from_millis(10));
+    let limiter = RequestLimiter::new(policy);
+
+    // When
+    let res: Result<()> = limiter
+        .send_once(async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok(())
+        })
+        .await;
+
+    // Then
+    let error = res.unwrap_err();
+    assert!(error.is_transient());
+    assert!(matches!(error, CMLLCError::Timeout(_)));
+}
+
+#[tokio::test]
+async fn send__bounds_concurrent_requests() {
+    // Given
+    let policy = fast_policy().with_max_concurrent_requests(2);
+    let limiter = &RequestLimiter::new(policy);
+    let in_flight = &AtomicUsize::new(0);
+    let most_in_flight = &AtomicUsize::new(0);
+
+    // When
+    let requests = (0..10).map(move |_| {
+        limiter.send(|| async move {
+            let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            most_in_flight.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            in_flight.fetch_sub(1, Ordering::SeqCst);
+            Ok(())
+        })
+    });
+    let results = future::join_all(requests).await;
+
+    // Then
+    assert!(results.iter().all(|res| res.is_ok()));
+    assert_eq!(most_in_flight.load(Ordering::SeqCst), 2);
+}
@@ -1,20 +1,38 @@
 use super::*;
 use crate::{
+    ledger_client::test_ledger_client::{
+        in_memory_storage::InMemoryStorage,
+        local_persisted_storage::starting_output,
+    },
+    scripts::plutus_validator::PlutusValidator,
     trireme_ledger_client::{
+        blockfrost_emulator::BlockfrostEmulator,
         cml_client::{
             blockfrost_ledger::BlockFrostLedger,
             key_manager::KeyManager,
         },
+        secret_phrase::{
+            private_key_to_base_address,
+            secret_phrase_to_account_key,
+        },
         Network,
     },
+    values::Values,
     PolicyId,
 };
 use blockfrost_http_client::{
     load_key_from_file,
     PREPROD_NETWORK_URL,
 };
-use cardano_multiplatform_lib::address::BaseAddress;
-use std::time::Duration;
+use cardano_multiplatform_lib::{
+    address::BaseAddress,
+    crypto::Bip32PrivateKey,
+    plutus::PlutusV2Script,
+};
+use std::{
+    net::TcpListener,
+    time::Duration,
+};
 use test_helpers::{
     always_succeeds_script_address,
     claim_always_succeeds_datum_tx,
@@ -23,6 +41,10 @@ use test_helpers::{
     transfer_tx,
 };
 use tokio::time::sleep;
+use uplc::ast::{
+    DeBruijn,
+    Program,
+};
 
 mod test_helpers;
 
@@ -44,6 +66,140 @@ async fn get_test_client<Datum: PlutusDataInterop, Redeemer: PlutusDataInterop>(
     )
 }
 
+const PHRASE: &str = "abandon abandon abandon abandon abandon abandon abandon abandon \
+    abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+    abandon abandon abandon abandon abandon art";
+
+struct PhraseKeys {
+    account_key: Bip32PrivateKey,
+    network: u8,
+}
+
+#[async_trait]
+impl Keys for PhraseKeys {
+    async fn base_addr(&self) -> Result<BaseAddress> {
+        Ok(private_key_to_base_address(&self.account_key, self.network))
+    }
+
+    async fn private_key(&self) -> Result<PrivateKey> {
+        Ok(self.account_key.derive(0).derive(0).to_raw_key())
+    }
+}
+
+#[tokio::test]
+async fn issue_with_local_ex_units_spends_script_output() {
+    let network: u8 = Network::Preprod.into();
+    let keys = PhraseKeys {
+        account_key: secret_phrase_to_account_key(PHRASE).unwrap(),
+        network,
+    };
+    let my_addr = keys.base_addr().await.unwrap().to_address();
+    let my_addr = Address::from_bech32(&my_addr.to_bech32(None).unwrap()).unwrap();
+    let script_addr = always_succeeds_script_address(network);
+    let mut values = Values::default();
+    values.add_one_value(&PolicyId::Lovelace, 6_000_000);
+    let locked = Output::new_validator_with_datum_hash(
+        vec![2; 32],
+        0,
+        script_addr.clone(),
+        values.clone(),
+        (),
+    );
+    let stored = Output::new_validator_with_datum_hash(
+        vec![2; 32],
+        0,
+        script_addr.clone(),
+        values,
+        NauPlutusData::from(()),
+    );
+    let outputs = vec![
+        (my_addr.clone(), starting_output(&my_addr, 100_000_000)),
+        (script_addr.clone(), stored),
+    ];
+    let storage = InMemoryStorage::new(my_addr, outputs, 0, 20);
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let emulator = BlockfrostEmulator::new(storage, Network::Preprod.into());
+    tokio::spawn(emulator.serve(listener));
+    let ledger = BlockFrostLedger::new(&url, "any key");
+    let client: CMLLedgerCLient<_, _, (), ()> =
+        CMLLedgerCLient::new(ledger, keys, Network::Preprod.into())
+            .with_ex_units_source(ExUnitsSource::Local);
+
+    client
+        .issue(claim_always_succeeds_datum_tx(&locked))
+        .await
+        .unwrap();
+
+    let script_outputs = client.all_outputs_at_address(&script_addr).await.unwrap();
+    assert!(script_outputs.is_empty());
+}
+
+#[tokio::test]
+async fn issue_spends_inline_datum_script_output() {
+    let network: u8 = Network::Preprod.into();
+    let keys = PhraseKeys {
+        account_key: secret_phrase_to_account_key(PHRASE).unwrap(),
+        network,
+    };
+    let my_addr = keys.base_addr().await.unwrap().to_address();
+    let my_addr = Address::from_bech32(&my_addr.to_bech32(None).unwrap()).unwrap();
+    let source = "(program 1.0.0 (lam d (lam r (lam c (con unit ())))))";
+    let program = uplc::parser::program(source).unwrap();
+    let program: Program<DeBruijn> = program.try_into().unwrap();
+    let script_cbor = program.to_hex().unwrap();
+    let v2 = PlutusV2Script::new(hex::decode(&script_cbor).unwrap());
+    let credential = StakeCredential::from_scripthash(&PlutusScript::from_v2(&v2).hash());
+    let script_addr = EnterpriseAddress::new(network, &credential).to_address();
+    let script_addr =
+        Address::from_bech32(&script_addr.to_bech32(None).unwrap()).unwrap();
+    let mut values = Values::default();
+    values.add_one_value(&PolicyId::Lovelace, 6_000_000);
+    let stored = Output::new_validator(
+        vec![3; 32],
+        0,
+        script_addr.clone(),
+        values,
+        NauPlutusData::from(()),
+    );
+    let outputs = vec![
+        (my_addr.clone(), starting_output(&my_addr, 100_000_000)),
+        (script_addr.clone(), stored),
+    ];
+    let storage = InMemoryStorage::new(my_addr, outputs, 0, 20);
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let emulator = BlockfrostEmulator::new(storage, Network::Preprod.into());
+    tokio::spawn(emulator.serve(listener));
+    let ledger = BlockFrostLedger::new(&url, "any key");
+    let client: CMLLedgerCLient<_, _, (), ()> =
+        CMLLedgerCLient::new(ledger, keys, Network::Preprod.into())
+            .with_ex_units_source(ExUnitsSource::Local);
+    let locked = client
+        .all_outputs_at_address(&script_addr)
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+    assert!(locked.datum_hash().is_none());
+    let script = PlutusValidator::v2_from_cbor(script_cbor).unwrap();
+    let script = Box::new(script) as Box<dyn Validator<(), ()>>;
+    let tx = UnbuiltTransaction {
+        script_version: TransactionVersion::V2,
+        script_inputs: vec![(locked, (), script)],
+        unbuilt_outputs: vec![],
+        minting: Default::default(),
+        specific_wallet_inputs: vec![],
+        valid_range: (None, None),
+    };
+
+    // The emulator rejects the datum if it's also supplied in the witness set
+    client.issue(tx).await.unwrap();
+
+    let script_outputs = client.all_outputs_at_address(&script_addr).await.unwrap();
+    assert!(script_outputs.is_empty());
+}
+
 #[ignore]
 #[tokio::test]
 async fn get_all_my_utxos() {
@@ -8,12 +8,23 @@ use dialoguer::{
 use hex;
 use naumachia::{
     ledger_client::{
-        test_ledger_client::local_persisted_storage::LocalPersistedStorage,
+        test_ledger_client::{
+            local_persisted_storage::LocalPersistedStorage,
+            sqlite_storage::SqliteStorage,
+            TestLedgerStorage,
+        },
         LedgerClient,
     },
-    scripts::context::pub_key_hash_from_address_if_available,
+    scripts::{
+        context::pub_key_hash_from_address_if_available,
+        plutus_validator::plutus_data::PlutusData,
+    },
     trireme_ledger_client::{
-        cml_client::blockfrost_ledger::BlockfrostApiKey,
+        blockfrost_emulator::BlockfrostEmulator,
+        cml_client::{
+            blockfrost_ledger::BlockfrostApiKey,
+            network_settings::NetworkSettings,
+        },
         get_current_client_config_from_file,
         get_trireme_config_from_file,
         get_trireme_ledger_client_from_file,
@@ -31,6 +42,8 @@ use naumachia::{
         KeySource,
         LedgerSource,
         Network,
+        TestClientConfig,
+        TestStorage,
         TriremeConfig,
         TriremeLedgerClient,
     },
@@ -38,6 +51,7 @@ use naumachia::{
 };
 use rand::Rng;
 use std::{
+    net::TcpListener,
     path::PathBuf,
     str::FromStr,
 };
@@ -103,6 +117,7 @@ fn get_env_type() -> Result<EnvironmentType> {
 }
 
 async fn setup_password_protected_blockfrost_env(name: &str) -> Result<()> {
+    let network = get_network()?;
     let ledger_source = get_ledger_source(name).await?;
 
     let secret_phrase: String = Input::new()
@@ -131,9 +146,6 @@ async fn setup_password_protected_blockfrost_env(name: &str) -> Result<()> {
         &encryption_nonce,
     )
     .await?;
-    // TODO: Do a prompt or derive network from api key
-    let network = Network::Preprod;
-
     write_cml_client_config_with_password_protection(
         &name,
         &name,
@@ -147,10 +159,91 @@ async fn setup_password_protected_blockfrost_env(name: &str) -> Result<()> {
     Ok(())
 }
 
+#[derive(Debug, Clone, Copy)]
+enum NetworkTypes {
+    Preprod,
+    Preview,
+    Custom,
+}
+
+impl ToString for NetworkTypes {
+    fn to_string(&self) -> String {
+        match self {
+            NetworkTypes::Preprod => "Preprod".to_string(),
+            NetworkTypes::Preview => "Preview".to_string(),
+            NetworkTypes::Custom => {
+                "Custom (private testnet or local devnet)".to_string()
+            }
+        }
+    }
+}
+
+fn get_network() -> Result<Network> {
+    let items = vec![
+        NetworkTypes::Preprod,
+        NetworkTypes::Preview,
+        NetworkTypes::Custom,
+    ];
+    let item_index = Select::new()
+        .with_prompt("Which network?")
+        .items(&items)
+        .interact()?;
+    let network_type = items
+        .get(item_index)
+        .expect("Should always be a valid index")
+        .to_owned();
+
+    match network_type {
+        NetworkTypes::Preprod => Ok(Network::Preprod),
+        NetworkTypes::Preview => Ok(Network::Preview),
+        NetworkTypes::Custom => setup_custom_network(),
+    }
+}
+
+fn setup_custom_network() -> Result<Network> {
+    let network_id: u8 = Input::new()
+        .with_prompt("Network id (0 for testnets)")
+        .default(0)
+        .interact_text()?;
+    let magic: u64 = Input::new()
+        .with_prompt("Network magic")
+        .default(42)
+        .interact_text()?;
+    let starting_slot_time: i64 = Input::new()
+        .with_prompt("POSIX time (in seconds) of the starting slot")
+        .interact_text()?;
+    let starting_slot_number: u64 = Input::new()
+        .with_prompt("Number of the starting slot")
+        .default(0)
+        .interact_text()?;
+    let slot_length: i64 = Input::new()
+        .with_prompt("Slot length (in seconds)")
+        .default(1)
+        .interact_text()?;
+    let blockfrost_url: String = Input::new()
+        .with_prompt("Blockfrost compatible API URL (leave empty if there is none)")
+        .allow_empty(true)
+        .interact_text()?;
+    let network_settings = NetworkSettings::new(
+        network_id,
+        slot_length,
+        starting_slot_time,
+        starting_slot_number,
+    );
+    let blockfrost_url = Some(blockfrost_url).filter(|url| !url.is_empty());
+    Ok(Network::Custom {
+        network_settings,
+        magic,
+        blockfrost_url,
+    })
+}
+
 #[derive(Debug)]
 enum LedgerTypes {
     BlockFrost,
     OgmiosAndScrolls,
+    KupoAndOgmios,
+    Ogmios,
 }
 
 impl ToString for LedgerTypes {
@@ -158,12 +251,19 @@ impl ToString for LedgerTypes {
         match self {
             LedgerTypes::BlockFrost => "Blockfrost API".to_string(),
             LedgerTypes::OgmiosAndScrolls => "Ogmios and Scrolls".to_string(),
+            LedgerTypes::KupoAndOgmios => "Kupo and Ogmios".to_string(),
+            LedgerTypes::Ogmios => "Ogmios only (v6)".to_string(),
         }
     }
 }
 
 async fn get_ledger_source(env_name: &str) -> Result<LedgerSource> {
-    let items = vec![LedgerTypes::BlockFrost, LedgerTypes::OgmiosAndScrolls];
+    let items = vec![
+        LedgerTypes::BlockFrost,
+        LedgerTypes::OgmiosAndScrolls,
+        LedgerTypes::KupoAndOgmios,
+        LedgerTypes::Ogmios,
+    ];
     let item_index = Select::new()
         .with_prompt("What is your ledger data provider?")
         .items(&items)
@@ -176,6 +276,8 @@ async fn get_ledger_source(env_name: &str) -> Result<LedgerSource> {
     match ledger_type {
         LedgerTypes::BlockFrost => setup_blockfrost_ledger(env_name).await,
         LedgerTypes::OgmiosAndScrolls => setup_ogmios_and_scrolls_ledger(),
+        LedgerTypes::KupoAndOgmios => setup_kupo_and_ogmios_ledger(),
+        LedgerTypes::Ogmios => setup_ogmios_ledger(),
     }
 }
 
@@ -216,16 +318,161 @@ fn setup_ogmios_and_scrolls_ledger() -> Result<LedgerSource> {
     Ok(ledger_source)
 }
 
+fn setup_kupo_and_ogmios_ledger() -> Result<LedgerSource> {
+    let kupo_ip: String = Input::new()
+        .with_prompt("Ip address of Kupo")
+        .default("127.0.0.1".to_string())
+        .interact_text()?;
+    let kupo_port: String = Input::new()
+        .with_prompt("Port for Kupo")
+        .default("1442".to_string())
+        .interact_text()?;
+    let ogmios_ip: String = Input::new()
+        .with_prompt("Ip address of Ogmios")
+        .default("127.0.0.1".to_string())
+        .interact_text()?;
+    let ogmios_port: String = Input::new()
+        .with_prompt("Port for Ogmios")
+        .default("1337".to_string())
+        .interact_text()?;
+    let ledger_source = LedgerSource::KupoAndOgmios {
+        kupo_ip,
+        kupo_port,
+        ogmios_ip,
+        ogmios_port,
+    };
+    Ok(ledger_source)
+}
+
+fn setup_ogmios_ledger() -> Result<LedgerSource> {
+    let ogmios_ip: String = Input::new()
+        .with_prompt("Ip address of Ogmios")
+        .default("127.0.0.1".to_string())
+        .interact_text()?;
+    let ogmios_port: String = Input::new()
+        .with_prompt("Port for Ogmios")
+        .default("1337".to_string())
+        .interact_text()?;
+    let ledger_source = LedgerSource::Ogmios {
+        ogmios_ip,
+        ogmios_port,
+    };
+    Ok(ledger_source)
+}
+
 fn get_password_with_prompt(prompt: &str) -> Result<String> {
     let password = InputPassword::new().with_prompt(prompt).interact()?;
     Ok(password)
 }
 
+/// Storage of a local mocked environment, whichever kind it is
+enum MockedStorage {
+    Json(LocalPersistedStorage<PathBuf, ()>),
+    Sqlite(SqliteStorage<PathBuf, ()>),
+}
+
+impl MockedStorage {
+    fn init(
+        kind: TestStorage,
+        dir: PathBuf,
+        signer_name: &str,
+        signer: &Address,
+        starting_amount: u64,
+        starting_time: i64,
+        block_length: i64,
+    ) -> Result<Self> {
+        let storage = match kind {
+            TestStorage::Json => MockedStorage::Json(LocalPersistedStorage::init(
+                dir,
+                signer_name,
+                signer,
+                starting_amount,
+                starting_time,
+                block_length,
+            )?),
+            TestStorage::Sqlite => MockedStorage::Sqlite(SqliteStorage::init(
+                dir,
+                signer_name,
+                signer,
+                starting_amount,
+                starting_time,
+                block_length,
+            )?),
+        };
+        Ok(storage)
+    }
+
+    fn load(config: &TestClientConfig) -> Result<Self> {
+        let path = config.data_path();
+        let storage = match config.storage() {
+            TestStorage::Json => MockedStorage::Json(LocalPersistedStorage::load(path)?),
+            TestStorage::Sqlite => MockedStorage::Sqlite(SqliteStorage::load(path)?),
+        };
+        Ok(storage)
+    }
+
+    fn add_new_signer(
+        &self,
+        name: &str,
+        address: &Address,
+        starting_amount: u64,
+    ) -> Result<()> {
+        match self {
+            MockedStorage::Json(storage) => {
+                storage.add_new_signer(name, address, starting_amount)?
+            }
+            MockedStorage::Sqlite(storage) => {
+                storage.add_new_signer(name, address, starting_amount)?
+            }
+        }
+        Ok(())
+    }
+
+    fn active_signer_name(&self) -> Result<String> {
+        let name = match self {
+            MockedStorage::Json(storage) => storage.active_signer_name()?,
+            MockedStorage::Sqlite(storage) => storage.active_signer_name()?,
+        };
+        Ok(name)
+    }
+
+    fn get_signers(&self) -> Result<Vec<String>> {
+        let signers = match self {
+            MockedStorage::Json(storage) => storage.get_signers()?,
+            MockedStorage::Sqlite(storage) => storage.get_signers()?,
+        };
+        Ok(signers)
+    }
+
+    fn switch_signer(&self, name: &str) -> Result<()> {
+        match self {
+            MockedStorage::Json(storage) => storage.switch_signer(name)?,
+            MockedStorage::Sqlite(storage) => storage.switch_signer(name)?,
+        }
+        Ok(())
+    }
+}
+
+fn get_test_storage() -> Result<TestStorage> {
+    let items = vec!["JSON file", "SQLite database"];
+    let item_index = Select::new()
+        .with_prompt("How should the ledger be stored?")
+        .items(&items)
+        .default(0)
+        .interact()?;
+    let storage = match item_index {
+        0 => TestStorage::Json,
+        _ => TestStorage::Sqlite,
+    };
+    Ok(storage)
+}
+
 async fn setup_local_mocked_env(name: &str) -> Result<()> {
     let block_length: i64 = Input::new()
         .with_prompt("What is the block length in secs?")
         .default(20)
         .interact_text()?;
+    let test_storage = get_test_storage()?;
 
     let alice_name = "Alice";
     let alice_address = Address::from_bech32("addr_test1qrksjmprvgcedgdt6rhg40590vr6exdzdc2hm5wc6pyl9ymkyskmqs55usm57gflrumk9kd63f3ty6r0l2tdfwfm28qs0rurdr")?;
@@ -244,18 +491,19 @@ async fn setup_local_mocked_env(name: &str) -> Result<()> {
     fs::create_dir_all(&parent_dir).await?;
 
     let starting_time = 0;
-    let storage = LocalPersistedStorage::<PathBuf, ()>::init(
+    let storage = MockedStorage::init(
+        test_storage,
         parent_dir.into(),
         alice_name,
         &alice_address,
         start_balance,
         starting_time,
         block_length,
-    );
-    storage.add_new_signer(bob_name, &bob_address, start_balance);
-    storage.add_new_signer(charlotte_name, &charlotte_address, start_balance);
-    storage.add_new_signer(dick_name, &dick_address, start_balance);
-    let client_config = ClientConfig::new_test(&name, &(parent_dir.into()));
+    )?;
+    storage.add_new_signer(bob_name, &bob_address, start_balance)?;
+    storage.add_new_signer(charlotte_name, &charlotte_address, start_balance)?;
+    storage.add_new_signer(dick_name, &dick_address, start_balance)?;
+    let client_config = ClientConfig::new_test(&name, &(parent_dir.into()), test_storage);
     write_toml_struct_to_file(&dir, &client_config).await?;
     Ok(())
 }
@@ -338,9 +586,8 @@ pub async fn active_signer_impl() -> Result<()> {
         .unwrap(); // TODO
     match config.variant() {
         ClientVariant::Test(inner) => {
-            let path = inner.data_path();
-            let storage = LocalPersistedStorage::<PathBuf, ()>::load(path);
-            let signer = storage.active_signer_name();
+            let storage = MockedStorage::load(&inner)?;
+            let signer = storage.active_signer_name()?;
             println!("Active signer: {}", signer);
         }
         _ => {
@@ -382,15 +629,14 @@ pub async fn switch_signer_impl() -> Result<()> {
         .unwrap(); // TODO
     match config.variant() {
         ClientVariant::Test(inner) => {
-            let path = inner.data_path();
-            let storage = LocalPersistedStorage::<PathBuf, ()>::load(path);
-            let items = storage.get_signers();
+            let storage = MockedStorage::load(&inner)?;
+            let items = storage.get_signers()?;
             let choice = Select::new()
                 .with_prompt("To which signer?")
                 .items(&items)
                 .interact()?;
             let name = items.get(choice).expect("should always be a valid index");
-            storage.switch_signer(&name);
+            storage.switch_signer(name)?;
             println!("Switched signer to: {}", &name);
         }
         _ => {
@@ -446,6 +692,60 @@ pub async fn advance_blocks(count: i64) -> Result<()> {
     Ok(())
 }
 
+pub async fn serve_impl(port: u16) -> Result<()> {
+    let sub_dir = get_trireme_config_from_file()
+        .await?
+        .and_then(|config| config.current_env())
+        .ok_or(Error::CLI("No environment selected".to_string()))?;
+    let dir = path_to_client_config_file(&sub_dir)?;
+    let config = read_toml_struct_from_file::<ClientConfig>(&dir)
+        .await?
+        .ok_or(Error::CLI("Environment has no config".to_string()))?;
+    let inner = match config.variant() {
+        ClientVariant::Test(inner) => inner,
+        _ => {
+            return Err(
+                Error::CLI("Only mocked environments can be served".to_string()).into(),
+            )
+        }
+    };
+    let listener = TcpListener::bind(("127.0.0.1", port))?;
+    println!(
+        "Serving {} as Blockfrost API at http://{}",
+        config.name(),
+        listener.local_addr()?
+    );
+    let data_path = inner.data_path();
+    match inner.storage() {
+        TestStorage::Json => {
+            let storage = LocalPersistedStorage::<PathBuf, PlutusData>::load(data_path)?;
+            serve_storage(storage, listener).await
+        }
+        TestStorage::Sqlite => {
+            let storage = SqliteStorage::<PathBuf, PlutusData>::load(data_path)?;
+            serve_storage(storage, listener).await
+        }
+    }
+}
+
+/// Mocked ledgers count time in POSIX seconds unless they were set up to run on slots, so
+/// serve those with one-second slots that start at time `0`
+const POSIX_SECOND_SLOTS: NetworkSettings = NetworkSettings::new(0, 1, 0, 0);
+
+async fn serve_storage<Storage>(storage: Storage, listener: TcpListener) -> Result<()>
+where
+    Storage: TestLedgerStorage<PlutusData> + Send + Sync + 'static,
+{
+    let network_settings = storage
+        .network_settings()
+        .await?
+        .unwrap_or(POSIX_SECOND_SLOTS);
+    BlockfrostEmulator::new(storage, network_settings)
+        .serve(listener)
+        .await?;
+    Ok(())
+}
+
 const RAW_PHRASE_FILE: &str = "secret_phrase.toml";
 
 async fn write_secret_phrase_with_password(
@@ -13,6 +13,7 @@ use crate::{
         last_block_time_impl,
         new_env_impl,
         remove_env_impl,
+        serve_impl,
         switch_env_impl,
         switch_signer_impl,
     },
@@ -65,6 +66,11 @@ enum ActionParams {
     LastBlockTime,
     /// Advance time and block height by count 🧱
     AdvanceBlocks { count: u16 },
+    /// Serve the environment over the Blockfrost API 🛰 (Mock Network Only)
+    Serve {
+        #[clap(short, long, default_value = "3000")]
+        port: u16,
+    },
 }
 
 #[derive(Debug, Error)]
@@ -92,6 +98,7 @@ async fn main() -> Result<()> {
         ActionParams::Time => current_time_impl().await?,
         ActionParams::LastBlockTime => last_block_time_impl().await?,
         ActionParams::AdvanceBlocks { count } => advance_blocks(count as i64).await?,
+        ActionParams::Serve { port } => serve_impl(port).await?,
     }
     Ok(())
 }